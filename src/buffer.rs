@@ -10,16 +10,21 @@
 //! ```
 //!
 
+use std::cmp;
 use std::time::Duration;
-use std::vec::IntoIter as VecIntoIter;
 
+use conversions::duration_to_frames;
+use source::peak_factor;
+use source::SeekError;
 use source::Source;
 
 use Sample;
 
 /// A buffer of samples treated as a source.
 pub struct SamplesBuffer<S> {
-    data: VecIntoIter<S>,
+    data: Vec<S>,
+    // Index of the next sample in `data`.
+    position: usize,
     channels: u16,
     sample_rate: u32,
     duration: Duration,
@@ -54,12 +59,34 @@ where
         );
 
         SamplesBuffer {
-            data: data.into_iter(),
+            data,
+            position: 0,
             channels: channels,
             sample_rate: sample_rate,
             duration: duration,
         }
     }
+
+    /// Amplifies the samples so that the loudest of them reaches `peak`, between `0.0` and `1.0`.
+    ///
+    /// Silent buffers are left untouched.
+    pub fn normalize(mut self, peak: f32) -> SamplesBuffer<S> {
+        let factor = peak_factor(self.data.iter().cloned(), peak);
+        for sample in self.data.iter_mut() {
+            *sample = sample.amplify(factor);
+        }
+        self
+    }
+
+    /// Reverses the order of the frames, so that the buffer plays backwards from its end.
+    ///
+    /// The channels stay in the same order inside each frame.
+    pub fn reverse(mut self) -> SamplesBuffer<S> {
+        let channels = self.channels as usize;
+        self.data = self.data.chunks(channels).rev().flatten().cloned().collect();
+        self.position = 0;
+        self
+    }
 }
 
 impl<S> Source for SamplesBuffer<S>
@@ -85,6 +112,14 @@ where
     fn total_duration(&self) -> Option<Duration> {
         Some(self.duration)
     }
+
+    #[inline]
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        let frame = duration_to_frames(pos, self.sample_rate);
+        let index = cmp::min(frame * self.channels as u64, self.data.len() as u64);
+        self.position = index as usize;
+        Ok(())
+    }
 }
 
 impl<S> Iterator for SamplesBuffer<S>
@@ -95,17 +130,24 @@ where
 
     #[inline]
     fn next(&mut self) -> Option<S> {
-        self.data.next()
+        let sample = self.data.get(self.position).cloned();
+        if sample.is_some() {
+            self.position += 1;
+        }
+        sample
     }
 
     #[inline]
     fn size_hint(&self) -> (usize, Option<usize>) {
-        self.data.size_hint()
+        let remaining = self.data.len() - self.position;
+        (remaining, Some(remaining))
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use std::time::Duration;
+
     use buffer::SamplesBuffer;
     use source::Source;
 
@@ -145,4 +187,28 @@ mod tests {
         assert_eq!(buf.next(), Some(6));
         assert_eq!(buf.next(), None);
     }
+
+    #[test]
+    fn seek() {
+        let mut buf = SamplesBuffer::new(2, 2, vec![1i16, 2, 3, 4, 5, 6]);
+        buf.next();
+        buf.try_seek(Duration::from_millis(500)).unwrap();
+        assert_eq!(buf.next(), Some(3));
+        buf.try_seek(Duration::from_secs(0)).unwrap();
+        assert_eq!(buf.next(), Some(1));
+        buf.try_seek(Duration::from_secs(10)).unwrap();
+        assert_eq!(buf.next(), None);
+    }
+
+    #[test]
+    fn normalize() {
+        let buf = SamplesBuffer::new(1, 44100, vec![0.25f32, -0.5, 0.125]).normalize(1.0);
+        assert_eq!(buf.collect::<Vec<_>>(), [0.5, -1.0, 0.25]);
+    }
+
+    #[test]
+    fn reverse() {
+        let buf = SamplesBuffer::new(2, 44100, vec![1i16, 2, 3, 4, 5, 6]).reverse();
+        assert_eq!(buf.collect::<Vec<_>>(), [5, 6, 3, 4, 1, 2]);
+    }
 }
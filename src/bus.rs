@@ -0,0 +1,627 @@
+//! Buses that mix sounds together and route them into each other, up to a master bus.
+//!
+//! A game typically has a bus for the sound effects, one for the music and one for the voices,
+//! all routed into the master bus. Each bus has its own gain and effects, and can send a part of
+//! its output to other buses, for example to a bus with a reverb.
+//!
+//! ```no_run
+//! use rodio::bus;
+//! use rodio::Sink;
+//!
+//! let device = rodio::default_output_device().unwrap();
+//! let (buses, output) = bus::buses(2, 48000);
+//! rodio::play_raw(&device, output);
+//!
+//! let music = buses.add_bus("music");
+//! music.set_gain(0.5);
+//! let sink = Sink::new_on_bus(&music);
+//! ```
+
+use std::collections::VecDeque;
+use std::error::Error;
+use std::fmt;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use dynamic_mixer::{self, DynamicMixer, DynamicMixerController, MixerInput};
+use sink::{Effect, EffectId};
+use Sample;
+use Source;
+
+/// Builds a new graph of buses, with only the master bus.
+///
+/// All the buses work with the given channels and sample rate, and the sounds added to them are
+/// converted to these values. The output of the master bus is returned, and is typically given
+/// to `play_raw`. It produces silence while there is nothing to play, and ends once the `Buses`
+/// and all the `Bus` handles have been dropped.
+pub fn buses(channels: u16, sample_rate: u32) -> (Buses, BusesOutput) {
+    let shared = Arc::new(Shared {
+        channels,
+        sample_rate,
+        graph: Mutex::new(Graph {
+            buses: Vec::new(),
+            order: vec![MASTER],
+            next_effect_id: 0,
+        }),
+        version: AtomicUsize::new(0),
+    });
+    let buses = Buses { shared };
+    buses.add_bus("master");
+
+    let output = BusesOutput {
+        shared: buses.shared.clone(),
+        version: usize::MAX,
+        buses: Vec::new(),
+        order: Vec::new(),
+        frames: Vec::new(),
+        scratch: Vec::new(),
+        output: Vec::new(),
+        position: 0,
+    };
+    (buses, output)
+}
+
+// Index of the master bus.
+const MASTER: usize = 0;
+
+/// Handle to the graph of buses.
+#[derive(Clone)]
+pub struct Buses {
+    shared: Arc<Shared>,
+}
+
+/// Handle to a bus of a graph.
+#[derive(Clone)]
+pub struct Bus {
+    shared: Arc<Shared>,
+    index: usize,
+}
+
+/// Error returned when buses can't be routed as asked.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RoutingError {
+    /// The routing would send the output of a bus back into itself.
+    Cycle,
+    /// The master bus can't be routed or sent into another bus.
+    Master,
+}
+
+impl fmt::Display for RoutingError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            RoutingError::Cycle => write!(f, "The routing would create a cycle of buses"),
+            RoutingError::Master => write!(f, "The master bus can't be routed"),
+        }
+    }
+}
+
+impl Error for RoutingError {}
+
+struct Shared {
+    channels: u16,
+    sample_rate: u32,
+    graph: Mutex<Graph>,
+    // Incremented every time `graph` is modified.
+    version: AtomicUsize,
+}
+
+struct Graph {
+    buses: Vec<BusConfig>,
+    // Indices of the buses, each one before the buses it is routed or sent into.
+    order: Vec<usize>,
+    next_effect_id: u64,
+}
+
+struct BusConfig {
+    name: String,
+    input: Arc<DynamicMixerController<f32>>,
+    // Output of the mixer of the inputs, until it is taken by the audio thread.
+    mixer: Option<DynamicMixer<f32>>,
+    gain: f32,
+    // Bus into which this one is routed, or `None` for the master.
+    parent: Option<usize>,
+    sends: Vec<(usize, f32)>,
+    effects: Vec<(EffectId, Arc<dyn Effect>)>,
+}
+
+impl BusConfig {
+    // Returns the buses into which this one is routed or sent.
+    fn targets<'a>(&'a self) -> impl Iterator<Item = usize> + 'a {
+        self.parent
+            .into_iter()
+            .chain(self.sends.iter().map(|&(target, _)| target))
+    }
+}
+
+impl Graph {
+    // Returns the buses in an order where each one comes before the buses it is routed or sent
+    // into, or `None` if there is a cycle.
+    fn sort(&self) -> Option<Vec<usize>> {
+        let mut incoming = vec![0; self.buses.len()];
+        for bus in &self.buses {
+            for target in bus.targets() {
+                incoming[target] += 1;
+            }
+        }
+
+        let mut ready = (0 .. self.buses.len()).filter(|&i| incoming[i] == 0).collect::<Vec<_>>();
+        let mut order = Vec::with_capacity(self.buses.len());
+        while let Some(index) = ready.pop() {
+            order.push(index);
+            for target in self.buses[index].targets() {
+                incoming[target] -= 1;
+                if incoming[target] == 0 {
+                    ready.push(target);
+                }
+            }
+        }
+
+        if order.len() == self.buses.len() {
+            Some(order)
+        } else {
+            None
+        }
+    }
+}
+
+impl Shared {
+    // Modifies the graph, and rejects the modification if it creates a cycle.
+    fn modify<F>(&self, modify: F) -> Result<(), RoutingError>
+    where
+        F: FnOnce(&mut Graph),
+    {
+        let mut graph = self.graph.lock().unwrap();
+        let old = graph
+            .buses
+            .iter()
+            .map(|bus| (bus.parent, bus.sends.clone()))
+            .collect::<Vec<_>>();
+        modify(&mut graph);
+
+        match graph.sort() {
+            Some(order) => graph.order = order,
+            None => {
+                for (bus, (parent, sends)) in graph.buses.iter_mut().zip(old) {
+                    bus.parent = parent;
+                    bus.sends = sends;
+                }
+                return Err(RoutingError::Cycle);
+            },
+        }
+        self.version.fetch_add(1, Ordering::SeqCst);
+        Ok(())
+    }
+}
+
+impl Buses {
+    /// Returns the master bus, whose output is the output of the graph.
+    #[inline]
+    pub fn master(&self) -> Bus {
+        Bus {
+            shared: self.shared.clone(),
+            index: MASTER,
+        }
+    }
+
+    /// Returns the bus with the given name, or adds a new one routed into the master bus.
+    pub fn add_bus(&self, name: &str) -> Bus {
+        if let Some(bus) = self.bus(name) {
+            return bus;
+        }
+
+        let (input, mixer) = dynamic_mixer::mixer(self.shared.channels, self.shared.sample_rate);
+
+        let mut index = 0;
+        self.shared
+            .modify(|graph| {
+                index = graph.buses.len();
+                graph.buses.push(BusConfig {
+                    name: name.to_owned(),
+                    input,
+                    mixer: Some(mixer),
+                    gain: 1.0,
+                    parent: if index == MASTER { None } else { Some(MASTER) },
+                    sends: Vec::new(),
+                    effects: Vec::new(),
+                });
+            })
+            .expect("A new bus can't create a cycle");
+
+        Bus {
+            shared: self.shared.clone(),
+            index,
+        }
+    }
+
+    /// Returns the bus with the given name, if there is one.
+    pub fn bus(&self, name: &str) -> Option<Bus> {
+        let graph = self.shared.graph.lock().unwrap();
+        let index = graph.buses.iter().position(|bus| bus.name == name)?;
+        Some(Bus {
+            shared: self.shared.clone(),
+            index,
+        })
+    }
+}
+
+impl Bus {
+    /// Returns the name of the bus.
+    #[inline]
+    pub fn name(&self) -> String {
+        self.shared.graph.lock().unwrap().buses[self.index].name.clone()
+    }
+
+    /// Adds a sound to mix into the bus. The returned handle can change its gain and pan, or
+    /// remove it.
+    #[inline]
+    pub fn add<S>(&self, source: S) -> MixerInput
+    where
+        S: Source + Send + 'static,
+        S::Item: Sample + Send,
+    {
+        let input = self.shared.graph.lock().unwrap().buses[self.index].input.clone();
+        input.add(source.convert_samples())
+    }
+
+    /// Gets the gain of the bus, which multiplies its output.
+    #[inline]
+    pub fn gain(&self) -> f32 {
+        self.shared.graph.lock().unwrap().buses[self.index].gain
+    }
+
+    /// Changes the gain of the bus, which multiplies its output.
+    ///
+    /// The value `1.0` is the "normal" volume. The gain applies to what the bus sends into other
+    /// buses too.
+    #[inline]
+    pub fn set_gain(&self, value: f32) {
+        let index = self.index;
+        let _ = self.shared.modify(|graph| graph.buses[index].gain = value);
+    }
+
+    /// Routes the output of the bus into another bus instead of the one it is routed into.
+    pub fn route_to(&self, parent: &Bus) -> Result<(), RoutingError> {
+        if self.index == MASTER {
+            return Err(RoutingError::Master);
+        }
+        let (index, parent) = (self.index, parent.index);
+        self.shared
+            .modify(|graph| graph.buses[index].parent = Some(parent))
+    }
+
+    /// Sends the output of the bus, multiplied by `level`, into another bus as well as into the
+    /// bus it is routed into. A level of `0.0` removes the send.
+    pub fn set_send(&self, target: &Bus, level: f32) -> Result<(), RoutingError> {
+        if self.index == MASTER {
+            return Err(RoutingError::Master);
+        }
+        let (index, target) = (self.index, target.index);
+        self.shared.modify(|graph| {
+            let sends = &mut graph.buses[index].sends;
+            sends.retain(|&(t, _)| t != target);
+            if level != 0.0 {
+                sends.push((target, level));
+            }
+        })
+    }
+
+    /// Adds an effect at the end of the chain of effects of the bus, applied to the mix of its
+    /// inputs before the gain.
+    ///
+    /// The effects must keep the channels and sample rate of the bus, and are given silence if
+    /// they read ahead of the bus.
+    pub fn add_effect<E>(&self, effect: E) -> EffectId
+    where
+        E: Effect + 'static,
+    {
+        let index = self.index;
+        let mut id = EffectId(0);
+        let _ = self.shared.modify(|graph| {
+            id = EffectId(graph.next_effect_id);
+            graph.next_effect_id += 1;
+            graph.buses[index].effects.push((id, Arc::new(effect)));
+        });
+        id
+    }
+
+    /// Removes an effect added with `add_effect`. Returns false if it was already removed.
+    pub fn remove_effect(&self, id: EffectId) -> bool {
+        let index = self.index;
+        let mut removed = false;
+        let _ = self.shared.modify(|graph| {
+            let effects = &mut graph.buses[index].effects;
+            let len = effects.len();
+            effects.retain(|&(effect_id, _)| effect_id != id);
+            removed = effects.len() != len;
+        });
+        removed
+    }
+}
+
+/// The output of the master bus of a graph. Implements `Source`.
+pub struct BusesOutput {
+    shared: Arc<Shared>,
+    // Value of `shared.version` when the buses below were updated.
+    version: usize,
+    buses: Vec<BusState>,
+    order: Vec<usize>,
+    // Sum of the inputs of each bus for the current frame.
+    frames: Vec<Vec<f32>>,
+    scratch: Vec<f32>,
+    // Current frame of the master bus, and position in it.
+    output: Vec<f32>,
+    position: usize,
+}
+
+// What the audio thread knows about a bus.
+struct BusState {
+    mixer: Option<DynamicMixer<f32>>,
+    gain: f32,
+    parent: Option<usize>,
+    sends: Vec<(usize, f32)>,
+    effect_ids: Vec<EffectId>,
+    effects: Option<EffectChain>,
+}
+
+// The effects of a bus, and the samples given to the first one.
+struct EffectChain {
+    input: Arc<Mutex<VecDeque<f32>>>,
+    output: Box<dyn Source<Item = f32> + Send>,
+}
+
+impl BusesOutput {
+    // Copies the modifications of the graph.
+    fn update(&mut self) {
+        let version = self.shared.version.load(Ordering::SeqCst);
+        if version == self.version {
+            return;
+        }
+        self.version = version;
+
+        let channels = self.shared.channels;
+        let sample_rate = self.shared.sample_rate;
+        let mut graph = self.shared.graph.lock().unwrap();
+        self.order = graph.order.clone();
+        for (index, config) in graph.buses.iter_mut().enumerate() {
+            if index == self.buses.len() {
+                self.buses.push(BusState {
+                    mixer: None,
+                    gain: 1.0,
+                    parent: None,
+                    sends: Vec::new(),
+                    effect_ids: Vec::new(),
+                    effects: None,
+                });
+                self.frames.push(vec![0.0; channels as usize]);
+            }
+
+            let bus = &mut self.buses[index];
+            if let Some(mixer) = config.mixer.take() {
+                bus.mixer = Some(mixer);
+            }
+            bus.gain = config.gain;
+            bus.parent = config.parent;
+            bus.sends = config.sends.clone();
+
+            let effect_ids = config.effects.iter().map(|&(id, _)| id).collect::<Vec<_>>();
+            if effect_ids != bus.effect_ids {
+                bus.effects = if config.effects.is_empty() {
+                    None
+                } else {
+                    let input = Arc::new(Mutex::new(VecDeque::new()));
+                    let mut output = Box::new(BusInput {
+                        samples: input.clone(),
+                        channels,
+                        sample_rate,
+                    }) as Box<dyn Source<Item = f32> + Send>;
+                    for (_, effect) in &config.effects {
+                        output = effect.apply(output);
+                    }
+                    Some(EffectChain { input, output })
+                };
+                bus.effect_ids = effect_ids;
+            }
+        }
+    }
+
+    // Computes the next frame of all the buses.
+    fn next_frame(&mut self) {
+        self.update();
+
+        for frame in &mut self.frames {
+            for value in frame.iter_mut() {
+                *value = 0.0;
+            }
+        }
+
+        for &index in &self.order {
+            let bus = &mut self.buses[index];
+            self.scratch.clear();
+            self.scratch.extend_from_slice(&self.frames[index]);
+            if let Some(ref mut mixer) = bus.mixer {
+                for value in &mut self.scratch {
+                    *value += mixer.next().unwrap_or(0.0);
+                }
+            }
+
+            if let Some(ref mut effects) = bus.effects {
+                effects.input.lock().unwrap().extend(self.scratch.iter().cloned());
+                for value in &mut self.scratch {
+                    *value = effects.output.next().unwrap_or(0.0);
+                }
+            }
+
+            for value in &mut self.scratch {
+                *value *= bus.gain;
+            }
+            match bus.parent {
+                Some(parent) => add(&mut self.frames[parent], &self.scratch, 1.0),
+                None => self.output.clone_from(&self.scratch),
+            }
+            for &(target, level) in &bus.sends {
+                add(&mut self.frames[target], &self.scratch, level);
+            }
+        }
+        self.position = 0;
+    }
+}
+
+// Adds `input` multiplied by `level` to `output`.
+#[inline]
+fn add(output: &mut [f32], input: &[f32], level: f32) {
+    for (output, input) in output.iter_mut().zip(input) {
+        *output += input * level;
+    }
+}
+
+impl Iterator for BusesOutput {
+    type Item = f32;
+
+    #[inline]
+    fn next(&mut self) -> Option<f32> {
+        if self.position >= self.output.len() {
+            // Nobody can add sounds or modify the buses anymore.
+            if Arc::strong_count(&self.shared) == 1 {
+                return None;
+            }
+            self.next_frame();
+        }
+
+        let value = self.output[self.position];
+        self.position += 1;
+        Some(value)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, None)
+    }
+}
+
+impl Source for BusesOutput {
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        None
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        self.shared.channels
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        self.shared.sample_rate
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        None
+    }
+}
+
+// The mix of the inputs of a bus, given to its first effect.
+struct BusInput {
+    samples: Arc<Mutex<VecDeque<f32>>>,
+    channels: u16,
+    sample_rate: u32,
+}
+
+impl Iterator for BusInput {
+    type Item = f32;
+
+    #[inline]
+    fn next(&mut self) -> Option<f32> {
+        Some(self.samples.lock().unwrap().pop_front().unwrap_or(0.0))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (usize::MAX, None)
+    }
+}
+
+impl Source for BusInput {
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        None
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        self.channels
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use buffer::SamplesBuffer;
+    use bus::{self, RoutingError};
+    use sink::Sink;
+    use source::Source;
+
+    #[test]
+    fn gain_and_routing() {
+        let (buses, mut output) = bus::buses(1, 1000);
+        let music = buses.add_bus("music");
+        let sfx = buses.add_bus("sfx");
+        music.set_gain(0.5);
+        buses.master().set_gain(2.0);
+        assert_eq!(buses.bus("music").unwrap().gain(), 0.5);
+
+        music.add(SamplesBuffer::new(1, 1000, vec![0.5f32, 0.25]));
+        sfx.add(SamplesBuffer::new(1, 1000, vec![0.25f32, 0.125]));
+        assert_eq!(output.next(), Some(1.0));
+        assert_eq!(output.next(), Some(0.5));
+        assert_eq!(output.next(), Some(0.0));
+
+        // Routing the sfx bus into the music bus applies the gain of both.
+        sfx.route_to(&music).unwrap();
+        sfx.add(SamplesBuffer::new(1, 1000, vec![0.5f32]));
+        assert_eq!(output.next(), Some(0.5));
+    }
+
+    #[test]
+    fn sends() {
+        let (buses, mut output) = bus::buses(1, 1000);
+        let voice = buses.add_bus("voice");
+        let reverb = buses.add_bus("reverb");
+        reverb.set_gain(0.5);
+        voice.set_send(&reverb, 0.5).unwrap();
+
+        voice.add(SamplesBuffer::new(1, 1000, vec![0.5f32]));
+        assert_eq!(output.next(), Some(0.625));
+
+        assert_eq!(reverb.set_send(&voice, 1.0), Err(RoutingError::Cycle));
+        assert_eq!(buses.master().route_to(&voice), Err(RoutingError::Master));
+    }
+
+    #[test]
+    fn effects() {
+        let (buses, mut output) = bus::buses(1, 48000);
+        let sfx = buses.add_bus("sfx");
+        let id = sfx.add_effect(|input: Box<dyn Source<Item = f32> + Send>| {
+            Box::new(input.amplify(2.0)) as Box<_>
+        });
+
+        let sink = Sink::new_on_bus(&sfx);
+        sink.append(SamplesBuffer::new(1, 48000, vec![0.25f32, 0.125, 0.25, 0.125]));
+        assert_eq!(output.next(), Some(0.5));
+        assert_eq!(output.next(), Some(0.25));
+
+        assert!(sfx.remove_effect(id));
+        assert_eq!(output.next(), Some(0.25));
+        assert_eq!(output.next(), Some(0.125));
+    }
+}
@@ -0,0 +1,160 @@
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Condvar, Mutex};
+use std::task::{Context, Poll, Waker};
+use std::time::{Duration, Instant};
+
+/// Builds a `Completion` and the `Completer` that completes it.
+pub(crate) fn completion() -> (Completion, Completer) {
+    let shared = Arc::new(CompletionShared {
+        state: Mutex::new(CompletionState {
+            done: false,
+            wakers: Vec::new(),
+        }),
+        condvar: Condvar::new(),
+    });
+    let completion = Completion {
+        shared: shared.clone(),
+    };
+    (completion, Completer { shared })
+}
+
+/// Handle to a sound that has been played, which completes when the sound ends, is stopped or is
+/// removed.
+///
+/// A thread can block until the end of the sound with `wait`, and async code can await the
+/// handle, which is a `Future`. Unlike `Sink::sleep_until_end`, this only waits for this sound.
+#[derive(Clone, Debug)]
+pub struct Completion {
+    shared: Arc<CompletionShared>,
+}
+
+#[derive(Debug)]
+struct CompletionShared {
+    state: Mutex<CompletionState>,
+    condvar: Condvar,
+}
+
+#[derive(Debug)]
+struct CompletionState {
+    done: bool,
+    // Tasks to wake when the sound completes.
+    wakers: Vec<Waker>,
+}
+
+impl Completion {
+    /// Returns true if the sound has completed.
+    #[inline]
+    pub fn is_done(&self) -> bool {
+        self.shared.state.lock().unwrap().done
+    }
+
+    /// Blocks the current thread until the sound completes.
+    pub fn wait(&self) {
+        let mut state = self.shared.state.lock().unwrap();
+        while !state.done {
+            state = self.shared.condvar.wait(state).unwrap();
+        }
+    }
+
+    /// Blocks the current thread until the sound completes, or for at most `timeout`.
+    ///
+    /// Returns true if the sound has completed.
+    pub fn wait_timeout(&self, timeout: Duration) -> bool {
+        let deadline = Instant::now() + timeout;
+        let mut state = self.shared.state.lock().unwrap();
+        while !state.done {
+            let now = Instant::now();
+            if now >= deadline {
+                return false;
+            }
+            state = self.shared.condvar.wait_timeout(state, deadline - now).unwrap().0;
+        }
+        true
+    }
+}
+
+impl Future for Completion {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<()> {
+        let mut state = self.shared.state.lock().unwrap();
+        if state.done {
+            return Poll::Ready(());
+        }
+        if !state.wakers.iter().any(|waker| waker.will_wake(cx.waker())) {
+            state.wakers.push(cx.waker().clone());
+        }
+        Poll::Pending
+    }
+}
+
+/// Completes a `Completion` when `complete` is called or when it is dropped, so that the
+/// `Completion` of a sound that is dropped before its end still completes.
+#[derive(Debug)]
+pub(crate) struct Completer {
+    shared: Arc<CompletionShared>,
+}
+
+impl Completer {
+    pub(crate) fn complete(&self) {
+        let wakers = {
+            let mut state = self.shared.state.lock().unwrap();
+            if state.done {
+                return;
+            }
+            state.done = true;
+            self.shared.condvar.notify_all();
+            state.wakers.split_off(0)
+        };
+        for waker in wakers {
+            waker.wake();
+        }
+    }
+}
+
+impl Drop for Completer {
+    #[inline]
+    fn drop(&mut self) {
+        self.complete();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::future::Future;
+    use std::pin::Pin;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::task::{Context, Poll, Wake, Waker};
+    use std::thread;
+    use std::time::Duration;
+
+    use completion::completion;
+
+    struct CountWakes(AtomicUsize);
+
+    impl Wake for CountWakes {
+        fn wake(self: Arc<Self>) {
+            self.0.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    #[test]
+    fn wait_and_poll() {
+        let (mut completion, completer) = completion();
+        assert!(!completion.wait_timeout(Duration::from_millis(1)));
+
+        let wakes = Arc::new(CountWakes(AtomicUsize::new(0)));
+        let waker = Waker::from(wakes.clone());
+        let mut cx = Context::from_waker(&waker);
+        assert_eq!(Pin::new(&mut completion).poll(&mut cx), Poll::Pending);
+
+        let thread = thread::spawn(move || drop(completer));
+        completion.wait();
+        thread.join().unwrap();
+        assert!(completion.is_done());
+        assert_eq!(wakes.0.load(Ordering::SeqCst), 1);
+        assert_eq!(Pin::new(&mut completion).poll(&mut cx), Poll::Ready(()));
+    }
+}
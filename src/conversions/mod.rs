@@ -6,12 +6,21 @@ This includes conversion between sample formats, channels or sample rates.
 */
 
 pub use self::channels::ChannelCountConverter;
-pub use self::sample::DataConverter;
+pub use self::sample::{DataConverter, Dither};
 pub use self::sample::Sample;
-pub use self::sample_rate::SampleRateConverter;
+pub use self::resampler::Resampler;
+pub use self::sample_rate::{ResampleQuality, SampleRateConverter};
+pub(crate) use self::random::Random;
+pub(crate) use self::resampler::Resampling;
+pub(crate) use self::units::{
+    amplitude_to_db, db_to_amplitude, duration_to_frames, duration_to_ns, frames_to_ns,
+};
 
 mod channels;
+mod random;
+mod resampler;
 // TODO: < shouldn't be public ; there's a bug in Rust 1.4 and below that makes This
 // `pub` mandatory
 pub mod sample;
 mod sample_rate;
+mod units;
@@ -0,0 +1,67 @@
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+
+/// Xorshift generator of the noises and of the random variations, which is fast and good enough
+/// for audio.
+#[derive(Clone, Debug)]
+pub(crate) struct Random {
+    // Never zero.
+    state: u64,
+}
+
+impl Random {
+    /// Builds a generator with a random seed.
+    #[inline]
+    pub(crate) fn new() -> Random {
+        // `RandomState` is randomly seeded by the standard library for every instance.
+        let seed = RandomState::new().build_hasher().finish();
+        Random::with_seed(seed)
+    }
+
+    /// Builds a generator that always produces the same values for a given seed.
+    #[inline]
+    pub(crate) fn with_seed(seed: u64) -> Random {
+        Random {
+            // A state of zero would make the xorshift generator produce zero forever.
+            state: if seed == 0 { 0x9e37_79b9_7f4a_7c15 } else { seed },
+        }
+    }
+
+    /// Returns a random value uniformly distributed between `0.0` and `1.0`.
+    #[inline]
+    pub(crate) fn unit(&mut self) -> f32 {
+        // The 24 upper bits fit exactly in the mantissa of a `f32`.
+        (self.next_u64() >> 40) as f32 / (1u32 << 24) as f32
+    }
+
+    /// Returns a random value uniformly distributed between `-1.0` and `1.0`.
+    #[inline]
+    pub(crate) fn signed(&mut self) -> f32 {
+        (self.next_u64() >> 40) as f32 / (1u32 << 23) as f32 - 1.0
+    }
+
+    #[inline]
+    fn next_u64(&mut self) -> u64 {
+        // xorshift64
+        self.state ^= self.state << 13;
+        self.state ^= self.state >> 7;
+        self.state ^= self.state << 17;
+        self.state
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use conversions::random::Random;
+
+    #[test]
+    fn ranges() {
+        let mut random = Random::with_seed(0);
+        for _ in 0 .. 1000 {
+            let unit = random.unit();
+            assert!(unit >= 0.0 && unit < 1.0);
+            let signed = random.signed();
+            assert!(signed >= -1.0 && signed < 1.0);
+        }
+    }
+}
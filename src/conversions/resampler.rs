@@ -0,0 +1,418 @@
+use conversions::Sample;
+
+use std::f32::consts::PI;
+use std::fmt;
+use std::marker::PhantomData;
+
+/// Number of values of the sinc kernel computed between two of its zero crossings. The values in
+/// between are linearly interpolated.
+const KERNEL_RESOLUTION: usize = 256;
+
+/// Highest number of coefficients in the filter bank of a `SincResampler`. Above this, the
+/// kernel is interpolated for each output frame instead.
+const MAX_COEFFICIENTS: usize = 1 << 18;
+
+/// Number of input frames given to a `Resampler` at once.
+const BLOCK_FRAMES: usize = 64;
+
+/// Algorithm that converts interleaved samples from a sample rate to another.
+///
+/// Implement this trait to convert sounds with another library, for example libsamplerate or
+/// rubato, and pass it to `UniformSourceIterator::with_resampler`,
+/// `DynamicMixerController::set_resampler` or `rodio::set_resampler`.
+pub trait Resampler: Send {
+    /// Prepares the resampler for a new sound, forgetting everything about the previous one.
+    fn reset(&mut self, from: u32, to: u32, channels: u16);
+
+    /// Converts some frames of the sound and appends the converted frames to `output`.
+    ///
+    /// The resampler can keep the last frames of the input until it is given the frames that
+    /// follow them.
+    fn process(&mut self, input: &[f32], output: &mut Vec<f32>);
+
+    /// Appends the remaining frames to `output`, as if the sound ended with silence.
+    fn flush(&mut self, output: &mut Vec<f32>);
+
+    /// Returns a copy of this resampler, including its state.
+    fn box_clone(&self) -> Box<dyn Resampler>;
+}
+
+impl Clone for Box<dyn Resampler> {
+    #[inline]
+    fn clone(&self) -> Box<dyn Resampler> {
+        self.box_clone()
+    }
+}
+
+/// Windowed-sinc interpolation.
+///
+/// Each output sample is the sum of the input samples around its position, weighted by a sinc
+/// kernel. When the sample rate is lowered, the kernel is stretched so that it also removes the
+/// frequencies that the output can't represent.
+///
+/// The output frames are at `to` different offsets from the input frames, so the weights are
+/// computed once for each offset, as a polyphase filter bank.
+#[derive(Clone, Debug)]
+pub(crate) struct SincResampler {
+    // Values of the kernel from its center to its last zero crossing.
+    kernel: Vec<f32>,
+    zero_crossings: usize,
+    from: u32,
+    to: u32,
+    channels: usize,
+    // Cutoff frequency of the kernel, relative to the Nyquist frequency of the input.
+    cutoff: f32,
+    // Number of input frames used on each side of an output frame.
+    half_width: usize,
+    // Weights of the `2 * half_width` input frames around an output frame, for each value of
+    // `fraction`. Empty if there would be more than `MAX_COEFFICIENTS`.
+    phases: Vec<f32>,
+    // Interleaved input frames around the position of the next output frame.
+    buffer: Vec<f32>,
+    // Index of the first frame of `buffer` in the input. Negative for the silence before the
+    // input.
+    buffer_start: i64,
+    // Position of the next output frame in the input, in frames, is `position + fraction / to`.
+    position: i64,
+    fraction: u32,
+}
+
+impl SincResampler {
+    pub(crate) fn new(zero_crossings: usize) -> SincResampler {
+        // Blackman-windowed sinc.
+        let len = zero_crossings * KERNEL_RESOLUTION;
+        let kernel = (0 ..= len)
+            .map(|i| {
+                let x = i as f32 / KERNEL_RESOLUTION as f32;
+                let sinc = if i == 0 { 1.0 } else { (PI * x).sin() / (PI * x) };
+                let u = i as f32 / len as f32;
+                let window = 0.42 + 0.5 * (PI * u).cos() + 0.08 * (2.0 * PI * u).cos();
+                sinc * window
+            })
+            .collect();
+
+        let mut resampler = SincResampler {
+            kernel,
+            zero_crossings,
+            from: 0,
+            to: 0,
+            channels: 1,
+            cutoff: 1.0,
+            half_width: 0,
+            phases: Vec::new(),
+            buffer: Vec::new(),
+            buffer_start: 0,
+            position: 0,
+            fraction: 0,
+        };
+        resampler.reset(1, 1, 1);
+        resampler
+    }
+
+    // Returns the value of the kernel at `x` input frames from its center.
+    #[inline]
+    fn kernel(&self, x: f32) -> f32 {
+        let index = x.abs() * self.cutoff * KERNEL_RESOLUTION as f32;
+        let i = index as usize;
+        if i + 1 >= self.kernel.len() {
+            return 0.0;
+        }
+        let frac = index - i as f32;
+        self.cutoff * (self.kernel[i] + (self.kernel[i + 1] - self.kernel[i]) * frac)
+    }
+
+    // Returns the weight of the input frame `tap` of the `2 * half_width` frames around an output
+    // frame, which is `offset` frames after the input frame `half_width - 1`.
+    #[inline]
+    fn weight(&self, tap: usize, offset: f32) -> f32 {
+        self.kernel((tap as i64 - self.half_width as i64 + 1) as f32 - offset)
+    }
+
+    // Returns the index of the frame after the last one in `buffer`.
+    #[inline]
+    fn buffer_end(&self) -> i64 {
+        self.buffer_start + (self.buffer.len() / self.channels) as i64
+    }
+
+    // Computes the output frames whose position is before `end`. The input frames up to
+    // `half_width` after the last one must be in `buffer`.
+    fn produce(&mut self, end: i64, output: &mut Vec<f32>) {
+        let channels = self.channels;
+        let half_width = self.half_width as i64;
+        let taps = 2 * self.half_width;
+        while self.position < end {
+            let first = self.position - half_width + 1;
+            let start = (first - self.buffer_start) as usize * channels;
+            let frames = &self.buffer[start .. start + taps * channels];
+            let base = output.len();
+            output.resize(base + channels, 0.0);
+            let sums = &mut output[base ..];
+
+            if self.phases.is_empty() {
+                let offset = self.fraction as f32 / self.to as f32;
+                for (tap, samples) in frames.chunks(channels).enumerate() {
+                    let weight = self.weight(tap, offset);
+                    for (sum, sample) in sums.iter_mut().zip(samples) {
+                        *sum += sample * weight;
+                    }
+                }
+            } else {
+                let phase = self.fraction as usize * taps;
+                let weights = &self.phases[phase .. phase + taps];
+                for (samples, weight) in frames.chunks(channels).zip(weights) {
+                    for (sum, sample) in sums.iter_mut().zip(samples) {
+                        *sum += sample * weight;
+                    }
+                }
+            }
+
+            self.fraction += self.from;
+            self.position += (self.fraction / self.to) as i64;
+            self.fraction %= self.to;
+        }
+
+        // Drops the frames that are not needed anymore.
+        let first_needed = self.position - half_width + 1;
+        if first_needed > self.buffer_start {
+            let drop = (first_needed - self.buffer_start) as usize;
+            let drop = drop.min(self.buffer.len() / channels);
+            self.buffer.drain(.. drop * channels);
+            self.buffer_start += drop as i64;
+        }
+    }
+}
+
+impl Resampler for SincResampler {
+    fn reset(&mut self, from: u32, to: u32, channels: u16) {
+        let gcd = gcd(from, to);
+        let (from, to) = (from / gcd, to / gcd);
+        self.channels = channels as usize;
+
+        if from != self.from || to != self.to {
+            self.from = from;
+            self.to = to;
+            // The cutoff is slightly under the Nyquist frequency, so that the transition band of
+            // the kernel stays below it.
+            self.cutoff = (to as f32 / from as f32).min(1.0) * 0.95;
+            self.half_width = (self.zero_crossings as f32 / self.cutoff).ceil() as usize;
+
+            let taps = 2 * self.half_width;
+            self.phases.clear();
+            if to as usize * taps <= MAX_COEFFICIENTS {
+                for fraction in 0 .. to {
+                    let offset = fraction as f32 / to as f32;
+                    for tap in 0 .. taps {
+                        let weight = self.weight(tap, offset);
+                        self.phases.push(weight);
+                    }
+                }
+            }
+        }
+
+        self.buffer.clear();
+        self.buffer.resize(self.half_width * self.channels, 0.0);
+        self.buffer_start = -(self.half_width as i64);
+        self.position = 0;
+        self.fraction = 0;
+    }
+
+    fn process(&mut self, input: &[f32], output: &mut Vec<f32>) {
+        self.buffer.extend_from_slice(input);
+        // An output frame needs the input frames up to `half_width` after its position.
+        let end = self.buffer_end() - self.half_width as i64;
+        self.produce(end, output);
+    }
+
+    fn flush(&mut self, output: &mut Vec<f32>) {
+        let end = self.buffer_end();
+        let len = self.buffer.len() + self.half_width * self.channels;
+        self.buffer.resize(len, 0.0);
+        self.produce(end, output);
+
+        let (from, to, channels) = (self.from, self.to, self.channels as u16);
+        self.reset(from, to, channels);
+    }
+
+    fn box_clone(&self) -> Box<dyn Resampler> {
+        Box::new(self.clone())
+    }
+}
+
+/// Feeds the samples of an iterator to a `Resampler`.
+#[derive(Clone)]
+pub(crate) struct Resampling<S> {
+    resampler: Box<dyn Resampler>,
+    from: u32,
+    to: u32,
+    channels: usize,
+    // Interleaved frames given to the resampler.
+    block: Vec<f32>,
+    // Interleaved frames returned by the resampler.
+    output: Vec<f32>,
+    output_pos: usize,
+    // Set once the input has ended.
+    input_ended: bool,
+    // Whether samples were read from the current input, and whether to keep the last frames in
+    // the resampler when it ends.
+    read_input: bool,
+    hold: bool,
+    // Set once the resampler was flushed.
+    flushed: bool,
+    marker: PhantomData<S>,
+}
+
+impl<S> Resampling<S>
+where
+    S: Sample,
+{
+    pub(crate) fn new(
+        resampler: Box<dyn Resampler>, from: u32, to: u32, channels: u16,
+    ) -> Resampling<S> {
+        let mut resampling = Resampling {
+            resampler,
+            from: 0,
+            to: 0,
+            channels: 0,
+            block: Vec::new(),
+            output: Vec::new(),
+            output_pos: 0,
+            input_ended: false,
+            read_input: false,
+            hold: false,
+            flushed: false,
+            marker: PhantomData,
+        };
+        resampling.restart(from, to, channels);
+        resampling
+    }
+
+    /// Prepares for a new input that follows the previous one. The frames kept by the resampler
+    /// are only used if the parameters didn't change. The buffers are reused either way.
+    pub(crate) fn restart(&mut self, from: u32, to: u32, channels: u16) {
+        let channels = channels as usize;
+        if self.flushed || from != self.from || to != self.to || channels != self.channels {
+            self.resampler.reset(from, to, channels as u16);
+            self.from = from;
+            self.to = to;
+            self.channels = channels;
+            self.output.clear();
+            self.output_pos = 0;
+            self.flushed = false;
+        }
+        self.input_ended = false;
+        self.read_input = false;
+    }
+
+    /// If `hold` is true, the frames kept by the resampler are not flushed when the input ends,
+    /// unless the input is empty.
+    #[inline]
+    pub(crate) fn set_hold(&mut self, hold: bool) {
+        self.hold = hold;
+    }
+
+    pub(crate) fn next<I>(&mut self, input: &mut I) -> Option<S>
+    where
+        I: Iterator<Item = S>,
+    {
+        loop {
+            if self.output_pos < self.output.len() {
+                self.output_pos += 1;
+                return Some(S::from_sample(&self.output[self.output_pos - 1]));
+            }
+            self.output.clear();
+            self.output_pos = 0;
+
+            if self.input_ended {
+                if self.flushed || (self.hold && self.read_input) {
+                    return None;
+                }
+                self.resampler.flush(&mut self.output);
+                self.flushed = true;
+                continue;
+            }
+
+            self.block.clear();
+            for _ in 0 .. BLOCK_FRAMES * self.channels {
+                match input.next() {
+                    Some(value) => self.block.push(value.to_f32()),
+                    None => {
+                        self.input_ended = true;
+                        break;
+                    },
+                }
+            }
+            if self.block.is_empty() {
+                continue;
+            }
+            // Completes the last frame if the input ended in the middle of it.
+            while !self.block.len().is_multiple_of(self.channels) {
+                self.block.push(0.0);
+            }
+            self.read_input = true;
+            self.resampler.process(&self.block, &mut self.output);
+        }
+    }
+
+    /// Returns an estimate of the number of samples left to output, given the number of samples
+    /// left in the input.
+    pub(crate) fn remaining(&self, input_samples: usize) -> usize {
+        let frames = (input_samples / self.channels) as u64;
+        let frames = (frames * self.to as u64).div_ceil(self.from as u64);
+        frames as usize * self.channels + self.output.len() - self.output_pos
+    }
+}
+
+impl<S> fmt::Debug for Resampling<S> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Resampling")
+            .field("from", &self.from)
+            .field("to", &self.to)
+            .field("channels", &self.channels)
+            .finish()
+    }
+}
+
+#[inline]
+fn gcd(a: u32, b: u32) -> u32 {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Resampler, SincResampler};
+    use std::f32::consts::PI;
+
+    fn resample(resampler: &mut SincResampler, input: &[f32]) -> Vec<f32> {
+        let mut output = Vec::new();
+        for block in input.chunks(100) {
+            resampler.process(block, &mut output);
+        }
+        resampler.flush(&mut output);
+        output
+    }
+
+    #[test]
+    fn filter_bank_matches_kernel() {
+        let input = (0 .. 4410)
+            .map(|i| (2.0 * PI * 1000.0 * i as f32 / 44100.0).sin())
+            .collect::<Vec<_>>();
+
+        let mut bank = SincResampler::new(8);
+        bank.reset(44100, 48000, 1);
+        assert!(!bank.phases.is_empty());
+        let mut kernel = bank.clone();
+        kernel.phases.clear();
+
+        let bank = resample(&mut bank, &input);
+        let kernel = resample(&mut kernel, &input);
+        assert_eq!(bank.len(), 4800);
+        assert_eq!(bank.len(), kernel.len());
+        for (a, b) in bank.iter().zip(&kernel) {
+            assert!((a - b).abs() < 1e-5, "{} {}", a, b);
+        }
+    }
+}
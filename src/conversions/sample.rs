@@ -1,10 +1,38 @@
-use cpal::Sample as CpalSample;
 use std::marker::PhantomData;
 
+use conversions::Random;
+
+/// Amplitude of the loudest negative 24-bit sample.
+const I24_SCALE: i32 = 1 << 23;
+
+/// Amplitude of the loudest negative `i64` sample.
+const I64_SCALE: f64 = 9_223_372_036_854_775_808.0;
+
+/// Noise added to the samples when they are converted to an integer format.
+///
+/// Converting to a format with fewer bits rounds each sample, and on quiet sounds the rounding
+/// errors follow the signal, which is heard as distortion. Adding a tiny noise before rounding
+/// turns this distortion into a constant and much less audible hiss.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Dither {
+    /// The samples are only rounded.
+    None,
+    /// Noise with a triangular probability distribution, of up to one step of the output format
+    /// in each direction, is added before rounding.
+    Triangular,
+    /// Same as `Triangular`, but the rounding errors are also fed back into the next samples of
+    /// the same channel, which moves the hiss to the high frequencies where it is less audible.
+    NoiseShaped,
+}
+
 /// Converts the samples data type to `O`.
 #[derive(Clone, Debug)]
 pub struct DataConverter<I, O> {
     input: I,
+    ditherer: Ditherer,
+    channels: u16,
+    // Channel of the next sample.
+    current_channel: u16,
     marker: PhantomData<O>,
 }
 
@@ -12,12 +40,42 @@ impl<I, O> DataConverter<I, O> {
     /// Builds a new converter.
     #[inline]
     pub fn new(input: I) -> DataConverter<I, O> {
+        DataConverter::with_dither(input, Dither::None, 1)
+    }
+
+    /// Builds a new converter that dithers the samples when `O` is an integer format.
+    ///
+    /// `channels` is the number of interleaved channels of the input, which the noise shaping
+    /// needs to keep the channels apart.
+    ///
+    /// # Panic
+    ///
+    /// Panics if `channels` is zero.
+    #[inline]
+    pub fn with_dither(input: I, dither: Dither, channels: u16) -> DataConverter<I, O> {
+        assert!(channels >= 1);
+
         DataConverter {
-            input: input,
+            input,
+            ditherer: Ditherer::new(dither),
+            channels,
+            current_channel: 0,
             marker: PhantomData,
         }
     }
 
+    /// Returns a reference to the underlying iterator.
+    #[inline]
+    pub fn inner(&self) -> &I {
+        &self.input
+    }
+
+    /// Returns a mutable reference to the underlying iterator.
+    #[inline]
+    pub fn inner_mut(&mut self) -> &mut I {
+        &mut self.input
+    }
+
     /// Destroys this iterator and returns the underlying iterator.
     #[inline]
     pub fn into_inner(self) -> I {
@@ -35,7 +93,13 @@ where
 
     #[inline]
     fn next(&mut self) -> Option<O> {
-        self.input.next().map(|s| CpalSample::from(&s))
+        let sample = self.input.next()?;
+        if !self.ditherer.is_enabled() {
+            return Some(Sample::from_sample(&sample));
+        }
+        let channel = self.current_channel;
+        self.current_channel = (self.current_channel + 1) % self.channels;
+        Some(self.ditherer.convert(sample, channel))
     }
 
     #[inline]
@@ -44,6 +108,76 @@ where
     }
 }
 
+/// Converts samples from one type to another, and applies a `Dither` if the output is an
+/// integer format.
+#[derive(Clone, Debug)]
+pub(crate) struct Ditherer {
+    dither: Dither,
+    // Rounding error of the last sample of each channel, for the noise shaping.
+    errors: Vec<f32>,
+    // Generator of the noise, seeded when the first noise is needed.
+    generator: Option<Random>,
+}
+
+impl Ditherer {
+    #[inline]
+    pub(crate) fn new(dither: Dither) -> Ditherer {
+        Ditherer {
+            dither,
+            errors: Vec::new(),
+            generator: None,
+        }
+    }
+
+    #[inline]
+    pub(crate) fn set_dither(&mut self, dither: Dither) {
+        self.dither = dither;
+    }
+
+    /// Returns true if the samples are dithered, when the output is an integer format.
+    #[inline]
+    pub(crate) fn is_enabled(&self) -> bool {
+        self.dither != Dither::None
+    }
+
+    /// Converts a sample of the given channel.
+    #[inline]
+    pub(crate) fn convert<S, O>(&mut self, sample: S, channel: u16) -> O
+    where
+        S: Sample,
+        O: Sample,
+    {
+        // Formats with more bits than the mantissa of a `f32` don't need any dither.
+        let bits = match O::integer_bits() {
+            Some(bits) if self.dither != Dither::None && bits <= 24 => bits,
+            _ => return Sample::from_sample(&sample),
+        };
+
+        let channel = channel as usize;
+        if self.errors.len() <= channel {
+            self.errors.resize(channel + 1, 0.0);
+        }
+
+        let scale = (1 << (bits - 1)) as f32;
+        let mut value = sample.to_f32() * scale;
+        if self.dither == Dither::NoiseShaped {
+            value -= self.errors[channel];
+        }
+        let noise = self.random() - self.random();
+        let rounded = (value + noise).round().clamp(-scale, scale - 1.0);
+        self.errors[channel] = rounded - value;
+
+        // Goes through a 24-bit sample, which all the integer formats convert from exactly.
+        Sample::from_sample(&((rounded as i32) << (24 - bits)))
+    }
+
+    // Returns a random value uniformly distributed between `0.0` and `1.0`.
+    #[inline]
+    fn random(&mut self) -> f32 {
+        self.generator.get_or_insert_with(Random::new).unit()
+    }
+}
+
 impl<I, O> ExactSizeIterator for DataConverter<I, O>
 where
     I: ExactSizeIterator,
@@ -54,18 +188,25 @@ where
 
 /// Represents a value of a single sample.
 ///
-/// This trait is implemented by default on three types: `i16`, `u16` and `f32`.
+/// This trait is implemented by default on seven types: `u8`, `i16`, `u16`, `i32`, `i64`, `f32`
+/// and `f64`.
 ///
+/// - For `u8`, silence corresponds to the value `128`. The minimum and maximum amplitudes are
+///   represented by `0` and `u8::max_value()` respectively.
 /// - For `i16`, silence corresponds to the value `0`. The minimum and maximum amplitudes are
 ///   represented by `i16::min_value()` and `i16::max_value()` respectively.
 /// - For `u16`, silence corresponds to the value `u16::max_value() / 2`. The minimum and maximum
 ///   amplitudes are represented by `0` and `u16::max_value()` respectively.
-/// - For `f32`, silence corresponds to the value `0.0`. The minimum and maximum amplitudes are
-///  represented by `-1.0` and `1.0` respectively.
+/// - For `i32`, the samples have 24 bits. Silence corresponds to the value `0`. The minimum and
+///   maximum amplitudes are represented by `-8388608` and `8388607` respectively.
+/// - For `i64`, silence corresponds to the value `0`. The minimum and maximum amplitudes are
+///   represented by `i64::min_value()` and `i64::max_value()` respectively.
+/// - For `f32` and `f64`, silence corresponds to the value `0.0`. The minimum and maximum
+///   amplitudes are represented by `-1.0` and `1.0` respectively.
 ///
 /// You can implement this trait on your own type as well if you wish so.
 ///
-pub trait Sample: CpalSample {
+pub trait Sample: Copy + Clone {
     /// Linear interpolation between two samples.
     ///
     /// The result should be equal to
@@ -79,6 +220,91 @@ pub trait Sample: CpalSample {
 
     /// Returns the value corresponding to the absence of sound.
     fn zero_value() -> Self;
+
+    /// Returns the number of bits of an integer sample type, or `None` for a floating-point one.
+    fn integer_bits() -> Option<u32>;
+
+    /// Turns the sample into its equivalent as a `f32`.
+    fn to_f32(&self) -> f32;
+
+    /// Turns the sample into its equivalent as a `f64`.
+    #[inline]
+    fn to_f64(&self) -> f64 {
+        self.to_f32() as f64
+    }
+
+    /// Converts this sample into a standard `i16` sample.
+    fn to_i16(&self) -> i16;
+
+    /// Converts this sample into a standard `u16` sample.
+    #[inline]
+    fn to_u16(&self) -> u16 {
+        (self.to_i16() as i32 + 32768) as u16
+    }
+
+    /// Converts this sample into a 24-bit `i32` sample.
+    #[inline]
+    fn to_i32(&self) -> i32 {
+        (self.to_i16() as i32) << 8
+    }
+
+    /// Converts this sample into a `i64` sample.
+    #[inline]
+    fn to_i64(&self) -> i64 {
+        (self.to_i32() as i64) << 40
+    }
+
+    /// Converts any sample type to this one.
+    fn from_sample<S>(sample: &S) -> Self
+    where
+        S: Sample;
+}
+
+impl Sample for u8 {
+    #[inline]
+    fn lerp(first: u8, second: u8, numerator: u32, denominator: u32) -> u8 {
+        (first as i32 + (second as i32 - first as i32) * numerator as i32 / denominator as i32)
+            as u8
+    }
+
+    #[inline]
+    fn amplify(self, value: f32) -> u8 {
+        Sample::from_sample(&self.to_i16().amplify(value))
+    }
+
+    #[inline]
+    fn saturating_add(self, other: u8) -> u8 {
+        // The values are offset by the silence at `128`.
+        (self as i16 - 128 + other as i16).clamp(0, 255) as u8
+    }
+
+    #[inline]
+    fn zero_value() -> u8 {
+        128
+    }
+
+    #[inline]
+    fn integer_bits() -> Option<u32> {
+        Some(8)
+    }
+
+    #[inline]
+    fn to_f32(&self) -> f32 {
+        self.to_i16().to_f32()
+    }
+
+    #[inline]
+    fn to_i16(&self) -> i16 {
+        (*self as i16 - 128) << 8
+    }
+
+    #[inline]
+    fn from_sample<S>(sample: &S) -> u8
+    where
+        S: Sample,
+    {
+        ((sample.to_i16() >> 8) + 128) as u8
+    }
 }
 
 impl Sample for u16 {
@@ -101,6 +327,34 @@ impl Sample for u16 {
     fn zero_value() -> u16 {
         32768
     }
+
+    #[inline]
+    fn integer_bits() -> Option<u32> {
+        Some(16)
+    }
+
+    #[inline]
+    fn to_f32(&self) -> f32 {
+        self.to_i16().to_f32()
+    }
+
+    #[inline]
+    fn to_i16(&self) -> i16 {
+        (*self as i32 - 32768) as i16
+    }
+
+    #[inline]
+    fn to_u16(&self) -> u16 {
+        *self
+    }
+
+    #[inline]
+    fn from_sample<S>(sample: &S) -> u16
+    where
+        S: Sample,
+    {
+        sample.to_u16()
+    }
 }
 
 impl Sample for i16 {
@@ -124,6 +378,154 @@ impl Sample for i16 {
     fn zero_value() -> i16 {
         0
     }
+
+    #[inline]
+    fn integer_bits() -> Option<u32> {
+        Some(16)
+    }
+
+    #[inline]
+    fn to_f32(&self) -> f32 {
+        if *self < 0 {
+            *self as f32 / -(i16::MIN as f32)
+        } else {
+            *self as f32 / i16::MAX as f32
+        }
+    }
+
+    #[inline]
+    fn to_i16(&self) -> i16 {
+        *self
+    }
+
+    #[inline]
+    fn from_sample<S>(sample: &S) -> i16
+    where
+        S: Sample,
+    {
+        sample.to_i16()
+    }
+}
+
+impl Sample for i32 {
+    #[inline]
+    fn lerp(first: i32, second: i32, numerator: u32, denominator: u32) -> i32 {
+        (first as i64 + (second as i64 - first as i64) * numerator as i64 / denominator as i64)
+            as i32
+    }
+
+    #[inline]
+    fn amplify(self, value: f32) -> i32 {
+        ((self as f64) * value as f64) as i32
+    }
+
+    #[inline]
+    fn saturating_add(self, other: i32) -> i32 {
+        self.saturating_add(other).clamp(-I24_SCALE, I24_SCALE - 1)
+    }
+
+    #[inline]
+    fn zero_value() -> i32 {
+        0
+    }
+
+    #[inline]
+    fn integer_bits() -> Option<u32> {
+        Some(24)
+    }
+
+    #[inline]
+    fn to_f32(&self) -> f32 {
+        self.to_f64() as f32
+    }
+
+    #[inline]
+    fn to_f64(&self) -> f64 {
+        if *self < 0 {
+            *self as f64 / I24_SCALE as f64
+        } else {
+            *self as f64 / (I24_SCALE - 1) as f64
+        }
+    }
+
+    #[inline]
+    fn to_i16(&self) -> i16 {
+        (self.clamp(&-I24_SCALE, &(I24_SCALE - 1)) >> 8) as i16
+    }
+
+    #[inline]
+    fn to_i32(&self) -> i32 {
+        *self
+    }
+
+    #[inline]
+    fn from_sample<S>(sample: &S) -> i32
+    where
+        S: Sample,
+    {
+        sample.to_i32()
+    }
+}
+
+impl Sample for i64 {
+    #[inline]
+    fn lerp(first: i64, second: i64, numerator: u32, denominator: u32) -> i64 {
+        (first as i128 + (second as i128 - first as i128) * numerator as i128 / denominator as i128)
+            as i64
+    }
+
+    #[inline]
+    fn amplify(self, value: f32) -> i64 {
+        ((self as f64) * value as f64) as i64
+    }
+
+    #[inline]
+    fn saturating_add(self, other: i64) -> i64 {
+        self.saturating_add(other)
+    }
+
+    #[inline]
+    fn zero_value() -> i64 {
+        0
+    }
+
+    #[inline]
+    fn integer_bits() -> Option<u32> {
+        Some(64)
+    }
+
+    #[inline]
+    fn to_f32(&self) -> f32 {
+        self.to_f64() as f32
+    }
+
+    #[inline]
+    fn to_f64(&self) -> f64 {
+        *self as f64 / I64_SCALE
+    }
+
+    #[inline]
+    fn to_i16(&self) -> i16 {
+        (*self >> 48) as i16
+    }
+
+    #[inline]
+    fn to_i32(&self) -> i32 {
+        (*self >> 40) as i32
+    }
+
+    #[inline]
+    fn to_i64(&self) -> i64 {
+        *self
+    }
+
+    #[inline]
+    fn from_sample<S>(sample: &S) -> i64
+    where
+        S: Sample,
+    {
+        sample.to_i64()
+    }
 }
 
 impl Sample for f32 {
@@ -146,4 +548,202 @@ impl Sample for f32 {
     fn zero_value() -> f32 {
         0.0
     }
+
+    #[inline]
+    fn integer_bits() -> Option<u32> {
+        None
+    }
+
+    #[inline]
+    fn to_f32(&self) -> f32 {
+        *self
+    }
+
+    #[inline]
+    fn to_i16(&self) -> i16 {
+        if *self >= 0.0 {
+            (*self * i16::MAX as f32) as i16
+        } else {
+            (-*self * i16::MIN as f32) as i16
+        }
+    }
+
+    #[inline]
+    fn to_u16(&self) -> u16 {
+        (((*self + 1.0) * 0.5) * u16::MAX as f32).round() as u16
+    }
+
+    #[inline]
+    fn to_i32(&self) -> i32 {
+        (*self as f64).to_i32()
+    }
+
+    #[inline]
+    fn to_i64(&self) -> i64 {
+        (*self as f64).to_i64()
+    }
+
+    #[inline]
+    fn from_sample<S>(sample: &S) -> f32
+    where
+        S: Sample,
+    {
+        sample.to_f32()
+    }
+}
+
+impl Sample for f64 {
+    #[inline]
+    fn lerp(first: f64, second: f64, numerator: u32, denominator: u32) -> f64 {
+        first + (second - first) * numerator as f64 / denominator as f64
+    }
+
+    #[inline]
+    fn amplify(self, value: f32) -> f64 {
+        self * value as f64
+    }
+
+    #[inline]
+    fn saturating_add(self, other: f64) -> f64 {
+        self + other
+    }
+
+    #[inline]
+    fn zero_value() -> f64 {
+        0.0
+    }
+
+    #[inline]
+    fn integer_bits() -> Option<u32> {
+        None
+    }
+
+    #[inline]
+    fn to_f32(&self) -> f32 {
+        *self as f32
+    }
+
+    #[inline]
+    fn to_f64(&self) -> f64 {
+        *self
+    }
+
+    #[inline]
+    fn to_i16(&self) -> i16 {
+        (*self as f32).to_i16()
+    }
+
+    #[inline]
+    fn to_u16(&self) -> u16 {
+        (*self as f32).to_u16()
+    }
+
+    #[inline]
+    fn to_i32(&self) -> i32 {
+        if *self >= 0.0 {
+            (self.min(1.0) * (I24_SCALE - 1) as f64) as i32
+        } else {
+            (self.max(-1.0) * I24_SCALE as f64) as i32
+        }
+    }
+
+    #[inline]
+    fn to_i64(&self) -> i64 {
+        // The conversion to `i64` saturates, so `1.0` gives `i64::MAX`.
+        (self.clamp(-1.0, 1.0) * I64_SCALE) as i64
+    }
+
+    #[inline]
+    fn from_sample<S>(sample: &S) -> f64
+    where
+        S: Sample,
+    {
+        sample.to_f64()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{DataConverter, Dither, Sample};
+
+    // Returns the average of the given samples converted to `i16`.
+    fn average(input: &[f32], dither: Dither) -> f32 {
+        let output = DataConverter::<_, i16>::with_dither(input.iter().cloned(), dither, 1);
+        output.map(|s| s as f32).sum::<f32>() / input.len() as f32
+    }
+
+    #[test]
+    fn no_dither() {
+        let input = vec![0.5f32, -0.5, 1.0];
+        let output = DataConverter::<_, i16>::new(input.into_iter()).collect::<Vec<_>>();
+        assert_eq!(output, [16383, -16384, 32767]);
+    }
+
+    #[test]
+    fn dither_keeps_quiet_signals() {
+        // A quarter of a step is rounded away without dither.
+        let input = vec![0.25 / 32768.0; 20000];
+        assert_eq!(average(&input, Dither::None), 0.0);
+        assert!((average(&input, Dither::Triangular) - 0.25).abs() < 0.05);
+        assert!((average(&input, Dither::NoiseShaped) - 0.25).abs() < 0.05);
+    }
+
+    #[test]
+    fn float_output_is_untouched() {
+        let input = vec![0.1f32, -0.3];
+        let output = input.iter().cloned();
+        let output = DataConverter::<_, f32>::with_dither(output, Dither::Triangular, 1);
+        assert_eq!(output.collect::<Vec<_>>(), input);
+    }
+
+    #[test]
+    fn u8_conversions() {
+        assert_eq!(128u8.to_i16(), 0);
+        assert_eq!(0u8.to_f32(), -1.0);
+        assert_eq!(<u8 as Sample>::from_sample(&-16384i16), 64);
+        assert_eq!(<u8 as Sample>::from_sample(&1.0f32), 255);
+    }
+
+    #[test]
+    fn i32_conversions() {
+        assert_eq!(8388607i32.to_f32(), 1.0);
+        assert_eq!((-4194304i32).to_f32(), -0.5);
+        assert_eq!((-4194304i32).to_i16(), -16384);
+        assert_eq!(<i32 as Sample>::from_sample(&-16384i16), -4194304);
+        assert_eq!(<i32 as Sample>::from_sample(&-0.5f64), -4194304);
+    }
+
+    #[test]
+    fn u8_mixing() {
+        assert_eq!(Sample::saturating_add(128u8, 128), 128);
+        assert_eq!(Sample::saturating_add(192u8, 160), 224);
+        assert_eq!(Sample::saturating_add(255u8, 255), 255);
+        assert_eq!(Sample::saturating_add(0u8, 64), 0);
+    }
+
+    #[test]
+    fn i64_conversions() {
+        assert_eq!(i64::MIN.to_f64(), -1.0);
+        assert_eq!((i64::MIN / 2).to_i16(), -16384);
+        assert_eq!(<i64 as Sample>::from_sample(&-4194304i32), i64::MIN / 2);
+        assert_eq!(<i64 as Sample>::from_sample(&1.0f64), i64::MAX);
+        assert_eq!(<i32 as Sample>::from_sample(&(i64::MIN / 2)), -4194304);
+    }
+
+    #[test]
+    fn f64_conversions() {
+        assert_eq!(0.5f64.to_f32(), 0.5);
+        assert_eq!((-0.5f64).to_i16(), -16384);
+        assert_eq!(<f64 as Sample>::from_sample(&8388607i32), 1.0);
+    }
+
+    #[test]
+    fn dither_to_u8() {
+        // A quarter of a step of a `u8` is rounded away without dither.
+        let input = vec![0.25 / 128.0; 20000];
+        let output = input.iter().cloned();
+        let output = DataConverter::<_, u8>::with_dither(output, Dither::Triangular, 1);
+        let average = output.map(|s| s as f32 - 128.0).sum::<f32>() / input.len() as f32;
+        assert!((average - 0.25).abs() < 0.05);
+    }
 }
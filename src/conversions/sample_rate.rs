@@ -1,8 +1,23 @@
+use conversions::resampler::{Resampling, SincResampler};
+use conversions::Resampler;
 use conversions::Sample;
 use cpal;
 
 use std::mem;
 
+/// Algorithm used to convert a sound from a sample rate to another.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ResampleQuality {
+    /// Linear interpolation between the two nearest samples. Very fast, but it aliases audibly
+    /// when the sample rates are far apart.
+    Linear,
+    /// Windowed-sinc interpolation over 8 zero crossings on each side.
+    Medium,
+    /// Windowed-sinc interpolation over 32 zero crossings on each side. Slower, with less
+    /// aliasing and a flatter response near the Nyquist frequency.
+    High,
+}
+
 /// Iterator that converts from a certain sample rate to another.
 #[derive(Clone, Debug)]
 pub struct SampleRateConverter<I>
@@ -26,6 +41,8 @@ where
     next_output_frame_pos_in_chunk: u32,
     /// The buffer containing the samples waiting to be output.
     output_buffer: Vec<I::Item>,
+    /// State of the `Resampler`, if it is used instead of the linear interpolation.
+    resampling: Option<Resampling<I::Item>>,
 }
 
 impl<I> SampleRateConverter<I>
@@ -39,10 +56,26 @@ where
     ///
     /// Panicks if `from` or `to` are equal to 0.
     ///
+    // Only the tests use linear conversion without saying so.
+    #[allow(dead_code)]
     #[inline]
     pub fn new(
-        mut input: I, from: cpal::SampleRate, to: cpal::SampleRate,
+        input: I, from: cpal::SampleRate, to: cpal::SampleRate,
         num_channels: cpal::ChannelCount,
+    ) -> SampleRateConverter<I> {
+        SampleRateConverter::with_quality(input, from, to, num_channels, ResampleQuality::Linear)
+    }
+
+    /// Builds a converter that uses the given algorithm.
+    ///
+    /// # Panic
+    ///
+    /// Panicks if `from` or `to` are equal to 0.
+    ///
+    #[inline]
+    pub fn with_quality(
+        mut input: I, from: cpal::SampleRate, to: cpal::SampleRate,
+        num_channels: cpal::ChannelCount, quality: ResampleQuality,
     ) -> SampleRateConverter<I> {
         let from = from.0;
         let to = to.0;
@@ -64,6 +97,22 @@ where
             gcd(from, to)
         };
 
+        let zero_crossings = match quality {
+            ResampleQuality::Linear => 0,
+            ResampleQuality::Medium => 8,
+            ResampleQuality::High => 32,
+        };
+        if zero_crossings != 0 {
+            let resampler = Box::new(SincResampler::new(zero_crossings));
+            return SampleRateConverter::with_resampler(
+                input,
+                cpal::SampleRate(from),
+                cpal::SampleRate(to),
+                num_channels,
+                resampler,
+            );
+        }
+
         let (first_samples, next_samples) = if from == to {
             // if `from` == `to` == 1, then we just pass through
             debug_assert_eq!(from, gcd);
@@ -89,15 +138,87 @@ where
             current_frame: first_samples,
             next_frame: next_samples,
             output_buffer: Vec::with_capacity(num_channels as usize - 1),
+            resampling: None,
         }
     }
 
+    /// Builds a converter that uses the given `Resampler`.
+    ///
+    /// # Panic
+    ///
+    /// Panicks if `from` or `to` are equal to 0.
+    ///
+    #[inline]
+    pub fn with_resampler(
+        input: I, from: cpal::SampleRate, to: cpal::SampleRate,
+        num_channels: cpal::ChannelCount, resampler: Box<dyn Resampler>,
+    ) -> SampleRateConverter<I> {
+        assert!(from.0 >= 1);
+        assert!(to.0 >= 1);
+
+        let resampling = Resampling::new(resampler, from.0, to.0, num_channels);
+        SampleRateConverter::with_resampling(input, from, to, num_channels, resampling)
+    }
+
+    /// Builds a converter whose input follows the input of a previous converter, and that reuses
+    /// its `Resampler` state. The frames kept by the `Resampler` are only used if the two
+    /// converters have the same parameters.
+    pub(crate) fn with_resampling(
+        input: I, from: cpal::SampleRate, to: cpal::SampleRate,
+        num_channels: cpal::ChannelCount, mut resampling: Resampling<I::Item>,
+    ) -> SampleRateConverter<I> {
+        let from = from.0;
+        let to = to.0;
+        resampling.restart(from, to, num_channels);
+
+        SampleRateConverter {
+            input,
+            from,
+            to,
+            current_frame_pos_in_chunk: 0,
+            next_output_frame_pos_in_chunk: 0,
+            current_frame: Vec::new(),
+            next_frame: Vec::new(),
+            output_buffer: Vec::new(),
+            resampling: Some(resampling),
+        }
+    }
+
+    /// Returns a reference to the underlying iterator.
+    #[inline]
+    pub fn inner(&self) -> &I {
+        &self.input
+    }
+
+    /// Returns a mutable reference to the underlying iterator.
+    #[inline]
+    pub fn inner_mut(&mut self) -> &mut I {
+        &mut self.input
+    }
+
     /// Destroys this iterator and returns the underlying iterator.
     #[inline]
     pub fn into_inner(self) -> I {
         self.input
     }
 
+    /// Destroys this iterator and returns the underlying iterator, along with the state of the
+    /// `Resampler` to give to `with_resampling`.
+    #[inline]
+    pub(crate) fn into_parts(self) -> (I, Option<Resampling<I::Item>>) {
+        (self.input, self.resampling)
+    }
+
+    /// If `hold` is true, the last frames of the input are kept in the `Resampler` when the input
+    /// ends, because they can only be computed once the frames that follow them are known. They
+    /// are output by the next converter, or when it gets an empty input.
+    #[inline]
+    pub(crate) fn set_hold(&mut self, hold: bool) {
+        if let Some(ref mut resampling) = self.resampling {
+            resampling.set_hold(hold);
+        }
+    }
+
     fn next_input_frame(&mut self) {
         self.current_frame_pos_in_chunk += 1;
 
@@ -123,10 +244,13 @@ where
     fn next(&mut self) -> Option<I::Item> {
         // the algorithm below doesn't work if `self.from == self.to`
         if self.from == self.to {
-            debug_assert_eq!(self.from, 1);
             return self.input.next();
         }
 
+        if let Some(ref mut resampling) = self.resampling {
+            return resampling.next(&mut self.input);
+        }
+
         // Short circuit if there are some samples waiting.
         if self.output_buffer.len() >= 1 {
             return Some(self.output_buffer.remove(0));
@@ -229,6 +353,9 @@ where
 
         if self.from == self.to {
             self.input.size_hint()
+        } else if let Some(ref resampling) = self.resampling {
+            let (min, max) = self.input.size_hint();
+            (resampling.remaining(min), max.map(|max| resampling.remaining(max)))
         } else {
             let (min, max) = self.input.size_hint();
             (apply(min), max.map(apply))
@@ -245,8 +372,9 @@ where
 
 #[cfg(test)]
 mod test {
-    use super::SampleRateConverter;
+    use super::{ResampleQuality, SampleRateConverter};
     use cpal::SampleRate;
+    use std::f32::consts::PI;
 
     #[test]
     fn zero() {
@@ -370,4 +498,53 @@ mod test {
         assert_eq!(output.len(), 0);
         assert_eq!(output.next(), None);
     }
+
+    // Returns the highest absolute value among the middle samples of a sine wave that went
+    // through a converter.
+    fn resampled_peak(freq: f32, from: u32, to: u32, quality: ResampleQuality) -> f32 {
+        let input = (0 .. from).map(|i| (2.0 * PI * freq * i as f32 / from as f32).sin());
+        let output = SampleRateConverter::with_quality(
+            input.collect::<Vec<_>>().into_iter(),
+            SampleRate(from),
+            SampleRate(to),
+            1,
+            quality,
+        );
+        let output = output.collect::<Vec<f32>>();
+        assert_eq!(output.len(), to as usize);
+        output[to as usize / 4 .. to as usize * 3 / 4].iter().fold(0.0, |m, s| m.max(s.abs()))
+    }
+
+    #[test]
+    fn sinc_keeps_audible_frequencies() {
+        for &quality in &[ResampleQuality::Medium, ResampleQuality::High] {
+            let peak = resampled_peak(1000.0, 44100, 48000, quality);
+            assert!((peak - 1.0).abs() < 0.01, "{}", peak);
+            let peak = resampled_peak(1000.0, 48000, 22050, quality);
+            assert!((peak - 1.0).abs() < 0.01, "{}", peak);
+        }
+    }
+
+    #[test]
+    fn sinc_removes_aliasing() {
+        // 15 kHz can't be represented at 22050 Hz, and would fold back to 7050 Hz.
+        let peak = resampled_peak(15000.0, 48000, 22050, ResampleQuality::High);
+        assert!(peak < 0.01, "{}", peak);
+        let peak = resampled_peak(15000.0, 48000, 22050, ResampleQuality::Linear);
+        assert!(peak > 0.1, "{}", peak);
+    }
+
+    #[test]
+    fn sinc_lengths() {
+        let input = vec![0.5f32; 100];
+        let output = SampleRateConverter::with_quality(
+            input.into_iter(),
+            SampleRate(2000),
+            SampleRate(3000),
+            2,
+            ResampleQuality::Medium,
+        );
+        assert_eq!(output.len(), 150);
+        assert_eq!(output.count(), 150);
+    }
 }
@@ -0,0 +1,50 @@
+use std::time::Duration;
+
+const NANOS_PER_SEC: u64 = 1_000_000_000;
+
+/// Returns the number of nanoseconds in a duration.
+#[inline]
+pub(crate) fn duration_to_ns(duration: Duration) -> u64 {
+    duration.as_secs() * NANOS_PER_SEC + duration.subsec_nanos() as u64
+}
+
+/// Returns the number of whole frames played in a duration at a sample rate.
+#[inline]
+pub(crate) fn duration_to_frames(duration: Duration, sample_rate: u32) -> u64 {
+    (duration.as_nanos() * sample_rate as u128 / NANOS_PER_SEC as u128) as u64
+}
+
+/// Returns the duration of a number of frames at a sample rate, in nanoseconds.
+#[inline]
+pub(crate) fn frames_to_ns(frames: u64, sample_rate: u32) -> u64 {
+    (frames as u128 * NANOS_PER_SEC as u128 / sample_rate as u128) as u64
+}
+
+/// Converts decibels to the factor by which the amplitude is multiplied.
+#[inline]
+pub(crate) fn db_to_amplitude(decibels: f32) -> f32 {
+    10f32.powf(decibels / 20.0)
+}
+
+/// Converts the factor by which the amplitude is multiplied to decibels.
+#[inline]
+pub(crate) fn amplitude_to_db(amplitude: f32) -> f32 {
+    20.0 * amplitude.log10()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use conversions::units::{db_to_amplitude, duration_to_frames, duration_to_ns, frames_to_ns};
+
+    #[test]
+    fn conversions() {
+        assert_eq!(duration_to_ns(Duration::new(2, 5)), 2_000_000_005);
+        assert_eq!(duration_to_frames(Duration::from_millis(1500), 44100), 66150);
+        assert_eq!(frames_to_ns(66150, 44100), 1_500_000_000);
+        // A day at 192 kHz doesn't overflow.
+        assert_eq!(duration_to_frames(Duration::from_secs(86400), 192000), 16_588_800_000);
+        assert!((db_to_amplitude(-6.0) - 0.501).abs() < 1e-3);
+    }
+}
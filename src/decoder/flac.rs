@@ -1,7 +1,9 @@
-use std::io::{Read, Seek, SeekFrom};
+use std::io::{self, Read, Seek, SeekFrom};
 use std::mem;
 use std::time::Duration;
 
+use conversions::duration_to_frames;
+use source::SeekError;
 use Source;
 
 use claxon::FlacReader;
@@ -11,7 +13,10 @@ pub struct FlacDecoder<R>
 where
     R: Read + Seek,
 {
-    reader: FlacReader<R>,
+    // `None` if decoding the data again after a seek failed.
+    reader: Option<FlacReader<R>>,
+    // Position of the start of the FLAC data in the stream.
+    stream_pos: u64,
     current_block: Vec<i32>,
     current_block_channel_len: usize,
     current_block_off: usize,
@@ -31,11 +36,13 @@ where
             return Err(data);
         }
 
+        let stream_pos = data.stream_position().unwrap();
         let reader = FlacReader::new(data).unwrap();
         let spec = reader.streaminfo();
 
         Ok(FlacDecoder {
-            reader: reader,
+            reader: Some(reader),
+            stream_pos,
             current_block: Vec::with_capacity(
                 spec.max_block_size as usize * spec.channels as usize,
             ),
@@ -74,6 +81,48 @@ where
         // so we do not divide by `self.channels` here.
         self.samples.map(|s| Duration::from_micros(s * 1_000_000 / self.sample_rate as u64))
     }
+
+    /// Seeks to the exact frame of `pos`. The FLAC data has no index that `claxon` can use, so
+    /// the data is decoded again from its start up to `pos`, which takes longer the further
+    /// `pos` is.
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        let mut frames = duration_to_frames(pos, self.sample_rate);
+
+        let mut data = match self.reader.take() {
+            Some(reader) => reader.into_inner(),
+            None => return Err(SeekError::NotDone),
+        };
+        data.seek(SeekFrom::Start(self.stream_pos))
+            .map_err(SeekError::Io)?;
+        let mut reader = FlacReader::new(data)
+            .map_err(|err| SeekError::Io(io::Error::new(io::ErrorKind::InvalidData, err)))?;
+
+        // Skips the blocks that end before `pos`, and the start of the one that contains it.
+        self.current_block_off = 0;
+        loop {
+            let buffer = mem::take(&mut self.current_block);
+            match reader.blocks().read_next_or_eof(buffer) {
+                Ok(Some(block)) => {
+                    let len = (block.len() / block.channels()) as u64;
+                    self.current_block_channel_len = len as usize;
+                    self.current_block = block.into_buffer();
+                    if frames < len {
+                        self.current_block_off = frames as usize * self.channels as usize;
+                        break;
+                    }
+                    frames -= len;
+                },
+                // Seeking past the end makes the source end.
+                _ => {
+                    self.current_block.clear();
+                    break;
+                },
+            }
+        }
+
+        self.reader = Some(reader);
+        Ok(())
+    }
 }
 
 impl<R> Iterator for FlacDecoder<R>
@@ -104,8 +153,12 @@ where
 
             // Load the next block.
             self.current_block_off = 0;
+            let reader = match self.reader {
+                Some(ref mut reader) => reader,
+                None => return None,
+            };
             let buffer = mem::replace(&mut self.current_block, Vec::new());
-            match self.reader.blocks().read_next_or_eof(buffer) {
+            match reader.blocks().read_next_or_eof(buffer) {
                 Ok(Some(block)) => {
                     self.current_block_channel_len = (block.len() / block.channels()) as usize;
                     self.current_block = block.into_buffer();
@@ -5,6 +5,7 @@ use std::fmt;
 use std::io::{Read, Seek};
 use std::time::Duration;
 
+use source::SeekError;
 use Source;
 
 #[cfg(feature = "flac")]
@@ -214,6 +215,20 @@ where
             DecoderImpl::Mp3(ref source) => source.total_duration(),
         }
     }
+
+    #[inline]
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        match self.0 {
+            #[cfg(feature = "wav")]
+            DecoderImpl::Wav(ref mut source) => source.try_seek(pos),
+            #[cfg(feature = "vorbis")]
+            DecoderImpl::Vorbis(ref mut source) => source.try_seek(pos),
+            #[cfg(feature = "flac")]
+            DecoderImpl::Flac(ref mut source) => source.try_seek(pos),
+            #[cfg(feature = "mp3")]
+            DecoderImpl::Mp3(ref mut source) => source.try_seek(pos),
+        }
+    }
 }
 
 /// Error that can happen when creating a decoder.
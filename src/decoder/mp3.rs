@@ -1,6 +1,7 @@
 use std::io::{Read, Seek};
 use std::time::Duration;
 
+use source::SeekError;
 use Source;
 
 use minimp3::{Decoder, Frame};
@@ -53,6 +54,16 @@ where
     fn total_duration(&self) -> Option<Duration> {
         None
     }
+
+    /// Seeking isn't supported, because `minimp3` can't be moved back to the start of the data
+    /// to decode it again, and returns `SeekError::NotSupported`.
+    #[inline]
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        let _ = pos;
+        Err(SeekError::NotSupported {
+            underlying_source: ::std::any::type_name::<Self>(),
+        })
+    }
 }
 
 impl<R> Iterator for Mp3Decoder<R>
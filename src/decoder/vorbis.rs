@@ -2,6 +2,8 @@ use std::io::{Read, Seek, SeekFrom};
 use std::time::Duration;
 use std::vec;
 
+use conversions::duration_to_frames;
+use source::SeekError;
 use Source;
 
 use lewton::inside_ogg::OggStreamReader;
@@ -69,6 +71,21 @@ where
     fn total_duration(&self) -> Option<Duration> {
         None
     }
+
+    /// Seeks with the granularity of an ogg page, which means that the playback may start
+    /// slightly before `pos`.
+    #[inline]
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        let granule = duration_to_frames(pos, self.sample_rate());
+
+        self.stream_reader
+            .seek_absgp_pg(granule)
+            .map_err(SeekError::Vorbis)?;
+
+        let data = self.stream_reader.read_dec_packet_itl().ok().and_then(|v| v);
+        self.current_data = data.unwrap_or_default().into_iter();
+        Ok(())
+    }
 }
 
 impl<R> Iterator for VorbisDecoder<R>
@@ -1,6 +1,9 @@
+use std::cmp;
 use std::io::{Read, Seek, SeekFrom};
 use std::time::Duration;
 
+use conversions::duration_to_frames;
+use source::SeekError;
 use Source;
 
 use hound::{SampleFormat, WavReader};
@@ -114,6 +117,16 @@ where
         let ms = self.len() * 1000 / (self.channels as usize * self.sample_rate as usize);
         Some(Duration::from_millis(ms as u64))
     }
+
+    #[inline]
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        let frame = duration_to_frames(pos, self.sample_rate);
+        let frame = cmp::min(frame, self.reader.reader.duration() as u64) as u32;
+
+        self.reader.reader.seek(frame).map_err(SeekError::Io)?;
+        self.reader.samples_read = frame * self.channels as u32;
+        Ok(())
+    }
 }
 
 impl<R> Iterator for WavDecoder<R>
@@ -1,11 +1,16 @@
 //! Mixer that plays multiple sounds at the same time.
 
-use std::sync::atomic::AtomicBool;
 use std::sync::atomic::Ordering;
+use std::sync::atomic::{AtomicBool, AtomicU32};
 use std::sync::Arc;
 use std::sync::Mutex;
 use std::time::Duration;
 
+use completion::{self, Completer, Completion};
+use conversions::ResampleQuality;
+use conversions::Resampler;
+use source::convert_to_layout;
+use source::ChannelLayout;
 use source::Source;
 use source::UniformSourceIterator;
 
@@ -28,11 +33,15 @@ where
         pending_sources: Mutex::new(Vec::new()),
         channels: channels,
         sample_rate: sample_rate,
+        quality: Mutex::new(ResampleQuality::Linear),
+        resampler: Mutex::new(None),
+        layout: Mutex::new(None),
     });
 
     let output = DynamicMixer {
         current_sources: Vec::with_capacity(16),
         input: input.clone(),
+        current_channel: 0,
     };
 
     (input, output)
@@ -41,9 +50,12 @@ where
 /// The input of the mixer.
 pub struct DynamicMixerController<S> {
     has_pending: AtomicBool,
-    pending_sources: Mutex<Vec<Box<dyn Source<Item = S> + Send>>>,
+    pending_sources: Mutex<Vec<Input<S>>>,
     channels: u16,
     sample_rate: u32,
+    quality: Mutex<ResampleQuality>,
+    resampler: Mutex<Option<Box<dyn Resampler>>>,
+    layout: Mutex<Option<ChannelLayout>>,
 }
 
 impl<S> DynamicMixerController<S>
@@ -51,27 +63,256 @@ where
     S: Sample + Send + 'static,
 {
     /// Adds a new source to mix to the existing ones.
+    ///
+    /// The returned handle can change how the source is mixed, remove it before its end, or wait
+    /// for its end. It can be dropped if this isn't needed.
     #[inline]
-    pub fn add<T>(&self, source: T)
+    pub fn add<T>(&self, source: T) -> MixerInput
     where
         T: Source<Item = S> + Send + 'static,
     {
-        let uniform_source = UniformSourceIterator::new(source, self.channels, self.sample_rate);
+        self.add_with(source, 1.0, 0.0)
+    }
+
+    /// Adds a new source to mix to the existing ones, with the given gain and pan from its start.
+    ///
+    /// See `MixerInput::set_gain` and `MixerInput::set_pan`.
+    pub fn add_with<T>(&self, source: T, gain: f32, pan: f32) -> MixerInput
+    where
+        T: Source<Item = S> + Send + 'static,
+    {
+        let layout = *self.layout.lock().unwrap();
+        let uniform_source = match layout {
+            Some(layout) => self.uniform(convert_to_layout(source, layout)),
+            None => self.uniform(source),
+        };
+        let pan = pan.clamp(-1.0, 1.0);
+        let controls = Arc::new(InputControls {
+            gain: AtomicU32::new(gain.to_bits()),
+            pan: AtomicU32::new(pan.to_bits()),
+            muted: AtomicBool::new(false),
+            removed: AtomicBool::new(false),
+        });
+        let (completion, completer) = completion::completion();
         self.pending_sources
             .lock()
             .unwrap()
-            .push(Box::new(uniform_source) as Box<_>);
+            .push(Input {
+                source: uniform_source,
+                controls: controls.clone(),
+                gain,
+                pan,
+                _completer: completer,
+            });
         self.has_pending.store(true, Ordering::SeqCst); // TODO: can we relax this ordering?
+        MixerInput {
+            controls,
+            completion,
+        }
+    }
+
+    // Converts a source to the channels and sample rate of the mixer.
+    fn uniform<T>(&self, source: T) -> Box<dyn Source<Item = S> + Send>
+    where
+        T: Source<Item = S> + Send + 'static,
+    {
+        match *self.resampler.lock().unwrap() {
+            Some(ref resampler) => Box::new(UniformSourceIterator::with_resampler(
+                source,
+                self.channels,
+                self.sample_rate,
+                resampler.clone(),
+            )) as Box<_>,
+            None => {
+                let quality = *self.quality.lock().unwrap();
+                Box::new(UniformSourceIterator::with_quality(
+                    source,
+                    self.channels,
+                    self.sample_rate,
+                    quality,
+                )) as Box<_>
+            },
+        }
+    }
+
+    /// Changes the algorithm that converts the sample rate of the sources added afterwards. The
+    /// default is `ResampleQuality::Linear`.
+    #[inline]
+    pub fn set_resample_quality(&self, quality: ResampleQuality) {
+        *self.quality.lock().unwrap() = quality;
+    }
+
+    /// Converts the sample rate of the sources added afterwards with the given `Resampler`
+    /// instead of the algorithm chosen with `set_resample_quality`. `None` goes back to that
+    /// algorithm.
+    #[inline]
+    pub fn set_resampler(&self, resampler: Option<Box<dyn Resampler>>) {
+        *self.resampler.lock().unwrap() = resampler;
+    }
+
+    /// Declares the channel layout of the output of the mixer. The sources added afterwards
+    /// whose number of channels has a usual layout are converted to it speaker by speaker, with
+    /// `Source::convert_layout`, instead of having their channels truncated or repeated.
+    ///
+    /// # Panic
+    ///
+    /// Panics if the layout doesn't have the number of channels of the mixer.
+    #[inline]
+    pub fn set_layout(&self, layout: Option<ChannelLayout>) {
+        if let Some(layout) = layout {
+            assert_eq!(layout.channels(), self.channels);
+        }
+        *self.layout.lock().unwrap() = layout;
+    }
+}
+
+/// Handle to a source added to a mixer, returned by `DynamicMixerController::add`.
+///
+/// The changes of gain and pan are spread over a few milliseconds, in order to avoid audible
+/// clicks.
+#[derive(Clone, Debug)]
+pub struct MixerInput {
+    controls: Arc<InputControls>,
+    completion: Completion,
+}
+
+/// Duration over which a change of gain or pan is spread, in milliseconds.
+const SMOOTHING_MS: f32 = 20.0;
+
+// A source of the mixer, and how it is mixed.
+struct Input<S> {
+    source: Box<dyn Source<Item = S> + Send>,
+    controls: Arc<InputControls>,
+    // Gain and pan currently applied, moving towards the values of `controls`.
+    gain: f32,
+    pan: f32,
+    // Completes the `Completion` of the handle when the source is dropped by the mixer.
+    _completer: Completer,
+}
+
+impl<S> Input<S> {
+    // Moves the gain and pan one frame closer to their targets.
+    #[inline]
+    fn step_towards_target(&mut self, max_step: f32) {
+        let gain = if self.controls.muted.load(Ordering::Relaxed) {
+            0.0
+        } else {
+            f32::from_bits(self.controls.gain.load(Ordering::Relaxed))
+        };
+        let pan = f32::from_bits(self.controls.pan.load(Ordering::Relaxed));
+        self.gain = step(self.gain, gain, max_step);
+        self.pan = step(self.pan, pan, max_step);
+    }
+
+    // Returns the factor applied to the samples of a channel.
+    #[inline]
+    fn factor(&self, channel: u16, channels: u16) -> f32 {
+        match channel {
+            0 if channels >= 2 => self.gain * (1.0 - self.pan.max(0.0)),
+            1 => self.gain * (1.0 + self.pan.min(0.0)),
+            _ => self.gain,
+        }
+    }
+}
+
+// Returns `current` moved towards `target` by at most `max_step`.
+#[inline]
+fn step(current: f32, target: f32, max_step: f32) -> f32 {
+    let diff = target - current;
+    if diff.abs() <= max_step {
+        target
+    } else {
+        current + max_step.copysign(diff)
+    }
+}
+
+// Stored as the bits of `f32`s, so that the audio thread can read them without locking.
+#[derive(Debug)]
+struct InputControls {
+    gain: AtomicU32,
+    pan: AtomicU32,
+    muted: AtomicBool,
+    removed: AtomicBool,
+}
+
+impl MixerInput {
+    /// Gets the gain of the source.
+    #[inline]
+    pub fn gain(&self) -> f32 {
+        f32::from_bits(self.controls.gain.load(Ordering::Relaxed))
+    }
+
+    /// Changes the gain of the source. The value `1.0` is the "normal" volume.
+    #[inline]
+    pub fn set_gain(&self, value: f32) {
+        self.controls.gain.store(value.to_bits(), Ordering::Relaxed);
+    }
+
+    /// Gets the pan of the source.
+    #[inline]
+    pub fn pan(&self) -> f32 {
+        f32::from_bits(self.controls.pan.load(Ordering::Relaxed))
+    }
+
+    /// Changes the pan of the source, from `-1.0` to only play it on the left channel to `1.0`
+    /// to only play it on the right one. The default is `0.0`.
+    ///
+    /// Like `Source::balance`, one channel is attenuated and the other one is left untouched. The
+    /// pan has no effect if the mixer has a single channel.
+    #[inline]
+    pub fn set_pan(&self, value: f32) {
+        let value = value.clamp(-1.0, 1.0);
+        self.controls.pan.store(value.to_bits(), Ordering::Relaxed);
+    }
+
+    /// Returns true if the source is muted.
+    #[inline]
+    pub fn is_muted(&self) -> bool {
+        self.controls.muted.load(Ordering::Relaxed)
+    }
+
+    /// Mutes or unmutes the source. A muted source keeps playing silently.
+    #[inline]
+    pub fn set_muted(&self, muted: bool) {
+        self.controls.muted.store(muted, Ordering::Relaxed);
+    }
+
+    /// Removes the source from the mixer without waiting for its end.
+    #[inline]
+    pub fn remove(&self) {
+        self.controls.removed.store(true, Ordering::Relaxed);
+    }
+
+    /// Returns true if the source has ended or has been removed from the mixer.
+    #[inline]
+    pub fn is_finished(&self) -> bool {
+        self.completion.is_done()
+    }
+
+    /// Blocks the current thread until the source ends or is removed from the mixer.
+    #[inline]
+    pub fn wait(&self) {
+        self.completion.wait()
+    }
+
+    /// Returns a handle that completes when the source ends or is removed from the mixer, which
+    /// can also be awaited.
+    #[inline]
+    pub fn completion(&self) -> Completion {
+        self.completion.clone()
     }
 }
 
 /// The output of the mixer. Implements `Source`.
 pub struct DynamicMixer<S> {
     // The current iterator that produces samples.
-    current_sources: Vec<Box<dyn Source<Item = S> + Send>>,
+    current_sources: Vec<Input<S>>,
 
     // The pending sounds.
     input: Arc<DynamicMixerController<S>>,
+
+    // Channel of the next sample returned by `next`.
+    current_channel: u16,
 }
 
 impl<S> Source for DynamicMixer<S>
@@ -107,11 +348,25 @@ where
 
     #[inline]
     fn next(&mut self) -> Option<S> {
-        if self.input.has_pending.load(Ordering::SeqCst) {
-            // TODO: relax ordering?
-            let mut pending = self.input.pending_sources.lock().unwrap();
-            self.current_sources.extend(pending.drain(..));
-            self.input.has_pending.store(false, Ordering::SeqCst); // TODO: relax ordering?
+        if self.current_channel >= self.input.channels {
+            self.current_channel = 0;
+        }
+        // The sources are added and removed at the start of a frame, so that their channels
+        // stay aligned with the output.
+        if self.current_channel == 0 {
+            if self.input.has_pending.load(Ordering::SeqCst) {
+                // TODO: relax ordering?
+                let mut pending = self.input.pending_sources.lock().unwrap();
+                self.current_sources.extend(pending.drain(..));
+                self.input.has_pending.store(false, Ordering::SeqCst); // TODO: relax ordering?
+            }
+            self.current_sources
+                .retain(|input| !input.controls.removed.load(Ordering::Relaxed));
+
+            let max_step = 1000.0 / (SMOOTHING_MS * self.input.sample_rate as f32);
+            for input in &mut self.current_sources {
+                input.step_towards_target(max_step);
+            }
         }
 
         if self.current_sources.is_empty() {
@@ -120,14 +375,19 @@ where
 
         let mut to_drop = Vec::new();
 
+        let channel = self.current_channel;
+        let channels = self.input.channels;
         let mut sum = S::zero_value();
-        for (num, src) in self.current_sources.iter_mut().enumerate() {
-            if let Some(val) = src.next() {
+        for (num, input) in self.current_sources.iter_mut().enumerate() {
+            if let Some(val) = input.source.next() {
+                let factor = input.factor(channel, channels);
+                let val = if factor == 1.0 { val } else { val.amplify(factor) };
                 sum = sum.saturating_add(val);
             } else {
                 to_drop.push(num);
             }
         }
+        self.current_channel += 1;
 
         for &td in to_drop.iter().rev() {
             self.current_sources.remove(td);
@@ -150,7 +410,7 @@ where
 mod tests {
     use buffer::SamplesBuffer;
     use dynamic_mixer;
-    use source::Source;
+    use source::{ChannelLayout, Source};
 
     #[test]
     fn basic() {
@@ -188,6 +448,16 @@ mod tests {
         assert_eq!(rx.next(), None);
     }
 
+    #[test]
+    fn layout_conv() {
+        let (tx, mut rx) = dynamic_mixer::mixer(6, 48000);
+        tx.set_layout(Some(ChannelLayout::Surround51));
+
+        // Stereo is played on the front speakers instead of being repeated on all of them.
+        tx.add(SamplesBuffer::new(2, 48000, vec![0.5f32, -0.5]));
+        assert_eq!(rx.by_ref().take(6).collect::<Vec<_>>(), [0.5, -0.5, 0.0, 0.0, 0.0, 0.0]);
+    }
+
     #[test]
     fn rate_conv() {
         let (tx, mut rx) = dynamic_mixer::mixer(1, 96000);
@@ -207,6 +477,69 @@ mod tests {
         assert_eq!(rx.next(), None);
     }
 
+    #[test]
+    fn input_controls() {
+        // Low rate so that the changes are applied in a single frame.
+        let (tx, mut rx) = dynamic_mixer::mixer(2, 25);
+
+        let first = tx.add(SamplesBuffer::new(2, 25, vec![10i16; 10]));
+        let second = tx.add(SamplesBuffer::new(2, 25, vec![5i16; 10]));
+        assert_eq!(rx.next(), Some(15));
+        assert_eq!(rx.next(), Some(15));
+
+        first.set_gain(2.0);
+        assert_eq!(rx.next(), Some(25));
+        assert_eq!(rx.next(), Some(25));
+
+        first.set_pan(1.0);
+        assert_eq!(rx.next(), Some(5));
+        assert_eq!(rx.next(), Some(25));
+
+        first.set_muted(true);
+        assert!(first.is_muted());
+        assert_eq!(rx.next(), Some(5));
+        assert_eq!(rx.next(), Some(5));
+
+        second.remove();
+        first.set_muted(false);
+        assert_eq!(rx.next(), Some(0));
+        assert_eq!(rx.next(), Some(20));
+        assert!(second.is_finished());
+        assert!(!first.is_finished());
+
+        first.remove();
+        assert_eq!(rx.next(), None);
+    }
+
+    #[test]
+    fn finished_at_end() {
+        let (tx, mut rx) = dynamic_mixer::mixer(1, 48000);
+
+        let input = tx.add(SamplesBuffer::new(1, 48000, vec![10i16, -10]));
+        let completion = input.completion();
+        assert_eq!(rx.next(), Some(10));
+        assert_eq!(rx.next(), Some(-10));
+        assert!(!input.is_finished());
+
+        assert_eq!(rx.next(), None);
+        assert!(completion.is_done());
+        input.wait();
+    }
+
+    #[test]
+    fn gain_is_smoothed() {
+        let (tx, mut rx) = dynamic_mixer::mixer(1, 500);
+
+        let input = tx.add_with(SamplesBuffer::new(1, 500, vec![1.0f32; 10]), 0.5, 0.0);
+        assert_eq!(rx.next(), Some(0.5));
+
+        // The gain moves by 0.1 every frame.
+        input.set_gain(0.8);
+        for &expected in &[0.6, 0.7, 0.8, 0.8] {
+            assert!((rx.next().unwrap() - expected).abs() < 1e-6);
+        }
+    }
+
     #[test]
     fn start_afterwards() {
         let (tx, mut rx) = dynamic_mixer::mixer(1, 48000);
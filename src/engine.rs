@@ -1,19 +1,43 @@
 use std::collections::hash_map::Entry;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::error::Error;
+use std::fmt;
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, AtomicU8, Ordering};
 use std::sync::Arc;
 use std::sync::Mutex;
 use std::sync::Weak;
 use std::thread::Builder;
+use std::time::{Duration, Instant};
 
 use cpal::traits::{DeviceTrait, EventLoopTrait, HostTrait};
 use cpal::Device;
 use cpal::EventLoop;
+use cpal::Format;
 use cpal::Sample as CpalSample;
 use cpal::StreamData;
 use cpal::StreamId;
+use cpal::SupportedFormat;
 use cpal::UnknownTypeOutputBuffer;
+use cpal::{BuildStreamError, PlayStreamError, SupportedFormatsError};
+use conversions::frames_to_ns;
+use conversions::sample::Ditherer;
+use conversions::Dither;
+use conversions::ResampleQuality;
+use conversions::Resampler;
 use dynamic_mixer;
-use source::Source;
+use source::{ChannelLayout, ChannelLevel, FadeIn, Source, TakeDuration, Tee};
+
+// Duration over which the levels of the output are measured.
+const METER_WINDOW_MS: u32 = 50;
+
+// Duration of the fades of `pause_all` and `resume_all`.
+const PAUSE_FADE_SECS: f32 = 0.01;
+
+// Time constant of the recovery of the limiter.
+const LIMITER_RELEASE_SECS: f32 = 0.05;
+
+// Duration of the crossfade when switching device.
+const SWITCH_CROSSFADE: Duration = Duration::from_millis(20);
 
 /// Plays a source with a device until it ends.
 ///
@@ -22,36 +46,335 @@ pub fn play_raw<S>(device: &Device, source: S)
 where
     S: Source<Item = f32> + Send + 'static,
 {
-    lazy_static! {
-        static ref ENGINE: Arc<Engine> = {
-            let engine = Arc::new(Engine {
-                events_loop: cpal::default_host().event_loop(),
-                dynamic_mixers: Mutex::new(HashMap::with_capacity(1)),
-                end_points: Mutex::new(HashMap::with_capacity(1)),
-            });
-
-            // We ignore errors when creating the background thread.
-            // The user won't get any audio, but that's better than a panic.
-            Builder::new()
-                .name("rodio audio processing".to_string())
-                .spawn({
-                    let engine = engine.clone();
-                    move || {
-                        engine.events_loop.run(|stream_id, buffer| {
-                            if let Ok(buf) = buffer {
-                                audio_callback(&engine, stream_id, buf);
-                            }
-                        })
-                    }
-                })
-                .ok()
-                .map(|jg| jg.thread().clone());
+    start(&ENGINE, device, source);
+}
 
-            engine
+/// Moves everything that is playing on a device, including the sounds of the `Sink`s, to another
+/// device, for example when headphones are plugged in.
+///
+/// The sounds fade out of the old device and into the new one over 20 milliseconds, so that the
+/// switch doesn't click. The sounds played on `from` afterwards play on it again. Does nothing if
+/// nothing is playing on `from`.
+pub fn switch_device(from: &Device, to: &Device) {
+    let from_name = device_name(from);
+    if from_name == device_name(to) {
+        return;
+    }
+
+    let end_point = ENGINE.end_points.lock().unwrap().remove(&from_name);
+    let stream_id = match end_point {
+        Some(ref end_point) if end_point.mixer.upgrade().is_some() => end_point.stream_id.clone(),
+        _ => return,
+    };
+
+    let moved = {
+        let mut dynamic_mixers = ENGINE.dynamic_mixers.lock().unwrap();
+        let mixer_rx = match dynamic_mixers.remove(&stream_id) {
+            Some(m) => m,
+            None => return,
         };
+        let (fade_out, moved) = crossfade_split(mixer_rx);
+
+        // The old stream keeps playing until the end of the fade out.
+        let (stub_tx, stub_rx) = dynamic_mixer::mixer(fade_out.channels(), fade_out.sample_rate());
+        stub_tx.add(fade_out);
+        dynamic_mixers.insert(stream_id.clone(), stub_rx);
+        ENGINE.retiring.lock().unwrap().insert(stream_id);
+        moved
+    };
+
+    start(&ENGINE, to, moved);
+}
+
+lazy_static! {
+    static ref ENGINE: Arc<Engine> = {
+        let engine = Arc::new(Engine {
+            events_loop: cpal::default_host().event_loop(),
+            dynamic_mixers: Mutex::new(HashMap::with_capacity(1)),
+            latencies: Mutex::new(HashMap::with_capacity(1)),
+            states: Mutex::new(HashMap::with_capacity(1)),
+            end_points: Mutex::new(HashMap::with_capacity(1)),
+            retiring: Mutex::new(HashSet::new()),
+        });
+
+        // We ignore errors when creating the background thread.
+        // The user won't get any audio, but that's better than a panic.
+        Builder::new()
+            .name("rodio audio processing".to_string())
+            .spawn({
+                let engine = engine.clone();
+                move || {
+                    engine.events_loop.run(|stream_id, buffer| {
+                        if let Ok(buf) = buffer {
+                            audio_callback(&engine, stream_id, buf);
+                        }
+                    })
+                }
+            })
+            .ok()
+            .map(|jg| jg.thread().clone());
+
+        engine
+    };
+    static ref RESAMPLER: Mutex<Option<Box<dyn Resampler>>> = Mutex::new(None);
+    // Stored as the bits of an `f32`, so that the audio thread can read it without locking.
+    static ref MASTER_VOLUME: AtomicU32 = AtomicU32::new(1f32.to_bits());
+    static ref LIMITER_CEILING: AtomicU32 = AtomicU32::new(1f32.to_bits());
+}
+
+static LIMITER_ENABLED: AtomicBool = AtomicBool::new(false);
+static ALL_PAUSED: AtomicBool = AtomicBool::new(false);
+// Index of the `Dither` in `DITHERS`.
+static OUTPUT_DITHER: AtomicU8 = AtomicU8::new(0);
+
+const DITHERS: [Dither; 3] = [Dither::None, Dither::Triangular, Dither::NoiseShaped];
+
+/// Returns the volume applied to everything that is played, after it is mixed.
+#[inline]
+pub fn master_volume() -> f32 {
+    f32::from_bits(MASTER_VOLUME.load(Ordering::Relaxed))
+}
+
+/// Changes the volume applied to everything that is played, after it is mixed.
+///
+/// The value `1.0` is the "normal" volume, and it multiplies the volume of each `Sink`. The
+/// change applies to the sounds that are already playing.
+#[inline]
+pub fn set_master_volume(value: f32) {
+    MASTER_VOLUME.store(value.to_bits(), Ordering::Relaxed);
+}
+
+/// Pauses everything that is played on the devices, for example when a game loses the focus.
+///
+/// The sounds fade out over 10 milliseconds and then aren't read anymore, so that they continue
+/// where they were on `resume_all`. This is independent of `Sink::pause`.
+#[inline]
+pub fn pause_all() {
+    ALL_PAUSED.store(true, Ordering::Relaxed);
+}
+
+/// Resumes everything paused with `pause_all`, with a fade in of 10 milliseconds.
+#[inline]
+pub fn resume_all() {
+    ALL_PAUSED.store(false, Ordering::Relaxed);
+}
+
+/// Returns true if everything has been paused with `pause_all`.
+#[inline]
+pub fn is_all_paused() -> bool {
+    ALL_PAUSED.load(Ordering::Relaxed)
+}
+
+/// Returns true if the limiter applied after the master volume is enabled.
+#[inline]
+pub fn is_limiter_enabled() -> bool {
+    LIMITER_ENABLED.load(Ordering::Relaxed)
+}
+
+/// Enables or disables the limiter applied after the master volume. It is disabled by default.
+///
+/// The limiter lowers the volume as soon as a sample would go over the ceiling, and then brings
+/// it back over 50 milliseconds, so that many sounds played at once never clip.
+#[inline]
+pub fn set_limiter_enabled(enabled: bool) {
+    LIMITER_ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+/// Returns the dither applied to the output of the devices that play integer samples.
+#[inline]
+pub fn output_dither() -> Dither {
+    DITHERS[OUTPUT_DITHER.load(Ordering::Relaxed) as usize]
+}
+
+/// Changes the dither applied when the output is converted to the integer samples of a device, as
+/// the last step after the master volume and the limiter. The default is `Dither::None`.
+///
+/// It has no effect on the devices that play `f32` samples.
+#[inline]
+pub fn set_output_dither(dither: Dither) {
+    let index = DITHERS.iter().position(|&d| d == dither).unwrap();
+    OUTPUT_DITHER.store(index as u8, Ordering::Relaxed);
+}
+
+/// Returns the maximum absolute value of the samples when the limiter is enabled.
+#[inline]
+pub fn limiter_ceiling() -> f32 {
+    f32::from_bits(LIMITER_CEILING.load(Ordering::Relaxed))
+}
+
+/// Changes the maximum absolute value of the samples when the limiter is enabled, between `0.0`
+/// and `1.0`. The default is `1.0`, which is full scale.
+#[inline]
+pub fn set_limiter_ceiling(value: f32) {
+    LIMITER_CEILING.store(value.clamp(0.0, 1.0).to_bits(), Ordering::Relaxed);
+}
+
+/// Converts the sample rate of the sources played afterwards with the given `Resampler`, instead
+/// of the windowed-sinc interpolation used by default. `None` goes back to the default.
+pub fn set_resampler(resampler: Option<Box<dyn Resampler>>) {
+    *RESAMPLER.lock().unwrap() = resampler;
+}
+
+/// Opens the output to a device with the given sample format, sample rate and channels, instead
+/// of the default format of the device, and declares the layout of its channels.
+///
+/// This must be called before anything is played on the device. The sounds played afterwards are
+/// converted to this format. The sounds whose number of channels has a usual layout are converted
+/// to `layout` speaker by speaker, as with `Source::convert_layout`.
+///
+/// `latency` is the duration of the buffers that the device should ask for, shorter for lower
+/// latency or longer to avoid glitches. It is only a hint: the version of `cpal` used by rodio
+/// lets the audio backend choose the size of the buffers, so it is currently ignored. The latency
+/// obtained is reported by `output_latency`.
+pub fn open_output(
+    device: &Device, format: &Format, layout: ChannelLayout, latency: Option<Duration>,
+) -> Result<(), OpenOutputError> {
+    // The buffer size can't be passed to the backend yet, see above.
+    let _ = latency;
+    if layout.channels() != format.channels {
+        return Err(OpenOutputError::LayoutMismatch);
     }
 
-    start(&ENGINE, device, source);
+    let mut end_points = ENGINE.end_points.lock().unwrap();
+    let name = device_name(device);
+    if let Some(end_point) = end_points.get(&name) {
+        if end_point.mixer.upgrade().is_some() {
+            return Err(OpenOutputError::AlreadyOpen);
+        }
+    }
+
+    let mut supported = device
+        .supported_output_formats()
+        .map_err(OpenOutputError::SupportedFormats)?;
+    if !supported.any(|supported| supports(&supported, format)) {
+        return Err(OpenOutputError::UnsupportedFormat);
+    }
+
+    let stream_id = ENGINE
+        .events_loop
+        .build_output_stream(device, format)
+        .map_err(OpenOutputError::BuildStream)?;
+    let mixer = add_output_stream(&ENGINE, format, stream_id.clone());
+    ENGINE
+        .events_loop
+        .play_stream(stream_id.clone())
+        .map_err(OpenOutputError::PlayStream)?;
+    mixer.set_resampler(RESAMPLER.lock().unwrap().clone());
+    mixer.set_layout(Some(layout));
+
+    end_points.insert(
+        name,
+        EndPoint {
+            mixer: Arc::downgrade(&mixer),
+            stream_id,
+        },
+    );
+    Ok(())
+}
+
+/// Error that can happen when opening the output to a device with `open_output`.
+#[derive(Debug)]
+pub enum OpenOutputError {
+    /// Something has already been played on the device, with another format.
+    AlreadyOpen,
+    /// The device doesn't support the format.
+    UnsupportedFormat,
+    /// The layout doesn't have the number of channels of the format.
+    LayoutMismatch,
+    /// The formats supported by the device couldn't be queried.
+    SupportedFormats(SupportedFormatsError),
+    /// The backend couldn't build the stream.
+    BuildStream(BuildStreamError),
+    /// The backend couldn't start the stream.
+    PlayStream(PlayStreamError),
+}
+
+impl fmt::Display for OpenOutputError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            OpenOutputError::AlreadyOpen => write!(f, "The output to the device is already open"),
+            OpenOutputError::UnsupportedFormat => {
+                write!(f, "The device doesn't support the format")
+            },
+            OpenOutputError::LayoutMismatch => {
+                write!(f, "The layout doesn't have the number of channels of the format")
+            },
+            OpenOutputError::SupportedFormats(ref err) => err.fmt(f),
+            OpenOutputError::BuildStream(ref err) => err.fmt(f),
+            OpenOutputError::PlayStream(ref err) => err.fmt(f),
+        }
+    }
+}
+
+impl Error for OpenOutputError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match *self {
+            OpenOutputError::SupportedFormats(ref err) => Some(err),
+            OpenOutputError::BuildStream(ref err) => Some(err),
+            OpenOutputError::PlayStream(ref err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+/// Returns the latency of the output to a device: the duration of the buffers that the device
+/// asks for, after which a change such as `Sink::pause` is heard.
+///
+/// Returns `None` if nothing has been played on the device yet. The size of the buffers is
+/// chosen by the audio backend, whatever the latency given to `open_output`.
+pub fn output_latency(device: &Device) -> Option<Duration> {
+    let stream_id = playing_stream(device)?;
+    ENGINE.latencies.lock().unwrap().get(&stream_id).cloned()
+}
+
+/// Returns the peak and RMS level of each channel of what is played on a device, after the master
+/// volume and the limiter, measured over the last 50 milliseconds.
+///
+/// Returns `None` if nothing has been played on the device yet.
+pub fn output_levels(device: &Device) -> Option<Vec<ChannelLevel>> {
+    let stream_id = playing_stream(device)?;
+    let levels = ENGINE.states.lock().unwrap().get(&stream_id)?.meter.levels.clone();
+    Some(
+        levels
+            .iter()
+            .map(|level| ChannelLevel {
+                peak: f32::from_bits(level.peak.load(Ordering::Relaxed)),
+                rms: f32::from_bits(level.rms.load(Ordering::Relaxed)),
+            })
+            .collect(),
+    )
+}
+
+/// Returns the clock of the output to a device.
+///
+/// Returns `None` if nothing has been played on the device yet.
+pub fn stream_clock(device: &Device) -> Option<StreamClock> {
+    let stream_id = playing_stream(device)?;
+    let states = ENGINE.states.lock().unwrap();
+    Some(states.get(&stream_id)?.clock.clone())
+}
+
+// Returns the stream that plays on a device, if there is one.
+fn playing_stream(device: &Device) -> Option<StreamId> {
+    ENGINE
+        .end_points
+        .lock()
+        .unwrap()
+        .get(&device_name(device))
+        .filter(|end_point| end_point.mixer.upgrade().is_some())
+        .map(|end_point| end_point.stream_id.clone())
+}
+
+// Splits what a stream plays into a copy that fades out, and a copy that fades in and then keeps
+// playing.
+fn crossfade_split<S>(source: S) -> (TakeDuration<Tee<S>>, FadeIn<Tee<S>>)
+where
+    S: Source<Item = f32>,
+{
+    let (old, new) = source.tee();
+    let mut old = old.take_duration(SWITCH_CROSSFADE);
+    old.set_filter_fadeout();
+    (old, new.fade_in(SWITCH_CROSSFADE))
 }
 
 // The internal engine of this library.
@@ -63,8 +386,24 @@ struct Engine {
 
     dynamic_mixers: Mutex<HashMap<StreamId, dynamic_mixer::DynamicMixer<f32>>>,
 
+    // Duration of the last buffer filled for each stream.
+    latencies: Mutex<HashMap<StreamId, Duration>>,
+
+    // State of the processing of the mix of each stream.
+    states: Mutex<HashMap<StreamId, StreamState>>,
+
     // TODO: don't use the device name, as it's slow
-    end_points: Mutex<HashMap<String, Weak<dynamic_mixer::DynamicMixerController<f32>>>>,
+    end_points: Mutex<HashMap<String, EndPoint>>,
+
+    // Streams of the devices that were switched away from, which are destroyed once their fade
+    // out ends.
+    retiring: Mutex<HashSet<StreamId>>,
+}
+
+// The stream that plays on a device.
+struct EndPoint {
+    mixer: Weak<dynamic_mixer::DynamicMixerController<f32>>,
+    stream_id: StreamId,
 }
 
 fn audio_callback(engine: &Arc<Engine>, stream_id: StreamId, buffer: StreamData) {
@@ -74,30 +413,417 @@ fn audio_callback(engine: &Arc<Engine>, stream_id: StreamId, buffer: StreamData)
         Some(m) => m,
         None => return,
     };
+    let volume = master_volume();
+    let (channels, sample_rate) = (mixer_rx.channels(), mixer_rx.sample_rate());
+
+    let mut states = engine.states.lock().unwrap();
+    let state = states
+        .entry(stream_id.clone())
+        .or_insert_with(|| StreamState::new(channels, sample_rate));
+    let ceiling = if is_limiter_enabled() {
+        Some(limiter_ceiling())
+    } else {
+        None
+    };
+    let paused = is_all_paused();
+    state.ditherer.set_dither(output_dither());
+    let dither = state.ditherer.is_enabled();
+    let mut ended = false;
+    let mut next = |state: &mut StreamState| {
+        let sample = state.next(mixer_rx, volume, ceiling, paused);
+        ended |= state.mixer_ended;
+        sample
+    };
 
-    match buffer {
+    let len = match buffer {
         StreamData::Output {
             buffer: UnknownTypeOutputBuffer::U16(mut buffer),
-        } => for d in buffer.iter_mut() {
-            *d = mixer_rx
-                .next()
-                .map(|s| s.to_u16())
-                .unwrap_or(u16::max_value() / 2);
+        } => {
+            for d in buffer.iter_mut() {
+                let channel = state.current_channel;
+                *d = match next(state) {
+                    Some(s) if dither => state.ditherer.convert(s, channel),
+                    Some(s) => s.to_u16(),
+                    None => u16::max_value() / 2,
+                };
+            }
+            buffer.len()
         },
         StreamData::Output {
             buffer: UnknownTypeOutputBuffer::I16(mut buffer),
-        } => for d in buffer.iter_mut() {
-            *d = mixer_rx.next().map(|s| s.to_i16()).unwrap_or(0i16);
+        } => {
+            for d in buffer.iter_mut() {
+                let channel = state.current_channel;
+                *d = match next(state) {
+                    Some(s) if dither => state.ditherer.convert(s, channel),
+                    Some(s) => s.to_i16(),
+                    None => 0i16,
+                };
+            }
+            buffer.len()
         },
         StreamData::Output {
             buffer: UnknownTypeOutputBuffer::F32(mut buffer),
-        } => for d in buffer.iter_mut() {
-            *d = mixer_rx.next().unwrap_or(0f32);
+        } => {
+            for d in buffer.iter_mut() {
+                *d = next(state).unwrap_or(0f32);
+            }
+            buffer.len()
         },
         StreamData::Input { .. } => {
             panic!("Can't play an input stream!");
         },
     };
+
+    state.clock.advance((len / channels as usize) as u64);
+
+    if ended && engine.retiring.lock().unwrap().remove(&stream_id) {
+        dynamic_mixers.remove(&stream_id);
+        states.remove(&stream_id);
+        engine.latencies.lock().unwrap().remove(&stream_id);
+        engine.events_loop.destroy_stream(stream_id);
+        return;
+    }
+
+    let latency = buffer_duration(len, channels, sample_rate);
+    engine.latencies.lock().unwrap().insert(stream_id, latency);
+}
+
+// Processing of the mix of a stream after the master volume.
+struct StreamState {
+    limiter: Limiter,
+    meter: OutputMeter,
+    // Gain of the fade when pausing or resuming everything.
+    pause_gain: f32,
+    // Change of `pause_gain` at each frame.
+    pause_step: f32,
+    channels: u16,
+    // Channel of the next sample returned by `next`.
+    current_channel: u16,
+    clock: StreamClock,
+    // Dither of the conversion to the integer samples of the device.
+    ditherer: Ditherer,
+    // Whether the mixer had nothing left to play the last time it was read. Unlike the `None`
+    // returned by `next`, it isn't set while everything is paused.
+    mixer_ended: bool,
+}
+
+impl StreamState {
+    fn new(channels: u16, sample_rate: u32) -> StreamState {
+        StreamState {
+            limiter: Limiter::new(channels, sample_rate),
+            meter: OutputMeter::new(channels, sample_rate),
+            pause_gain: 1.0,
+            pause_step: 1.0 / (PAUSE_FADE_SECS * sample_rate as f32),
+            channels,
+            current_channel: 0,
+            clock: StreamClock::new(sample_rate),
+            ditherer: Ditherer::new(Dither::None),
+            mixer_ended: false,
+        }
+    }
+
+    // Returns the next sample of the mix, or `None` if there is nothing to play, either because
+    // everything is paused or because the mixer has ended.
+    fn next(
+        &mut self, mixer: &mut dynamic_mixer::DynamicMixer<f32>, volume: f32,
+        ceiling: Option<f32>, paused: bool,
+    ) -> Option<f32> {
+        // The fade changes at frame boundaries, and the mixer stops being read at one, so that
+        // its channels stay aligned with the ones of the device.
+        if self.current_channel == 0 {
+            // Once the fade out is over, the sounds aren't read anymore.
+            if paused && self.pause_gain <= 0.0 {
+                self.meter.process(0.0);
+                return None;
+            }
+            self.pause_gain = if paused {
+                (self.pause_gain - self.pause_step).max(0.0)
+            } else {
+                (self.pause_gain + self.pause_step).min(1.0)
+            };
+        }
+        self.current_channel = (self.current_channel + 1) % self.channels;
+
+        let value = mixer.next().map(|value| {
+            let value = value * volume * self.pause_gain;
+            match ceiling {
+                Some(ceiling) => self.limiter.process(value, ceiling),
+                None => value,
+            }
+        });
+        self.mixer_ended = value.is_none();
+        self.meter.process(value.unwrap_or(0.0));
+        value
+    }
+}
+
+/// Clock of the output to a device, returned by `stream_clock`, to synchronize something else
+/// such as the frames of a video with what is played.
+#[derive(Clone)]
+pub struct StreamClock {
+    shared: Arc<ClockShared>,
+}
+
+// Written by the audio thread without locking. The fields about the buffers are published with
+// a sequence number, which is odd while they are written and 0 until the first buffer, so that
+// the readers retry instead of reading a buffer half-written.
+struct ClockShared {
+    sample_rate: u32,
+    // Instant from which the times below are counted.
+    origin: Instant,
+    // Number of frames written to the device.
+    frames_written: AtomicU64,
+    sequence: AtomicU64,
+    // Nanoseconds since `origin` when the first and the last buffers were filled.
+    first_filled: AtomicU64,
+    last_filled: AtomicU64,
+    // `first_frame` and `frames` of the last buffer.
+    last_first_frame: AtomicU64,
+    last_frames: AtomicU64,
+    // Last value returned by `time`, in nanoseconds, so that it never goes back.
+    last_time: AtomicU64,
+}
+
+/// When a buffer of the output to a device was filled, returned by `StreamClock::last_buffer`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct BufferTimestamp {
+    /// When the buffer was filled, by the system clock.
+    pub filled_at: Instant,
+    /// Number of frames written to the device before this buffer.
+    pub first_frame: u64,
+    /// Number of frames of the buffer.
+    pub frames: u64,
+    /// Estimated latency: the duration of the buffer, after which its last frame is heard.
+    pub latency: Duration,
+}
+
+impl StreamClock {
+    fn new(sample_rate: u32) -> StreamClock {
+        StreamClock {
+            shared: Arc::new(ClockShared {
+                sample_rate,
+                origin: Instant::now(),
+                frames_written: AtomicU64::new(0),
+                sequence: AtomicU64::new(0),
+                first_filled: AtomicU64::new(0),
+                last_filled: AtomicU64::new(0),
+                last_first_frame: AtomicU64::new(0),
+                last_frames: AtomicU64::new(0),
+                last_time: AtomicU64::new(0),
+            }),
+        }
+    }
+
+    // Called by the audio thread after a buffer of `frames` frames has been filled. Only one
+    // thread calls it.
+    fn advance(&self, frames: u64) {
+        let shared = &*self.shared;
+        let filled = shared.origin.elapsed().as_nanos() as u64;
+        let first_frame = shared.frames_written.fetch_add(frames, Ordering::SeqCst);
+
+        let sequence = shared.sequence.fetch_add(1, Ordering::SeqCst);
+        if sequence == 0 {
+            shared.first_filled.store(filled, Ordering::SeqCst);
+        }
+        shared.last_filled.store(filled, Ordering::SeqCst);
+        shared.last_first_frame.store(first_frame, Ordering::SeqCst);
+        shared.last_frames.store(frames, Ordering::SeqCst);
+        shared.sequence.fetch_add(1, Ordering::SeqCst);
+    }
+
+    // Returns when the first buffer was filled, in nanoseconds since `origin`, and the last
+    // buffer, or `None` if none has been filled yet.
+    fn read_buffers(&self) -> Option<(u64, BufferTimestamp)> {
+        let shared = &*self.shared;
+        loop {
+            let sequence = shared.sequence.load(Ordering::SeqCst);
+            if sequence == 0 {
+                return None;
+            }
+            if sequence % 2 == 1 {
+                continue;
+            }
+
+            let first_filled = shared.first_filled.load(Ordering::SeqCst);
+            let last_filled = shared.last_filled.load(Ordering::SeqCst);
+            let first_frame = shared.last_first_frame.load(Ordering::SeqCst);
+            let frames = shared.last_frames.load(Ordering::SeqCst);
+            if shared.sequence.load(Ordering::SeqCst) != sequence {
+                continue;
+            }
+
+            let last = BufferTimestamp {
+                filled_at: shared.origin + Duration::from_nanos(last_filled),
+                first_frame,
+                frames,
+                latency: buffer_duration(frames as usize, 1, shared.sample_rate),
+            };
+            return Some((first_filled, last));
+        }
+    }
+
+    /// Returns when the last buffer was filled, or `None` if none has been yet.
+    #[inline]
+    pub fn last_buffer(&self) -> Option<BufferTimestamp> {
+        self.read_buffers().map(|(_, last)| last)
+    }
+
+    /// Returns how far the device is ahead of the system clock, in seconds, since the first
+    /// buffer. It is negative if the device plays slower than the system clock.
+    ///
+    /// The device consumes the frames at its own clock, which slowly drifts from the system
+    /// clock. The value is only precise to the duration of a buffer, which is negligible after a
+    /// long time.
+    pub fn drift(&self) -> f64 {
+        match self.read_buffers() {
+            Some((first_filled, last)) => {
+                // When a buffer is filled, the device has consumed all the previous ones.
+                let played = last.first_frame as f64 / self.shared.sample_rate as f64;
+                let first = self.shared.origin + Duration::from_nanos(first_filled);
+                let elapsed = last.filled_at.duration_since(first).as_secs_f64();
+                played - elapsed
+            },
+            None => 0.0,
+        }
+    }
+
+    /// Returns the number of frames written to the device since the output was opened, silence
+    /// included.
+    #[inline]
+    pub fn frames_written(&self) -> u64 {
+        self.shared.frames_written.load(Ordering::SeqCst)
+    }
+
+    /// Returns the sample rate of the output.
+    #[inline]
+    pub fn sample_rate(&self) -> u32 {
+        self.shared.sample_rate
+    }
+
+    /// Returns the time played since the output was opened, silence included.
+    ///
+    /// The buffer filled last is assumed to start playing when it is filled: the time is
+    /// interpolated with the system clock from the start of this buffer until its end. It never
+    /// goes back.
+    pub fn time(&self) -> Duration {
+        let rate = self.shared.sample_rate as f64;
+        let time = match self.last_buffer() {
+            Some(buffer) => {
+                let elapsed = buffer.filled_at.elapsed().min(buffer.latency);
+                Duration::from_secs_f64(buffer.first_frame as f64 / rate) + elapsed
+            },
+            None => Duration::from_secs(0),
+        };
+
+        let time = time.as_nanos() as u64;
+        let last_time = self.shared.last_time.fetch_max(time, Ordering::SeqCst);
+        Duration::from_nanos(last_time.max(time))
+    }
+}
+
+// Level of a channel that can be read without locking, stored as the bits of `f32`s.
+struct AtomicLevel {
+    peak: AtomicU32,
+    rms: AtomicU32,
+}
+
+// Measures the levels of what is played, over windows of `METER_WINDOW_MS`.
+struct OutputMeter {
+    levels: Arc<Vec<AtomicLevel>>,
+    // Channel of the next sample.
+    current_channel: usize,
+    // Number of frames of the current window measured so far, and in a whole window.
+    window_frames: u32,
+    window_len: u32,
+    peaks: Vec<f32>,
+    sum_squares: Vec<f32>,
+}
+
+impl OutputMeter {
+    fn new(channels: u16, sample_rate: u32) -> OutputMeter {
+        let channels = channels as usize;
+        let levels = (0 .. channels)
+            .map(|_| AtomicLevel {
+                peak: AtomicU32::new(0),
+                rms: AtomicU32::new(0),
+            })
+            .collect();
+        OutputMeter {
+            levels: Arc::new(levels),
+            current_channel: 0,
+            window_frames: 0,
+            window_len: (METER_WINDOW_MS * sample_rate / 1000).max(1),
+            peaks: vec![0.0; channels],
+            sum_squares: vec![0.0; channels],
+        }
+    }
+
+    #[inline]
+    fn process(&mut self, value: f32) {
+        let channel = self.current_channel;
+        self.peaks[channel] = self.peaks[channel].max(value.abs());
+        self.sum_squares[channel] += value * value;
+
+        self.current_channel += 1;
+        if self.current_channel < self.peaks.len() {
+            return;
+        }
+        self.current_channel = 0;
+        self.window_frames += 1;
+        if self.window_frames < self.window_len {
+            return;
+        }
+
+        let window_frames = self.window_frames as f32;
+        for ((level, peak), sum) in self
+            .levels
+            .iter()
+            .zip(&mut self.peaks)
+            .zip(&mut self.sum_squares)
+        {
+            level.peak.store(peak.to_bits(), Ordering::Relaxed);
+            level.rms.store((*sum / window_frames).sqrt().to_bits(), Ordering::Relaxed);
+            *peak = 0.0;
+            *sum = 0.0;
+        }
+        self.window_frames = 0;
+    }
+}
+
+// Limiter with an instant attack, so that no sample ever goes over the ceiling.
+struct Limiter {
+    // Gain currently applied.
+    gain: f32,
+    // Fraction of the distance to a gain of 1 recovered after each sample.
+    release: f32,
+}
+
+impl Limiter {
+    fn new(channels: u16, sample_rate: u32) -> Limiter {
+        let samples_per_sec = channels as f32 * sample_rate as f32;
+        Limiter {
+            gain: 1.0,
+            release: 1.0 - (-1.0 / (LIMITER_RELEASE_SECS * samples_per_sec)).exp(),
+        }
+    }
+
+    #[inline]
+    fn process(&mut self, value: f32, ceiling: f32) -> f32 {
+        if value.abs() * self.gain > ceiling {
+            self.gain = ceiling / value.abs();
+        }
+        let value = value * self.gain;
+        self.gain += (1.0 - self.gain) * self.release;
+        value
+    }
+}
+
+// Returns the duration of a buffer of `len` samples.
+#[inline]
+fn buffer_duration(len: usize, channels: u16, sample_rate: u32) -> Duration {
+    let frames = len / channels as usize;
+    Duration::from_nanos(frames_to_ns(frames as u64, sample_rate))
 }
 
 // Builds a new sink that targets a given device.
@@ -110,19 +836,25 @@ where
     let mixer = {
         let mut end_points = engine.end_points.lock().unwrap();
 
-        match end_points.entry(device.name().expect("No device name")) {
+        match end_points.entry(device_name(device)) {
             Entry::Vacant(e) => {
                 let (mixer, stream) = new_output_stream(engine, device);
-                e.insert(Arc::downgrade(&mixer));
+                e.insert(EndPoint {
+                    mixer: Arc::downgrade(&mixer),
+                    stream_id: stream.clone(),
+                });
                 stream_to_start = Some(stream);
                 mixer
             },
             Entry::Occupied(mut e) => {
-                if let Some(m) = e.get().upgrade() {
+                if let Some(m) = e.get().mixer.upgrade() {
                     m.clone()
                 } else {
                     let (mixer, stream) = new_output_stream(engine, device);
-                    e.insert(Arc::downgrade(&mixer));
+                    e.insert(EndPoint {
+                        mixer: Arc::downgrade(&mixer),
+                        stream_id: stream.clone(),
+                    });
                     stream_to_start = Some(stream);
                     mixer
                 }
@@ -134,9 +866,15 @@ where
         engine.events_loop.play_stream(stream).expect("play_stream failed");
     }
 
+    mixer.set_resampler(RESAMPLER.lock().unwrap().clone());
     mixer.add(source);
 }
 
+#[inline]
+fn device_name(device: &Device) -> String {
+    device.name().expect("No device name")
+}
+
 // Adds a new stream to the engine.
 fn new_output_stream(
     engine: &Arc<Engine>,
@@ -159,15 +897,35 @@ fn new_output_stream(
         }
     };
 
+    (add_output_stream(engine, &format, stream_id.clone()), stream_id)
+}
+
+// Adds the mixer of a stream that has been built with the given format.
+fn add_output_stream(
+    engine: &Arc<Engine>,
+    format: &Format,
+    stream_id: StreamId,
+) -> Arc<dynamic_mixer::DynamicMixerController<f32>> {
     let (mixer_tx, mixer_rx) = dynamic_mixer::mixer::<f32>(format.channels, format.sample_rate.0);
+    // Sounds often have to be converted from 44.1 kHz to 48 kHz or the other way around, where
+    // linear interpolation would alias audibly.
+    mixer_tx.set_resample_quality(ResampleQuality::Medium);
 
     engine
         .dynamic_mixers
         .lock()
         .unwrap()
-        .insert(stream_id.clone(), mixer_rx);
+        .insert(stream_id, mixer_rx);
+
+    mixer_tx
+}
 
-    (mixer_tx, stream_id)
+// Returns true if a format is within the supported range.
+fn supports(supported: &SupportedFormat, format: &Format) -> bool {
+    supported.channels == format.channels
+        && supported.data_type == format.data_type
+        && supported.min_sample_rate <= format.sample_rate
+        && format.sample_rate <= supported.max_sample_rate
 }
 
 /// Search through all the supported formats trying to find one that
@@ -223,3 +981,157 @@ impl SupportedFormatExt for cpal::SupportedFormat {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::Ordering;
+    use std::thread;
+    use std::time::Duration;
+
+    use buffer::SamplesBuffer;
+    use cpal::{Format, SampleFormat, SampleRate, SupportedFormat};
+    use conversions::Dither;
+    use dynamic_mixer;
+    use engine::{crossfade_split, supports, Limiter, OutputMeter, StreamClock, StreamState};
+    use engine::{output_dither, set_output_dither};
+
+    #[test]
+    fn buffer_duration() {
+        assert_eq!(super::buffer_duration(960, 2, 48000), Duration::from_millis(10));
+        assert_eq!(super::buffer_duration(441, 1, 44100), Duration::from_millis(10));
+    }
+
+    #[test]
+    fn pause_keeps_channel_order() {
+        let (tx, mut rx) = dynamic_mixer::mixer(2, 48000);
+        tx.add(SamplesBuffer::new(2, 48000, [1.0f32, -1.0].repeat(2000)));
+        let mut state = StreamState::new(2, 48000);
+
+        let mut read = 0;
+        while state.next(&mut rx, 1.0, None, true).is_some() {
+            read += 1;
+        }
+        assert_eq!(read % 2, 0);
+
+        // The device asks for whole frames after resuming.
+        for _ in 0 .. 1000 {
+            let left = state.next(&mut rx, 1.0, None, false).unwrap();
+            let right = state.next(&mut rx, 1.0, None, false).unwrap();
+            assert!(left >= 0.0 && right <= 0.0);
+        }
+        assert_eq!(state.next(&mut rx, 1.0, None, false), Some(1.0));
+    }
+
+    #[test]
+    fn pause_does_not_end_the_stream() {
+        let (tx, mut rx) = dynamic_mixer::mixer(1, 1000);
+        tx.add(SamplesBuffer::new(1, 1000, vec![0.5f32; 2000]));
+        let mut state = StreamState::new(1, 1000);
+
+        while state.next(&mut rx, 1.0, None, true).is_some() {}
+        assert!(!state.mixer_ended);
+        assert_eq!(state.next(&mut rx, 1.0, None, true), None);
+        assert!(!state.mixer_ended);
+
+        while state.next(&mut rx, 1.0, None, false).is_some() {}
+        assert!(state.mixer_ended);
+    }
+
+    #[test]
+    fn output_dither_setting() {
+        assert_eq!(output_dither(), Dither::None);
+        set_output_dither(Dither::NoiseShaped);
+        assert_eq!(output_dither(), Dither::NoiseShaped);
+        set_output_dither(Dither::None);
+    }
+
+    #[test]
+    fn limiter_never_clips() {
+        let mut limiter = Limiter::new(1, 1000);
+        let output = [0.5f32, 2.0, -1.5, 1.2, 0.5]
+            .iter()
+            .map(|&value| limiter.process(value, 0.9))
+            .collect::<Vec<_>>();
+        assert_eq!(output[0], 0.5);
+        assert!(output.iter().all(|value| value.abs() <= 0.9));
+        assert!((output[1] - 0.9).abs() < 1e-6);
+
+        // The gain recovers after the peaks.
+        for _ in 0 .. 1000 {
+            limiter.process(0.0, 0.9);
+        }
+        assert!((limiter.process(0.5, 0.9) - 0.5).abs() < 1e-3);
+    }
+
+    #[test]
+    fn output_meter() {
+        let mut meter = OutputMeter::new(2, 1000);
+        for _ in 0 .. 50 {
+            meter.process(0.5);
+            meter.process(-0.25);
+        }
+        let level = |channel: usize| {
+            let level = &meter.levels[channel];
+            (
+                f32::from_bits(level.peak.load(Ordering::Relaxed)),
+                f32::from_bits(level.rms.load(Ordering::Relaxed)),
+            )
+        };
+        assert_eq!(level(0), (0.5, 0.5));
+        assert_eq!(level(1), (0.25, 0.25));
+    }
+
+    #[test]
+    fn stream_clock() {
+        let clock = StreamClock::new(1000);
+        assert_eq!(clock.time(), Duration::from_secs(0));
+
+        clock.advance(10);
+        thread::sleep(Duration::from_millis(20));
+        assert_eq!(clock.frames_written(), 10);
+        assert_eq!(clock.time(), Duration::from_millis(10));
+
+        clock.advance(10);
+        let time = clock.time();
+        assert!(time >= Duration::from_millis(10) && time <= Duration::from_millis(20));
+
+        let buffer = clock.last_buffer().unwrap();
+        assert_eq!((buffer.first_frame, buffer.frames), (10, 10));
+        assert_eq!(buffer.latency, Duration::from_millis(10));
+        // 10 milliseconds were played in at least 20 milliseconds.
+        assert!(clock.drift() <= -0.01);
+    }
+
+    #[test]
+    fn supported_formats() {
+        let supported = SupportedFormat {
+            channels: 2,
+            min_sample_rate: SampleRate(44100),
+            max_sample_rate: SampleRate(48000),
+            data_type: SampleFormat::F32,
+        };
+        let format = |data_type, rate| Format {
+            channels: 2,
+            sample_rate: SampleRate(rate),
+            data_type,
+        };
+        assert!(supports(&supported, &format(SampleFormat::F32, 48000)));
+        assert!(!supports(&supported, &format(SampleFormat::I16, 48000)));
+        assert!(!supports(&supported, &format(SampleFormat::F32, 96000)));
+    }
+
+    #[test]
+    fn switch_crossfade() {
+        let source = SamplesBuffer::new(1, 1000, vec![1.0f32; 100]);
+        let (old, new) = crossfade_split(source);
+        let old = old.collect::<Vec<_>>();
+        let new = new.collect::<Vec<_>>();
+
+        // 20 milliseconds are 20 samples.
+        assert!((19 ..= 20).contains(&old.len()));
+        assert!(old[0] > old[18]);
+        assert_eq!(new.len(), 100);
+        assert!(new[0] < new[19]);
+        assert_eq!(new[50], 1.0);
+    }
+}
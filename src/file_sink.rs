@@ -0,0 +1,216 @@
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use hound::{self, SampleFormat, WavSpec, WavWriter};
+
+use source::UniformSourceIterator;
+use Completion;
+use Sample;
+use Sink;
+use Source;
+
+/// Handle to a 16-bit WAV file into which sounds are written instead of being played.
+///
+/// It has the same API as `Sink`: the sounds appended to it are written one after the other, as
+/// fast as possible, with the volume that the sink has when they are written. Nothing is written
+/// while the sink is paused, and the time between the sounds isn't written either.
+///
+/// The file is complete once `finish` returns. Dropping the `FileSink` stops it and completes the
+/// file without writing the sounds that are left.
+pub struct FileSink {
+    sink: Arc<Sink>,
+    channels: u16,
+    sample_rate: u32,
+    // Tells the writing thread to stop once everything has been written.
+    finished: Arc<AtomicBool>,
+    writer: Option<JoinHandle<Result<(), hound::Error>>>,
+}
+
+impl FileSink {
+    /// Creates a WAV file with the given channels and sample rate. The sounds appended are
+    /// converted to them.
+    pub fn new<P>(path: P, channels: u16, sample_rate: u32) -> Result<FileSink, hound::Error>
+    where
+        P: AsRef<Path>,
+    {
+        let spec = WavSpec {
+            channels,
+            sample_rate,
+            bits_per_sample: 16,
+            sample_format: SampleFormat::Int,
+        };
+        let mut writer = WavWriter::create(path, spec)?;
+
+        let (sink, mut queue_rx) = Sink::new_idle_without_silence();
+        let sink = Arc::new(sink);
+        let finished = Arc::new(AtomicBool::new(false));
+
+        let writer = thread::Builder::new()
+            .name("rodio file sink".to_string())
+            .spawn({
+                let sink = sink.clone();
+                let finished = finished.clone();
+                move || {
+                    loop {
+                        let finished = finished.load(Ordering::SeqCst);
+                        let sample = if sink.is_paused() { None } else { queue_rx.next() };
+                        match sample {
+                            Some(sample) => writer.write_sample(sample.to_i16())?,
+                            None if finished => break,
+                            None => thread::sleep(Duration::from_millis(1)),
+                        }
+                    }
+                    writer.finalize()
+                }
+            })?;
+
+        Ok(FileSink {
+            sink,
+            channels,
+            sample_rate,
+            finished,
+            writer: Some(writer),
+        })
+    }
+
+    /// Appends a sound to the queue of sounds to write.
+    ///
+    /// The returned handle completes when this sound ends. See `Sink::append`.
+    #[inline]
+    pub fn append<S>(&self, source: S) -> Completion
+    where
+        S: Source + Send + 'static,
+        S::Item: Sample + Send,
+    {
+        let source = UniformSourceIterator::<_, f32>::new(source, self.channels, self.sample_rate);
+        self.sink.append(source)
+    }
+
+    /// Gets the volume of the sound.
+    ///
+    /// The value `1.0` is the "normal" volume (unfiltered input). Any value other than 1.0 will
+    /// multiply each sample by this value.
+    #[inline]
+    pub fn volume(&self) -> f32 {
+        self.sink.volume()
+    }
+
+    /// Changes the volume of the sound.
+    ///
+    /// The value `1.0` is the "normal" volume (unfiltered input). Any value other than 1.0 will
+    /// multiply each sample by this value.
+    #[inline]
+    pub fn set_volume(&self, value: f32) {
+        self.sink.set_volume(value);
+    }
+
+    /// Resumes writing the sounds.
+    ///
+    /// No effect if not paused.
+    #[inline]
+    pub fn play(&self) {
+        self.sink.play();
+    }
+
+    /// Pauses the writing of the sounds.
+    ///
+    /// No effect if already paused.
+    #[inline]
+    pub fn pause(&self) {
+        self.sink.pause();
+    }
+
+    /// Gets if the sink is paused.
+    #[inline]
+    pub fn is_paused(&self) -> bool {
+        self.sink.is_paused()
+    }
+
+    /// Stops the sink by emptying the queue.
+    #[inline]
+    pub fn stop(&self) {
+        self.sink.stop();
+    }
+
+    /// Sleeps the current thread until the sounds have been written.
+    #[inline]
+    pub fn sleep_until_end(&self) {
+        self.sink.sleep_until_end();
+    }
+
+    /// Returns true if this sink has no more sounds to write.
+    #[inline]
+    pub fn empty(&self) -> bool {
+        self.sink.empty()
+    }
+
+    /// Writes the sounds of the queue and completes the file.
+    ///
+    /// If the sink is paused, the sounds that haven't been written yet are dropped. This never
+    /// returns if a sound of the queue never ends.
+    #[inline]
+    pub fn finish(mut self) -> Result<(), hound::Error> {
+        self.join()
+    }
+
+    fn join(&mut self) -> Result<(), hound::Error> {
+        self.finished.store(true, Ordering::SeqCst);
+        match self.writer.take() {
+            Some(writer) => writer.join().expect("The writing thread panicked"),
+            None => Ok(()),
+        }
+    }
+}
+
+impl Drop for FileSink {
+    #[inline]
+    fn drop(&mut self) {
+        self.sink.stop();
+        let _ = self.join();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use std::fs;
+
+    use hound::WavReader;
+
+    use buffer::SamplesBuffer;
+    use file_sink::FileSink;
+    use source::Zero;
+
+    #[test]
+    fn writes_the_sounds() {
+        let path = env::temp_dir().join("rodio_file_sink_test.wav");
+        let sink = FileSink::new(&path, 2, 1000).unwrap();
+        sink.set_volume(0.5);
+        sink.append(SamplesBuffer::new(2, 1000, vec![1000i16, -1000, 2000, -2000]));
+        sink.append(SamplesBuffer::new(1, 1000, vec![3000i16]));
+        sink.finish().unwrap();
+
+        let mut reader = WavReader::open(&path).unwrap();
+        assert_eq!(reader.spec().channels, 2);
+        assert_eq!(reader.spec().sample_rate, 1000);
+        let samples = reader.samples::<i16>().map(|s| s.unwrap()).collect::<Vec<_>>();
+        fs::remove_file(&path).unwrap();
+
+        // The mono sound is converted to stereo.
+        assert_eq!(samples, [500, -500, 1000, -1000, 1500, 1500]);
+    }
+
+    #[test]
+    fn drop_stops_writing() {
+        let path = env::temp_dir().join("rodio_file_sink_drop_test.wav");
+        let sink = FileSink::new(&path, 1, 1000).unwrap();
+        sink.append(Zero::<i16>::new(1, 1000));
+        drop(sink);
+
+        assert!(WavReader::open(&path).is_ok());
+        fs::remove_file(&path).unwrap();
+    }
+}
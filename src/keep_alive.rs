@@ -0,0 +1,102 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use cpal::traits::DeviceTrait;
+
+use dynamic_mixer::{self, DynamicMixer, DynamicMixerController};
+use Device;
+use Source;
+
+/// Builds a mixer whose output plays silence when it has nothing to mix, and ends once the
+/// returned `KeepAlive` is dropped.
+///
+/// This is the output of the types that mix their own sounds, such as `Voices`, which keep it
+/// playing for as long as they exist.
+pub(crate) fn keep_alive_mixer(
+    channels: u16, sample_rate: u32,
+) -> (Arc<DynamicMixerController<f32>>, KeepAlive, KeepAliveMixer) {
+    let (mixer_tx, mixer_rx) = dynamic_mixer::mixer(channels, sample_rate);
+    let alive = Arc::new(AtomicBool::new(true));
+    let keep_alive = KeepAlive {
+        alive: alive.clone(),
+    };
+    let output = KeepAliveMixer {
+        mixer: mixer_rx,
+        alive,
+    };
+    (mixer_tx, keep_alive, output)
+}
+
+/// Returns the default channels and sample rate of a device, or stereo at 44.1 kHz if they can't
+/// be queried.
+pub(crate) fn default_format(device: &Device) -> (u16, u32) {
+    device
+        .default_output_format()
+        .map_or((2, 44100), |format| (format.channels, format.sample_rate.0))
+}
+
+/// Ends the output of a `keep_alive_mixer` when dropped.
+pub(crate) struct KeepAlive {
+    alive: Arc<AtomicBool>,
+}
+
+impl Drop for KeepAlive {
+    #[inline]
+    fn drop(&mut self) {
+        self.alive.store(false, Ordering::Relaxed);
+    }
+}
+
+/// The output of a `keep_alive_mixer`.
+pub(crate) struct KeepAliveMixer {
+    mixer: DynamicMixer<f32>,
+    alive: Arc<AtomicBool>,
+}
+
+impl KeepAliveMixer {
+    /// Returns false once the `KeepAlive` has been dropped.
+    #[inline]
+    pub(crate) fn is_alive(&self) -> bool {
+        self.alive.load(Ordering::Relaxed)
+    }
+}
+
+impl Iterator for KeepAliveMixer {
+    type Item = f32;
+
+    #[inline]
+    fn next(&mut self) -> Option<f32> {
+        if !self.is_alive() {
+            return None;
+        }
+        Some(self.mixer.next().unwrap_or(0.0))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, None)
+    }
+}
+
+impl Source for KeepAliveMixer {
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        None
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        self.mixer.channels()
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        self.mixer.sample_rate()
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        None
+    }
+}
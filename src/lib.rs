@@ -86,6 +86,8 @@
 #[cfg(feature = "flac")]
 extern crate claxon;
 extern crate cpal;
+#[cfg(feature = "dasp")]
+extern crate dasp;
 #[cfg(feature = "wav")]
 extern crate hound;
 #[macro_use]
@@ -97,24 +99,51 @@ extern crate minimp3;
 
 pub use cpal::{
     traits::DeviceTrait, Device, Devices, DevicesError, Format, InputDevices, OutputDevices,
+    SampleFormat, SampleRate,
 };
 
-pub use conversions::Sample;
+pub use completion::Completion;
+pub use conversions::{Dither, ResampleQuality, Resampler, Sample};
 pub use decoder::Decoder;
-pub use engine::play_raw;
-pub use sink::Sink;
+pub use engine::{
+    is_all_paused, is_limiter_enabled, limiter_ceiling, master_volume, open_output,
+    output_dither, output_latency, output_levels, pause_all, play_raw, resume_all,
+    set_limiter_ceiling, set_limiter_enabled, set_master_volume, set_output_dither, set_resampler,
+    stream_clock, switch_device, BufferTimestamp, OpenOutputError, StreamClock,
+};
+#[cfg(feature = "wav")]
+pub use file_sink::FileSink;
+pub use multi_output::MultiOutput;
+pub use null_output::NullOutput;
+pub use sink::{Effect, EffectId, Sink, SinkEvent, SinkOutput};
+pub use sink_group::{Ducking, SinkGroup, SinkGroups};
 pub use source::Source;
+pub use sound_pool::{SoundPool, SoundPoolOutput, Variation};
 pub use spatial_sink::SpatialSink;
+pub use sync_group::{SyncGroup, SyncGroupOutput};
+pub use voices::{StealPolicy, Voices, VoicesOutput};
 
 use cpal::traits::HostTrait;
 use std::io::{Read, Seek};
+use std::time::Duration;
 
+mod completion;
 mod conversions;
 mod engine;
+#[cfg(feature = "wav")]
+mod file_sink;
+mod keep_alive;
+mod multi_output;
+mod null_output;
 mod sink;
+mod sink_group;
+mod sound_pool;
 mod spatial_sink;
+mod sync_group;
+mod voices;
 
 pub mod buffer;
+pub mod bus;
 pub mod decoder;
 pub mod dynamic_mixer;
 pub mod queue;
@@ -134,6 +163,31 @@ where
     Ok(sink)
 }
 
+/// Renders a source into a buffer, as fast as possible and without any device.
+///
+/// The source is read until its end, or until `duration` if it is given, which is required for
+/// sources that never end. The buffer has the channels and sample rate that the source has at its
+/// start, and the rest of the source is converted to them if they change.
+///
+/// This is useful to test sources, to save a mix, or to compute procedural sounds in advance.
+pub fn render<S>(source: S, duration: Option<Duration>) -> buffer::SamplesBuffer<S::Item>
+where
+    S: Source,
+    S::Item: Sample,
+{
+    let channels = source.channels();
+    let sample_rate = source.sample_rate();
+    let source = source::UniformSourceIterator::new(source, channels, sample_rate);
+    let samples = match duration {
+        Some(duration) => {
+            let frames = (duration.as_secs_f64() * sample_rate as f64).round() as usize;
+            source.take(frames * channels as usize).collect::<Vec<_>>()
+        },
+        None => source.collect(),
+    };
+    buffer::SamplesBuffer::new(channels, sample_rate, samples)
+}
+
 /// The default input audio device on the system.
 ///
 /// Returns `None` if no input device is available.
@@ -0,0 +1,271 @@
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use dynamic_mixer::{self, DynamicMixer, DynamicMixerController};
+use keep_alive::default_format;
+use play_raw;
+use Device;
+use Source;
+
+/// How far behind the fastest device a device can get, in milliseconds, before it skips ahead.
+const MAX_LAG_MS: u64 = 100;
+
+/// A logical output that plays the same sounds on several devices at the same time, for example
+/// speakers and a monitoring interface.
+///
+/// Each device has its own gain. The clocks of the devices drift apart over time: when a device
+/// gets more than 100 milliseconds behind the fastest one, it skips ahead to catch up.
+pub struct MultiOutput {
+    mixer: Arc<DynamicMixerController<f32>>,
+    gains: Vec<Arc<AtomicU32>>,
+    // Set to false when the output is dropped, so that the devices stop playing.
+    alive: Arc<AtomicBool>,
+}
+
+impl MultiOutput {
+    /// Builds an output that plays on all the given devices.
+    ///
+    /// The sounds are mixed with the default format of the first device, and converted to the
+    /// format of the others.
+    pub fn new(devices: &[Device]) -> MultiOutput {
+        let (channels, sample_rate) = devices.first().map_or((2, 44100), default_format);
+        let (output, readers) = MultiOutput::build(channels, sample_rate, devices.len());
+        for (device, reader) in devices.iter().zip(readers) {
+            play_raw(device, reader);
+        }
+        output
+    }
+
+    // Builds the output and one reader of the mix for each device.
+    fn build(channels: u16, sample_rate: u32, count: usize) -> (MultiOutput, Vec<FanOut>) {
+        let (mixer_tx, mixer_rx) = dynamic_mixer::mixer(channels, sample_rate);
+        let alive = Arc::new(AtomicBool::new(true));
+        let max_lag = (sample_rate as u64 * MAX_LAG_MS / 1000) as usize * channels as usize;
+        let shared = Arc::new(Mutex::new(FanOutShared {
+            input: mixer_rx,
+            buffer: VecDeque::new(),
+            start: 0,
+            positions: vec![Some(0); count],
+            max_lag,
+        }));
+
+        let gains = (0 .. count)
+            .map(|_| Arc::new(AtomicU32::new(1f32.to_bits())))
+            .collect::<Vec<_>>();
+        let readers = gains
+            .iter()
+            .enumerate()
+            .map(|(index, gain)| FanOut {
+                shared: shared.clone(),
+                index,
+                gain: gain.clone(),
+                alive: alive.clone(),
+                channels,
+                sample_rate,
+            })
+            .collect();
+
+        let output = MultiOutput {
+            mixer: mixer_tx,
+            gains,
+            alive,
+        };
+        (output, readers)
+    }
+
+    /// Plays a source on all the devices until it ends.
+    #[inline]
+    pub fn play_raw<S>(&self, source: S)
+    where
+        S: Source<Item = f32> + Send + 'static,
+    {
+        self.mixer.add(source);
+    }
+
+    /// Returns the gain of the device at the given index in the list given to `new`.
+    ///
+    /// # Panic
+    ///
+    /// Panics if the index is out of bounds.
+    #[inline]
+    pub fn gain(&self, device: usize) -> f32 {
+        f32::from_bits(self.gains[device].load(Ordering::Relaxed))
+    }
+
+    /// Changes the gain of the device at the given index in the list given to `new`. The value
+    /// `1.0` is the "normal" volume.
+    ///
+    /// # Panic
+    ///
+    /// Panics if the index is out of bounds.
+    #[inline]
+    pub fn set_gain(&self, device: usize, value: f32) {
+        self.gains[device].store(value.to_bits(), Ordering::Relaxed);
+    }
+}
+
+impl Drop for MultiOutput {
+    #[inline]
+    fn drop(&mut self) {
+        self.alive.store(false, Ordering::Relaxed);
+    }
+}
+
+// The mix, and what each device has read from it.
+struct FanOutShared {
+    input: DynamicMixer<f32>,
+    // Samples read by the fastest device and not yet by all the others.
+    buffer: VecDeque<f32>,
+    // Position of the first sample of `buffer` in the mix.
+    start: usize,
+    // Position in the mix of the next sample of each device, or `None` once its reader is
+    // dropped.
+    positions: Vec<Option<usize>>,
+    // Maximum number of samples that a device can be behind the fastest one.
+    max_lag: usize,
+}
+
+impl FanOutShared {
+    // Drops what all the devices have read.
+    fn trim(&mut self) {
+        let end = self.start + self.buffer.len();
+        let slowest = self.positions.iter().flatten().cloned().min().unwrap_or(end);
+        while self.start < slowest {
+            self.buffer.pop_front();
+            self.start += 1;
+        }
+    }
+}
+
+// The mix given to one device.
+struct FanOut {
+    shared: Arc<Mutex<FanOutShared>>,
+    index: usize,
+    gain: Arc<AtomicU32>,
+    alive: Arc<AtomicBool>,
+    channels: u16,
+    sample_rate: u32,
+}
+
+impl Iterator for FanOut {
+    type Item = f32;
+
+    fn next(&mut self) -> Option<f32> {
+        if !self.alive.load(Ordering::Relaxed) {
+            return None;
+        }
+
+        let mut shared = self.shared.lock().unwrap();
+        let shared = &mut *shared;
+        let end = shared.start + shared.buffer.len();
+        let mut position = shared.positions[self.index].unwrap_or(end);
+
+        // Skips whole frames to catch up with the fastest device.
+        if end - position > shared.max_lag {
+            let channels = self.channels as usize;
+            position += (end - position - shared.max_lag).div_ceil(channels) * channels;
+        }
+
+        let value = if position == end {
+            // The mixer produces nothing when it has nothing to play.
+            let value = shared.input.next().unwrap_or(0.0);
+            shared.buffer.push_back(value);
+            value
+        } else {
+            shared.buffer[position - shared.start]
+        };
+        shared.positions[self.index] = Some(position + 1);
+        shared.trim();
+
+        Some(value * f32::from_bits(self.gain.load(Ordering::Relaxed)))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, None)
+    }
+}
+
+impl Drop for FanOut {
+    #[inline]
+    fn drop(&mut self) {
+        // A device that stopped playing must not hold back what the others have read.
+        let mut shared = self.shared.lock().unwrap();
+        shared.positions[self.index] = None;
+        shared.trim();
+    }
+}
+
+impl Source for FanOut {
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        None
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        self.channels
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use buffer::SamplesBuffer;
+    use multi_output::MultiOutput;
+
+    #[test]
+    fn same_sound_on_all_devices() {
+        let (output, mut readers) = MultiOutput::build(1, 1000, 2);
+        output.set_gain(1, 0.5);
+        output.play_raw(SamplesBuffer::new(1, 1000, vec![0.5f32, 0.25]));
+
+        assert_eq!(readers[0].next(), Some(0.5));
+        assert_eq!(readers[0].next(), Some(0.25));
+        assert_eq!(readers[1].next(), Some(0.25));
+        assert_eq!(readers[1].next(), Some(0.125));
+        assert_eq!(readers[0].next(), Some(0.0));
+
+        drop(output);
+        assert_eq!(readers[1].next(), None);
+    }
+
+    #[test]
+    fn slow_device_skips_ahead() {
+        let (output, mut readers) = MultiOutput::build(2, 1000, 2);
+        let samples = (0 .. 400).map(|i| i as f32).collect::<Vec<_>>();
+        output.play_raw(SamplesBuffer::new(2, 1000, samples));
+
+        // 100 milliseconds are 200 samples.
+        for _ in 0 .. 300 {
+            readers[0].next();
+        }
+        assert_eq!(readers[1].next(), Some(100.0));
+        assert_eq!(readers[1].next(), Some(101.0));
+    }
+
+    #[test]
+    fn dropped_device_frees_the_buffer() {
+        let (output, mut readers) = MultiOutput::build(1, 1000, 2);
+        output.play_raw(SamplesBuffer::new(1, 1000, vec![0.5f32; 10]));
+        for _ in 0 .. 10 {
+            readers[0].next();
+        }
+
+        let shared = readers[0].shared.clone();
+        assert_eq!(shared.lock().unwrap().buffer.len(), 10);
+        drop(readers.pop());
+        assert!(shared.lock().unwrap().buffer.is_empty());
+    }
+}
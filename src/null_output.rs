@@ -0,0 +1,157 @@
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+use dynamic_mixer::{self, DynamicMixerController};
+use Source;
+
+/// Output that consumes sounds without any audio hardware.
+///
+/// It mixes the sounds like a device would, and drops the result. This lets tests and servers use
+/// sinks, queues and effects on machines without a sound card. The sounds can be consumed in real
+/// time, like a device does, or as fast as possible.
+pub struct NullOutput {
+    mixer: Arc<DynamicMixerController<f32>>,
+    channels: u16,
+    sample_rate: u32,
+    samples_played: Arc<AtomicU64>,
+    // Tells the thread to stop.
+    stopped: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+// Number of samples consumed at once.
+const BLOCK_SAMPLES: usize = 512;
+
+impl NullOutput {
+    /// Builds an output that consumes the sounds in real time with the given channels and
+    /// sample rate.
+    #[inline]
+    pub fn new(channels: u16, sample_rate: u32) -> NullOutput {
+        NullOutput::build(channels, sample_rate, true)
+    }
+
+    /// Builds an output that consumes the sounds as fast as possible.
+    ///
+    /// Nothing slows down the consumption, so a `Sink` is consumed as long as it exists, even
+    /// when it has nothing to play.
+    #[inline]
+    pub fn fast(channels: u16, sample_rate: u32) -> NullOutput {
+        NullOutput::build(channels, sample_rate, false)
+    }
+
+    fn build(channels: u16, sample_rate: u32, real_time: bool) -> NullOutput {
+        let (mixer_tx, mut mixer_rx) = dynamic_mixer::mixer::<f32>(channels, sample_rate);
+        let samples_played = Arc::new(AtomicU64::new(0));
+        let stopped = Arc::new(AtomicBool::new(false));
+
+        let samples_per_second = sample_rate as u128 * channels as u128;
+        let thread = thread::Builder::new()
+            .name("rodio null output".to_string())
+            .spawn({
+                let samples_played = samples_played.clone();
+                let stopped = stopped.clone();
+                move || {
+                    let start = Instant::now();
+                    while !stopped.load(Ordering::Relaxed) {
+                        let mut block = BLOCK_SAMPLES as u64;
+                        if real_time {
+                            let due = (start.elapsed().as_nanos() * samples_per_second
+                                / 1_000_000_000) as u64;
+                            block = block.min(due - samples_played.load(Ordering::Relaxed));
+                            if block == 0 {
+                                thread::sleep(Duration::from_millis(1));
+                                continue;
+                            }
+                        }
+
+                        let consumed = mixer_rx.by_ref().take(block as usize).count();
+                        // A device plays silence when the mixer has nothing to play.
+                        if real_time {
+                            samples_played.fetch_add(block, Ordering::Relaxed);
+                        } else if consumed == 0 {
+                            thread::sleep(Duration::from_millis(1));
+                        } else {
+                            samples_played.fetch_add(consumed as u64, Ordering::Relaxed);
+                        }
+                    }
+                }
+            })
+            .ok();
+
+        NullOutput {
+            mixer: mixer_tx,
+            channels,
+            sample_rate,
+            samples_played,
+            stopped,
+            thread,
+        }
+    }
+
+    /// Plays a source until it ends.
+    #[inline]
+    pub fn play_raw<S>(&self, source: S)
+    where
+        S: Source<Item = f32> + Send + 'static,
+    {
+        self.mixer.add(source);
+    }
+
+    /// Returns the number of channels of the output.
+    #[inline]
+    pub fn channels(&self) -> u16 {
+        self.channels
+    }
+
+    /// Returns the sample rate of the output.
+    #[inline]
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    /// Returns the number of samples consumed since the output was built, silence included.
+    #[inline]
+    pub fn samples_played(&self) -> u64 {
+        self.samples_played.load(Ordering::Relaxed)
+    }
+}
+
+impl Drop for NullOutput {
+    #[inline]
+    fn drop(&mut self) {
+        self.stopped.store(true, Ordering::Relaxed);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use buffer::SamplesBuffer;
+    use null_output::NullOutput;
+    use sink::Sink;
+
+    #[test]
+    fn fast() {
+        let output = NullOutput::fast(2, 44100);
+        let sink = Sink::new_null(&output);
+        sink.append(SamplesBuffer::new(2, 44100, vec![0.5f32; 44100 * 2]));
+        sink.sleep_until_end();
+        assert!(output.samples_played() >= 44100 * 2);
+    }
+
+    #[test]
+    fn real_time() {
+        let output = NullOutput::new(1, 48000);
+        let sink = Sink::new_null(&output);
+        let start = Instant::now();
+        sink.append(SamplesBuffer::new(1, 48000, vec![0.5f32; 2400]));
+        sink.sleep_until_end();
+        assert!(start.elapsed() >= Duration::from_millis(40));
+    }
+}
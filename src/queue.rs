@@ -9,8 +9,10 @@ use std::sync::Arc;
 use std::sync::Mutex;
 use std::time::Duration;
 
+use conversions::duration_to_frames;
 use source::stoppable;
 use source::Empty;
+use source::SeekError;
 use source::Source;
 use source::Stoppable;
 use source::Zero;
@@ -174,6 +176,12 @@ where
     fn total_duration(&self) -> Option<Duration> {
         None
     }
+
+    /// Seeks in the sound that is currently playing.
+    #[inline]
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        self.current.try_seek(pos)
+    }
 }
 
 impl<S> Iterator for SourcesQueueOutput<S>
@@ -230,14 +238,14 @@ where
 
             if next.len() == 0 {
                 if self.input.keep_alive_if_empty.load(Ordering::Acquire) {
-                    // Play a short silence in order to avoid spinlocking.
-                    let silence = Zero::<S>::new(1, 44100); // TODO: meh
-                    (
-                        stoppable(
-                            Box::new(silence.take_duration(Duration::from_millis(10))) as Box<_>
-                        ),
-                        None,
-                    )
+                    // Play a short silence in order to avoid spinlocking. It keeps the format of
+                    // the previous sound, so that the effects of a sink play their tail through
+                    // it, and lasts at least one frame even at very low rates.
+                    let channels = self.current.channels().max(1);
+                    let sample_rate = self.current.sample_rate().max(1);
+                    let frames = duration_to_frames(Duration::from_millis(10), sample_rate).max(1);
+                    let silence = Zero::<S>::new(channels, sample_rate).take_frames(frames);
+                    (stoppable(Box::new(silence) as Box<_>), None)
                 } else {
                     return Err(());
                 }
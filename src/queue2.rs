@@ -1,6 +1,7 @@
 use std::time::Duration;
 
 use source::Empty;
+use source::SeekError;
 use source::Source;
 use source::Zero;
 
@@ -136,6 +137,12 @@ where
     fn total_duration(&self) -> Option<Duration> {
         None
     }
+
+    /// Seeks in the sound that is currently playing.
+    #[inline]
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        self.current.try_seek(pos)
+    }
 }
 
 impl<S> Iterator for SourcesQueue<S>
@@ -1,13 +1,21 @@
+use std::collections::VecDeque;
 use std::sync::atomic::Ordering;
-use std::sync::atomic::{AtomicBool, AtomicUsize};
-use std::sync::mpsc::Receiver;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
 use std::sync::Arc;
 use std::sync::Mutex;
 use std::time::Duration;
 
+use bus::Bus;
+use completion::{self, Completer};
+use conversions::{amplitude_to_db, db_to_amplitude};
 use play_raw;
+use MultiOutput;
+use NullOutput;
 use queue;
-use source::Done;
+use sink_group::SinkGroup;
+use source::{Done, PositionHandle, SeekError};
+use Completion;
 use Device;
 use Sample;
 use Source;
@@ -29,7 +37,123 @@ pub struct Sink {
 struct Controls {
     pause: AtomicBool,
     volume: Mutex<f32>,
+    speed: Mutex<f32>,
     stopped: AtomicBool,
+    seek: Mutex<Option<SeekOrder>>,
+    // Number of seeks requested, which tells the orders apart.
+    seeks: AtomicU64,
+    // Number of sounds appended, which is the index of the next one.
+    appended: AtomicU64,
+    // Number of sounds that have ended or have been skipped, which is the index of the sound
+    // currently playing.
+    ended: AtomicU64,
+    // Position of the sound currently playing, set when it starts.
+    position: Mutex<Option<PositionHandle>>,
+    // Senders of the receivers returned by `subscribe`.
+    subscribers: Mutex<Vec<Sender<SinkEvent>>>,
+    effects: Mutex<EffectList>,
+    chains: ChainHandover,
+    group: Mutex<Option<SinkGroup>>,
+}
+
+// The effects of a sink, in order.
+struct EffectList {
+    effects: Vec<(EffectId, Arc<dyn Effect>)>,
+    next_id: u64,
+    // Numbers of channels and sample rates of the sounds appended, for which the effects are
+    // built.
+    formats: Vec<(u16, u32)>,
+}
+
+impl Controls {
+    // Records the format of an appended sound, and builds the effects for it if needed.
+    fn add_format(&self, channels: u16, sample_rate: u32) {
+        let mut list = self.effects.lock().unwrap();
+        if list.formats.contains(&(channels, sample_rate)) {
+            return;
+        }
+        list.formats.push((channels, sample_rate));
+        if !list.effects.is_empty() {
+            let chain = EffectChain::new(&list.effects, channels, sample_rate);
+            self.chains.publish(vec![chain], false);
+        }
+    }
+
+    // Builds the effects again after they were modified, and hands them to the output.
+    fn rebuild_effects(&self, list: &EffectList) {
+        let chains = if list.effects.is_empty() {
+            Vec::new()
+        } else {
+            list.formats
+                .iter()
+                .map(|&(channels, rate)| EffectChain::new(&list.effects, channels, rate))
+                .collect()
+        };
+        self.chains.publish(chains, true);
+    }
+
+    // Sends an event to all subscribers, and forgets those that have been dropped.
+    fn notify(&self, event: SinkEvent) {
+        self.subscribers
+            .lock()
+            .unwrap()
+            .retain(|subscriber| subscriber.send(event).is_ok());
+    }
+}
+
+/// Event sent by a `Sink` to the receivers returned by `Sink::subscribe`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SinkEvent {
+    /// A sound of the queue started playing.
+    SourceStarted,
+    /// A sound of the queue ended, was stopped or was skipped.
+    SourceEnded,
+    /// The last sound of the queue ended.
+    Emptied,
+    /// The sink was paused.
+    Paused,
+    /// The sink was resumed.
+    Resumed,
+}
+
+/// An effect applied to the sounds of a `Sink` or of a bus, added with `Sink::add_effect` or
+/// `bus::Bus::add_effect`.
+///
+/// It is implemented for closures that wrap a source in adapters, for example
+/// `|input| Box::new(input.low_pass(2000)) as Box<_>`.
+pub trait Effect: Send + Sync {
+    /// Wraps the output of the previous effect, or the sound if this is the first effect.
+    fn apply(&self, input: Box<dyn Source<Item = f32> + Send>)
+        -> Box<dyn Source<Item = f32> + Send>;
+}
+
+impl<F> Effect for F
+where
+    F: Fn(Box<dyn Source<Item = f32> + Send>) -> Box<dyn Source<Item = f32> + Send> + Send + Sync,
+{
+    #[inline]
+    fn apply(&self, input: Box<dyn Source<Item = f32> + Send>)
+        -> Box<dyn Source<Item = f32> + Send> {
+        self(input)
+    }
+}
+
+/// Identifies an effect added to a `Sink` or to a bus, to remove it with `remove_effect`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct EffectId(pub(crate) u64);
+
+// Fraction of the remaining difference to the target speed applied every 5 milliseconds, so that
+// speed changes are ramped over about 50 milliseconds.
+const SPEED_SMOOTHING: f32 = 0.1;
+
+// Seek requested by `try_seek` and not done yet.
+struct SeekOrder {
+    id: u64,
+    // Index of the sound to seek.
+    sound: u64,
+    pos: Duration,
+    // Where to send the result of the seek.
+    result_tx: Sender<Result<(), SeekError>>,
 }
 
 impl Sink {
@@ -41,9 +165,42 @@ impl Sink {
         sink
     }
 
+    /// Builds a new `Sink`, beginning playback on all the devices of a `MultiOutput`.
+    #[inline]
+    pub fn new_multi(output: &MultiOutput) -> Sink {
+        let (sink, queue_rx) = Sink::new_idle();
+        output.play_raw(queue_rx);
+        sink
+    }
+
+    /// Builds a new `Sink`, beginning playback on an output without audio hardware.
+    #[inline]
+    pub fn new_null(output: &NullOutput) -> Sink {
+        let (sink, queue_rx) = Sink::new_idle();
+        output.play_raw(queue_rx);
+        sink
+    }
+
+    /// Builds a new `Sink` whose sounds are mixed into a bus.
+    #[inline]
+    pub fn new_on_bus(bus: &Bus) -> Sink {
+        let (sink, queue_rx) = Sink::new_idle();
+        bus.add(queue_rx);
+        sink
+    }
+
+    // Builds a new `Sink` whose output returns `None` when there is nothing to play, instead of
+    // a silence. It can still play the sounds appended afterwards.
+    #[inline]
+    pub(crate) fn new_idle_without_silence() -> (Sink, SinkOutput) {
+        let (sink, queue_rx) = Sink::new_idle();
+        sink.queue_tx.set_keep_alive_if_empty(false);
+        (sink, queue_rx)
+    }
+
     /// Builds a new `Sink`.
     #[inline]
-    pub fn new_idle() -> (Sink, queue::SourcesQueueOutput<f32>) {
+    pub fn new_idle() -> (Sink, SinkOutput) {
         let (queue_tx, queue_rx) = queue::queue(true);
 
         let sink = Sink {
@@ -52,42 +209,114 @@ impl Sink {
             controls: Arc::new(Controls {
                 pause: AtomicBool::new(false),
                 volume: Mutex::new(1.0),
+                speed: Mutex::new(1.0),
                 stopped: AtomicBool::new(false),
+                seek: Mutex::new(None),
+                seeks: AtomicU64::new(0),
+                appended: AtomicU64::new(0),
+                ended: AtomicU64::new(0),
+                position: Mutex::new(None),
+                subscribers: Mutex::new(Vec::new()),
+                effects: Mutex::new(EffectList {
+                    effects: Vec::new(),
+                    next_id: 0,
+                    formats: Vec::new(),
+                }),
+                chains: ChainHandover::default(),
+                group: Mutex::new(None),
             }),
             sound_count: Arc::new(AtomicUsize::new(0)),
             detached: false,
         };
-        (sink, queue_rx)
+        let output = SinkOutput {
+            queue: queue_rx,
+            controls: sink.controls.clone(),
+            chains: Vec::new(),
+            frame: Vec::new(),
+            position: 0,
+            direct_remaining: 0,
+            channels: 1,
+            sample_rate: 44100,
+        };
+        (sink, output)
     }
 
     /// Appends a sound to the queue of sounds to play.
+    ///
+    /// The returned handle completes when this sound ends, is skipped or is stopped. It can be
+    /// dropped if this isn't needed.
     #[inline]
-    pub fn append<S>(&self, source: S)
+    pub fn append<S>(&self, source: S) -> Completion
     where
         S: Source + Send + 'static,
         S::Item: Sample,
         S::Item: Send,
     {
+        self.controls.add_format(source.channels(), source.sample_rate());
         let controls = self.controls.clone();
+        let index = controls.appended.fetch_add(1, Ordering::SeqCst);
+        let source = source.track_position();
+        let mut position = Some(source.handle());
 
         let source = source
+            .speed(self.speed())
             .pausable(false)
             .amplify(1.0)
             .stoppable()
             .periodic_access(Duration::from_millis(5), move |src| {
+                // The first call happens when the sound starts playing.
+                if let Some(position) = position.take() {
+                    *controls.position.lock().unwrap() = Some(position);
+                    controls.notify(SinkEvent::SourceStarted);
+                }
+
                 if controls.stopped.load(Ordering::SeqCst) {
                     src.stop();
                 } else {
-                    src.inner_mut().set_factor(*controls.volume.lock().unwrap());
-                    src.inner_mut()
-                        .inner_mut()
-                        .set_paused(controls.pause.load(Ordering::SeqCst));
+                    let amplify = src.inner_mut();
+                    let volume = *controls.volume.lock().unwrap();
+                    let group = controls.group.lock().unwrap();
+                    amplify.set_factor(volume * group.as_ref().map_or(1.0, SinkGroup::gain));
+                    let paused = controls.pause.load(Ordering::SeqCst);
+                    if let (Some(group), false) = (group.as_ref(), paused) {
+                        group.mark_active();
+                    }
+                    let pausable = amplify.inner_mut();
+                    pausable.set_paused(paused);
+                    let speed = pausable.inner_mut();
+                    let target = *controls.speed.lock().unwrap();
+                    let mut factor = speed.factor() + (target - speed.factor()) * SPEED_SMOOTHING;
+                    if (target - factor).abs() < 1e-3 {
+                        factor = target;
+                    }
+                    speed.set_factor(factor);
+
+                    // The position is in the time of the sound, whatever its speed. The orders for
+                    // a sound that has ended are dropped, which tells `try_seek` that they weren't
+                    // done.
+                    let mut seek = controls.seek.lock().unwrap();
+                    match seek.take() {
+                        Some(order) if order.sound == index => {
+                            let _ = order.result_tx.send(speed.inner_mut().try_seek(order.pos));
+                        },
+                        Some(order) if order.sound > index => *seek = Some(order),
+                        _ => (),
+                    }
                 }
             })
             .convert_samples();
         self.sound_count.fetch_add(1, Ordering::Relaxed);
         let source = Done::new(source, self.sound_count.clone());
+        let (completion, completer) = completion::completion();
+        let source = Notify {
+            input: source,
+            controls: self.controls.clone(),
+            sound_count: self.sound_count.clone(),
+            completer,
+            ended: false,
+        };
         *self.sleep_until_end.lock().unwrap() = Some(self.queue_tx.append_with_signal(source));
+        completion
     }
 
     /// Gets the volume of the sound.
@@ -108,12 +337,163 @@ impl Sink {
         *self.controls.volume.lock().unwrap() = value;
     }
 
+    /// Returns the group that the sink belongs to, if any.
+    #[inline]
+    pub fn group(&self) -> Option<SinkGroup> {
+        self.controls.group.lock().unwrap().clone()
+    }
+
+    /// Adds the sink to a group, or removes it from its group with `None`. A sink belongs to at
+    /// most one group.
+    ///
+    /// The volume of the group multiplies the volume of the sink.
+    #[inline]
+    pub fn set_group(&self, group: Option<&SinkGroup>) {
+        *self.controls.group.lock().unwrap() = group.cloned();
+    }
+
+    /// Gets the volume of the sound, in decibels.
+    ///
+    /// The value `0.0` is the "normal" volume (unfiltered input). A volume of `0.0` is negative
+    /// infinity.
+    #[inline]
+    pub fn volume_db(&self) -> f32 {
+        amplitude_to_db(self.volume())
+    }
+
+    /// Changes the volume of the sound, in decibels.
+    ///
+    /// The value `0.0` is the "normal" volume (unfiltered input), and every `-6.0` approximately
+    /// halves the amplitude.
+    #[inline]
+    pub fn set_volume_db(&self, decibels: f32) {
+        self.set_volume(db_to_amplitude(decibels));
+    }
+
+    /// Seeks the sound currently playing to the given position.
+    ///
+    /// The seek is done by the audio thread, and this function waits for it to happen. If the
+    /// sound doesn't support seeking, an error is returned and it keeps playing where it was. If
+    /// the sink is empty, this does nothing.
+    ///
+    /// If the sound ends before the seek, if another seek replaces this one, or if the audio
+    /// thread doesn't get to the seek within a few seconds, for example because the output is
+    /// paused with `pause_all`, the seek is cancelled and `SeekError::NotDone` is returned.
+    pub fn try_seek(&self, pos: Duration) -> Result<(), SeekError> {
+        if self.empty() {
+            return Ok(());
+        }
+
+        let (result_tx, result_rx) = mpsc::channel();
+        let id = self.controls.seeks.fetch_add(1, Ordering::SeqCst);
+        *self.controls.seek.lock().unwrap() = Some(SeekOrder {
+            id,
+            sound: self.controls.ended.load(Ordering::SeqCst),
+            pos,
+            result_tx,
+        });
+
+        match result_rx.recv_timeout(Duration::from_secs(5)) {
+            Ok(result) => result,
+            Err(RecvTimeoutError::Timeout) => {
+                let mut seek = self.controls.seek.lock().unwrap();
+                if seek.as_ref().map(|order| order.id) == Some(id) {
+                    *seek = None;
+                }
+                drop(seek);
+                // The audio thread may have done the seek just before the order was removed.
+                result_rx.try_recv().unwrap_or(Err(SeekError::NotDone))
+            },
+            // The order was dropped because the sound ended or another seek replaced it.
+            Err(RecvTimeoutError::Disconnected) => Err(SeekError::NotDone),
+        }
+    }
+
+    /// Returns how much of the sound currently playing has been played, or zero if the sink is
+    /// empty.
+    ///
+    /// The position doesn't advance while the sink is paused, and takes into account the seeks
+    /// done with `try_seek` and the speed set with `set_speed`. If the sound was sped up with
+    /// `Source::speed` before being appended, the position is in the time of the sped up sound;
+    /// call `Source::track_position` before `speed` to get the position in the original sound.
+    #[inline]
+    pub fn get_pos(&self) -> Duration {
+        if self.empty() {
+            return Duration::from_secs(0);
+        }
+        self.controls
+            .position
+            .lock()
+            .unwrap()
+            .as_ref()
+            .map_or(Duration::from_secs(0), |position| position.position())
+    }
+
+    /// Gets the speed of the sound.
+    ///
+    /// The value `1.0` is the "normal" speed. Higher values play the sound faster and raise its
+    /// pitch, lower values play it slower and lower its pitch.
+    #[inline]
+    pub fn speed(&self) -> f32 {
+        *self.controls.speed.lock().unwrap()
+    }
+
+    /// Changes the speed of the sound.
+    ///
+    /// The value `1.0` is the "normal" speed. The speed of the sound currently playing is ramped
+    /// to the new value over a few tens of milliseconds, and the next sounds start at this speed.
+    /// `get_pos` and `try_seek` use the time of the sound, whatever its speed.
+    #[inline]
+    pub fn set_speed(&self, value: f32) {
+        *self.controls.speed.lock().unwrap() = value;
+    }
+
+    /// Adds an effect at the end of the chain of effects applied to the sounds of this sink.
+    ///
+    /// The effects apply to the output of the sink, so to the sound currently playing as well as
+    /// to those appended later, and the tail of an effect such as a reverb continues over the
+    /// next sound of the same format. They are built on the calling thread every time the chain
+    /// is modified, once for each number of channels and sample rate of the sounds, so the
+    /// effects shouldn't rely on being built only once.
+    pub fn add_effect<E>(&self, effect: E) -> EffectId
+    where
+        E: Effect + 'static,
+    {
+        let mut list = self.controls.effects.lock().unwrap();
+        let id = EffectId(list.next_id);
+        list.next_id += 1;
+        list.effects.push((id, Arc::new(effect)));
+        self.controls.rebuild_effects(&list);
+        id
+    }
+
+    /// Removes an effect added with `add_effect`. Returns false if it was already removed.
+    pub fn remove_effect(&self, id: EffectId) -> bool {
+        let mut list = self.controls.effects.lock().unwrap();
+        let len = list.effects.len();
+        list.effects.retain(|&(effect_id, _)| effect_id != id);
+        if list.effects.len() == len {
+            return false;
+        }
+        self.controls.rebuild_effects(&list);
+        true
+    }
+
+    /// Removes all the effects of this sink.
+    pub fn clear_effects(&self) {
+        let mut list = self.controls.effects.lock().unwrap();
+        list.effects.clear();
+        self.controls.rebuild_effects(&list);
+    }
+
     /// Resumes playback of a paused sink.
     ///
     /// No effect if not paused.
     #[inline]
     pub fn play(&self) {
-        self.controls.pause.store(false, Ordering::SeqCst);
+        if self.controls.pause.swap(false, Ordering::SeqCst) {
+            self.controls.notify(SinkEvent::Resumed);
+        }
     }
 
     /// Pauses playback of this sink.
@@ -122,7 +502,9 @@ impl Sink {
     ///
     /// A paused sink can be resumed with `play()`.
     pub fn pause(&self) {
-        self.controls.pause.store(true, Ordering::SeqCst);
+        if !self.controls.pause.swap(true, Ordering::SeqCst) {
+            self.controls.notify(SinkEvent::Paused);
+        }
     }
 
     /// Gets if a sink is paused
@@ -145,6 +527,18 @@ impl Sink {
         self.queue_tx.skip_sound();
     }
 
+    /// Returns a receiver of the events of this sink.
+    ///
+    /// The events of the sounds are sent from the audio thread, when they start or end playing.
+    /// The receiver can be polled with `try_recv` from a GUI or game loop, or waited on from
+    /// another thread.
+    #[inline]
+    pub fn subscribe(&self) -> Receiver<SinkEvent> {
+        let (tx, rx) = mpsc::channel();
+        self.controls.subscribers.lock().unwrap().push(tx);
+        rx
+    }
+
     /// Destroys the sink without stopping the sounds that are still playing.
     #[inline]
     pub fn detach(mut self) {
@@ -183,10 +577,384 @@ impl Drop for Sink {
     }
 }
 
+/// The output of a `Sink`, which plays its sounds through its effects. Implements `Source`.
+///
+/// The effects run on the output of the sink rather than on each sound, so that they keep their
+/// state from one sound to the next.
+pub struct SinkOutput {
+    queue: queue::SourcesQueueOutput<f32>,
+    controls: Arc<Controls>,
+    // The effects, built for each number of channels and sample rate of the sounds.
+    chains: Vec<EffectChain>,
+    // Frame currently being returned through the effects, and position in it.
+    frame: Vec<f32>,
+    position: usize,
+    // Number of samples of the current frame still to read directly from the queue, when no
+    // effect applies.
+    direct_remaining: usize,
+    channels: u16,
+    sample_rate: u32,
+}
+
+impl SinkOutput {
+    // Picks the effects built by the threads that modified them.
+    fn receive_chains(&mut self) {
+        let handover = &self.controls.chains;
+        if !handover.has_update.load(Ordering::SeqCst) {
+            return;
+        }
+        // The audio thread never waits for the lock, and tries again at the next frame.
+        let mut update = match handover.update.try_lock() {
+            Ok(update) => update,
+            Err(_) => return,
+        };
+
+        if update.replace {
+            let old = self.chains.drain(..).collect::<Vec<_>>();
+            update.retired.extend(old);
+            update.replace = false;
+        }
+        let chains = update.chains.split_off(0);
+        for chain in chains {
+            if self.chains.iter().any(|c| c.format() == chain.format()) {
+                update.retired.push(chain);
+            } else {
+                self.chains.push(chain);
+            }
+        }
+        handover.has_update.store(false, Ordering::SeqCst);
+    }
+
+    // Returns true if the current frame has samples left.
+    #[inline]
+    fn in_frame(&self) -> bool {
+        self.position < self.frame.len() || self.direct_remaining > 0
+    }
+
+    // Starts the next frame of the sounds, and returns its first sample. Returns `None` if the
+    // sink has nothing more to play.
+    fn start_frame(&mut self) -> Option<f32> {
+        self.receive_chains();
+
+        // The format is known once the first sample of the frame is read, which is when the
+        // queue moves to the next sound.
+        let first = self.queue.next()?;
+        self.channels = self.queue.channels();
+        self.sample_rate = self.queue.sample_rate();
+
+        let channels = self.channels;
+        let format = (channels, self.sample_rate);
+        let chain = match self.chains.iter_mut().find(|c| c.format() == format) {
+            Some(chain) => chain,
+            None => {
+                self.direct_remaining = channels as usize - 1;
+                return Some(first);
+            },
+        };
+        self.frame.clear();
+        self.frame.push(first);
+        self.frame.extend(self.queue.by_ref().take(channels as usize - 1));
+        chain.process(&mut self.frame);
+        self.position = 1;
+        Some(self.frame[0])
+    }
+}
+
+impl Iterator for SinkOutput {
+    type Item = f32;
+
+    #[inline]
+    fn next(&mut self) -> Option<f32> {
+        if self.position < self.frame.len() {
+            let value = self.frame[self.position];
+            self.position += 1;
+            return Some(value);
+        }
+        if self.direct_remaining > 0 {
+            self.direct_remaining -= 1;
+            return self.queue.next();
+        }
+        self.start_frame()
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.frame.len() - self.position;
+        (self.queue.size_hint().0 + remaining, None)
+    }
+}
+
+impl Source for SinkOutput {
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        let remaining = self.frame.len() - self.position;
+        if remaining == 0 {
+            self.queue.current_frame_len()
+        } else {
+            self.queue.current_frame_len().map(|len| len + remaining)
+        }
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        if self.in_frame() {
+            self.channels
+        } else {
+            self.queue.channels()
+        }
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        if self.in_frame() {
+            self.sample_rate
+        } else {
+            self.queue.sample_rate()
+        }
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        None
+    }
+
+    /// Seeks in the sound that is currently playing.
+    #[inline]
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        self.queue.try_seek(pos)?;
+        self.frame.clear();
+        self.position = 0;
+        self.direct_remaining = 0;
+        Ok(())
+    }
+}
+
+// Number of frames given to the effects that they can lag behind.
+const CHAIN_BUFFER_FRAMES: usize = 4096;
+
+// The effects of a sink built for a number of channels and a sample rate.
+struct EffectChain {
+    channels: u16,
+    sample_rate: u32,
+    // Samples given to the first effect. The lock is only taken by the audio thread.
+    input: Arc<Mutex<VecDeque<f32>>>,
+    output: Box<dyn Source<Item = f32> + Send>,
+}
+
+impl EffectChain {
+    fn new(effects: &[(EffectId, Arc<dyn Effect>)], channels: u16, sample_rate: u32) -> EffectChain {
+        let capacity = CHAIN_BUFFER_FRAMES * channels as usize;
+        let input = Arc::new(Mutex::new(VecDeque::with_capacity(capacity)));
+        let mut output = Box::new(ChainInput {
+            buffer: input.clone(),
+            channels,
+            sample_rate,
+        }) as Box<dyn Source<Item = f32> + Send>;
+        for (_, effect) in effects {
+            output = effect.apply(output);
+        }
+        EffectChain {
+            channels,
+            sample_rate,
+            input,
+            output,
+        }
+    }
+
+    #[inline]
+    fn format(&self) -> (u16, u32) {
+        (self.channels, self.sample_rate)
+    }
+
+    // Passes a frame through the effects.
+    fn process(&mut self, frame: &mut [f32]) {
+        {
+            let mut input = self.input.lock().unwrap();
+            for &value in frame.iter() {
+                // If the effects are too far behind, the oldest sample is dropped.
+                if input.len() == CHAIN_BUFFER_FRAMES * self.channels as usize {
+                    input.pop_front();
+                }
+                input.push_back(value);
+            }
+        }
+        for value in frame.iter_mut() {
+            *value = self.output.next().unwrap_or(0.0);
+        }
+    }
+}
+
+// The sounds of the sink, given to the first effect. It plays silence when the effects are ahead
+// of the sounds.
+struct ChainInput {
+    buffer: Arc<Mutex<VecDeque<f32>>>,
+    channels: u16,
+    sample_rate: u32,
+}
+
+impl Iterator for ChainInput {
+    type Item = f32;
+
+    #[inline]
+    fn next(&mut self) -> Option<f32> {
+        Some(self.buffer.lock().unwrap().pop_front().unwrap_or(0.0))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (usize::MAX, None)
+    }
+}
+
+impl Source for ChainInput {
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        None
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        self.channels
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        None
+    }
+}
+
+// Chains of effects built by the threads that modify the effects, waiting to be picked up by the
+// output of the sink.
+#[derive(Default)]
+struct ChainHandover {
+    has_update: AtomicBool,
+    update: Mutex<ChainUpdate>,
+}
+
+#[derive(Default)]
+struct ChainUpdate {
+    // True if `chains` replace all the chains of the output, false if they are added to them.
+    replace: bool,
+    chains: Vec<EffectChain>,
+    // Chains that the output doesn't use anymore, dropped by the next update rather than on the
+    // audio thread.
+    retired: Vec<EffectChain>,
+}
+
+impl ChainHandover {
+    fn publish(&self, chains: Vec<EffectChain>, replace: bool) {
+        let mut update = self.update.lock().unwrap();
+        update.retired.clear();
+        if replace {
+            update.replace = true;
+            update.chains = chains;
+        } else {
+            update.chains.extend(chains);
+        }
+        self.has_update.store(true, Ordering::SeqCst);
+    }
+}
+
+// Sends the end events of a sound of the sink, and completes the handle returned by `append`.
+struct Notify<I> {
+    input: I,
+    controls: Arc<Controls>,
+    sound_count: Arc<AtomicUsize>,
+    completer: Completer,
+    ended: bool,
+}
+
+impl<I> Notify<I> {
+    fn end(&mut self) {
+        if !self.ended {
+            self.ended = true;
+            self.controls.ended.fetch_add(1, Ordering::SeqCst);
+            self.controls.notify(SinkEvent::SourceEnded);
+            self.completer.complete();
+        }
+    }
+}
+
+impl<I> Iterator for Notify<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    type Item = I::Item;
+
+    #[inline]
+    fn next(&mut self) -> Option<I::Item> {
+        let next = self.input.next();
+        if next.is_none() && !self.ended {
+            self.end();
+            if self.sound_count.load(Ordering::Relaxed) == 0 {
+                self.controls.notify(SinkEvent::Emptied);
+            }
+        }
+        next
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.input.size_hint()
+    }
+}
+
+impl<I> Source for Notify<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        self.input.current_frame_len()
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        self.input.channels()
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        self.input.sample_rate()
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        self.input.total_duration()
+    }
+
+    #[inline]
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        self.input.try_seek(pos)
+    }
+}
+
+impl<I> Drop for Notify<I> {
+    #[inline]
+    fn drop(&mut self) {
+        // A skipped sound is dropped before its end.
+        self.end();
+    }
+}
+
 #[cfg(test)]
 mod tests {
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::mpsc;
+    use std::sync::Arc;
+    use std::thread;
+    use std::time::Duration;
+
     use buffer::SamplesBuffer;
-    use sink::Sink;
+    use sink::{SeekOrder, Sink, SinkEvent};
+    use sink_group::SinkGroups;
     use source::Source;
 
     #[test]
@@ -238,6 +1006,31 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_group() {
+        let (sink, mut queue_rx) = Sink::new_idle();
+        let groups = SinkGroups::new();
+        let music = groups.group("music");
+        music.set_volume(0.5);
+        sink.set_group(Some(&music));
+        sink.set_volume(0.5);
+
+        sink.append(SamplesBuffer::new(1, 48000, vec![1.0f32, 1.0]));
+        assert_eq!(queue_rx.next(), Some(0.25));
+
+        sink.set_group(None);
+        assert!(sink.group().is_none());
+    }
+
+    #[test]
+    fn test_volume_db() {
+        let (sink, _queue_rx) = Sink::new_idle();
+
+        sink.set_volume_db(-20.0);
+        assert!((sink.volume() - 0.1).abs() < 1e-6);
+        assert!((sink.volume_db() + 20.0).abs() < 1e-5);
+    }
+
     #[test]
     fn test_skip() {
         let (sink, mut queue_rx) = Sink::new_idle();
@@ -245,8 +1038,8 @@ mod tests {
         let v1 = vec![10i16, -10, 10, -10, 10, -10];
         let v2 = vec![20i16, -20, 20, -20, 20, -20];
 
-        sink.append(SamplesBuffer::new(2, 44100, v1.clone()));
-        sink.append(SamplesBuffer::new(2, 44100, v2.clone()));
+        let first = sink.append(SamplesBuffer::new(2, 44100, v1.clone()));
+        let second = sink.append(SamplesBuffer::new(2, 44100, v2.clone()));
 
         let mut src1 = SamplesBuffer::new(2, 44100, v1.clone()).convert_samples();
         let mut src2 = SamplesBuffer::new(2, 44100, v2.clone()).convert_samples();
@@ -254,9 +1047,211 @@ mod tests {
         for _ in 0..(v1.len() / 2) {
             assert_eq!(queue_rx.next(), src1.next());
         }
+        assert!(!first.is_done());
         sink.skip();
         for _ in 0..(v2.len()) {
             assert_eq!(queue_rx.next(), src2.next());
         }
+        assert!(first.is_done());
+        assert!(!second.is_done());
+
+        queue_rx.next();
+        assert!(second.wait_timeout(Duration::from_secs(0)));
+    }
+
+    #[test]
+    fn test_try_seek() {
+        let (sink, mut queue_rx) = Sink::new_idle();
+
+        let v = (0 .. 1000).map(|i| i as f32 / 1000.0).collect::<Vec<_>>();
+        sink.append(SamplesBuffer::new(1, 1000, v.clone()));
+        assert_eq!(queue_rx.next(), Some(0.0));
+
+        let done = Arc::new(AtomicBool::new(false));
+        let audio = {
+            let done = done.clone();
+            thread::spawn(move || {
+                while !done.load(Ordering::SeqCst) {
+                    queue_rx.next();
+                    thread::sleep(Duration::from_millis(1));
+                }
+                queue_rx
+            })
+        };
+        assert!(sink.try_seek(Duration::from_millis(500)).is_ok());
+        done.store(true, Ordering::SeqCst);
+        let mut queue_rx = audio.join().unwrap();
+
+        let position = sink.get_pos();
+        assert!(position >= Duration::from_millis(500), "{:?}", position);
+        assert!(position < Duration::from_millis(600), "{:?}", position);
+        let value = queue_rx.next().unwrap();
+        assert!(value >= 0.5 && value < 0.6, "{}", value);
+    }
+
+    #[test]
+    fn stale_seek_is_dropped() {
+        let (sink, mut queue_rx) = Sink::new_idle();
+        sink.append(SamplesBuffer::new(1, 1000, vec![1.0f32; 10]));
+        sink.append(SamplesBuffer::new(1, 1000, vec![0.5f32; 1000]));
+        assert_eq!(queue_rx.by_ref().take(10).collect::<Vec<_>>(), vec![1.0; 10]);
+        assert_eq!(queue_rx.next(), Some(0.5));
+
+        // An order for the first sound, which has ended.
+        let (result_tx, result_rx) = mpsc::channel();
+        *sink.controls.seek.lock().unwrap() = Some(SeekOrder {
+            id: 0,
+            sound: 0,
+            pos: Duration::from_millis(500),
+            result_tx,
+        });
+
+        assert_eq!(queue_rx.by_ref().take(5).collect::<Vec<_>>(), vec![0.5; 5]);
+        assert!(sink.controls.seek.lock().unwrap().is_none());
+        assert!(result_rx.recv().is_err());
+        assert!(sink.get_pos() < Duration::from_millis(10));
+    }
+
+    #[test]
+    fn test_get_pos() {
+        let (sink, mut queue_rx) = Sink::new_idle();
+        assert_eq!(sink.get_pos(), Duration::from_secs(0));
+
+        sink.append(SamplesBuffer::new(2, 1000, vec![0.5f32; 40]));
+        sink.append(SamplesBuffer::new(1, 1000, vec![0.5f32; 10]));
+        for _ in 0 .. 20 {
+            queue_rx.next();
+        }
+        assert_eq!(sink.get_pos(), Duration::from_millis(9));
+
+        // The position doesn't advance while paused. The controls are read every 10 samples.
+        sink.pause();
+        for _ in 0 .. 10 {
+            queue_rx.next();
+        }
+        assert_eq!(sink.get_pos(), Duration::from_millis(9));
+        sink.play();
+
+        // The second sound starts from zero.
+        for _ in 0 .. 22 {
+            queue_rx.next();
+        }
+        assert_eq!(sink.get_pos(), Duration::from_millis(1));
+
+        for _ in 0 .. 9 {
+            queue_rx.next();
+        }
+        assert_eq!(sink.get_pos(), Duration::from_secs(0));
+    }
+
+    #[test]
+    fn test_events() {
+        let (sink, mut queue_rx) = Sink::new_idle();
+        let events = sink.subscribe();
+
+        sink.append(SamplesBuffer::new(1, 1000, vec![0.5f32; 2]));
+        sink.append(SamplesBuffer::new(1, 1000, vec![0.5f32; 2]));
+        sink.pause();
+        sink.pause();
+        sink.play();
+        for _ in 0 .. 6 {
+            queue_rx.next();
+        }
+
+        assert_eq!(
+            events.try_iter().collect::<Vec<_>>(),
+            [
+                SinkEvent::Paused,
+                SinkEvent::Resumed,
+                SinkEvent::SourceStarted,
+                SinkEvent::SourceEnded,
+                SinkEvent::SourceStarted,
+                SinkEvent::SourceEnded,
+                SinkEvent::Emptied,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_speed() {
+        let (sink, mut queue_rx) = Sink::new_idle();
+
+        sink.set_speed(0.5);
+        sink.append(SamplesBuffer::new(1, 1000, vec![0.5f32; 1000]));
+        queue_rx.next();
+        assert_eq!(queue_rx.sample_rate(), 500);
+
+        // The speed is ramped by a bit every 5 samples.
+        sink.set_speed(2.0);
+        for _ in 0 .. 5 {
+            queue_rx.next();
+        }
+        let rate = queue_rx.sample_rate();
+        assert!(rate > 500 && rate < 2000, "{}", rate);
+        for _ in 0 .. 500 {
+            queue_rx.next();
+        }
+        assert_eq!(queue_rx.sample_rate(), 2000);
+    }
+
+    #[test]
+    fn test_effects() {
+        let (sink, mut queue_rx) = Sink::new_idle();
+
+        let v = vec![10i16, -10, 20, -20, 30, -30, 40, -40];
+        sink.append(SamplesBuffer::new(2, 44100, v.clone()));
+        let mut src = SamplesBuffer::new(2, 44100, v.clone()).convert_samples::<f32>();
+
+        let half = sink.add_effect(|input: Box<dyn Source<Item = f32> + Send>| {
+            Box::new(input.amplify(0.5)) as Box<_>
+        });
+        let double = sink.add_effect(|input: Box<dyn Source<Item = f32> + Send>| {
+            Box::new(input.amplify(2.0)) as Box<_>
+        });
+        sink.add_effect(|input: Box<dyn Source<Item = f32> + Send>| {
+            Box::new(input.amplify(3.0)) as Box<_>
+        });
+        for _ in 0 .. 2 {
+            assert_eq!(queue_rx.next(), src.next().map(|s| s * 3.0));
+        }
+
+        assert!(sink.remove_effect(double));
+        assert!(!sink.remove_effect(double));
+        for _ in 0 .. 2 {
+            assert_eq!(queue_rx.next(), src.next().map(|s| s * 1.5));
+        }
+
+        sink.remove_effect(half);
+        sink.clear_effects();
+        for _ in 0 .. 4 {
+            assert_eq!(queue_rx.next(), src.next());
+        }
+    }
+
+    #[test]
+    fn effects_carry_over_between_sounds() {
+        let (sink, mut queue_rx) = Sink::new_idle();
+        sink.add_effect(|input: Box<dyn Source<Item = f32> + Send>| {
+            Box::new(input.delay(Duration::from_millis(2))) as Box<_>
+        });
+
+        sink.append(SamplesBuffer::new(1, 1000, vec![1.0f32, 2.0]));
+        sink.append(SamplesBuffer::new(1, 1000, vec![3.0f32, 4.0]));
+        let played: Vec<f32> = queue_rx.by_ref().take(6).collect();
+        assert_eq!(played, vec![0.0, 0.0, 1.0, 2.0, 3.0, 4.0]);
+    }
+
+    #[test]
+    fn effects_follow_the_sample_rate() {
+        let (sink, mut queue_rx) = Sink::new_idle();
+        sink.add_effect(|input: Box<dyn Source<Item = f32> + Send>| {
+            let factor = input.sample_rate() as f32 / 1000.0;
+            Box::new(input.amplify(factor)) as Box<_>
+        });
+
+        sink.append(SamplesBuffer::new(1, 1000, vec![1.0f32, 1.0]));
+        sink.append(SamplesBuffer::new(1, 2000, vec![1.0f32, 1.0]));
+        let played: Vec<f32> = queue_rx.by_ref().take(4).collect();
+        assert_eq!(played, vec![1.0, 1.0, 2.0, 2.0]);
     }
 }
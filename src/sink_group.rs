@@ -0,0 +1,332 @@
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Duration after the last sound played by a group during which it is still considered active.
+const ACTIVITY_HOLD: Duration = Duration::from_millis(50);
+
+/// A set of named groups of sinks, for example "music", "effects" and "voices".
+///
+/// A `Sink` is added to a group with `Sink::set_group`. The volume of the group multiplies the
+/// volume of each of its sinks, and muting the group silences them. When a group is soloed, the
+/// sinks of the groups that aren't soloed are silenced; the sinks without a group aren't
+/// affected.
+///
+/// A group can also duck another one, for example the dialogues can lower the music while they
+/// play: see `SinkGroups::duck`.
+#[derive(Clone, Default)]
+pub struct SinkGroups {
+    shared: Arc<GroupsShared>,
+}
+
+#[derive(Default)]
+struct GroupsShared {
+    groups: Mutex<Vec<Arc<GroupControls>>>,
+    // Number of groups that are soloed.
+    soloed: AtomicUsize,
+    rules: Mutex<Vec<DuckRule>>,
+}
+
+// A group that ducks another one.
+struct DuckRule {
+    trigger: Arc<GroupControls>,
+    target: Arc<GroupControls>,
+    ducking: Ducking,
+}
+
+/// How a group is lowered while another one plays, given to `SinkGroups::duck`.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Ducking {
+    /// Volume of the ducked group while the other one plays, between `0.0` and `1.0`.
+    pub amount: f32,
+    /// Duration of the fade from the normal volume to `amount` when the other group starts
+    /// playing.
+    pub attack: Duration,
+    /// Duration of the fade back to the normal volume when the other group stops playing.
+    pub release: Duration,
+}
+
+impl Default for Ducking {
+    fn default() -> Ducking {
+        Ducking {
+            amount: 0.25,
+            attack: Duration::from_millis(50),
+            release: Duration::from_millis(500),
+        }
+    }
+}
+
+/// A group of sinks of `SinkGroups`.
+#[derive(Clone)]
+pub struct SinkGroup {
+    controls: Arc<GroupControls>,
+    shared: Arc<GroupsShared>,
+}
+
+struct GroupControls {
+    name: String,
+    // Stored as the bits of an `f32`, so that the audio thread can read it without locking.
+    volume: AtomicU32,
+    muted: AtomicBool,
+    soloed: AtomicBool,
+    // Last time a sink of the group played.
+    last_active: Mutex<Option<Instant>>,
+    duck: Mutex<DuckState>,
+}
+
+// Current ducking of a group.
+struct DuckState {
+    gain: f32,
+    updated: Instant,
+}
+
+impl GroupControls {
+    fn is_active(&self) -> bool {
+        self.last_active
+            .lock()
+            .unwrap()
+            .is_some_and(|last| last.elapsed() < ACTIVITY_HOLD)
+    }
+}
+
+impl SinkGroups {
+    /// Builds an empty set of groups.
+    #[inline]
+    pub fn new() -> SinkGroups {
+        SinkGroups::default()
+    }
+
+    /// Returns the group with the given name, creating it if it doesn't exist.
+    pub fn group(&self, name: &str) -> SinkGroup {
+        let mut groups = self.shared.groups.lock().unwrap();
+        let existing = groups.iter().find(|controls| controls.name == name).cloned();
+        let controls = existing.unwrap_or_else(|| {
+            let controls = Arc::new(GroupControls {
+                name: name.to_owned(),
+                volume: AtomicU32::new(1f32.to_bits()),
+                muted: AtomicBool::new(false),
+                soloed: AtomicBool::new(false),
+                last_active: Mutex::new(None),
+                duck: Mutex::new(DuckState {
+                    gain: 1.0,
+                    updated: Instant::now(),
+                }),
+            });
+            groups.push(controls.clone());
+            controls
+        });
+
+        SinkGroup {
+            controls,
+            shared: self.shared.clone(),
+        }
+    }
+
+    /// Returns all the groups, in the order they were created.
+    #[inline]
+    pub fn groups(&self) -> Vec<SinkGroup> {
+        let groups = self.shared.groups.lock().unwrap();
+        groups
+            .iter()
+            .map(|controls| SinkGroup {
+                controls: controls.clone(),
+                shared: self.shared.clone(),
+            })
+            .collect()
+    }
+
+    /// Lowers the volume of `target` while the sinks of `trigger` play, replacing the previous
+    /// ducking of `target` by `trigger`.
+    ///
+    /// When several groups duck the same group, the lowest `amount` applies.
+    pub fn duck(&self, trigger: &SinkGroup, target: &SinkGroup, ducking: Ducking) {
+        let mut rules = self.shared.rules.lock().unwrap();
+        rules.retain(|rule| !rule.is(trigger, target));
+        rules.push(DuckRule {
+            trigger: trigger.controls.clone(),
+            target: target.controls.clone(),
+            ducking,
+        });
+    }
+
+    /// Stops `trigger` from ducking `target`. The volume of `target` goes back to normal with
+    /// the release of the ducking.
+    ///
+    /// Returns false if `trigger` didn't duck `target`.
+    pub fn remove_ducking(&self, trigger: &SinkGroup, target: &SinkGroup) -> bool {
+        let mut rules = self.shared.rules.lock().unwrap();
+        let len = rules.len();
+        rules.retain(|rule| !rule.is(trigger, target));
+        rules.len() != len
+    }
+}
+
+impl DuckRule {
+    #[inline]
+    fn is(&self, trigger: &SinkGroup, target: &SinkGroup) -> bool {
+        Arc::ptr_eq(&self.trigger, &trigger.controls)
+            && Arc::ptr_eq(&self.target, &target.controls)
+    }
+}
+
+impl SinkGroup {
+    /// Returns the name of the group.
+    #[inline]
+    pub fn name(&self) -> &str {
+        &self.controls.name
+    }
+
+    /// Returns the volume of the group.
+    #[inline]
+    pub fn volume(&self) -> f32 {
+        f32::from_bits(self.controls.volume.load(Ordering::Relaxed))
+    }
+
+    /// Changes the volume of the group. The value `1.0` is the "normal" volume, and it
+    /// multiplies the volume of each sink of the group.
+    #[inline]
+    pub fn set_volume(&self, value: f32) {
+        self.controls.volume.store(value.to_bits(), Ordering::Relaxed);
+    }
+
+    /// Returns true if the group is muted.
+    #[inline]
+    pub fn is_muted(&self) -> bool {
+        self.controls.muted.load(Ordering::Relaxed)
+    }
+
+    /// Mutes or unmutes the group, without changing its volume.
+    #[inline]
+    pub fn set_muted(&self, muted: bool) {
+        self.controls.muted.store(muted, Ordering::Relaxed);
+    }
+
+    /// Returns true if the group is soloed.
+    #[inline]
+    pub fn is_soloed(&self) -> bool {
+        self.controls.soloed.load(Ordering::Relaxed)
+    }
+
+    /// Solos the group or stops soloing it. While at least one group is soloed, the sinks of the
+    /// others are silent.
+    pub fn set_soloed(&self, soloed: bool) {
+        if self.controls.soloed.swap(soloed, Ordering::SeqCst) != soloed {
+            if soloed {
+                self.shared.soloed.fetch_add(1, Ordering::SeqCst);
+            } else {
+                self.shared.soloed.fetch_sub(1, Ordering::SeqCst);
+            }
+        }
+    }
+
+    // Records that a sink of the group is playing, for the ducking.
+    pub(crate) fn mark_active(&self) {
+        *self.controls.last_active.lock().unwrap() = Some(Instant::now());
+    }
+
+    // Returns the gain applied to the sinks of the group, with the mute, solos and ducking.
+    pub(crate) fn gain(&self) -> f32 {
+        let silenced = self.is_muted()
+            || (!self.is_soloed() && self.shared.soloed.load(Ordering::Relaxed) != 0);
+        if silenced {
+            0.0
+        } else {
+            self.volume() * self.duck_gain()
+        }
+    }
+
+    // Moves the ducking towards the amount of the active rules, and returns it.
+    fn duck_gain(&self) -> f32 {
+        let (mut target, mut attack, mut release) = (1.0f32, None, None);
+        for rule in self.shared.rules.lock().unwrap().iter() {
+            if !Arc::ptr_eq(&rule.target, &self.controls) {
+                continue;
+            }
+            if rule.trigger.is_active() && rule.ducking.amount < target {
+                target = rule.ducking.amount;
+                attack = Some(rule.ducking.attack);
+            }
+            release = Some(release.map_or(rule.ducking.release, |r: Duration| {
+                r.max(rule.ducking.release)
+            }));
+        }
+
+        let mut duck = self.controls.duck.lock().unwrap();
+        let elapsed = duck.updated.elapsed().as_secs_f32();
+        duck.updated = Instant::now();
+        // The fades go over the whole range in the attack or release time.
+        let fade = if target < duck.gain { attack } else { release };
+        duck.gain = match fade {
+            Some(fade) if fade.as_secs_f32() > 0.0 => {
+                let step = elapsed / fade.as_secs_f32();
+                if target < duck.gain {
+                    (duck.gain - step).max(target)
+                } else {
+                    (duck.gain + step).min(target)
+                }
+            },
+            _ => target,
+        };
+        duck.gain
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+    use std::time::Duration;
+
+    use sink_group::{Ducking, SinkGroups};
+
+    #[test]
+    fn volume_mute_and_solo() {
+        let groups = SinkGroups::new();
+        let music = groups.group("music");
+        let effects = groups.group("effects");
+        assert_eq!(groups.group("music").name(), "music");
+        assert_eq!(groups.groups().len(), 2);
+
+        music.set_volume(0.5);
+        assert_eq!(music.gain(), 0.5);
+        music.set_muted(true);
+        assert_eq!(music.gain(), 0.0);
+        music.set_muted(false);
+
+        effects.set_soloed(true);
+        effects.set_soloed(true);
+        assert_eq!(music.gain(), 0.0);
+        assert_eq!(effects.gain(), 1.0);
+        effects.set_soloed(false);
+        assert_eq!(music.gain(), 0.5);
+    }
+
+    #[test]
+    fn ducking() {
+        let groups = SinkGroups::new();
+        let voice = groups.group("voice");
+        let music = groups.group("music");
+        let ducking = Ducking {
+            amount: 0.5,
+            attack: Duration::from_millis(10),
+            release: Duration::from_millis(10),
+        };
+        groups.duck(&voice, &music, ducking);
+        assert_eq!(music.gain(), 1.0);
+
+        voice.mark_active();
+        music.gain();
+        thread::sleep(Duration::from_millis(20));
+        voice.mark_active();
+        assert_eq!(music.gain(), 0.5);
+        assert_eq!(voice.gain(), 1.0);
+
+        // The voice stops playing.
+        thread::sleep(Duration::from_millis(100));
+        music.gain();
+        thread::sleep(Duration::from_millis(20));
+        assert_eq!(music.gain(), 1.0);
+
+        assert!(groups.remove_ducking(&voice, &music));
+        assert!(!groups.remove_ducking(&voice, &music));
+    }
+}
@@ -0,0 +1,248 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::ops::RangeInclusive;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use conversions::Random;
+use dynamic_mixer::{DynamicMixerController, MixerInput};
+use keep_alive::{default_format, keep_alive_mixer, KeepAlive, KeepAliveMixer};
+use play_raw;
+use source::Buffered;
+use Device;
+use NullOutput;
+use Source;
+
+type PooledSource = Buffered<Box<dyn Source<Item = f32> + Send>>;
+
+/// Random variations applied each time a sound of a `SoundPool` is played, so that a sound
+/// played many times doesn't sound mechanical.
+///
+/// Each value is picked uniformly in its range. The default doesn't change anything.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Variation {
+    /// Range of the gain. `1.0` is the volume of the sound.
+    pub volume: RangeInclusive<f32>,
+    /// Range of the speed, which changes the pitch. `1.0` is the speed of the sound.
+    pub pitch: RangeInclusive<f32>,
+    /// Range of the pan, from `-1.0` for the left to `1.0` for the right.
+    pub pan: RangeInclusive<f32>,
+}
+
+impl Default for Variation {
+    fn default() -> Variation {
+        Variation {
+            volume: 1.0 ..= 1.0,
+            pitch: 1.0 ..= 1.0,
+            pan: 0.0 ..= 0.0,
+        }
+    }
+}
+
+/// Sounds loaded in memory in advance and played by key, for example the sound effects of a
+/// game.
+///
+/// Each sound is decoded once, when it is inserted, and each play can vary in volume, pitch and
+/// pan.
+pub struct SoundPool<K> {
+    mixer: Arc<DynamicMixerController<f32>>,
+    state: Mutex<PoolState<K>>,
+    // Ends the output when the pool is dropped.
+    _keep_alive: KeepAlive,
+}
+
+struct PoolState<K> {
+    sounds: HashMap<K, PooledSound>,
+    // Generator of the variations.
+    random: Random,
+}
+
+struct PooledSound {
+    source: PooledSource,
+    variation: Variation,
+}
+
+impl<K> SoundPool<K>
+where
+    K: Eq + Hash,
+{
+    /// Builds a new `SoundPool` that plays on a device.
+    pub fn new(device: &Device) -> SoundPool<K> {
+        let (channels, sample_rate) = default_format(device);
+        let (pool, output) = SoundPool::new_idle(channels, sample_rate);
+        play_raw(device, output);
+        pool
+    }
+
+    /// Builds a new `SoundPool` that plays on an output without audio hardware.
+    pub fn new_null(output: &NullOutput) -> SoundPool<K> {
+        let (pool, pool_output) = SoundPool::new_idle(output.channels(), output.sample_rate());
+        output.play_raw(pool_output);
+        pool
+    }
+
+    /// Builds a new `SoundPool` whose sounds are mixed into the returned source, with the given
+    /// channels and sample rate.
+    ///
+    /// The source plays silence when no sound plays, and ends when the pool is dropped.
+    pub fn new_idle(channels: u16, sample_rate: u32) -> (SoundPool<K>, SoundPoolOutput) {
+        let (mixer_tx, keep_alive, mixer_rx) = keep_alive_mixer(channels, sample_rate);
+        let pool = SoundPool {
+            mixer: mixer_tx,
+            state: Mutex::new(PoolState {
+                sounds: HashMap::new(),
+                random: Random::new(),
+            }),
+            _keep_alive: keep_alive,
+        };
+        let output = SoundPoolOutput { mixer: mixer_rx };
+        (pool, output)
+    }
+
+    /// Loads a sound in memory and stores it under a key, replacing the previous sound with
+    /// this key.
+    pub fn insert<S>(&self, key: K, source: S)
+    where
+        S: Source<Item = f32> + Send + 'static,
+    {
+        let source: Box<dyn Source<Item = f32> + Send> = Box::new(source);
+        let source = source.buffered();
+        // Decodes the whole sound now rather than during the first play. The clones share the
+        // decoded samples.
+        source.clone().for_each(drop);
+
+        let mut state = self.state.lock().unwrap();
+        let variation = state
+            .sounds
+            .remove(&key)
+            .map(|sound| sound.variation)
+            .unwrap_or_default();
+        state.sounds.insert(key, PooledSound { source, variation });
+    }
+
+    /// Changes the variations applied when the sound stored under a key is played.
+    ///
+    /// Returns false if there is no sound with this key.
+    pub fn set_variation(&self, key: &K, variation: Variation) -> bool {
+        match self.state.lock().unwrap().sounds.get_mut(key) {
+            Some(sound) => {
+                sound.variation = variation;
+                true
+            },
+            None => false,
+        }
+    }
+
+    /// Removes the sound stored under a key. The plays in progress continue.
+    ///
+    /// Returns false if there is no sound with this key.
+    #[inline]
+    pub fn remove(&self, key: &K) -> bool {
+        self.state.lock().unwrap().sounds.remove(key).is_some()
+    }
+
+    /// Plays the sound stored under a key, and returns a handle to control it while it plays.
+    ///
+    /// Returns `None` if there is no sound with this key.
+    pub fn play(&self, key: &K) -> Option<MixerInput> {
+        let mut state = self.state.lock().unwrap();
+        let (source, variation) = {
+            let sound = state.sounds.get(key)?;
+            (sound.source.clone(), sound.variation.clone())
+        };
+        let volume = state.pick(&variation.volume);
+        let pitch = state.pick(&variation.pitch);
+        let pan = state.pick(&variation.pan);
+        Some(self.mixer.add_with(source.speed(pitch), volume, pan))
+    }
+}
+
+impl<K> PoolState<K> {
+    // Returns a random value in the range.
+    fn pick(&mut self, range: &RangeInclusive<f32>) -> f32 {
+        let t = self.random.unit();
+        range.start() + (range.end() - range.start()) * t
+    }
+}
+
+/// The mix of the sounds played by a `SoundPool`.
+pub struct SoundPoolOutput {
+    mixer: KeepAliveMixer,
+}
+
+impl Iterator for SoundPoolOutput {
+    type Item = f32;
+
+    #[inline]
+    fn next(&mut self) -> Option<f32> {
+        self.mixer.next()
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.mixer.size_hint()
+    }
+}
+
+impl Source for SoundPoolOutput {
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        self.mixer.current_frame_len()
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        self.mixer.channels()
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        self.mixer.sample_rate()
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use buffer::SamplesBuffer;
+    use sound_pool::{SoundPool, Variation};
+
+    #[test]
+    fn plays_by_key() {
+        let (pool, mut output) = SoundPool::new_idle(1, 1000);
+        pool.insert("click", SamplesBuffer::new(1, 1000, vec![0.5f32, 0.25]));
+        assert!(pool.play(&"missing").is_none());
+
+        pool.play(&"click").unwrap();
+        assert_eq!(output.next(), Some(0.5));
+        pool.play(&"click").unwrap();
+        assert_eq!(output.next(), Some(0.75));
+        assert_eq!(output.next(), Some(0.25));
+        assert_eq!(output.next(), Some(0.0));
+    }
+
+    #[test]
+    fn random_variations() {
+        let (pool, mut output) = SoundPool::new_idle(1, 1000);
+        pool.insert(1, SamplesBuffer::new(1, 1000, vec![1.0f32; 10]));
+        let variation = Variation {
+            volume: 0.5 ..= 0.8,
+            ..Variation::default()
+        };
+        assert!(pool.set_variation(&1, variation));
+
+        let mut levels = Vec::new();
+        for _ in 0 .. 10 {
+            let handle = pool.play(&1).unwrap();
+            levels.push(output.next().unwrap());
+            handle.remove();
+            output.by_ref().take(10).count();
+        }
+        assert!(levels.iter().all(|&level| level >= 0.5 && level <= 0.8));
+        assert!(levels.iter().any(|&level| level != levels[0]));
+    }
+}
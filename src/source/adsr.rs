@@ -0,0 +1,280 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use source::SeekError;
+use Sample;
+use Source;
+
+/// Internal function that builds an `Adsr` object.
+pub fn adsr<I>(
+    input: I, attack: Duration, decay: Duration, sustain_level: f32, release: Duration,
+) -> Adsr<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    Adsr {
+        input,
+        attack,
+        decay,
+        sustain_level,
+        release,
+        stage: Stage::Attack,
+        level: 0.0,
+        release_step: 0.0,
+        note_off: Arc::new(AtomicBool::new(false)),
+        current_channel: 0,
+    }
+}
+
+/// Shapes the volume of a source with an attack-decay-sustain-release envelope.
+///
+/// The volume rises from silence to its maximum during the attack, falls to the sustain level
+/// during the decay, and then stays there until the note is released with `note_off`. The volume
+/// then goes back to silence during the release, after which the source ends.
+#[derive(Clone, Debug)]
+pub struct Adsr<I> {
+    input: I,
+    attack: Duration,
+    decay: Duration,
+    sustain_level: f32,
+    release: Duration,
+    stage: Stage,
+    // Volume applied to the current frame.
+    level: f32,
+    // Amount by which `level` decreases every frame during the release.
+    release_step: f32,
+    note_off: Arc<AtomicBool>,
+    // Channel of the next sample returned by `next`.
+    current_channel: u16,
+}
+
+// The stages are ordered chronologically.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum Stage {
+    Attack,
+    Decay,
+    Sustain,
+    Release,
+    Done,
+}
+
+/// Handle that releases the note of an `Adsr` from another thread.
+#[derive(Clone, Debug)]
+pub struct AdsrHandle {
+    note_off: Arc<AtomicBool>,
+}
+
+impl AdsrHandle {
+    /// Releases the note. See `Adsr::note_off`.
+    #[inline]
+    pub fn note_off(&self) {
+        self.note_off.store(true, Ordering::Relaxed);
+    }
+}
+
+impl<I> Adsr<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    /// Releases the note. The envelope moves to its release stage, and the source ends once the
+    /// release is over.
+    #[inline]
+    pub fn note_off(&self) {
+        self.note_off.store(true, Ordering::Relaxed);
+    }
+
+    /// Returns a handle that can release the note after the source has been handed to a sink.
+    #[inline]
+    pub fn handle(&self) -> AdsrHandle {
+        AdsrHandle {
+            note_off: self.note_off.clone(),
+        }
+    }
+
+    /// Returns a reference to the inner source.
+    #[inline]
+    pub fn inner(&self) -> &I {
+        &self.input
+    }
+
+    /// Returns a mutable reference to the inner source.
+    #[inline]
+    pub fn inner_mut(&mut self) -> &mut I {
+        &mut self.input
+    }
+
+    /// Returns the inner source.
+    #[inline]
+    pub fn into_inner(self) -> I {
+        self.input
+    }
+
+    // Returns how many frames `duration` lasts, or `None` if it is shorter than one frame.
+    #[inline]
+    fn frames(&self, duration: Duration) -> Option<f32> {
+        let frames = duration.as_secs_f32() * self.input.sample_rate() as f32;
+        if frames >= 1.0 {
+            Some(frames)
+        } else {
+            None
+        }
+    }
+
+    // Computes the level of the next frame.
+    fn next_frame(&mut self) {
+        if self.stage < Stage::Release && self.note_off.load(Ordering::Relaxed) {
+            self.stage = Stage::Release;
+            self.release_step = match self.frames(self.release) {
+                Some(frames) => self.level / frames,
+                None => self.level,
+            };
+        }
+
+        match self.stage {
+            Stage::Attack => match self.frames(self.attack) {
+                Some(frames) if self.level + 1.0 / frames < 1.0 => self.level += 1.0 / frames,
+                _ => {
+                    self.level = 1.0;
+                    self.stage = Stage::Decay;
+                },
+            },
+            Stage::Decay => {
+                let step = match self.frames(self.decay) {
+                    Some(frames) => (1.0 - self.sustain_level) / frames,
+                    None => 1.0,
+                };
+                if self.level - step > self.sustain_level {
+                    self.level -= step;
+                } else {
+                    self.level = self.sustain_level;
+                    self.stage = Stage::Sustain;
+                }
+            },
+            Stage::Sustain => (),
+            Stage::Release => {
+                if self.level > 0.0 {
+                    self.level = (self.level - self.release_step).max(0.0);
+                } else {
+                    self.stage = Stage::Done;
+                }
+            },
+            Stage::Done => (),
+        }
+    }
+}
+
+impl<I> Iterator for Adsr<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    type Item = I::Item;
+
+    #[inline]
+    fn next(&mut self) -> Option<I::Item> {
+        if self.current_channel >= self.input.channels() {
+            self.current_channel = 0;
+        }
+        if self.current_channel == 0 {
+            self.next_frame();
+        }
+        self.current_channel += 1;
+
+        if self.stage == Stage::Done {
+            return None;
+        }
+
+        self.input.next().map(|value| value.amplify(self.level))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, self.input.size_hint().1)
+    }
+}
+
+impl<I> Source for Adsr<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        if self.stage == Stage::Done {
+            return Some(0);
+        }
+        self.input.current_frame_len()
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        self.input.channels()
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        self.input.sample_rate()
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        None
+    }
+
+    #[inline]
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        self.input.try_seek(pos)?;
+        self.current_channel = 0;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use buffer::SamplesBuffer;
+    use source::Source;
+
+    fn assert_approx(actual: Option<f32>, expected: f32) {
+        let actual = actual.unwrap();
+        assert!((actual - expected).abs() < 1e-4, "{} != {}", actual, expected);
+    }
+
+    #[test]
+    fn envelope() {
+        // 10 frames per second, so 200ms are 2 frames.
+        let input = SamplesBuffer::new(1, 10, vec![1.0f32; 20]);
+        let ms = Duration::from_millis(200);
+        let mut source = input.adsr(ms, ms, 0.5, ms);
+
+        assert_approx(source.next(), 0.5);
+        assert_approx(source.next(), 1.0);
+        assert_approx(source.next(), 0.75);
+        assert_approx(source.next(), 0.5);
+        assert_approx(source.next(), 0.5);
+        assert_approx(source.next(), 0.5);
+
+        source.handle().note_off();
+        assert_approx(source.next(), 0.25);
+        assert_approx(source.next(), 0.0);
+        assert_eq!(source.next(), None);
+        assert_eq!(source.current_frame_len(), Some(0));
+    }
+
+    #[test]
+    fn instant_stages() {
+        let input = SamplesBuffer::new(2, 10, vec![1.0f32; 20]);
+        let zero = Duration::from_secs(0);
+        let mut source = input.adsr(zero, zero, 0.5, zero);
+
+        assert_eq!(source.by_ref().take(4).collect::<Vec<_>>(), [1.0, 1.0, 0.5, 0.5]);
+        source.note_off();
+        assert_eq!(source.next(), Some(0.0));
+        assert_eq!(source.next(), Some(0.0));
+        assert_eq!(source.next(), None);
+    }
+}
@@ -0,0 +1,191 @@
+use std::time::Duration;
+
+use source::SeekError;
+use Sample;
+use Source;
+
+/// Maximum amplification applied by the filter, so that silence and background noise aren't
+/// boosted to the target level.
+const MAX_GAIN: f32 = 10.0;
+
+/// Internal function that builds an `AutomaticGainControl` object.
+pub fn agc<I>(
+    input: I, target_level: f32, attack: Duration, release: Duration,
+) -> AutomaticGainControl<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    AutomaticGainControl {
+        input,
+        target_level,
+        attack,
+        release,
+        // Starting at the target level means that the gain is initially `1.0`.
+        mean_square: target_level * target_level,
+    }
+}
+
+/// Filter that continuously adjusts the volume of a source so that its RMS level stays close to
+/// a target level.
+///
+/// The level is measured with a moving average that reacts to louder sounds with the attack time
+/// constant and to quieter sounds with the release time constant. The amplification is capped at
+/// 20dB.
+#[derive(Clone, Debug)]
+pub struct AutomaticGainControl<I> {
+    input: I,
+    target_level: f32,
+    attack: Duration,
+    release: Duration,
+    // Moving average of the square of the samples.
+    mean_square: f32,
+}
+
+impl<I> AutomaticGainControl<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    /// Modifies the RMS level that the filter is aiming for, between `0.0` and `1.0`.
+    #[inline]
+    pub fn set_target_level(&mut self, target_level: f32) {
+        self.target_level = target_level;
+    }
+
+    /// Returns the amplification currently applied.
+    #[inline]
+    pub fn gain(&self) -> f32 {
+        let level = self.mean_square.sqrt();
+        if level * MAX_GAIN <= self.target_level {
+            MAX_GAIN
+        } else {
+            self.target_level / level
+        }
+    }
+
+    /// Returns a reference to the inner source.
+    #[inline]
+    pub fn inner(&self) -> &I {
+        &self.input
+    }
+
+    /// Returns a mutable reference to the inner source.
+    #[inline]
+    pub fn inner_mut(&mut self) -> &mut I {
+        &mut self.input
+    }
+
+    /// Returns the inner source.
+    #[inline]
+    pub fn into_inner(self) -> I {
+        self.input
+    }
+
+    // Returns the smoothing coefficient per sample of a time constant.
+    #[inline]
+    fn coefficient(&self, time: Duration) -> f32 {
+        let samples =
+            time.as_secs_f32() * self.input.sample_rate() as f32 * self.input.channels() as f32;
+        if samples > 0.0 {
+            (-1.0 / samples).exp()
+        } else {
+            0.0
+        }
+    }
+}
+
+impl<I> Iterator for AutomaticGainControl<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    type Item = I::Item;
+
+    #[inline]
+    fn next(&mut self) -> Option<I::Item> {
+        let value = self.input.next()?;
+
+        let square = value.to_f32() * value.to_f32();
+        let coefficient = if square > self.mean_square {
+            self.coefficient(self.attack)
+        } else {
+            self.coefficient(self.release)
+        };
+        self.mean_square = coefficient * self.mean_square + (1.0 - coefficient) * square;
+
+        Some(value.amplify(self.gain()))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.input.size_hint()
+    }
+}
+
+impl<I> ExactSizeIterator for AutomaticGainControl<I>
+where
+    I: Source + ExactSizeIterator,
+    I::Item: Sample,
+{
+}
+
+impl<I> Source for AutomaticGainControl<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        self.input.current_frame_len()
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        self.input.channels()
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        self.input.sample_rate()
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        self.input.total_duration()
+    }
+
+    #[inline]
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        self.input.try_seek(pos)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use buffer::SamplesBuffer;
+    use source::Source;
+
+    #[test]
+    fn converges_to_target() {
+        let ms = Duration::from_millis(10);
+
+        let loud = SamplesBuffer::new(1, 1000, vec![0.8f32; 1000]);
+        let last = loud.agc(0.2, ms, ms).last().unwrap();
+        assert!((last - 0.2).abs() < 1e-3);
+
+        let quiet = SamplesBuffer::new(1, 1000, vec![0.05f32; 1000]);
+        let last = quiet.agc(0.2, ms, ms).last().unwrap();
+        assert!((last - 0.2).abs() < 1e-3);
+    }
+
+    #[test]
+    fn gain_is_capped() {
+        let ms = Duration::from_millis(10);
+        let silent = SamplesBuffer::new(1, 1000, vec![0.001f32; 1000]);
+        let last = silent.agc(0.2, ms, ms).last().unwrap();
+        assert!((last - 0.01).abs() < 1e-4);
+    }
+}
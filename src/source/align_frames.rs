@@ -0,0 +1,173 @@
+use std::time::Duration;
+
+use source::SeekError;
+use Sample;
+use Source;
+
+/// Internal function that builds an `AlignFrames` object.
+pub fn align_frames<I>(input: I) -> AlignFrames<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    AlignFrames {
+        input,
+        current_channel: 0,
+        channels: 0,
+        samples_from_input: 0,
+    }
+}
+
+/// Filter that makes sure that a source only contains whole frames.
+///
+/// If the source ends, or if one of its frames as returned by `current_frame_len` ends, in the
+/// middle of a frame, the frame is completed with silence. Without this, the channels that follow
+/// would be swapped by filters that convert the number of channels.
+#[derive(Clone, Debug)]
+pub struct AlignFrames<I> {
+    input: I,
+    // Channel of the next sample returned by `next`.
+    current_channel: u16,
+    // Number of channels of the current frame.
+    channels: u16,
+    // Number of samples of the current frame that are read from the input. The others are silent.
+    samples_from_input: u16,
+}
+
+impl<I> AlignFrames<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    /// Returns a reference to the inner source.
+    #[inline]
+    pub fn inner(&self) -> &I {
+        &self.input
+    }
+
+    /// Returns a mutable reference to the inner source.
+    #[inline]
+    pub fn inner_mut(&mut self) -> &mut I {
+        &mut self.input
+    }
+
+    /// Returns the inner source.
+    #[inline]
+    pub fn into_inner(self) -> I {
+        self.input
+    }
+}
+
+impl<I> Iterator for AlignFrames<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    type Item = I::Item;
+
+    #[inline]
+    fn next(&mut self) -> Option<I::Item> {
+        if self.current_channel >= self.channels {
+            self.current_channel = 0;
+            self.channels = self.input.channels();
+            self.samples_from_input = match self.input.current_frame_len() {
+                Some(len) if len != 0 && len < self.channels as usize => len as u16,
+                _ => self.channels,
+            };
+        }
+
+        if self.current_channel < self.samples_from_input {
+            match self.input.next() {
+                Some(value) => {
+                    self.current_channel += 1;
+                    return Some(value);
+                },
+                None if self.current_channel == 0 => {
+                    // Makes sure that the next call reads the input again.
+                    self.channels = 0;
+                    return None;
+                },
+                None => self.samples_from_input = self.current_channel,
+            }
+        }
+
+        self.current_channel += 1;
+        Some(I::Item::zero_value())
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let (min, max) = self.input.size_hint();
+        let in_frame = self.channels.saturating_sub(self.current_channel) as usize;
+        (min, max.map(|max| max + in_frame + self.channels as usize))
+    }
+}
+
+impl<I> Source for AlignFrames<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        if self.current_channel < self.channels {
+            return Some((self.channels - self.current_channel) as usize);
+        }
+
+        let channels = self.input.channels() as usize;
+        self.input
+            .current_frame_len()
+            .map(|len| len.div_ceil(channels) * channels)
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        if self.current_channel < self.channels {
+            self.channels
+        } else {
+            self.input.channels()
+        }
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        self.input.sample_rate()
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        self.input.total_duration()
+    }
+
+    #[inline]
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        self.input.try_seek(pos)?;
+        self.current_channel = 0;
+        self.channels = 0;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use buffer::SamplesBuffer;
+    use source::{from_iter, Source};
+
+    #[test]
+    fn pads_incomplete_frames() {
+        let source = from_iter(vec![
+            SamplesBuffer::new(2, 44100, vec![1i16, 2, 3]),
+            SamplesBuffer::new(2, 44100, vec![4i16, 5, 6]),
+        ]);
+        assert_eq!(
+            source.align_frames().collect::<Vec<_>>(),
+            [1, 2, 3, 0, 4, 5, 6, 0]
+        );
+    }
+
+    #[test]
+    fn aligned_source_is_untouched() {
+        let source = SamplesBuffer::new(2, 44100, vec![1i16, 2, 3, 4]).align_frames();
+        assert_eq!(source.collect::<Vec<_>>(), [1, 2, 3, 4]);
+    }
+}
@@ -0,0 +1,804 @@
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use source::uniform::UniformSourceIterator;
+use source::{HrirSet, SeekError, ToMono};
+use Sample;
+use Source;
+
+// Number of channels of first-order B-format. The channels are W, Y, Z and X, in this order, with
+// the SN3D normalization (also known as the AmbiX format).
+const B_FORMAT_CHANNELS: u16 = 4;
+
+/// Internal function that builds an `AmbisonicEncoder` object.
+pub fn encode<I>(input: I, azimuth: f32, elevation: f32) -> AmbisonicEncoder<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    AmbisonicEncoder {
+        input: input.to_mono(),
+        direction: Arc::new(Angles::new([azimuth, elevation, 0.0])),
+        last_direction: None,
+        gains: [0.0; 4],
+        value: None,
+        current_channel: B_FORMAT_CHANNELS,
+    }
+}
+
+/// Internal function that builds an `AmbisonicRotate` object.
+pub fn rotate<I>(input: I, yaw: f32, pitch: f32, roll: f32) -> AmbisonicRotate<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    AmbisonicRotate {
+        input,
+        orientation: Arc::new(Angles::new([yaw, pitch, roll])),
+        last_orientation: None,
+        matrix: [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]],
+        frame: [0.0; 4],
+        current_channel: B_FORMAT_CHANNELS,
+    }
+}
+
+/// Internal function that builds an `AmbisonicDecoder` object.
+pub fn decode<I>(input: I, speakers: &[(f32, f32)]) -> AmbisonicDecoder<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    assert!(!speakers.is_empty(), "There must be at least one speaker");
+
+    AmbisonicDecoder {
+        input,
+        matrix: speakers
+            .iter()
+            .map(|&(azimuth, elevation)| cardioid(azimuth, elevation))
+            .collect(),
+        frame: [0.0; 4],
+        current_channel: speakers.len() as u16,
+    }
+}
+
+/// Internal function that builds an `AmbisonicBinaural` object.
+pub fn binaural<I>(input: I, hrirs: &HrirSet) -> AmbisonicBinaural<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    let len = hrirs.response_len();
+    assert!(len != 0, "The set of impulse responses is empty");
+
+    // The field is decoded to the corners of a cube, and each corner is played through the
+    // response closest to it. Both steps are linear, so they are merged into one filter per
+    // channel and per ear.
+    let mut filters = vec![[vec![0.0; len], vec![0.0; len]]; B_FORMAT_CHANNELS as usize];
+    for &azimuth in &[45.0, 135.0, -135.0, -45.0] {
+        for &elevation in &[35.26, -35.26] {
+            let gains = cardioid(azimuth, elevation);
+            let (left, right) = hrirs.response(hrirs.nearest(azimuth, elevation));
+            for (filter, gain) in filters.iter_mut().zip(gains.iter()) {
+                for k in 0 .. len {
+                    // The cube has 8 corners, and the sum of their gains is 4 for every
+                    // direction.
+                    filter[0][k] += gain * left[k] / 4.0;
+                    filter[1][k] += gain * right[k] / 4.0;
+                }
+            }
+        }
+    }
+
+    AmbisonicBinaural {
+        input: UniformSourceIterator::new(input, B_FORMAT_CHANNELS, hrirs.sample_rate()),
+        sample_rate: hrirs.sample_rate(),
+        filters,
+        history: vec![vec![0.0; 2 * len]; B_FORMAT_CHANNELS as usize],
+        history_pos: 0,
+        remaining_tail: len,
+        right: None,
+    }
+}
+
+// Angles that can be changed from another thread.
+#[derive(Debug)]
+struct Angles {
+    values: [AtomicU32; 3],
+}
+
+impl Angles {
+    fn new(values: [f32; 3]) -> Angles {
+        Angles {
+            values: [
+                AtomicU32::new(values[0].to_bits()),
+                AtomicU32::new(values[1].to_bits()),
+                AtomicU32::new(values[2].to_bits()),
+            ],
+        }
+    }
+
+    #[inline]
+    fn set(&self, index: usize, value: f32) {
+        self.values[index].store(value.to_bits(), Ordering::Relaxed);
+    }
+
+    // Returns the angles if they have changed since `last`, and updates `last`.
+    #[inline]
+    fn changed(&self, last: &mut Option<[u32; 3]>) -> Option<[f32; 3]> {
+        let bits = [
+            self.values[0].load(Ordering::Relaxed),
+            self.values[1].load(Ordering::Relaxed),
+            self.values[2].load(Ordering::Relaxed),
+        ];
+        if *last == Some(bits) {
+            return None;
+        }
+        *last = Some(bits);
+        Some([
+            f32::from_bits(bits[0]),
+            f32::from_bits(bits[1]),
+            f32::from_bits(bits[2]),
+        ])
+    }
+}
+
+// Returns the B-format gains of a sound coming from a direction given in degrees.
+fn encoding_gains(azimuth: f32, elevation: f32) -> [f32; 4] {
+    let (azimuth, elevation) = (azimuth.to_radians(), elevation.to_radians());
+    [
+        1.0,
+        azimuth.sin() * elevation.cos(),
+        elevation.sin(),
+        azimuth.cos() * elevation.cos(),
+    ]
+}
+
+// Returns the gains of a virtual cardioid microphone pointing to a direction.
+fn cardioid(azimuth: f32, elevation: f32) -> [f32; 4] {
+    let gains = encoding_gains(azimuth, elevation);
+    [0.5, gains[1] * 0.5, gains[2] * 0.5, gains[3] * 0.5]
+}
+
+// Reads a frame of the input and returns its B-format channels. Missing channels are silent.
+fn read_frame<I>(input: &mut I, frame: &mut [f32; 4]) -> bool
+where
+    I: Source,
+    I::Item: Sample,
+{
+    let channels = input.channels() as usize;
+    *frame = [0.0; 4];
+    let mut read = 0;
+    for value in input.by_ref().take(channels) {
+        if read < frame.len() {
+            frame[read] = value.to_f32();
+        }
+        read += 1;
+    }
+    read != 0
+}
+
+/// Encodes a sound coming from a direction into first-order ambisonics.
+///
+/// The source is downmixed to mono. The output has four channels, in the AmbiX format: W, Y, Z
+/// and X, with the SN3D normalization. Directions are in degrees: an azimuth of `0.0` is in front,
+/// an azimuth of `90.0` is on the left, and an elevation of `90.0` is above.
+#[derive(Clone, Debug)]
+pub struct AmbisonicEncoder<I> {
+    input: ToMono<I>,
+    direction: Arc<Angles>,
+    last_direction: Option<[u32; 3]>,
+    gains: [f32; 4],
+    // Sample of the current frame.
+    value: Option<f32>,
+    // Channel of the next sample returned by `next`.
+    current_channel: u16,
+}
+
+/// Handle that changes the direction of an `AmbisonicEncoder` from another thread.
+#[derive(Clone, Debug)]
+pub struct AmbisonicEncoderHandle {
+    direction: Arc<Angles>,
+}
+
+impl AmbisonicEncoderHandle {
+    /// Changes the direction of the sound, in degrees.
+    #[inline]
+    pub fn set_direction(&self, azimuth: f32, elevation: f32) {
+        self.direction.set(0, azimuth);
+        self.direction.set(1, elevation);
+    }
+}
+
+impl<I> AmbisonicEncoder<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    /// Changes the direction of the sound, in degrees.
+    #[inline]
+    pub fn set_direction(&mut self, azimuth: f32, elevation: f32) {
+        self.direction.set(0, azimuth);
+        self.direction.set(1, elevation);
+    }
+
+    /// Returns a handle that can change the direction after the source has been handed to a sink.
+    #[inline]
+    pub fn handle(&self) -> AmbisonicEncoderHandle {
+        AmbisonicEncoderHandle {
+            direction: self.direction.clone(),
+        }
+    }
+
+    /// Returns a reference to the inner source.
+    #[inline]
+    pub fn inner(&self) -> &I {
+        self.input.inner()
+    }
+
+    /// Returns a mutable reference to the inner source.
+    #[inline]
+    pub fn inner_mut(&mut self) -> &mut I {
+        self.input.inner_mut()
+    }
+
+    /// Returns the inner source.
+    #[inline]
+    pub fn into_inner(self) -> I {
+        self.input.into_inner()
+    }
+}
+
+impl<I> Iterator for AmbisonicEncoder<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    type Item = I::Item;
+
+    #[inline]
+    fn next(&mut self) -> Option<I::Item> {
+        if self.current_channel >= B_FORMAT_CHANNELS {
+            self.current_channel = 0;
+            if let Some(direction) = self.direction.changed(&mut self.last_direction) {
+                self.gains = encoding_gains(direction[0], direction[1]);
+            }
+            self.value = self.input.next().map(|value| value.to_f32());
+        }
+
+        let value = self.value? * self.gains[self.current_channel as usize];
+        self.current_channel += 1;
+        Some(Sample::from_sample(&value))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let (min, max) = self.input.size_hint();
+        let in_frame = if self.value.is_some() {
+            (B_FORMAT_CHANNELS - self.current_channel) as usize
+        } else {
+            0
+        };
+        let channels = B_FORMAT_CHANNELS as usize;
+        (
+            min * channels + in_frame,
+            max.map(|max| max * channels + in_frame),
+        )
+    }
+}
+
+impl<I> Source for AmbisonicEncoder<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        if self.value.is_some() && self.current_channel < B_FORMAT_CHANNELS {
+            return Some((B_FORMAT_CHANNELS - self.current_channel) as usize);
+        }
+        self.input
+            .current_frame_len()
+            .map(|len| len * B_FORMAT_CHANNELS as usize)
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        B_FORMAT_CHANNELS
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        self.input.sample_rate()
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        self.input.total_duration()
+    }
+
+    #[inline]
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        self.input.try_seek(pos)?;
+        self.value = None;
+        self.current_channel = B_FORMAT_CHANNELS;
+        Ok(())
+    }
+}
+
+/// Rotates a first-order ambisonic sound field to follow the orientation of the listener.
+///
+/// The angles are in degrees and describe the orientation of the head of the listener: a
+/// positive yaw turns it to the left, a positive pitch tilts it up and a positive roll tilts it to
+/// the right. The field is rotated the other way, so that sounds stay at the same place in the
+/// world. The input must have four channels, as produced by `Source::ambisonic_encode`.
+#[derive(Clone, Debug)]
+pub struct AmbisonicRotate<I> {
+    input: I,
+    orientation: Arc<Angles>,
+    last_orientation: Option<[u32; 3]>,
+    // Converts the X, Y and Z channels of the input to the ones of the output.
+    matrix: [[f32; 3]; 3],
+    // Rotated channels of the current frame.
+    frame: [f32; 4],
+    // Channel of the next sample returned by `next`.
+    current_channel: u16,
+}
+
+/// Handle that changes the orientation of an `AmbisonicRotate` from another thread.
+#[derive(Clone, Debug)]
+pub struct AmbisonicRotateHandle {
+    orientation: Arc<Angles>,
+}
+
+impl AmbisonicRotateHandle {
+    /// Changes the orientation of the listener, in degrees.
+    #[inline]
+    pub fn set_orientation(&self, yaw: f32, pitch: f32, roll: f32) {
+        self.orientation.set(0, yaw);
+        self.orientation.set(1, pitch);
+        self.orientation.set(2, roll);
+    }
+}
+
+impl<I> AmbisonicRotate<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    /// Changes the orientation of the listener, in degrees.
+    #[inline]
+    pub fn set_orientation(&mut self, yaw: f32, pitch: f32, roll: f32) {
+        self.orientation.set(0, yaw);
+        self.orientation.set(1, pitch);
+        self.orientation.set(2, roll);
+    }
+
+    /// Returns a handle that can change the orientation after the source has been handed to a
+    /// sink.
+    #[inline]
+    pub fn handle(&self) -> AmbisonicRotateHandle {
+        AmbisonicRotateHandle {
+            orientation: self.orientation.clone(),
+        }
+    }
+
+    /// Returns a reference to the inner source.
+    #[inline]
+    pub fn inner(&self) -> &I {
+        &self.input
+    }
+
+    /// Returns a mutable reference to the inner source.
+    #[inline]
+    pub fn inner_mut(&mut self) -> &mut I {
+        &mut self.input
+    }
+
+    /// Returns the inner source.
+    #[inline]
+    pub fn into_inner(self) -> I {
+        self.input
+    }
+
+    // Computes the rotation of the field, which is the inverse of the orientation of the head.
+    fn update_matrix(&mut self, angles: [f32; 3]) {
+        let (sy, cy) = angles[0].to_radians().sin_cos();
+        let (sp, cp) = (-angles[1]).to_radians().sin_cos();
+        let (sr, cr) = angles[2].to_radians().sin_cos();
+
+        // Orientation of the head, as yaw around Z, then pitch around Y, then roll around X.
+        let head = [
+            [cy * cp, cy * sp * sr - sy * cr, cy * sp * cr + sy * sr],
+            [sy * cp, sy * sp * sr + cy * cr, sy * sp * cr - cy * sr],
+            [-sp, cp * sr, cp * cr],
+        ];
+
+        // The inverse of a rotation is its transpose.
+        for (i, row) in self.matrix.iter_mut().enumerate() {
+            for (j, value) in row.iter_mut().enumerate() {
+                *value = head[j][i];
+            }
+        }
+    }
+}
+
+impl<I> Iterator for AmbisonicRotate<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    type Item = I::Item;
+
+    #[inline]
+    fn next(&mut self) -> Option<I::Item> {
+        if self.current_channel >= B_FORMAT_CHANNELS {
+            if self.input.channels() != B_FORMAT_CHANNELS {
+                return self.input.next();
+            }
+
+            self.current_channel = 0;
+            if let Some(angles) = self.orientation.changed(&mut self.last_orientation) {
+                self.update_matrix(angles);
+            }
+
+            let mut input = [0.0; 4];
+            if !read_frame(&mut self.input, &mut input) {
+                self.current_channel = B_FORMAT_CHANNELS;
+                return None;
+            }
+
+            let xyz = [input[3], input[1], input[2]];
+            let rotated = |row: [f32; 3]| (0 .. 3).map(|i| row[i] * xyz[i]).sum::<f32>();
+            let (x, y, z) = (
+                rotated(self.matrix[0]),
+                rotated(self.matrix[1]),
+                rotated(self.matrix[2]),
+            );
+            self.frame = [input[0], y, z, x];
+        }
+
+        let value = self.frame[self.current_channel as usize];
+        self.current_channel += 1;
+        Some(Sample::from_sample(&value))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let in_frame = B_FORMAT_CHANNELS.saturating_sub(self.current_channel) as usize;
+        let (min, max) = self.input.size_hint();
+        (min + in_frame, max.map(|max| max + in_frame))
+    }
+}
+
+impl<I> Source for AmbisonicRotate<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        if self.current_channel < B_FORMAT_CHANNELS {
+            return Some((B_FORMAT_CHANNELS - self.current_channel) as usize);
+        }
+        self.input.current_frame_len()
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        if self.current_channel < B_FORMAT_CHANNELS {
+            B_FORMAT_CHANNELS
+        } else {
+            self.input.channels()
+        }
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        self.input.sample_rate()
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        self.input.total_duration()
+    }
+
+    #[inline]
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        self.input.try_seek(pos)?;
+        self.current_channel = B_FORMAT_CHANNELS;
+        Ok(())
+    }
+}
+
+/// Decodes a first-order ambisonic sound field to a layout of speakers.
+///
+/// Each speaker plays the signal of a virtual cardioid microphone pointing to it. The output has
+/// one channel per speaker, in the order in which they were given.
+#[derive(Clone, Debug)]
+pub struct AmbisonicDecoder<I> {
+    input: I,
+    // B-format gains of each speaker.
+    matrix: Vec<[f32; 4]>,
+    // B-format channels of the current frame.
+    frame: [f32; 4],
+    // Channel of the next sample returned by `next`.
+    current_channel: u16,
+}
+
+impl<I> AmbisonicDecoder<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    /// Returns a reference to the inner source.
+    #[inline]
+    pub fn inner(&self) -> &I {
+        &self.input
+    }
+
+    /// Returns a mutable reference to the inner source.
+    #[inline]
+    pub fn inner_mut(&mut self) -> &mut I {
+        &mut self.input
+    }
+
+    /// Returns the inner source.
+    #[inline]
+    pub fn into_inner(self) -> I {
+        self.input
+    }
+}
+
+impl<I> Iterator for AmbisonicDecoder<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    type Item = I::Item;
+
+    #[inline]
+    fn next(&mut self) -> Option<I::Item> {
+        let speakers = self.matrix.len() as u16;
+        if self.current_channel >= speakers {
+            if !read_frame(&mut self.input, &mut self.frame) {
+                return None;
+            }
+            self.current_channel = 0;
+        }
+
+        let gains = self.matrix[self.current_channel as usize];
+        let value = (0 .. 4).map(|i| gains[i] * self.frame[i]).sum::<f32>();
+        self.current_channel += 1;
+        Some(Sample::from_sample(&value))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let speakers = self.matrix.len();
+        let in_frame = speakers.saturating_sub(self.current_channel as usize);
+        let (min, max) = self.input.size_hint();
+        (
+            min / B_FORMAT_CHANNELS as usize * speakers + in_frame,
+            max.map(|max| max.div_ceil(B_FORMAT_CHANNELS as usize) * speakers + in_frame),
+        )
+    }
+}
+
+impl<I> Source for AmbisonicDecoder<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        let speakers = self.matrix.len();
+        let in_frame = speakers.saturating_sub(self.current_channel as usize);
+        if in_frame != 0 {
+            return Some(in_frame);
+        }
+
+        let channels = self.input.channels() as usize;
+        self.input
+            .current_frame_len()
+            .map(|len| len.div_ceil(channels) * speakers)
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        self.matrix.len() as u16
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        self.input.sample_rate()
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        self.input.total_duration()
+    }
+
+    #[inline]
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        self.input.try_seek(pos)?;
+        self.current_channel = self.matrix.len() as u16;
+        Ok(())
+    }
+}
+
+/// Decodes a first-order ambisonic sound field for headphones, with head-related impulse
+/// responses.
+///
+/// The input is converted to the sample rate of the impulse responses. The output has two
+/// channels.
+#[derive(Clone)]
+pub struct AmbisonicBinaural<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    input: UniformSourceIterator<I, f32>,
+    sample_rate: u32,
+    // Left and right filters of each B-format channel.
+    filters: Vec<[Vec<f32>; 2]>,
+    // The last samples of each channel, written twice so that they can always be read as one
+    // slice.
+    history: Vec<Vec<f32>>,
+    history_pos: usize,
+    // Number of frames to play after the input ends, so that the responses aren't cut.
+    remaining_tail: usize,
+    // Sample of the right channel to return next.
+    right: Option<f32>,
+}
+
+impl<I> Iterator for AmbisonicBinaural<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    type Item = f32;
+
+    #[inline]
+    fn next(&mut self) -> Option<f32> {
+        if let Some(right) = self.right.take() {
+            return Some(right);
+        }
+
+        let mut frame = [0.0; 4];
+        if !read_frame(&mut self.input, &mut frame) {
+            if self.remaining_tail <= 1 {
+                return None;
+            }
+            self.remaining_tail -= 1;
+        }
+
+        let len = self.filters[0][0].len();
+        self.history_pos = (self.history_pos + 1) % len;
+        let mut left = 0.0;
+        let mut right = 0.0;
+        for ((history, filters), &value) in
+            self.history.iter_mut().zip(self.filters.iter()).zip(frame.iter())
+        {
+            history[self.history_pos] = value;
+            history[self.history_pos + len] = value;
+
+            let history = &history[self.history_pos + 1 .. self.history_pos + 1 + len];
+            for (k, &value) in history.iter().rev().enumerate() {
+                left += value * filters[0][k];
+                right += value * filters[1][k];
+            }
+        }
+
+        self.right = Some(right);
+        Some(left)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let (min, max) = self.input.size_hint();
+        let pending = self.right.is_some() as usize;
+        let tail = self.remaining_tail.saturating_sub(1);
+        (
+            min / 2 + pending,
+            max.map(|max| (max / B_FORMAT_CHANNELS as usize + tail) * 2 + pending),
+        )
+    }
+}
+
+impl<I> Source for AmbisonicBinaural<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        None
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        2
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        self.input.total_duration()
+    }
+
+    #[inline]
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        self.input.try_seek(pos)?;
+        for history in &mut self.history {
+            for value in history.iter_mut() {
+                *value = 0.0;
+            }
+        }
+        self.remaining_tail = self.filters[0][0].len();
+        self.right = None;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use buffer::SamplesBuffer;
+    use source::{HrirSet, Source};
+
+    fn assert_close(actual: Vec<f32>, expected: &[f32]) {
+        assert_eq!(actual.len(), expected.len());
+        for (a, e) in actual.iter().zip(expected) {
+            assert!((a - e).abs() < 1e-5, "{:?} != {:?}", actual, expected);
+        }
+    }
+
+    #[test]
+    fn encode_left() {
+        let source = SamplesBuffer::new(1, 44100, vec![1.0f32, 0.5]);
+        let source = source.ambisonic_encode(90.0, 0.0);
+        assert_eq!(source.channels(), 4);
+        assert_close(source.collect(), &[1.0, 1.0, 0.0, 0.0, 0.5, 0.5, 0.0, 0.0]);
+    }
+
+    #[test]
+    fn rotate_towards_source() {
+        let source = SamplesBuffer::new(1, 44100, vec![1.0f32]);
+        // The listener turns to the left, where the sound is, which puts it in front.
+        let source = source.ambisonic_encode(90.0, 0.0).ambisonic_rotate(90.0, 0.0, 0.0);
+        assert_close(source.collect(), &[1.0, 0.0, 0.0, 1.0]);
+    }
+
+    #[test]
+    fn rotate_pitch() {
+        let source = SamplesBuffer::new(1, 44100, vec![1.0f32]);
+        // The listener looks up, which puts a sound in front below them.
+        let source = source.ambisonic_encode(0.0, 0.0).ambisonic_rotate(0.0, 90.0, 0.0);
+        assert_close(source.collect(), &[1.0, 0.0, -1.0, 0.0]);
+    }
+
+    #[test]
+    fn decode_stereo() {
+        let source = SamplesBuffer::new(1, 44100, vec![1.0f32]);
+        let source = source
+            .ambisonic_encode(90.0, 0.0)
+            .ambisonic_decode(&[(90.0, 0.0), (-90.0, 0.0)]);
+        assert_eq!(source.channels(), 2);
+        assert_close(source.collect(), &[1.0, 0.0]);
+    }
+
+    #[test]
+    fn binaural_matches_responses() {
+        let mut hrirs = HrirSet::new(44100);
+        hrirs.add(0.0, 0.0, vec![1.0], vec![1.0]);
+        let source = SamplesBuffer::new(1, 44100, vec![1.0f32]);
+        let source = source.ambisonic_encode(30.0, 0.0).ambisonic_binaural(&hrirs);
+        assert_eq!(source.channels(), 2);
+        // A single response is used for every direction, so the field is only its W channel.
+        assert_close(source.collect(), &[1.0, 1.0]);
+    }
+}
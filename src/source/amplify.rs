@@ -1,8 +1,19 @@
 use std::time::Duration;
 
+use conversions::{amplitude_to_db, db_to_amplitude};
+use source::SeekError;
 use Sample;
 use Source;
 
+/// Internal function that builds a `Amplify` object from a gain in decibels.
+pub fn amplify_db<I>(input: I, decibels: f32) -> Amplify<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    amplify(input, db_to_amplitude(decibels))
+}
+
 /// Internal function that builds a `Amplify` object.
 pub fn amplify<I>(input: I, factor: f32) -> Amplify<I>
 where
@@ -29,6 +40,28 @@ impl<I> Amplify<I> {
         self.factor = factor;
     }
 
+    /// Returns the amplification factor.
+    #[inline]
+    pub fn factor(&self) -> f32 {
+        self.factor
+    }
+
+    /// Modifies the amplification, in decibels.
+    ///
+    /// `0.0` leaves the sound untouched, and every `-6.0` approximately halves the amplitude.
+    #[inline]
+    pub fn set_decibels(&mut self, decibels: f32) {
+        self.factor = db_to_amplitude(decibels);
+    }
+
+    /// Returns the amplification, in decibels.
+    ///
+    /// A factor of `0.0` is negative infinity.
+    #[inline]
+    pub fn decibels(&self) -> f32 {
+        amplitude_to_db(self.factor)
+    }
+
     /// Returns a reference to the inner source.
     #[inline]
     pub fn inner(&self) -> &I {
@@ -97,4 +130,26 @@ where
     fn total_duration(&self) -> Option<Duration> {
         self.input.total_duration()
     }
+
+    #[inline]
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        self.input.try_seek(pos)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use buffer::SamplesBuffer;
+    use source::Source;
+
+    #[test]
+    fn decibels() {
+        let mut source = SamplesBuffer::new(1, 44100, vec![1.0f32; 4]).amplify_db(-20.0);
+        assert!((source.factor() - 0.1).abs() < 1e-6);
+        assert!((source.next().unwrap() - 0.1).abs() < 1e-6);
+
+        source.set_decibels(20.0);
+        assert!((source.factor() - 10.0).abs() < 1e-5);
+        assert!((source.decibels() - 20.0).abs() < 1e-5);
+    }
 }
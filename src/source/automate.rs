@@ -0,0 +1,230 @@
+use std::time::Duration;
+
+use conversions::duration_to_ns;
+use source::{FramePosition, SeekError};
+use Sample;
+use Source;
+
+/// Internal function that builds an `Automate` object.
+pub fn automate<I, F>(
+    input: I, param: F, mut keyframes: Vec<(Duration, f32)>, interpolation: Interpolation,
+) -> Automate<I, F>
+where
+    I: Source,
+    I::Item: Sample,
+    F: FnMut(&mut I, f32),
+{
+    keyframes.sort_by_key(|&(time, _)| time);
+    let position = FramePosition::new(input.sample_rate());
+
+    Automate {
+        input,
+        param,
+        keyframes,
+        interpolation,
+        next_keyframe: 0,
+        done: false,
+        position,
+    }
+}
+
+/// How the value of a parameter is computed between two keyframes.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Interpolation {
+    /// The value of the previous keyframe is kept until the next one.
+    Step,
+    /// The value changes linearly.
+    Linear,
+    /// The value changes by a constant ratio per second, which sounds linear for volumes and
+    /// frequencies. Falls back to `Linear` if one of the values isn't strictly positive.
+    Exponential,
+}
+
+/// Drives a parameter of a source along a timeline of keyframes.
+///
+/// At the start of every frame of the inner source, the closure is called with the inner source
+/// and with the value of the timeline at the position of that frame. The first value is used
+/// before the first keyframe, and the closure isn't called anymore once the last keyframe has been
+/// reached.
+#[derive(Clone, Debug)]
+pub struct Automate<I, F> {
+    input: I,
+    param: F,
+    // Sorted by time.
+    keyframes: Vec<(Duration, f32)>,
+    interpolation: Interpolation,
+    // Index of the first keyframe after the current position.
+    next_keyframe: usize,
+    // True once the value of the last keyframe has been passed to `param`.
+    done: bool,
+    position: FramePosition,
+}
+
+impl<I, F> Automate<I, F>
+where
+    I: Source,
+    I::Item: Sample,
+    F: FnMut(&mut I, f32),
+{
+    /// Returns a reference to the inner source.
+    #[inline]
+    pub fn inner(&self) -> &I {
+        &self.input
+    }
+
+    /// Returns a mutable reference to the inner source.
+    #[inline]
+    pub fn inner_mut(&mut self) -> &mut I {
+        &mut self.input
+    }
+
+    /// Returns the inner source.
+    #[inline]
+    pub fn into_inner(self) -> I {
+        self.input
+    }
+
+    // Called at the start of each frame.
+    fn next_frame(&mut self) {
+        let position_ns = self.position.next_frame(self.input.sample_rate());
+
+        if self.done || self.keyframes.is_empty() {
+            return;
+        }
+
+        let position = Duration::from_nanos(position_ns);
+        while self.next_keyframe < self.keyframes.len()
+            && self.keyframes[self.next_keyframe].0 <= position
+        {
+            self.next_keyframe += 1;
+        }
+
+        let value = if self.next_keyframe == 0 {
+            self.keyframes[0].1
+        } else if self.next_keyframe == self.keyframes.len() {
+            self.done = true;
+            self.keyframes[self.keyframes.len() - 1].1
+        } else {
+            let (start, from) = self.keyframes[self.next_keyframe - 1];
+            let (end, to) = self.keyframes[self.next_keyframe];
+            let start_ns = duration_to_ns(start);
+            let end_ns = duration_to_ns(end);
+            let progress = (position_ns - start_ns) as f32 / (end_ns - start_ns) as f32;
+            self.interpolation.apply(from, to, progress)
+        };
+
+        (self.param)(&mut self.input, value);
+    }
+}
+
+impl Interpolation {
+    // Returns the value at `progress`, between `0.0` at `from` and `1.0` at `to`.
+    #[inline]
+    fn apply(self, from: f32, to: f32, progress: f32) -> f32 {
+        match self {
+            Interpolation::Step => from,
+            Interpolation::Exponential if from > 0.0 && to > 0.0 => {
+                from * (to / from).powf(progress)
+            },
+            Interpolation::Linear | Interpolation::Exponential => from + (to - from) * progress,
+        }
+    }
+}
+
+impl<I, F> Iterator for Automate<I, F>
+where
+    I: Source,
+    I::Item: Sample,
+    F: FnMut(&mut I, f32),
+{
+    type Item = I::Item;
+
+    #[inline]
+    fn next(&mut self) -> Option<I::Item> {
+        if self.position.is_frame_start(self.input.channels()) {
+            self.next_frame();
+        }
+
+        self.input.next()
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.input.size_hint()
+    }
+}
+
+impl<I, F> ExactSizeIterator for Automate<I, F>
+where
+    I: Source + ExactSizeIterator,
+    I::Item: Sample,
+    F: FnMut(&mut I, f32),
+{
+}
+
+impl<I, F> Source for Automate<I, F>
+where
+    I: Source,
+    I::Item: Sample,
+    F: FnMut(&mut I, f32),
+{
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        self.input.current_frame_len()
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        self.input.channels()
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        self.input.sample_rate()
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        self.input.total_duration()
+    }
+
+    #[inline]
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        self.input.try_seek(pos)?;
+        self.position.set_position_ns(duration_to_ns(pos), self.input.sample_rate());
+        self.next_keyframe = 0;
+        self.done = false;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use buffer::SamplesBuffer;
+    use source::{Interpolation, Source};
+
+    #[test]
+    fn linear_volume_ride() {
+        let keyframes = vec![
+            (Duration::from_secs(3), 0.0),
+            (Duration::from_secs(1), 1.0),
+            (Duration::from_secs(2), 0.5),
+        ];
+        let source = SamplesBuffer::new(1, 2, vec![1.0f32; 8])
+            .amplify(1.0)
+            .automate(|src, value| src.set_factor(value), keyframes, Interpolation::Linear);
+        assert_eq!(
+            source.collect::<Vec<_>>(),
+            [1.0, 1.0, 1.0, 0.75, 0.5, 0.25, 0.0, 0.0]
+        );
+    }
+
+    #[test]
+    fn exponential() {
+        assert_eq!(Interpolation::Exponential.apply(100.0, 400.0, 0.5), 200.0);
+        assert_eq!(Interpolation::Exponential.apply(0.0, 1.0, 0.5), 0.5);
+        assert_eq!(Interpolation::Step.apply(1.0, 2.0, 0.9), 1.0);
+    }
+}
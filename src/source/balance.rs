@@ -0,0 +1,206 @@
+use std::time::Duration;
+
+use source::SeekError;
+use Sample;
+use Source;
+
+/// Duration over which a change of balance is spread, in milliseconds.
+const SMOOTHING_MS: f32 = 20.0;
+
+/// Internal function that builds a `Balance` object.
+pub fn balance<I>(input: I, value: f32) -> Balance<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    let value = clamp_balance(value);
+
+    Balance {
+        input,
+        current: value,
+        target: value,
+        current_channel: 0,
+    }
+}
+
+/// Attenuates the left or the right channel of a source relative to the other.
+///
+/// A balance of `-1.0` only keeps the left channel, `0.0` leaves the sound untouched and `1.0`
+/// only keeps the right channel. Unlike panning, the channels are never mixed together. Channels
+/// other than the first two are left untouched, so mono sources are not affected.
+#[derive(Clone, Debug)]
+pub struct Balance<I> {
+    input: I,
+    // Balance currently applied, moving towards `target`.
+    current: f32,
+    target: f32,
+    // Channel of the next sample returned by `next`.
+    current_channel: u16,
+}
+
+#[inline]
+fn clamp_balance(value: f32) -> f32 {
+    value.clamp(-1.0, 1.0)
+}
+
+impl<I> Balance<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    /// Returns the balance that the filter is applying or moving towards.
+    #[inline]
+    pub fn balance(&self) -> f32 {
+        self.target
+    }
+
+    /// Modifies the balance. The value is clamped between `-1.0` and `1.0`.
+    ///
+    /// The change isn't applied at once but spread over a few milliseconds, in order to avoid
+    /// audible clicks.
+    #[inline]
+    pub fn set_balance(&mut self, value: f32) {
+        self.target = clamp_balance(value);
+    }
+
+    /// Returns a reference to the inner source.
+    #[inline]
+    pub fn inner(&self) -> &I {
+        &self.input
+    }
+
+    /// Returns a mutable reference to the inner source.
+    #[inline]
+    pub fn inner_mut(&mut self) -> &mut I {
+        &mut self.input
+    }
+
+    /// Returns the inner source.
+    #[inline]
+    pub fn into_inner(self) -> I {
+        self.input
+    }
+
+    // Moves `current` one frame closer to `target`.
+    #[inline]
+    fn step_towards_target(&mut self) {
+        if self.current == self.target {
+            return;
+        }
+
+        let max_step = 1000.0 / (SMOOTHING_MS * self.input.sample_rate() as f32);
+        let diff = self.target - self.current;
+        if diff.abs() <= max_step {
+            self.current = self.target;
+        } else {
+            self.current += max_step.copysign(diff);
+        }
+    }
+}
+
+impl<I> Iterator for Balance<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    type Item = I::Item;
+
+    #[inline]
+    fn next(&mut self) -> Option<I::Item> {
+        if self.current_channel >= self.input.channels() {
+            self.current_channel = 0;
+        }
+        if self.current_channel == 0 {
+            self.step_towards_target();
+        }
+
+        let factor = match self.current_channel {
+            0 if self.input.channels() >= 2 => 1.0 - self.current.max(0.0),
+            1 => 1.0 + self.current.min(0.0),
+            _ => 1.0,
+        };
+        self.current_channel += 1;
+
+        self.input.next().map(|value| value.amplify(factor))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.input.size_hint()
+    }
+}
+
+impl<I> ExactSizeIterator for Balance<I>
+where
+    I: Source + ExactSizeIterator,
+    I::Item: Sample,
+{
+}
+
+impl<I> Source for Balance<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        self.input.current_frame_len()
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        self.input.channels()
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        self.input.sample_rate()
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        self.input.total_duration()
+    }
+
+    #[inline]
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        self.input.try_seek(pos)?;
+        self.current_channel = 0;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use buffer::SamplesBuffer;
+    use source::Source;
+
+    #[test]
+    fn attenuates_one_side() {
+        let input = SamplesBuffer::new(2, 44100, vec![1.0f32, 1.0, 1.0, 1.0]);
+        assert_eq!(input.balance(0.5).collect::<Vec<_>>(), [0.5, 1.0, 0.5, 1.0]);
+
+        let input = SamplesBuffer::new(2, 44100, vec![1.0f32, 1.0]);
+        assert_eq!(input.balance(-1.0).collect::<Vec<_>>(), [1.0, 0.0]);
+    }
+
+    #[test]
+    fn mono_is_untouched() {
+        let input = SamplesBuffer::new(1, 44100, vec![1.0f32, 1.0]);
+        assert_eq!(input.balance(1.0).collect::<Vec<_>>(), [1.0, 1.0]);
+    }
+
+    #[test]
+    fn changes_are_smoothed() {
+        // At 1kHz, a full change of balance takes 20 frames.
+        let input = SamplesBuffer::new(2, 1000, vec![1.0f32; 100]);
+        let mut source = input.balance(0.0);
+        source.set_balance(1.0);
+
+        let left = source.step_by(2).collect::<Vec<_>>();
+        assert!((left[0] - 0.95).abs() < 1e-4);
+        assert!((left[9] - 0.5).abs() < 1e-4);
+        assert_eq!(left[19], 0.0);
+        assert_eq!(left[49], 0.0);
+    }
+}
@@ -0,0 +1,70 @@
+use std::f32::consts::PI;
+
+/// Quality factor of the Butterworth filters. Two of them in a row make a Linkwitz-Riley filter.
+const Q: f32 = 0.707;
+
+/// Second-order filter, with coefficients normalized by `a0`.
+#[derive(Clone, Copy, Debug, Default)]
+pub(crate) struct Biquad {
+    b0: f32,
+    b1: f32,
+    b2: f32,
+    a1: f32,
+    a2: f32,
+}
+
+impl Biquad {
+    /// Builds a filter from coefficients that are already normalized by `a0`.
+    pub(crate) fn new(b: [f32; 3], a: [f32; 2]) -> Biquad {
+        Biquad {
+            b0: b[0],
+            b1: b[1],
+            b2: b[2],
+            a1: a[0],
+            a2: a[1],
+        }
+    }
+
+    pub(crate) fn high_pass(freq: f32, sample_rate: u32) -> Biquad {
+        let w0 = 2.0 * PI * freq / sample_rate as f32;
+        let alpha = w0.sin() / (2.0 * Q);
+        let b1 = -(1.0 + w0.cos());
+        Biquad::normalized(-b1 / 2.0, b1, -b1 / 2.0, alpha, w0)
+    }
+
+    pub(crate) fn low_pass(freq: f32, sample_rate: u32) -> Biquad {
+        let w0 = 2.0 * PI * freq / sample_rate as f32;
+        let alpha = w0.sin() / (2.0 * Q);
+        let b1 = 1.0 - w0.cos();
+        Biquad::normalized(b1 / 2.0, b1, b1 / 2.0, alpha, w0)
+    }
+
+    /// Filter that changes the phase like a Linkwitz-Riley crossover at `freq`, without changing
+    /// the level of any frequency.
+    pub(crate) fn all_pass(freq: f32, sample_rate: u32) -> Biquad {
+        let w0 = 2.0 * PI * freq / sample_rate as f32;
+        let alpha = w0.sin() / (2.0 * Q);
+        Biquad::normalized(1.0 - alpha, -2.0 * w0.cos(), 1.0 + alpha, alpha, w0)
+    }
+
+    fn normalized(b0: f32, b1: f32, b2: f32, alpha: f32, w0: f32) -> Biquad {
+        let a0 = 1.0 + alpha;
+        Biquad {
+            b0: b0 / a0,
+            b1: b1 / a0,
+            b2: b2 / a0,
+            a1: -2.0 * w0.cos() / a0,
+            a2: (1.0 - alpha) / a0,
+        }
+    }
+
+    /// Filters a sample. `state` holds the last two inputs and the last two outputs.
+    #[inline]
+    pub(crate) fn apply(&self, state: &mut [f32; 4], x: f32) -> f32 {
+        let y = self.b0 * x + self.b1 * state[0] + self.b2 * state[1]
+            - self.a1 * state[2]
+            - self.a2 * state[3];
+        *state = [x, state[0], y, state[2]];
+        y
+    }
+}
@@ -0,0 +1,211 @@
+use std::cmp;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use source::uniform::UniformSourceIterator;
+
+use source::SeekError;
+use Sample;
+use Source;
+
+/// Internal function that builds a `Blend` object.
+pub fn blend<I1, I2>(input1: I1, input2: I2, ratio: f32) -> Blend<I1, I2>
+where
+    I1: Source,
+    I1::Item: Sample,
+    I2: Source,
+    I2::Item: Sample,
+{
+    let channels = input1.channels();
+    let rate = input1.sample_rate();
+
+    Blend {
+        input1: UniformSourceIterator::new(input1, channels, rate),
+        input2: UniformSourceIterator::new(input2, channels, rate),
+        ratio: Arc::new(AtomicU32::new(ratio.clamp(0.0, 1.0).to_bits())),
+    }
+}
+
+/// Filter that mixes two sources with a ratio that can be changed while playing.
+///
+/// A ratio of `0.0` only plays the first source, and a ratio of `1.0` only plays the second one.
+#[derive(Clone)]
+pub struct Blend<I1, I2>
+where
+    I1: Source,
+    I1::Item: Sample,
+    I2: Source,
+    I2::Item: Sample,
+{
+    input1: UniformSourceIterator<I1, I1::Item>,
+    input2: UniformSourceIterator<I2, I1::Item>,
+    ratio: Arc<AtomicU32>,
+}
+
+/// Handle that changes the ratio of a `Blend` from another thread.
+#[derive(Clone, Debug)]
+pub struct BlendHandle {
+    ratio: Arc<AtomicU32>,
+}
+
+impl BlendHandle {
+    /// Changes the ratio of the blend. The value is clamped between `0.0` and `1.0`.
+    #[inline]
+    pub fn set_ratio(&self, ratio: f32) {
+        self.ratio
+            .store(ratio.clamp(0.0, 1.0).to_bits(), Ordering::Relaxed);
+    }
+
+    /// Returns the ratio of the blend.
+    #[inline]
+    pub fn ratio(&self) -> f32 {
+        f32::from_bits(self.ratio.load(Ordering::Relaxed))
+    }
+}
+
+impl<I1, I2> Blend<I1, I2>
+where
+    I1: Source,
+    I1::Item: Sample,
+    I2: Source,
+    I2::Item: Sample,
+{
+    /// Changes the ratio of the blend. The value is clamped between `0.0` and `1.0`.
+    #[inline]
+    pub fn set_ratio(&mut self, ratio: f32) {
+        self.ratio
+            .store(ratio.clamp(0.0, 1.0).to_bits(), Ordering::Relaxed);
+    }
+
+    /// Returns the ratio of the blend.
+    #[inline]
+    pub fn ratio(&self) -> f32 {
+        f32::from_bits(self.ratio.load(Ordering::Relaxed))
+    }
+
+    /// Returns a handle that can change the ratio after the source has been handed to a sink.
+    #[inline]
+    pub fn handle(&self) -> BlendHandle {
+        BlendHandle {
+            ratio: self.ratio.clone(),
+        }
+    }
+}
+
+impl<I1, I2> Iterator for Blend<I1, I2>
+where
+    I1: Source,
+    I1::Item: Sample,
+    I2: Source,
+    I2::Item: Sample,
+{
+    type Item = I1::Item;
+
+    #[inline]
+    fn next(&mut self) -> Option<I1::Item> {
+        let s1 = self.input1.next();
+        let s2 = self.input2.next();
+        let ratio = f32::from_bits(self.ratio.load(Ordering::Relaxed));
+
+        match (s1, s2) {
+            (Some(s1), Some(s2)) => {
+                Some(s1.amplify(1.0 - ratio).saturating_add(s2.amplify(ratio)))
+            },
+            (Some(s1), None) => Some(s1.amplify(1.0 - ratio)),
+            (None, Some(s2)) => Some(s2.amplify(ratio)),
+            (None, None) => None,
+        }
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let s1 = self.input1.size_hint();
+        let s2 = self.input2.size_hint();
+
+        let min = cmp::max(s1.0, s2.0);
+        let max = match (s1.1, s2.1) {
+            (Some(s1), Some(s2)) => Some(cmp::max(s1, s2)),
+            _ => None,
+        };
+
+        (min, max)
+    }
+}
+
+impl<I1, I2> ExactSizeIterator for Blend<I1, I2>
+where
+    I1: Source + ExactSizeIterator,
+    I1::Item: Sample,
+    I2: Source + ExactSizeIterator,
+    I2::Item: Sample,
+{
+}
+
+impl<I1, I2> Source for Blend<I1, I2>
+where
+    I1: Source,
+    I1::Item: Sample,
+    I2: Source,
+    I2::Item: Sample,
+{
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        let f1 = self.input1.current_frame_len();
+        let f2 = self.input2.current_frame_len();
+
+        match (f1, f2) {
+            (Some(f1), Some(f2)) => Some(cmp::min(f1, f2)),
+            _ => None,
+        }
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        self.input1.channels()
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        self.input1.sample_rate()
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        let f1 = self.input1.total_duration();
+        let f2 = self.input2.total_duration();
+
+        match (f1, f2) {
+            (Some(f1), Some(f2)) => Some(cmp::max(f1, f2)),
+            _ => None,
+        }
+    }
+
+    #[inline]
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        self.input1.try_seek(pos)?;
+        self.input2.try_seek(pos)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use buffer::SamplesBuffer;
+    use source::Source;
+
+    #[test]
+    fn change_ratio() {
+        let a = SamplesBuffer::new(1, 44100, vec![1.0f32; 4]);
+        let b = SamplesBuffer::new(1, 44100, vec![-1.0f32; 4]);
+        let mut source = a.blend(b, 0.0);
+        let handle = source.handle();
+
+        assert_eq!(source.next(), Some(1.0));
+        handle.set_ratio(1.0);
+        assert_eq!(source.next(), Some(-1.0));
+        handle.set_ratio(0.25);
+        assert_eq!(source.next(), Some(0.5));
+        handle.set_ratio(2.0);
+        assert_eq!(handle.ratio(), 1.0);
+    }
+}
@@ -1,10 +1,16 @@
 use std::f32::consts::PI;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Arc;
 use std::time::Duration;
 
+use source::SeekError;
 use Source;
 
 // Implemented following http://www.musicdsp.org/files/Audio-EQ-Cookbook.txt
 
+/// Time constant of the smoothing applied when the parameters are changed through a handle.
+const SMOOTHING_SECS: f32 = 0.01;
+
 /// Internal function that builds a `BltFilter` object.
 pub fn low_pass<I>(input: I, freq: u32) -> BltFilter<I>
 where
@@ -12,8 +18,12 @@ where
 {
     BltFilter {
         input: input,
-        formula: BltFormula::LowPass { freq: freq, q: 0.5 },
+        formula: BltFormula::LowPass { freq: freq as f32, q: 0.5 },
         applier: None,
+        control: Arc::new(FilterControl {
+            freq: AtomicU32::new((freq as f32).to_bits()),
+            q: AtomicU32::new(0.5f32.to_bits()),
+        }),
         x_n1: 0.0,
         x_n2: 0.0,
         y_n1: 0.0,
@@ -26,19 +36,74 @@ pub struct BltFilter<I> {
     input: I,
     formula: BltFormula,
     applier: Option<BltApplier>,
+    // Parameters that `formula` moves towards.
+    control: Arc<FilterControl>,
     x_n1: f32,
     x_n2: f32,
     y_n1: f32,
     y_n2: f32,
 }
 
+#[derive(Debug)]
+struct FilterControl {
+    // Stored as the bits of `f32`s.
+    freq: AtomicU32,
+    q: AtomicU32,
+}
+
+/// Handle that changes the parameters of a `BltFilter` from another thread.
+///
+/// The filter moves smoothly towards the new parameters over about 10ms, so that they can be
+/// swept without clicks.
+#[derive(Clone, Debug)]
+pub struct FilterHandle {
+    control: Arc<FilterControl>,
+}
+
+impl FilterHandle {
+    /// Modifies the cutoff frequency of the filter, in Hz.
+    #[inline]
+    pub fn set_cutoff(&self, freq: f32) {
+        self.control.freq.store(freq.to_bits(), Ordering::Relaxed);
+    }
+
+    /// Returns the cutoff frequency that the filter is moving towards.
+    #[inline]
+    pub fn cutoff(&self) -> f32 {
+        f32::from_bits(self.control.freq.load(Ordering::Relaxed))
+    }
+
+    /// Modifies the Q factor of the filter.
+    #[inline]
+    pub fn set_q(&self, q: f32) {
+        self.control.q.store(q.to_bits(), Ordering::Relaxed);
+    }
+
+    /// Returns the Q factor that the filter is moving towards.
+    #[inline]
+    pub fn q(&self) -> f32 {
+        f32::from_bits(self.control.q.load(Ordering::Relaxed))
+    }
+}
+
 impl<I> BltFilter<I> {
     /// Modifies this filter so that it becomes a low-pass filter.
     pub fn to_low_pass(&mut self, freq: u32) {
-        self.formula = BltFormula::LowPass { freq: freq, q: 0.5 };
+        self.formula = BltFormula::LowPass { freq: freq as f32, q: 0.5 };
+        self.control.freq.store((freq as f32).to_bits(), Ordering::Relaxed);
+        self.control.q.store(0.5f32.to_bits(), Ordering::Relaxed);
         self.applier = None;
     }
 
+    /// Returns a handle that can change the cutoff frequency and the Q factor of the filter
+    /// while it is playing.
+    #[inline]
+    pub fn handle(&self) -> FilterHandle {
+        FilterHandle {
+            control: self.control.clone(),
+        }
+    }
+
     /// Returns a reference to the inner source.
     #[inline]
     pub fn inner(&self) -> &I {
@@ -68,6 +133,20 @@ where
     fn next(&mut self) -> Option<f32> {
         let last_in_frame = self.input.current_frame_len() == Some(1);
 
+        let target_freq = f32::from_bits(self.control.freq.load(Ordering::Relaxed));
+        let target_q = f32::from_bits(self.control.q.load(Ordering::Relaxed));
+        match self.formula {
+            BltFormula::LowPass { ref mut freq, ref mut q } => {
+                if *freq != target_freq || *q != target_q {
+                    let rate = self.input.sample_rate() as f32;
+                    let smoothing = 1.0 - (-1.0 / (SMOOTHING_SECS * rate)).exp();
+                    *freq = smooth(*freq, target_freq, smoothing, 0.01);
+                    *q = smooth(*q, target_q, smoothing, 0.0001);
+                    self.applier = None;
+                }
+            },
+        }
+
         if self.applier.is_none() {
             self.applier = Some(self.formula.to_applier(self.input.sample_rate()));
         }
@@ -129,18 +208,34 @@ where
     fn total_duration(&self) -> Option<Duration> {
         self.input.total_duration()
     }
+
+    #[inline]
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        self.input.try_seek(pos)
+    }
+}
+
+// Moves `current` towards `target`, and snaps to it once they are closer than `epsilon`.
+#[inline]
+fn smooth(current: f32, target: f32, smoothing: f32, epsilon: f32) -> f32 {
+    let value = current + (target - current) * smoothing;
+    if (target - value).abs() < epsilon {
+        target
+    } else {
+        value
+    }
 }
 
 #[derive(Clone, Debug)]
 enum BltFormula {
-    LowPass { freq: u32, q: f32 },
+    LowPass { freq: f32, q: f32 },
 }
 
 impl BltFormula {
     fn to_applier(&self, sampling_frequency: u32) -> BltApplier {
         match self {
             &BltFormula::LowPass { freq, q } => {
-                let w0 = 2.0 * PI * freq as f32 / sampling_frequency as f32;
+                let w0 = 2.0 * PI * freq / sampling_frequency as f32;
 
                 let alpha = w0.sin() / (2.0 * q);
                 let b1 = 1.0 - w0.cos();
@@ -177,3 +272,27 @@ impl BltApplier {
         self.b0 * x_n + self.b1 * x_n1 + self.b2 * x_n2 - self.a1 * y_n1 - self.a2 * y_n2
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use source::{SineWave, Source};
+
+    // Returns the highest absolute value among the next `len` samples.
+    fn peak<I: Iterator<Item = f32>>(source: &mut I, len: usize) -> f32 {
+        source.take(len).fold(0.0, |peak, s| peak.max(s.abs()))
+    }
+
+    #[test]
+    fn cutoff_sweep() {
+        let mut source = SineWave::new(4000).low_pass(200);
+        let handle = source.handle();
+
+        assert!(peak(&mut source, 4800) < 0.05);
+        handle.set_cutoff(16000.0);
+        assert_eq!(handle.cutoff(), 16000.0);
+        // The cutoff moves progressively.
+        assert!(peak(&mut source, 10) < 0.2);
+        source.by_ref().take(4800).count();
+        assert!(peak(&mut source, 4800) > 0.9);
+    }
+}
@@ -0,0 +1,87 @@
+use std::time::Duration;
+
+use source::WhiteNoise;
+use Source;
+
+/// An infinite source that produces brown noise, whose power decreases by 6dB per octave.
+///
+/// This is integrated white noise. The integrator is slightly leaky so that the signal doesn't
+/// drift away from zero, and the output is kept between `-1.0` and `1.0`.
+///
+/// Always has one channel.
+#[derive(Clone, Debug)]
+pub struct BrownNoise {
+    white: WhiteNoise,
+    // State of the integrator.
+    last: f32,
+}
+
+impl BrownNoise {
+    /// Builds a new brown noise generator with a random seed.
+    #[inline]
+    pub fn new(sample_rate: u32) -> BrownNoise {
+        BrownNoise::from_white_noise(WhiteNoise::new(sample_rate))
+    }
+
+    /// Builds a new brown noise generator that always produces the same samples for a given
+    /// seed.
+    #[inline]
+    pub fn with_seed(sample_rate: u32, seed: u64) -> BrownNoise {
+        BrownNoise::from_white_noise(WhiteNoise::with_seed(sample_rate, seed))
+    }
+
+    #[inline]
+    fn from_white_noise(white: WhiteNoise) -> BrownNoise {
+        BrownNoise { white, last: 0.0 }
+    }
+}
+
+impl Iterator for BrownNoise {
+    type Item = f32;
+
+    #[inline]
+    fn next(&mut self) -> Option<f32> {
+        let white = self.white.next()?;
+
+        // Dividing by slightly more than one makes the integrator leak towards zero.
+        self.last = (self.last + 0.02 * white) / 1.02;
+
+        Some((self.last * 3.5).clamp(-1.0, 1.0))
+    }
+}
+
+impl Source for BrownNoise {
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        None
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        1
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        self.white.sample_rate()
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use source::BrownNoise;
+
+    #[test]
+    fn no_dc_drift() {
+        let samples = BrownNoise::with_seed(48000, 7).take(480000).collect::<Vec<_>>();
+
+        assert!(samples.iter().all(|s| s.abs() <= 1.0));
+        let mean = samples.iter().sum::<f32>() / samples.len() as f32;
+        assert!(mean.abs() < 0.05);
+    }
+}
@@ -2,6 +2,7 @@ use std::cmp;
 use std::sync::{Arc, Mutex};
 use std::time::Duration;
 
+use source::shared_buffered::{self, SharedBuffered};
 use Sample;
 use Source;
 
@@ -102,6 +103,15 @@ where
     I: Source,
     I::Item: Sample,
 {
+    /// Reads the rest of the source and turns it into a `SharedBuffered`, whose clones are
+    /// cheaper to create and to play.
+    ///
+    /// This is useful for sounds that are played many times, such as sound effects in a game.
+    #[inline]
+    pub fn into_shared(self) -> SharedBuffered<I::Item> {
+        shared_buffered::shared_buffered(self)
+    }
+
     /// Advances to the next frame.
     fn next_frame(&mut self) {
         let next_frame = {
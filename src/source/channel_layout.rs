@@ -0,0 +1,429 @@
+use std::f32::consts::FRAC_1_SQRT_2;
+use std::time::Duration;
+
+use source::SeekError;
+use Sample;
+use Source;
+
+/// A speaker of a channel layout.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Speaker {
+    /// Front left speaker.
+    FrontLeft,
+    /// Front right speaker.
+    FrontRight,
+    /// Front center speaker. The only speaker of a mono layout.
+    FrontCenter,
+    /// Subwoofer.
+    LowFrequency,
+    /// Back left speaker.
+    BackLeft,
+    /// Back right speaker.
+    BackRight,
+    /// Side left speaker.
+    SideLeft,
+    /// Side right speaker.
+    SideRight,
+}
+
+/// The speakers that the channels of a sound are meant for, in the order of the channels.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum ChannelLayout {
+    /// A single channel.
+    Mono,
+    /// Left and right.
+    Stereo,
+    /// Front left, front right, front center, subwoofer, back left and back right.
+    Surround51,
+    /// Front left, front right, front center, subwoofer, back left, back right, side left and
+    /// side right.
+    Surround71,
+}
+
+impl ChannelLayout {
+    /// Returns the usual layout of the given number of channels, if there is one.
+    #[inline]
+    pub fn from_channels(channels: u16) -> Option<ChannelLayout> {
+        match channels {
+            1 => Some(ChannelLayout::Mono),
+            2 => Some(ChannelLayout::Stereo),
+            6 => Some(ChannelLayout::Surround51),
+            8 => Some(ChannelLayout::Surround71),
+            _ => None,
+        }
+    }
+
+    /// Returns the number of channels of the layout.
+    #[inline]
+    pub fn channels(&self) -> u16 {
+        self.speakers().len() as u16
+    }
+
+    /// Returns the speakers of the layout, in the order of the channels.
+    pub fn speakers(&self) -> &'static [Speaker] {
+        use self::Speaker::*;
+
+        match *self {
+            ChannelLayout::Mono => &[FrontCenter],
+            ChannelLayout::Stereo => &[FrontLeft, FrontRight],
+            ChannelLayout::Surround51 => {
+                &[FrontLeft, FrontRight, FrontCenter, LowFrequency, BackLeft, BackRight]
+            },
+            ChannelLayout::Surround71 => &[
+                FrontLeft,
+                FrontRight,
+                FrontCenter,
+                LowFrequency,
+                BackLeft,
+                BackRight,
+                SideLeft,
+                SideRight,
+            ],
+        }
+    }
+
+    /// Returns the channel of a speaker, if the layout has it.
+    #[inline]
+    pub fn channel(&self, speaker: Speaker) -> Option<u16> {
+        self.speakers()
+            .iter()
+            .position(|&s| s == speaker)
+            .map(|index| index as u16)
+    }
+
+    // Returns the channels of this layout that a speaker of another layout is played on, with
+    // their gains.
+    fn route(&self, speaker: Speaker) -> Vec<(u16, f32)> {
+        use self::Speaker::*;
+
+        if let Some(channel) = self.channel(speaker) {
+            return vec![(channel, 1.0)];
+        }
+        match speaker {
+            FrontLeft | FrontRight => self.attenuated(FrontCenter),
+            FrontCenter => {
+                let mut routes = self.attenuated(FrontLeft);
+                routes.extend(self.attenuated(FrontRight));
+                routes
+            },
+            LowFrequency => Vec::new(),
+            BackLeft => self.substitute(SideLeft, FrontLeft),
+            BackRight => self.substitute(SideRight, FrontRight),
+            SideLeft => self.substitute(BackLeft, FrontLeft),
+            SideRight => self.substitute(BackRight, FrontRight),
+        }
+    }
+
+    // Returns the route to `substitute` if the layout has it, or else to `fallback` attenuated.
+    fn substitute(&self, substitute: Speaker, fallback: Speaker) -> Vec<(u16, f32)> {
+        match self.channel(substitute) {
+            Some(channel) => vec![(channel, 1.0)],
+            None => self.attenuated(fallback),
+        }
+    }
+
+    // Returns the route to a speaker at -3dB.
+    fn attenuated(&self, speaker: Speaker) -> Vec<(u16, f32)> {
+        self.route(speaker)
+            .into_iter()
+            .map(|(channel, gain)| (channel, gain * FRAC_1_SQRT_2))
+            .collect()
+    }
+}
+
+/// Internal function that builds a `ConvertLayout` object that converts between layouts.
+///
+/// # Panic
+///
+/// Panics if the input doesn't have the channels of `from`.
+pub fn convert_layout<I>(input: I, from: ChannelLayout, to: ChannelLayout) -> ConvertLayout<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    assert_eq!(input.channels(), from.channels());
+    ConvertLayout::new(input, Target::Layout(to))
+}
+
+/// Internal function that builds a `ConvertLayout` object that converts from the usual layout of
+/// the channels of the input, if it has one, to `to`.
+///
+/// The channels of an input without a usual layout are played on the channels with the same
+/// index, and dropped if the layout doesn't have them.
+pub(crate) fn convert_to_layout<I>(input: I, to: ChannelLayout) -> ConvertLayout<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    ConvertLayout::new(input, Target::Layout(to))
+}
+
+/// Internal function that builds a `ConvertLayout` object that plays on a single speaker.
+///
+/// # Panic
+///
+/// Panics if `layout` doesn't have `speaker`.
+pub fn to_speaker<I>(input: I, speaker: Speaker, layout: ChannelLayout) -> ConvertLayout<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    let channel = layout.channel(speaker).expect("the layout doesn't have the speaker");
+    ConvertLayout::new(input, Target::Speaker(channel, layout))
+}
+
+// What the channels of the input of a `ConvertLayout` are converted to.
+#[derive(Copy, Clone, Debug)]
+enum Target {
+    // All the speakers of a layout.
+    Layout(ChannelLayout),
+    // A single channel of a layout.
+    Speaker(u16, ChannelLayout),
+}
+
+impl Target {
+    #[inline]
+    fn layout(&self) -> ChannelLayout {
+        match *self {
+            Target::Layout(layout) | Target::Speaker(_, layout) => layout,
+        }
+    }
+
+    // Returns the gain of each input channel in each output channel, output channel by output
+    // channel.
+    fn matrix(&self, in_channels: usize) -> Vec<f32> {
+        let out_channels = self.layout().channels() as usize;
+        let mut matrix = vec![0.0; in_channels * out_channels];
+        match *self {
+            Target::Layout(to) => match ChannelLayout::from_channels(in_channels as u16) {
+                Some(from) => {
+                    for (input_channel, &speaker) in from.speakers().iter().enumerate() {
+                        for (output_channel, gain) in to.route(speaker) {
+                            matrix[output_channel as usize * in_channels + input_channel] += gain;
+                        }
+                    }
+                },
+                None => {
+                    for channel in 0 .. in_channels.min(out_channels) {
+                        matrix[channel * in_channels + channel] = 1.0;
+                    }
+                },
+            },
+            Target::Speaker(channel, _) => {
+                let channel = channel as usize;
+                for gain in &mut matrix[channel * in_channels .. (channel + 1) * in_channels] {
+                    *gain = 1.0 / in_channels as f32;
+                }
+            },
+        }
+        matrix
+    }
+}
+
+/// Converts the channels of a source to a channel layout, playing each channel on the matching
+/// speaker.
+///
+/// If the number of channels of the input changes, its channels are taken to be in the usual
+/// layout of the new number.
+#[derive(Clone, Debug)]
+pub struct ConvertLayout<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    input: I,
+    target: Target,
+    // Gain of each input channel in each output channel, output channel by output channel.
+    matrix: Vec<f32>,
+    in_channels: u16,
+    out_channels: u16,
+    // The input frame being converted.
+    input_frame: Vec<f32>,
+    // The output frame currently being returned.
+    frame: Vec<I::Item>,
+    position_in_frame: usize,
+}
+
+impl<I> ConvertLayout<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    #[inline]
+    fn new(input: I, target: Target) -> ConvertLayout<I> {
+        let out_channels = target.layout().channels();
+        ConvertLayout {
+            in_channels: input.channels(),
+            input_frame: Vec::with_capacity(input.channels() as usize),
+            matrix: target.matrix(input.channels() as usize),
+            input,
+            target,
+            out_channels,
+            frame: Vec::with_capacity(out_channels as usize),
+            position_in_frame: 0,
+        }
+    }
+
+    /// Returns a reference to the inner source.
+    #[inline]
+    pub fn inner(&self) -> &I {
+        &self.input
+    }
+
+    /// Returns a mutable reference to the inner source.
+    #[inline]
+    pub fn inner_mut(&mut self) -> &mut I {
+        &mut self.input
+    }
+
+    /// Returns the inner source.
+    #[inline]
+    pub fn into_inner(self) -> I {
+        self.input
+    }
+}
+
+impl<I> Iterator for ConvertLayout<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    type Item = I::Item;
+
+    #[inline]
+    fn next(&mut self) -> Option<I::Item> {
+        if self.position_in_frame >= self.frame.len() {
+            // The number of channels of the input is known once the first sample of the frame is
+            // read.
+            let first = self.input.next()?;
+            if self.input.channels() != self.in_channels {
+                self.in_channels = self.input.channels();
+                self.matrix = self.target.matrix(self.in_channels as usize);
+            }
+
+            let in_channels = self.in_channels as usize;
+            self.input_frame.clear();
+            self.input_frame.push(first.to_f32());
+            for _ in 1 .. in_channels {
+                self.input_frame.push(self.input.next()?.to_f32());
+            }
+
+            self.frame.clear();
+            for gains in self.matrix.chunks(in_channels) {
+                let value = gains.iter().zip(&self.input_frame).map(|(g, s)| g * s).sum::<f32>();
+                self.frame.push(Sample::from_sample(&value));
+            }
+            self.position_in_frame = 0;
+        }
+
+        let value = self.frame[self.position_in_frame];
+        self.position_in_frame += 1;
+        Some(value)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let (in_channels, out_channels) = (self.in_channels as usize, self.out_channels as usize);
+        let remaining = self.frame.len() - self.position_in_frame;
+        let (min, max) = self.input.size_hint();
+        (
+            min / in_channels * out_channels + remaining,
+            max.map(|max| max / in_channels * out_channels + remaining),
+        )
+    }
+}
+
+impl<I> Source for ConvertLayout<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        let (in_channels, out_channels) = (self.in_channels as usize, self.out_channels as usize);
+        let remaining = self.frame.len() - self.position_in_frame;
+        self.input
+            .current_frame_len()
+            .map(|len| len / in_channels * out_channels + remaining)
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        self.out_channels
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        self.input.sample_rate()
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        self.input.total_duration()
+    }
+
+    #[inline]
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        self.frame.clear();
+        self.position_in_frame = 0;
+        self.input.try_seek(pos)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::FRAC_1_SQRT_2;
+
+    use buffer::SamplesBuffer;
+    use source::{from_iter, ChannelLayout, Source, Speaker};
+
+    #[test]
+    fn downmix_to_stereo() {
+        // Front left, front right, center, subwoofer, back left, back right.
+        let input = SamplesBuffer::new(6, 48000, vec![1.0f32, 0.0, 1.0, 1.0, 0.0, 1.0]);
+        let output = input
+            .convert_layout(ChannelLayout::Surround51, ChannelLayout::Stereo)
+            .collect::<Vec<_>>();
+        assert_eq!(output.len(), 2);
+        assert!((output[0] - (1.0 + FRAC_1_SQRT_2)).abs() < 1e-6);
+        assert!((output[1] - 2.0 * FRAC_1_SQRT_2).abs() < 1e-6);
+    }
+
+    #[test]
+    fn upmix_keeps_speakers() {
+        let input = SamplesBuffer::new(2, 48000, vec![0.5f32, -0.5]);
+        let output = input
+            .convert_layout(ChannelLayout::Stereo, ChannelLayout::Surround71)
+            .collect::<Vec<_>>();
+        assert_eq!(output, [0.5, -0.5, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0]);
+
+        let input = SamplesBuffer::new(1, 48000, vec![1.0f32]);
+        let output = input
+            .convert_layout(ChannelLayout::Mono, ChannelLayout::Stereo)
+            .collect::<Vec<_>>();
+        assert_eq!(output, [FRAC_1_SQRT_2, FRAC_1_SQRT_2]);
+    }
+
+    #[test]
+    fn single_speaker() {
+        let input = SamplesBuffer::new(1, 48000, vec![0.5f32, 0.25]);
+        let mut source = input.to_speaker(Speaker::BackRight, ChannelLayout::Surround51);
+        assert_eq!(source.channels(), 6);
+        let output = source.by_ref().collect::<Vec<_>>();
+        assert_eq!(&output[.. 6], [0.0, 0.0, 0.0, 0.0, 0.0, 0.5]);
+        assert_eq!(&output[6 ..], [0.0, 0.0, 0.0, 0.0, 0.0, 0.25]);
+    }
+
+    #[test]
+    fn follows_channel_changes() {
+        let source = from_iter(vec![
+            SamplesBuffer::new(2, 48000, vec![0.5f32, -0.5]),
+            SamplesBuffer::new(1, 48000, vec![1.0f32]),
+        ]);
+        let output = source
+            .convert_layout(ChannelLayout::Stereo, ChannelLayout::Surround51)
+            .collect::<Vec<_>>();
+        assert_eq!(&output[.. 6], [0.5, -0.5, 0.0, 0.0, 0.0, 0.0]);
+        assert_eq!(&output[6 ..], [0.0, 0.0, 1.0, 0.0, 0.0, 0.0]);
+    }
+}
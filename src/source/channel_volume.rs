@@ -1,4 +1,5 @@
 use std::time::Duration;
+use source::SeekError;
 use Sample;
 use Source;
 
@@ -133,4 +134,19 @@ where
     fn total_duration(&self) -> Option<Duration> {
         self.input.total_duration()
     }
+
+    #[inline]
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        self.input.try_seek(pos)?;
+        self.current_channel = 0;
+        self.current_sample = None;
+        for _ in 0 .. self.input.channels() {
+            if let Some(s) = self.input.next() {
+                self.current_sample = Some(self.current_sample
+                    .get_or_insert_with(I::Item::zero_value)
+                    .saturating_add(s));
+            }
+        }
+        Ok(())
+    }
 }
@@ -0,0 +1,160 @@
+use std::f32::consts::PI;
+use std::time::Duration;
+
+use conversions::duration_to_frames;
+use Source;
+
+/// A source that produces a sine whose frequency sweeps between two values, and then ends.
+///
+/// Always has one channel.
+#[derive(Clone, Debug)]
+pub struct Chirp {
+    sample_rate: u32,
+    start_freq: f32,
+    end_freq: f32,
+    sweep: Sweep,
+    duration: Duration,
+    total_samples: u64,
+    elapsed_samples: u64,
+    // Position in the current period, between 0 and 1.
+    phase: f32,
+}
+
+#[derive(Copy, Clone, Debug)]
+enum Sweep {
+    Linear,
+    Logarithmic,
+}
+
+impl Chirp {
+    /// Builds a chirp whose frequency changes by the same number of hertz every second.
+    ///
+    /// # Panic
+    ///
+    /// Panics if `sample_rate` is zero.
+    #[inline]
+    pub fn linear(sample_rate: u32, start_freq: f32, end_freq: f32, duration: Duration) -> Chirp {
+        Chirp::new(sample_rate, start_freq, end_freq, Sweep::Linear, duration)
+    }
+
+    /// Builds a chirp that spends the same time in every octave. This is the usual sweep for
+    /// measuring the frequency response of speakers and rooms.
+    ///
+    /// # Panic
+    ///
+    /// Panics if `sample_rate` is zero or if a frequency isn't strictly positive.
+    #[inline]
+    pub fn logarithmic(
+        sample_rate: u32, start_freq: f32, end_freq: f32, duration: Duration,
+    ) -> Chirp {
+        assert!(start_freq > 0.0 && end_freq > 0.0);
+        Chirp::new(sample_rate, start_freq, end_freq, Sweep::Logarithmic, duration)
+    }
+
+    fn new(
+        sample_rate: u32, start_freq: f32, end_freq: f32, sweep: Sweep, duration: Duration,
+    ) -> Chirp {
+        assert!(sample_rate != 0);
+
+        let total_samples = duration_to_frames(duration, sample_rate);
+
+        Chirp {
+            sample_rate,
+            start_freq,
+            end_freq,
+            sweep,
+            duration,
+            total_samples,
+            elapsed_samples: 0,
+            phase: 0.0,
+        }
+    }
+
+    // Returns the instantaneous frequency of the current sample.
+    #[inline]
+    fn frequency(&self) -> f32 {
+        let progress = self.elapsed_samples as f32 / self.total_samples as f32;
+        match self.sweep {
+            Sweep::Linear => self.start_freq + (self.end_freq - self.start_freq) * progress,
+            Sweep::Logarithmic => self.start_freq * (self.end_freq / self.start_freq).powf(progress),
+        }
+    }
+}
+
+impl Iterator for Chirp {
+    type Item = f32;
+
+    #[inline]
+    fn next(&mut self) -> Option<f32> {
+        if self.elapsed_samples >= self.total_samples {
+            return None;
+        }
+
+        let value = (2.0 * PI * self.phase).sin();
+        self.phase = (self.phase + self.frequency() / self.sample_rate as f32) % 1.0;
+        self.elapsed_samples += 1;
+        Some(value)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = (self.total_samples - self.elapsed_samples) as usize;
+        (remaining, Some(remaining))
+    }
+}
+
+impl ExactSizeIterator for Chirp {}
+
+impl Source for Chirp {
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        None
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        1
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        Some(self.duration)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use source::Chirp;
+
+    // Counts the upward zero crossings, which is the number of periods.
+    fn periods(samples: &[f32]) -> usize {
+        samples.windows(2).filter(|w| w[0] < 0.0 && w[1] >= 0.0).count()
+    }
+
+    #[test]
+    fn linear() {
+        let chirp = Chirp::linear(48000, 100.0, 300.0, Duration::from_secs(1));
+        assert_eq!(chirp.len(), 48000);
+
+        // The average frequency is 200Hz.
+        let samples = chirp.collect::<Vec<_>>();
+        assert!((periods(&samples) as i32 - 200).abs() <= 1);
+    }
+
+    #[test]
+    fn logarithmic() {
+        let chirp = Chirp::logarithmic(48000, 100.0, 400.0, Duration::from_secs(2));
+        let samples = chirp.collect::<Vec<_>>();
+
+        // Each of the two octaves lasts one second.
+        assert!((periods(&samples[.. 48000]) as i32 - 144).abs() <= 1);
+        assert!((periods(&samples[48000 ..]) as i32 - 288).abs() <= 1);
+    }
+}
@@ -0,0 +1,237 @@
+use std::f32::consts::PI;
+use std::time::Duration;
+
+use conversions::{amplitude_to_db, db_to_amplitude};
+use source::SeekError;
+use Sample;
+use Source;
+
+/// Internal function that builds a `Crossfeed` object.
+pub fn crossfeed<I>(input: I, cutoff: u32, level_db: f32) -> Crossfeed<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    Crossfeed {
+        input,
+        cutoff: cutoff as f32,
+        level_db,
+        sample_rate: 0,
+        coefficients: Coefficients::default(),
+        low: [0.0; 2],
+        high: [0.0; 2],
+        previous: [0.0; 2],
+        pending: None,
+    }
+}
+
+/// Filter that bleeds a low-passed copy of each channel of a stereo source into the other one,
+/// in the manner of the Bauer stereophonic-to-binaural DSP.
+///
+/// Hard-panned sounds are tiring to listen to with headphones, because each ear only hears one
+/// channel. With the crossfeed, they sound closer to what speakers give. Frames that don't have
+/// two channels are left untouched.
+#[derive(Clone, Debug)]
+pub struct Crossfeed<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    input: I,
+    cutoff: f32,
+    level_db: f32,
+    // Sample rate that the coefficients were computed for.
+    sample_rate: u32,
+    coefficients: Coefficients,
+    // State of the low-pass and high-shelf filters of each channel.
+    low: [f32; 2],
+    high: [f32; 2],
+    previous: [f32; 2],
+    // Right sample of the current frame.
+    pending: Option<I::Item>,
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+struct Coefficients {
+    low_a0: f32,
+    low_b1: f32,
+    high_a0: f32,
+    high_a1: f32,
+    high_b1: f32,
+}
+
+impl<I> Crossfeed<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    /// Changes the cutoff frequency of the crossfeed, in Hertz.
+    #[inline]
+    pub fn set_cutoff(&mut self, cutoff: u32) {
+        self.cutoff = cutoff as f32;
+        self.sample_rate = 0;
+    }
+
+    /// Changes the level of the crossfeed, in decibels.
+    #[inline]
+    pub fn set_level(&mut self, level_db: f32) {
+        self.level_db = level_db;
+        self.sample_rate = 0;
+    }
+
+    /// Returns a reference to the inner source.
+    #[inline]
+    pub fn inner(&self) -> &I {
+        &self.input
+    }
+
+    /// Returns a mutable reference to the inner source.
+    #[inline]
+    pub fn inner_mut(&mut self) -> &mut I {
+        &mut self.input
+    }
+
+    /// Returns the inner source.
+    #[inline]
+    pub fn into_inner(self) -> I {
+        self.input
+    }
+
+    fn update_coefficients(&mut self) {
+        let sample_rate = self.input.sample_rate();
+        if sample_rate == self.sample_rate {
+            return;
+        }
+        self.sample_rate = sample_rate;
+
+        let low_db = self.level_db * -5.0 / 6.0 - 3.0;
+        let high_db = self.level_db / 6.0 - 3.0;
+        let low_gain = db_to_amplitude(low_db);
+        let high_gain = 1.0 - db_to_amplitude(high_db);
+        let high_cutoff = self.cutoff * 2f32.powf((low_db - amplitude_to_db(high_gain)) / 12.0);
+
+        let x = (-2.0 * PI * self.cutoff / sample_rate as f32).exp();
+        self.coefficients.low_b1 = x;
+        self.coefficients.low_a0 = low_gain * (1.0 - x);
+
+        let x = (-2.0 * PI * high_cutoff / sample_rate as f32).exp();
+        self.coefficients.high_b1 = x;
+        self.coefficients.high_a0 = 1.0 - high_gain * (1.0 - x);
+        self.coefficients.high_a1 = -x;
+    }
+
+    // Filters one channel, and returns its direct and crossed parts.
+    #[inline]
+    fn filter(&mut self, channel: usize, value: f32) -> (f32, f32) {
+        let c = self.coefficients;
+        self.low[channel] = c.low_a0 * value + c.low_b1 * self.low[channel];
+        self.high[channel] =
+            c.high_a0 * value + c.high_a1 * self.previous[channel] + c.high_b1 * self.high[channel];
+        self.previous[channel] = value;
+        (self.high[channel], self.low[channel])
+    }
+}
+
+impl<I> Iterator for Crossfeed<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    type Item = I::Item;
+
+    #[inline]
+    fn next(&mut self) -> Option<I::Item> {
+        if let Some(value) = self.pending.take() {
+            return Some(value);
+        }
+        if self.input.channels() != 2 {
+            return self.input.next();
+        }
+
+        self.update_coefficients();
+        let left = self.input.next()?.to_f32();
+        let right = self.input.next().map_or(0.0, |value| value.to_f32());
+
+        let (left_direct, left_crossed) = self.filter(0, left);
+        let (right_direct, right_crossed) = self.filter(1, right);
+        self.pending = Some(Sample::from_sample(&(right_direct + left_crossed)));
+        Some(Sample::from_sample(&(left_direct + right_crossed)))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let pending = self.pending.is_some() as usize;
+        let (min, max) = self.input.size_hint();
+        (min + pending, max.map(|max| max + pending))
+    }
+}
+
+impl<I> ExactSizeIterator for Crossfeed<I>
+where
+    I: Source + ExactSizeIterator,
+    I::Item: Sample,
+{
+}
+
+impl<I> Source for Crossfeed<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        if self.pending.is_some() {
+            return Some(1);
+        }
+        self.input.current_frame_len()
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        if self.pending.is_some() {
+            2
+        } else {
+            self.input.channels()
+        }
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        self.input.sample_rate()
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        self.input.total_duration()
+    }
+
+    #[inline]
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        self.input.try_seek(pos)?;
+        self.pending = None;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use buffer::SamplesBuffer;
+    use source::Source;
+
+    #[test]
+    fn hard_panned_bleeds_into_other_channel() {
+        let data = (0 .. 2000).flat_map(|_| vec![0.5f32, 0.0]).collect::<Vec<_>>();
+        let source = SamplesBuffer::new(2, 44100, data).crossfeed(700, 4.5);
+        let samples = source.collect::<Vec<_>>();
+
+        let (left, right) = (samples[3998], samples[3999]);
+        assert!(right > 0.05 && right < left, "{} {}", left, right);
+    }
+
+    #[test]
+    fn mono_is_untouched() {
+        let source = SamplesBuffer::new(1, 44100, vec![0.5f32, 0.25]);
+        let source = source.crossfeed(700, 4.5);
+        assert_eq!(source.collect::<Vec<_>>(), [0.5, 0.25]);
+    }
+}
@@ -0,0 +1,274 @@
+use std::time::Duration;
+
+use conversions::db_to_amplitude;
+use source::biquad::Biquad;
+use source::SeekError;
+use Sample;
+use Source;
+
+/// Time it takes for the envelope of the sibilance to rise by a factor of `e`.
+const ATTACK_SECS: f32 = 0.001;
+/// Time it takes for the envelope of the sibilance to decay by a factor of `e`.
+const RELEASE_SECS: f32 = 0.05;
+
+/// Internal function that builds a `DeEsser` object.
+pub fn de_esser<I>(input: I, threshold_db: f32) -> DeEsser<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    DeEsser {
+        input,
+        threshold: db_to_amplitude(threshold_db),
+        ratio: 4.0,
+        low_freq: 5000.0,
+        high_freq: 10000.0,
+        sample_rate: 0,
+        split_low: Biquad::default(),
+        split_high: Biquad::default(),
+        band: Biquad::default(),
+        states: Vec::new(),
+        envelope: 0.0,
+        lows: Vec::new(),
+        highs: Vec::new(),
+        frame: Vec::new(),
+        current_channel: 0,
+    }
+}
+
+/// Filter that compresses the sibilance of a voice, the harsh "s" and "sh" sounds.
+///
+/// The level of the sibilance band, 5 kHz to 10 kHz by default, is followed on all the channels
+/// at once. When it is above the threshold, the frequencies above the bottom of the band are
+/// compressed, and the rest of the sound is left untouched.
+#[derive(Clone, Debug)]
+pub struct DeEsser<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    input: I,
+    // Threshold as an amplitude between `0.0` and `1.0`.
+    threshold: f32,
+    ratio: f32,
+    low_freq: f32,
+    high_freq: f32,
+    // Sample rate that the filters were computed for.
+    sample_rate: u32,
+    // Filters that split the sound at the bottom of the band. Each one is applied twice, as a
+    // Linkwitz-Riley crossover, so that the two parts add up to the original sound.
+    split_low: Biquad,
+    split_high: Biquad,
+    // Low-pass filter that limits the detection to the top of the band.
+    band: Biquad,
+    // State of the filters for each channel: two for `split_low`, two for `split_high` and one
+    // for `band`.
+    states: Vec<[[f32; 4]; 5]>,
+    envelope: f32,
+    // Parts of each channel of the current frame under and above the bottom of the band.
+    lows: Vec<f32>,
+    highs: Vec<f32>,
+    // Output samples of the current frame.
+    frame: Vec<I::Item>,
+    // Channel of the next sample returned by `next`.
+    current_channel: u16,
+}
+
+impl<I> DeEsser<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    /// Modifies the level of the sibilance above which it is compressed.
+    #[inline]
+    pub fn set_threshold_db(&mut self, threshold_db: f32) {
+        self.threshold = db_to_amplitude(threshold_db);
+    }
+
+    /// Modifies how much the sibilance above the threshold is compressed. The default is `4.0`.
+    #[inline]
+    pub fn set_ratio(&mut self, ratio: f32) {
+        self.ratio = ratio.max(1.0);
+    }
+
+    /// Modifies the band of frequencies of the sibilance, in Hertz.
+    #[inline]
+    pub fn set_band(&mut self, low_freq: f32, high_freq: f32) {
+        self.low_freq = low_freq;
+        self.high_freq = high_freq;
+        self.sample_rate = 0;
+    }
+
+    /// Returns a reference to the inner source.
+    #[inline]
+    pub fn inner(&self) -> &I {
+        &self.input
+    }
+
+    /// Returns a mutable reference to the inner source.
+    #[inline]
+    pub fn inner_mut(&mut self) -> &mut I {
+        &mut self.input
+    }
+
+    /// Returns the inner source.
+    #[inline]
+    pub fn into_inner(self) -> I {
+        self.input
+    }
+
+    // Reads and processes a frame of the input. Returns `false` if the input has ended.
+    fn next_frame(&mut self) -> bool {
+        let sample_rate = self.input.sample_rate();
+        if sample_rate != self.sample_rate {
+            self.sample_rate = sample_rate;
+            // The filters must stay under the Nyquist frequency.
+            let max_freq = sample_rate as f32 * 0.45;
+            let low_freq = self.low_freq.min(max_freq);
+            self.split_low = Biquad::low_pass(low_freq, sample_rate);
+            self.split_high = Biquad::high_pass(low_freq, sample_rate);
+            self.band = Biquad::low_pass(self.high_freq.min(max_freq), sample_rate);
+        }
+
+        let channels = self.input.channels() as usize;
+        self.frame.clear();
+        self.frame.extend(self.input.by_ref().take(channels));
+        if self.frame.is_empty() {
+            return false;
+        }
+        self.states.resize(self.frame.len(), [[0.0; 4]; 5]);
+
+        // Splits each channel, and measures the level of the band.
+        let mut level = 0.0f32;
+        self.lows.clear();
+        self.highs.clear();
+        for (value, state) in self.frame.iter().zip(self.states.iter_mut()) {
+            let value = value.to_f32();
+            let low = self.split_low.apply(&mut state[0], value);
+            let low = self.split_low.apply(&mut state[1], low);
+            let high = self.split_high.apply(&mut state[2], value);
+            let high = self.split_high.apply(&mut state[3], high);
+            let band = self.band.apply(&mut state[4], high);
+            level = level.max(band.abs());
+            self.lows.push(low);
+            self.highs.push(high);
+        }
+
+        let time = if level > self.envelope { ATTACK_SECS } else { RELEASE_SECS };
+        let coeff = (-1.0 / (time * sample_rate as f32)).exp();
+        self.envelope = level + coeff * (self.envelope - level);
+
+        let gain = if self.envelope > self.threshold {
+            (self.threshold / self.envelope).powf(1.0 - 1.0 / self.ratio)
+        } else {
+            1.0
+        };
+        for (i, value) in self.frame.iter_mut().enumerate() {
+            *value = Sample::from_sample(&(self.lows[i] + self.highs[i] * gain));
+        }
+        true
+    }
+}
+
+impl<I> Iterator for DeEsser<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    type Item = I::Item;
+
+    #[inline]
+    fn next(&mut self) -> Option<I::Item> {
+        if self.current_channel as usize >= self.frame.len() {
+            if !self.next_frame() {
+                return None;
+            }
+            self.current_channel = 0;
+        }
+
+        let value = self.frame[self.current_channel as usize];
+        self.current_channel += 1;
+        Some(value)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let in_frame = self.frame.len().saturating_sub(self.current_channel as usize);
+        let (min, max) = self.input.size_hint();
+        (min + in_frame, max.map(|max| max + in_frame))
+    }
+}
+
+impl<I> ExactSizeIterator for DeEsser<I>
+where
+    I: Source + ExactSizeIterator,
+    I::Item: Sample,
+{
+}
+
+impl<I> Source for DeEsser<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        let in_frame = self.frame.len().saturating_sub(self.current_channel as usize);
+        if in_frame != 0 {
+            return Some(in_frame);
+        }
+        self.input.current_frame_len()
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        if (self.current_channel as usize) < self.frame.len() {
+            self.frame.len() as u16
+        } else {
+            self.input.channels()
+        }
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        self.input.sample_rate()
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        self.input.total_duration()
+    }
+
+    #[inline]
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        self.input.try_seek(pos)?;
+        self.frame.clear();
+        self.current_channel = 0;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use buffer::SamplesBuffer;
+    use source::{SineWave, Source};
+
+    // Returns the highest absolute value among the last samples of a source.
+    fn tail_peak<I: Iterator<Item = f32>>(source: I) -> f32 {
+        source.skip(20000).fold(0.0, |peak, s| peak.max(s.abs()))
+    }
+
+    #[test]
+    fn compresses_sibilance() {
+        let sibilance = SineWave::new(7000).take(22050).collect::<Vec<_>>();
+        let source = SamplesBuffer::new(1, 44100, sibilance).de_ess(-20.0);
+        assert!(tail_peak(source) < 0.5);
+    }
+
+    #[test]
+    fn leaves_low_frequencies() {
+        let voice = SineWave::new(200).take(22050).collect::<Vec<_>>();
+        let source = SamplesBuffer::new(1, 44100, voice).de_ess(-20.0);
+        assert!(tail_peak(source) > 0.95);
+    }
+}
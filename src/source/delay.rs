@@ -1,5 +1,7 @@
 use std::time::Duration;
 
+use conversions::duration_to_frames;
+use source::SeekError;
 use Sample;
 use Source;
 
@@ -9,8 +11,7 @@ where
     I: Source,
     I::Item: Sample,
 {
-    let duration_ns = duration.as_secs() * 1000000000 + duration.subsec_nanos() as u64;
-    let samples = duration_ns * input.sample_rate() as u64 / 1000000000 * input.channels() as u64; 
+    let samples = duration_to_frames(duration, input.sample_rate()) * input.channels() as u64;
 
     Delay {
         input: input,
@@ -106,4 +107,19 @@ where
             .total_duration()
             .map(|val| val + self.requested_duration)
     }
+
+    #[inline]
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        if pos < self.requested_duration {
+            self.input.try_seek(Duration::new(0, 0))?;
+            let remaining = self.requested_duration - pos;
+            let samples = duration_to_frames(remaining, self.input.sample_rate())
+                * self.input.channels() as u64;
+            self.remaining_samples = samples as usize;
+        } else {
+            self.input.try_seek(pos - self.requested_duration)?;
+            self.remaining_samples = 0;
+        }
+        Ok(())
+    }
 }
@@ -0,0 +1,391 @@
+use std::collections::VecDeque;
+use std::f32::consts::PI;
+use std::time::Duration;
+
+use conversions::db_to_amplitude;
+use source::fft::{fft, ifft};
+use source::SeekError;
+use Sample;
+use Source;
+
+/// Number of frames of each FFT.
+const FFT_SIZE: usize = 1024;
+/// Number of frames between two FFTs. The windows overlap by half.
+const HOP: usize = FFT_SIZE / 2;
+/// Factor applied to the noise before it is subtracted, which leaves less residual noise.
+const OVERSUBTRACTION: f32 = 1.5;
+/// How fast the magnitudes of the sound are smoothed, per FFT, to estimate the noise floor.
+const SMOOTHING: f32 = 0.8;
+/// How fast the estimated noise floor rises when the sound gets louder, in decibels per second.
+const FLOOR_RISE_DB: f32 = 3.0;
+
+/// Internal function that builds a `Denoise` object.
+pub fn denoise<I>(input: I, profile: NoiseProfile, reduction_db: f32) -> Denoise<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    let channels = input.channels() as usize;
+    let bins = FFT_SIZE / 2 + 1;
+    let learn_frames = match profile {
+        NoiseProfile::Estimate => None,
+        NoiseProfile::Learn(duration) => {
+            Some((duration.as_secs_f64() * input.sample_rate() as f64) as u64)
+        },
+    };
+
+    // Square root of a Hann window, applied before and after the processing, so that the
+    // overlapping windows add up to one.
+    let window = (0 .. FFT_SIZE)
+        .map(|n| (PI * n as f32 / FFT_SIZE as f32).sin())
+        .collect();
+
+    Denoise {
+        input,
+        floor: db_to_amplitude(-reduction_db.abs()),
+        learn_frames,
+        channels,
+        window,
+        history: vec![0.0; FFT_SIZE * channels],
+        overlap: vec![0.0; FFT_SIZE * channels],
+        noise: vec![0.0; bins * channels],
+        smoothed: vec![0.0; bins * channels],
+        learned: vec![0.0; bins * channels],
+        learned_ffts: 0,
+        frames_read: 0,
+        frames_written: 0,
+        skip: FFT_SIZE - HOP,
+        input_ended: false,
+        real: vec![0.0; FFT_SIZE],
+        imag: vec![0.0; FFT_SIZE],
+        output: VecDeque::with_capacity(HOP * channels),
+    }
+}
+
+/// How a `Denoise` filter knows what the noise sounds like.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NoiseProfile {
+    /// Follows the noise floor of each frequency, which is the level that the sound keeps coming
+    /// back to. Suited to steady noise under a sound that has pauses, such as a voice.
+    Estimate,
+    /// Learns the noise from the given duration at the start of the source, which must only
+    /// contain noise. This part is played untouched.
+    Learn(Duration),
+}
+
+/// Filter that reduces steady background noise, such as hiss or hum, by spectral subtraction.
+///
+/// The sound is split into overlapping blocks of 1024 frames, whose frequencies are attenuated
+/// according to how much of them is noise. The output is delayed by 512 frames internally, but
+/// it has the same length as the input. The source must keep the same number of channels.
+#[derive(Clone, Debug)]
+pub struct Denoise<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    input: I,
+    // Lowest gain applied to a frequency.
+    floor: f32,
+    // Number of frames to learn the noise from, or `None` to estimate the noise floor.
+    learn_frames: Option<u64>,
+    channels: usize,
+    window: Vec<f32>,
+    // Last `FFT_SIZE` input frames of each channel, one channel after the other.
+    history: Vec<f32>,
+    // Sum of the processed blocks that overlap the next output frames, laid out as `history`.
+    overlap: Vec<f32>,
+    // Magnitude of the noise of each bin of each channel.
+    noise: Vec<f32>,
+    // Smoothed magnitude of each bin of each channel, used to estimate the noise floor.
+    smoothed: Vec<f32>,
+    // Sum of the magnitudes of each bin of each channel while the noise is learned.
+    learned: Vec<f32>,
+    learned_ffts: u32,
+    // Number of frames read from the input and written to `output`.
+    frames_read: u64,
+    frames_written: u64,
+    // Number of processed frames to drop, which come before the start of the input.
+    skip: usize,
+    input_ended: bool,
+    // Buffers for the FFT, kept around to avoid allocating on the audio thread.
+    real: Vec<f32>,
+    imag: Vec<f32>,
+    // Interleaved output samples.
+    output: VecDeque<f32>,
+}
+
+impl<I> Denoise<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    /// Modifies how much the noise is reduced, in decibels.
+    #[inline]
+    pub fn set_reduction(&mut self, reduction_db: f32) {
+        self.floor = db_to_amplitude(-reduction_db.abs());
+    }
+
+    /// Returns true while the noise is being learned.
+    #[inline]
+    pub fn is_learning(&self) -> bool {
+        self.learn_frames.is_some_and(|frames| self.frames_read <= frames)
+    }
+
+    /// Returns a reference to the inner source.
+    #[inline]
+    pub fn inner(&self) -> &I {
+        &self.input
+    }
+
+    /// Returns a mutable reference to the inner source.
+    #[inline]
+    pub fn inner_mut(&mut self) -> &mut I {
+        &mut self.input
+    }
+
+    /// Returns the inner source.
+    #[inline]
+    pub fn into_inner(self) -> I {
+        self.input
+    }
+
+    // Reads `HOP` frames and processes a block. Returns `false` once all the input was output.
+    fn next_block(&mut self) -> bool {
+        if self.input_ended && self.frames_written >= self.frames_read {
+            return false;
+        }
+
+        // Reads the frames, completed with silence after the end of the input.
+        let channels = self.channels;
+        for channel in 0 .. channels {
+            self.history[channel * FFT_SIZE .. (channel + 1) * FFT_SIZE].rotate_left(HOP);
+        }
+        for frame in FFT_SIZE - HOP .. FFT_SIZE {
+            let mut read = false;
+            for channel in 0 .. channels {
+                let value = match self.input.next() {
+                    Some(value) => {
+                        read = true;
+                        value.to_f32()
+                    },
+                    None => {
+                        self.input_ended = true;
+                        0.0
+                    },
+                };
+                self.history[channel * FFT_SIZE + frame] = value;
+            }
+            if read {
+                self.frames_read += 1;
+            }
+        }
+
+        let learning = self.is_learning();
+        let sample_rate = self.input.sample_rate() as f32;
+        let rise = db_to_amplitude(FLOOR_RISE_DB * HOP as f32 / sample_rate);
+        let bins = FFT_SIZE / 2 + 1;
+        for channel in 0 .. channels {
+            let history = &self.history[channel * FFT_SIZE .. (channel + 1) * FFT_SIZE];
+            for (n, (&sample, &w)) in history.iter().zip(&self.window).enumerate() {
+                self.real[n] = sample * w;
+                self.imag[n] = 0.0;
+            }
+            fft(&mut self.real, &mut self.imag);
+
+            for bin in 0 .. bins {
+                let index = channel * bins + bin;
+                let re = self.real[bin];
+                let im = self.imag[bin];
+                let magnitude = (re * re + im * im).sqrt();
+
+                match self.learn_frames {
+                    Some(_) if learning => self.learned[index] += magnitude,
+                    Some(_) => (),
+                    None => {
+                        let smoothed = &mut self.smoothed[index];
+                        let noise = &mut self.noise[index];
+                        // The first blocks start with the silence before the input.
+                        if self.frames_read <= FFT_SIZE as u64 {
+                            *smoothed = magnitude;
+                            *noise = magnitude;
+                        } else {
+                            *smoothed = magnitude + SMOOTHING * (*smoothed - magnitude);
+                            *noise = smoothed.min(*noise * rise);
+                        }
+                    },
+                }
+
+                let gain = if learning || magnitude == 0.0 {
+                    1.0
+                } else {
+                    let noise = OVERSUBTRACTION * self.noise[index];
+                    ((magnitude - noise) / magnitude).max(self.floor)
+                };
+                self.real[bin] *= gain;
+                self.imag[bin] *= gain;
+                if bin != 0 && bin != FFT_SIZE / 2 {
+                    self.real[FFT_SIZE - bin] *= gain;
+                    self.imag[FFT_SIZE - bin] *= gain;
+                }
+            }
+
+            ifft(&mut self.real, &mut self.imag);
+            let overlap = &mut self.overlap[channel * FFT_SIZE .. (channel + 1) * FFT_SIZE];
+            for (n, (out, &w)) in overlap.iter_mut().zip(&self.window).enumerate() {
+                *out += self.real[n] * w;
+            }
+        }
+        if learning {
+            self.learned_ffts += 1;
+        } else if self.learned_ffts != 0 {
+            let count = self.learned_ffts as f32;
+            for (noise, learned) in self.noise.iter_mut().zip(&self.learned) {
+                *noise = learned / count;
+            }
+            self.learned_ffts = 0;
+        }
+
+        // The first `HOP` frames of `overlap` are complete.
+        for frame in 0 .. HOP {
+            if self.skip != 0 {
+                self.skip -= 1;
+            } else if self.frames_written < self.frames_read {
+                for channel in 0 .. channels {
+                    self.output.push_back(self.overlap[channel * FFT_SIZE + frame]);
+                }
+                self.frames_written += 1;
+            }
+        }
+        for channel in 0 .. channels {
+            let overlap = &mut self.overlap[channel * FFT_SIZE .. (channel + 1) * FFT_SIZE];
+            overlap.rotate_left(HOP);
+            for value in &mut overlap[FFT_SIZE - HOP ..] {
+                *value = 0.0;
+            }
+        }
+        true
+    }
+}
+
+impl<I> Iterator for Denoise<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    type Item = I::Item;
+
+    #[inline]
+    fn next(&mut self) -> Option<I::Item> {
+        loop {
+            if let Some(value) = self.output.pop_front() {
+                return Some(Sample::from_sample(&value));
+            }
+            if !self.next_block() {
+                return None;
+            }
+        }
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let pending = (self.frames_read - self.frames_written) as usize * self.channels;
+        let buffered = self.output.len() + pending;
+        let (min, max) = self.input.size_hint();
+        (min + buffered, max.map(|max| max + buffered))
+    }
+}
+
+impl<I> ExactSizeIterator for Denoise<I>
+where
+    I: Source + ExactSizeIterator,
+    I::Item: Sample,
+{
+}
+
+impl<I> Source for Denoise<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        None
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        self.channels as u16
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        self.input.sample_rate()
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        self.input.total_duration()
+    }
+
+    #[inline]
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        self.input.try_seek(pos)?;
+        for value in self.history.iter_mut().chain(self.overlap.iter_mut()) {
+            *value = 0.0;
+        }
+        self.output.clear();
+        self.frames_read = 0;
+        self.frames_written = 0;
+        self.skip = FFT_SIZE - HOP;
+        self.input_ended = false;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::PI;
+    use std::time::Duration;
+
+    use buffer::SamplesBuffer;
+    use source::{NoiseProfile, Source, WhiteNoise};
+
+    fn rms(samples: &[f32]) -> f32 {
+        (samples.iter().map(|s| s * s).sum::<f32>() / samples.len() as f32).sqrt()
+    }
+
+    #[test]
+    fn keeps_length_and_clean_sound() {
+        let wave = (0 .. 20000)
+            .map(|i| 0.5 * (2.0 * PI * 440.0 * i as f32 / 44100.0).sin())
+            .collect::<Vec<f32>>();
+        let source = SamplesBuffer::new(1, 44100, wave.clone());
+        let output = source.denoise(NoiseProfile::Learn(Duration::from_millis(0)), 30.0);
+        let output = output.collect::<Vec<_>>();
+        assert_eq!(output.len(), wave.len());
+        for (a, b) in output.iter().zip(&wave).skip(1024) {
+            assert!((a - b).abs() < 1e-3, "{} {}", a, b);
+        }
+    }
+
+    #[test]
+    fn learns_and_removes_noise() {
+        let noise = WhiteNoise::with_seed(44100, 1).amplify(0.05).take(88200);
+        let noise = noise.collect::<Vec<f32>>();
+        let source = SamplesBuffer::new(1, 44100, noise.clone());
+        let output = source.denoise(NoiseProfile::Learn(Duration::from_millis(500)), 30.0);
+        let output = output.collect::<Vec<_>>();
+        assert_eq!(output.len(), noise.len());
+        assert!(rms(&output[66150 ..]) < rms(&noise[66150 ..]) * 0.3);
+    }
+
+    #[test]
+    fn estimates_noise_floor() {
+        let noise = WhiteNoise::with_seed(44100, 2).amplify(0.05).take(88200);
+        let noise = noise.collect::<Vec<f32>>();
+        let source = SamplesBuffer::new(1, 44100, noise.clone());
+        let output = source.denoise(NoiseProfile::Estimate, 30.0).collect::<Vec<_>>();
+        assert!(rms(&output[66150 ..]) < rms(&noise[66150 ..]) * 0.5);
+    }
+}
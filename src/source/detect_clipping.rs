@@ -0,0 +1,160 @@
+use std::time::Duration;
+
+use source::SeekError;
+use Sample;
+use Source;
+
+/// Internal function that builds a `DetectClipping` object.
+pub fn detect_clipping<I, F>(input: I, callback: F) -> DetectClipping<I, F>
+where
+    I: Source,
+    I::Item: Sample,
+    F: FnMut(u16, f32),
+{
+    DetectClipping {
+        input,
+        callback,
+        clipped: 0,
+        current_channel: 0,
+    }
+}
+
+/// Tap that calls a function for each sample of a source that goes beyond full scale.
+///
+/// The samples are passed through untouched. The function is called with the channel of the
+/// sample and its value, whose absolute value is greater than `1.0`.
+#[derive(Clone, Debug)]
+pub struct DetectClipping<I, F> {
+    input: I,
+    callback: F,
+    // Number of samples that went beyond full scale so far.
+    clipped: u64,
+    // Channel of the next sample returned by `next`.
+    current_channel: u16,
+}
+
+impl<I, F> DetectClipping<I, F>
+where
+    I: Source,
+    I::Item: Sample,
+    F: FnMut(u16, f32),
+{
+    /// Returns the number of samples that went beyond full scale so far.
+    #[inline]
+    pub fn clipped_samples(&self) -> u64 {
+        self.clipped
+    }
+
+    /// Returns a reference to the inner source.
+    #[inline]
+    pub fn inner(&self) -> &I {
+        &self.input
+    }
+
+    /// Returns a mutable reference to the inner source.
+    #[inline]
+    pub fn inner_mut(&mut self) -> &mut I {
+        &mut self.input
+    }
+
+    /// Returns the inner source.
+    #[inline]
+    pub fn into_inner(self) -> I {
+        self.input
+    }
+}
+
+impl<I, F> Iterator for DetectClipping<I, F>
+where
+    I: Source,
+    I::Item: Sample,
+    F: FnMut(u16, f32),
+{
+    type Item = I::Item;
+
+    #[inline]
+    fn next(&mut self) -> Option<I::Item> {
+        let value = self.input.next()?;
+
+        if self.current_channel >= self.input.channels() {
+            self.current_channel = 0;
+        }
+        let channel = self.current_channel;
+        self.current_channel += 1;
+
+        let x = value.to_f32();
+        if x.abs() > 1.0 {
+            self.clipped += 1;
+            (self.callback)(channel, x);
+        }
+
+        Some(value)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.input.size_hint()
+    }
+}
+
+impl<I, F> ExactSizeIterator for DetectClipping<I, F>
+where
+    I: Source + ExactSizeIterator,
+    I::Item: Sample,
+    F: FnMut(u16, f32),
+{
+}
+
+impl<I, F> Source for DetectClipping<I, F>
+where
+    I: Source,
+    I::Item: Sample,
+    F: FnMut(u16, f32),
+{
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        self.input.current_frame_len()
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        self.input.channels()
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        self.input.sample_rate()
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        self.input.total_duration()
+    }
+
+    #[inline]
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        self.input.try_seek(pos)?;
+        self.current_channel = 0;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use buffer::SamplesBuffer;
+    use source::Source;
+
+    #[test]
+    fn reports_clipped_samples() {
+        let data = vec![0.5f32, 1.5, 1.0, -0.5, -2.0, 0.0];
+        let source = SamplesBuffer::new(2, 44100, data.clone());
+
+        let mut events = Vec::new();
+        let output = source
+            .detect_clipping(|channel, value| events.push((channel, value)))
+            .collect::<Vec<_>>();
+
+        assert_eq!(output, data);
+        assert_eq!(events, [(1, 1.5), (0, -2.0)]);
+    }
+}
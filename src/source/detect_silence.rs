@@ -0,0 +1,197 @@
+use std::time::Duration;
+
+use conversions::{db_to_amplitude, duration_to_frames};
+use source::SeekError;
+use Sample;
+use Source;
+
+/// Internal function that builds a `DetectSilence` object.
+pub fn detect_silence<I, F>(
+    input: I, threshold_db: f32, window: Duration, callback: F,
+) -> DetectSilence<I, F>
+where
+    I: Source,
+    I::Item: Sample,
+    F: FnMut(bool),
+{
+    DetectSilence {
+        input,
+        threshold: db_to_amplitude(threshold_db),
+        window,
+        callback,
+        current_channel: 0,
+        window_frames: 0,
+        sum_squares: 0.0,
+        window_samples: 0,
+        silent: false,
+    }
+}
+
+/// Tap that calls a function when a source becomes silent or stops being silent.
+///
+/// The samples are passed through untouched. Their RMS level is measured over consecutive windows,
+/// and a window is silent if its level is under the threshold.
+#[derive(Clone, Debug)]
+pub struct DetectSilence<I, F> {
+    input: I,
+    // Threshold as an amplitude between `0.0` and `1.0`.
+    threshold: f32,
+    window: Duration,
+    callback: F,
+    // Channel of the next sample returned by `next`.
+    current_channel: u16,
+    // Number of frames of the current window read so far.
+    window_frames: u64,
+    sum_squares: f32,
+    window_samples: u32,
+    silent: bool,
+}
+
+impl<I, F> DetectSilence<I, F>
+where
+    I: Source,
+    I::Item: Sample,
+    F: FnMut(bool),
+{
+    /// Returns `true` if the last window was silent.
+    #[inline]
+    pub fn is_silent(&self) -> bool {
+        self.silent
+    }
+
+    /// Returns a reference to the inner source.
+    #[inline]
+    pub fn inner(&self) -> &I {
+        &self.input
+    }
+
+    /// Returns a mutable reference to the inner source.
+    #[inline]
+    pub fn inner_mut(&mut self) -> &mut I {
+        &mut self.input
+    }
+
+    /// Returns the inner source.
+    #[inline]
+    pub fn into_inner(self) -> I {
+        self.input
+    }
+
+    // Called at the start of each frame.
+    fn next_frame(&mut self) {
+        let window_len = duration_to_frames(self.window, self.input.sample_rate());
+
+        if self.window_frames >= window_len.max(1) {
+            let rms = (self.sum_squares / self.window_samples as f32).sqrt();
+            let silent = rms < self.threshold;
+            if silent != self.silent {
+                self.silent = silent;
+                (self.callback)(silent);
+            }
+
+            self.window_frames = 0;
+            self.sum_squares = 0.0;
+            self.window_samples = 0;
+        }
+
+        self.window_frames += 1;
+    }
+}
+
+impl<I, F> Iterator for DetectSilence<I, F>
+where
+    I: Source,
+    I::Item: Sample,
+    F: FnMut(bool),
+{
+    type Item = I::Item;
+
+    #[inline]
+    fn next(&mut self) -> Option<I::Item> {
+        let value = self.input.next()?;
+
+        if self.current_channel >= self.input.channels() {
+            self.current_channel = 0;
+        }
+        if self.current_channel == 0 {
+            self.next_frame();
+        }
+        self.current_channel += 1;
+
+        self.sum_squares += value.to_f32() * value.to_f32();
+        self.window_samples += 1;
+
+        Some(value)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.input.size_hint()
+    }
+}
+
+impl<I, F> ExactSizeIterator for DetectSilence<I, F>
+where
+    I: Source + ExactSizeIterator,
+    I::Item: Sample,
+    F: FnMut(bool),
+{
+}
+
+impl<I, F> Source for DetectSilence<I, F>
+where
+    I: Source,
+    I::Item: Sample,
+    F: FnMut(bool),
+{
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        self.input.current_frame_len()
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        self.input.channels()
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        self.input.sample_rate()
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        self.input.total_duration()
+    }
+
+    #[inline]
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        self.input.try_seek(pos)?;
+        self.current_channel = 0;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use buffer::SamplesBuffer;
+    use source::Source;
+
+    #[test]
+    fn reports_changes() {
+        let mut data = vec![0.5f32; 20];
+        data.extend(vec![0.0; 20]);
+        data.extend(vec![0.5; 20]);
+        let source = SamplesBuffer::new(1, 10, data.clone());
+
+        let mut events = Vec::new();
+        let output = source
+            .detect_silence(-40.0, Duration::from_millis(500), |silent| events.push(silent))
+            .collect::<Vec<_>>();
+
+        assert_eq!(output, data);
+        assert_eq!(events, [true, false]);
+    }
+}
@@ -0,0 +1,238 @@
+use std::fs::{self, File};
+use std::io::{self, BufReader, BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use conversions::duration_to_frames;
+use source::SeekError;
+use Sample;
+use Source;
+
+/// Internal function that builds a `DiskCache` object.
+pub fn disk_cache<I, P>(input: I, path: P) -> io::Result<DiskCache<I>>
+where
+    I: Source,
+    I::Item: Sample,
+    P: AsRef<Path>,
+{
+    let path = path.as_ref().to_path_buf();
+    let writer = BufWriter::new(File::create(&path)?);
+    let reader = BufReader::new(File::open(&path)?);
+
+    Ok(DiskCache {
+        channels: input.channels(),
+        sample_rate: input.sample_rate(),
+        total_duration: input.total_duration(),
+        input: Some(input),
+        path,
+        writer,
+        reader,
+        reading: false,
+        broken: false,
+        cached: 0,
+        position: 0,
+    })
+}
+
+/// Source that writes the samples of a source to a file while playing it, and reads them back
+/// from the file when seeking backwards.
+///
+/// The samples are stored as 32 bits floats. The number of channels and the sample rate of the
+/// source are read once at creation and are assumed not to change. The file is deleted when the
+/// `DiskCache` is dropped.
+pub struct DiskCache<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    // `None` once the source has ended.
+    input: Option<I>,
+    path: PathBuf,
+    writer: BufWriter<File>,
+    reader: BufReader<File>,
+    // True if the next sample is read from `reader`.
+    reading: bool,
+    // True if writing to the file failed, in which case the source can't be seeked anymore.
+    broken: bool,
+    // Number of samples in the file.
+    cached: u64,
+    // Index of the next sample.
+    position: u64,
+    channels: u16,
+    sample_rate: u32,
+    total_duration: Option<Duration>,
+}
+
+impl<I> DiskCache<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    /// Returns the path of the file the samples are written to.
+    #[inline]
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Returns true if the whole source has been written to the file.
+    #[inline]
+    pub fn is_complete(&self) -> bool {
+        self.input.is_none()
+    }
+
+    // Reads the next sample of the input and appends it to the file.
+    fn next_from_input(&mut self) -> Option<I::Item> {
+        let value = self.input.as_mut()?.next();
+        let value = match value {
+            Some(value) => value,
+            None => {
+                self.input = None;
+                // Errors are detected when reading the file back.
+                let _ = self.writer.flush();
+                return None;
+            },
+        };
+
+        if !self.broken {
+            let bytes = value.to_f32().to_bits().to_le_bytes();
+            match self.writer.write_all(&bytes) {
+                Ok(()) => self.cached += 1,
+                // The source can still be played without the file.
+                Err(_) => self.broken = true,
+            }
+        }
+        Some(value)
+    }
+
+    fn next_from_file(&mut self) -> io::Result<I::Item> {
+        if !self.reading {
+            self.writer.flush()?;
+            self.reader.seek(SeekFrom::Start(self.position * 4))?;
+            self.reading = true;
+        }
+
+        let mut bytes = [0; 4];
+        self.reader.read_exact(&mut bytes)?;
+        Ok(Sample::from_sample(&f32::from_bits(u32::from_le_bytes(bytes))))
+    }
+}
+
+impl<I> Drop for DiskCache<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+impl<I> Iterator for DiskCache<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    type Item = I::Item;
+
+    #[inline]
+    fn next(&mut self) -> Option<I::Item> {
+        if self.position < self.cached {
+            let value = self.next_from_file().ok()?;
+            self.position += 1;
+            return Some(value);
+        }
+
+        self.reading = false;
+        let value = self.next_from_input()?;
+        self.position += 1;
+        Some(value)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let from_file = self.cached.saturating_sub(self.position) as usize;
+        match self.input {
+            Some(ref input) => {
+                let (min, max) = input.size_hint();
+                (min + from_file, max.map(|max| max + from_file))
+            },
+            None => (from_file, Some(from_file)),
+        }
+    }
+}
+
+impl<I> Source for DiskCache<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        None
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        self.channels
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        self.total_duration
+    }
+
+    #[inline]
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        if self.broken {
+            return Err(SeekError::NotSupported {
+                underlying_source: ::std::any::type_name::<Self>(),
+            });
+        }
+
+        let frame = duration_to_frames(pos, self.sample_rate);
+        let target = frame * self.channels as u64;
+
+        if target <= self.cached {
+            self.position = target;
+            self.reading = false;
+            return Ok(());
+        }
+
+        // The samples up to the target are read from the input so that the file has no gap.
+        self.position = self.cached;
+        while self.position < target && self.next().is_some() {}
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use std::time::Duration;
+
+    use buffer::SamplesBuffer;
+    use source::Source;
+
+    #[test]
+    fn replays_from_file() {
+        let path = env::temp_dir().join("rodio_disk_cache_test.raw");
+        let data = vec![1i16, -2, 3, -4, 5, -6];
+        let mut source = SamplesBuffer::new(2, 2, data.clone()).cache_to_disk(&path).unwrap();
+
+        assert_eq!(source.by_ref().take(2).collect::<Vec<_>>(), [1, -2]);
+        source.try_seek(Duration::from_secs(2)).unwrap();
+        assert_eq!(source.next(), None);
+        assert!(source.is_complete());
+
+        source.try_seek(Duration::from_millis(500)).unwrap();
+        assert_eq!(source.by_ref().collect::<Vec<_>>(), [3, -4, 5, -6]);
+        source.try_seek(Duration::new(0, 0)).unwrap();
+        assert_eq!(source.collect::<Vec<_>>(), data);
+        assert!(!path.exists());
+    }
+}
@@ -0,0 +1,265 @@
+use std::mem;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use source::SeekError;
+use Sample;
+use Source;
+
+/// Internal function that builds a `Doppler` object.
+pub fn doppler<I>(input: I, velocity: f32, speed_of_sound: f32) -> Doppler<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    Doppler {
+        input,
+        velocity: Arc::new(AtomicU32::new(velocity.to_bits())),
+        speed_of_sound,
+        previous: Vec::new(),
+        next: Vec::new(),
+        // Makes sure that the first two frames are read before the first output frame.
+        position: 1.0,
+        step: 1.0,
+        input_ended: false,
+        current_channel: 0,
+    }
+}
+
+/// Filter that shifts the pitch of a source according to the velocity of its emitter relative to
+/// the listener.
+///
+/// The velocity is positive when the emitter moves away from the listener, which lowers the
+/// pitch, and negative when it comes closer, which raises it. The pitch is never raised more than
+/// four times.
+#[derive(Clone, Debug)]
+pub struct Doppler<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    input: I,
+    velocity: Arc<AtomicU32>,
+    speed_of_sound: f32,
+    // The two frames of the input around the position of the current frame.
+    previous: Vec<I::Item>,
+    next: Vec<I::Item>,
+    // Position of the current frame between `previous` and `next`, and increment per frame.
+    position: f32,
+    step: f32,
+    input_ended: bool,
+    // Channel of the next sample returned by `next`.
+    current_channel: u16,
+}
+
+/// Handle that changes the velocity of a `Doppler` from another thread.
+#[derive(Clone, Debug)]
+pub struct DopplerHandle {
+    velocity: Arc<AtomicU32>,
+}
+
+impl DopplerHandle {
+    /// Changes the velocity of the emitter relative to the listener.
+    #[inline]
+    pub fn set_velocity(&self, velocity: f32) {
+        self.velocity.store(velocity.to_bits(), Ordering::Relaxed);
+    }
+
+    /// Returns the velocity of the emitter relative to the listener.
+    #[inline]
+    pub fn velocity(&self) -> f32 {
+        f32::from_bits(self.velocity.load(Ordering::Relaxed))
+    }
+}
+
+impl<I> Doppler<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    /// Changes the velocity of the emitter relative to the listener.
+    #[inline]
+    pub fn set_velocity(&mut self, velocity: f32) {
+        self.velocity.store(velocity.to_bits(), Ordering::Relaxed);
+    }
+
+    /// Returns the velocity of the emitter relative to the listener.
+    #[inline]
+    pub fn velocity(&self) -> f32 {
+        f32::from_bits(self.velocity.load(Ordering::Relaxed))
+    }
+
+    /// Changes the speed of sound, in the same unit as the velocity.
+    #[inline]
+    pub fn set_speed_of_sound(&mut self, speed_of_sound: f32) {
+        self.speed_of_sound = speed_of_sound;
+    }
+
+    /// Returns a handle that can change the velocity after the source has been handed to a sink.
+    #[inline]
+    pub fn handle(&self) -> DopplerHandle {
+        DopplerHandle {
+            velocity: self.velocity.clone(),
+        }
+    }
+
+    /// Returns a reference to the inner source.
+    #[inline]
+    pub fn inner(&self) -> &I {
+        &self.input
+    }
+
+    /// Returns a mutable reference to the inner source.
+    #[inline]
+    pub fn inner_mut(&mut self) -> &mut I {
+        &mut self.input
+    }
+
+    /// Returns the inner source.
+    #[inline]
+    pub fn into_inner(self) -> I {
+        self.input
+    }
+
+    // Called at the start of each frame. Returns `false` once the input has been played entirely.
+    fn next_frame(&mut self) -> bool {
+        self.position += self.step;
+        while self.position >= 1.0 {
+            self.position -= 1.0;
+            if self.input_ended {
+                return false;
+            }
+            mem::swap(&mut self.previous, &mut self.next);
+            self.read_frame();
+        }
+
+        let velocity = f32::from_bits(self.velocity.load(Ordering::Relaxed));
+        let speed_of_sound = self.speed_of_sound;
+        self.step = speed_of_sound / (speed_of_sound + velocity).max(speed_of_sound * 0.25);
+        true
+    }
+
+    // Reads a frame of the input into `next`. Once the input ends, `next` is silent.
+    fn read_frame(&mut self) {
+        let channels = self.input.channels() as usize;
+        self.next.clear();
+        self.next.extend(self.input.by_ref().take(channels));
+
+        if self.next.is_empty() {
+            self.input_ended = true;
+            let len = self.previous.len();
+            self.next.resize(len, I::Item::zero_value());
+            return;
+        }
+        self.next.resize(channels, I::Item::zero_value());
+
+        // Frames with a different number of channels can't be interpolated.
+        if self.previous.len() != channels {
+            self.previous.clone_from(&self.next);
+        }
+    }
+}
+
+impl<I> Iterator for Doppler<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    type Item = I::Item;
+
+    #[inline]
+    fn next(&mut self) -> Option<I::Item> {
+        if self.current_channel as usize >= self.previous.len() {
+            self.current_channel = 0;
+        }
+        if self.current_channel == 0 && (!self.next_frame() || self.previous.is_empty()) {
+            return None;
+        }
+
+        let channel = self.current_channel as usize;
+        self.current_channel += 1;
+
+        let previous = self.previous[channel].to_f32();
+        let next = self.next[channel].to_f32();
+        let value = previous + (next - previous) * self.position;
+        Some(Sample::from_sample(&value))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, None)
+    }
+}
+
+impl<I> Source for Doppler<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        let channels = self.previous.len();
+        if self.current_channel != 0 && (self.current_channel as usize) < channels {
+            return Some(channels - self.current_channel as usize);
+        }
+        self.input.current_frame_len()
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        if self.previous.is_empty() {
+            self.input.channels()
+        } else {
+            self.previous.len() as u16
+        }
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        self.input.sample_rate()
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        None
+    }
+
+    #[inline]
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        self.input.try_seek(pos)?;
+        self.previous.clear();
+        self.next.clear();
+        self.position = 1.0;
+        self.step = 1.0;
+        self.input_ended = false;
+        self.current_channel = 0;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use buffer::SamplesBuffer;
+    use source::Source;
+
+    #[test]
+    fn still_emitter() {
+        let data = vec![0.1f32, 0.2, 0.3, 0.4, 0.5, 0.6];
+        let source = SamplesBuffer::new(2, 44100, data.clone()).doppler(0.0, 340.0);
+        assert_eq!(source.collect::<Vec<_>>(), data);
+    }
+
+    #[test]
+    fn receding_emitter() {
+        let source = SamplesBuffer::new(1, 44100, vec![0.0f32, 0.25, 0.5, 0.75]);
+        let mut source = source.doppler(340.0, 340.0);
+        assert_eq!(
+            source.by_ref().take(6).collect::<Vec<_>>(),
+            [0.0, 0.125, 0.25, 0.375, 0.5, 0.625]
+        );
+
+        source.handle().set_velocity(0.0);
+        assert_eq!(source.collect::<Vec<_>>(), [0.75]);
+    }
+}
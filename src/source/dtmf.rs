@@ -0,0 +1,198 @@
+use std::error::Error;
+use std::f32::consts::PI;
+use std::fmt;
+use std::time::Duration;
+
+use conversions::{duration_to_frames, frames_to_ns};
+use Source;
+
+const ROW_FREQS: [f32; 4] = [697.0, 770.0, 852.0, 941.0];
+const COLUMN_FREQS: [f32; 4] = [1209.0, 1336.0, 1477.0, 1633.0];
+const KEYPAD: [[char; 4]; 4] = [
+    ['1', '2', '3', 'A'],
+    ['4', '5', '6', 'B'],
+    ['7', '8', '9', 'C'],
+    ['*', '0', '#', 'D'],
+];
+
+/// A source that plays a sequence of DTMF (telephone keypad) digits, and then ends.
+///
+/// Each digit is made of two sines of equal amplitude, followed by silence.
+///
+/// Always has one channel.
+#[derive(Clone, Debug)]
+pub struct Dtmf {
+    sample_rate: u32,
+    // Frequencies of the two tones of each digit.
+    digits: Vec<(f32, f32)>,
+    tone_samples: u64,
+    gap_samples: u64,
+    // Index of the digit being played.
+    current_digit: usize,
+    // Position in the tone and gap of the current digit.
+    position: u64,
+}
+
+/// Error returned when building a `Dtmf` from a character that isn't on a DTMF keypad.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InvalidDtmfDigit(pub char);
+
+impl fmt::Display for InvalidDtmfDigit {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Invalid DTMF digit: {:?}", self.0)
+    }
+}
+
+impl Error for InvalidDtmfDigit {}
+
+impl Dtmf {
+    /// Builds a source that plays `digits` with tones and gaps of 100ms each.
+    ///
+    /// The valid digits are `0` to `9`, `*`, `#` and `A` to `D`.
+    ///
+    /// # Panic
+    ///
+    /// Panics if `sample_rate` is zero.
+    #[inline]
+    pub fn new(sample_rate: u32, digits: &str) -> Result<Dtmf, InvalidDtmfDigit> {
+        let duration = Duration::from_millis(100);
+        Dtmf::with_timing(sample_rate, digits, duration, duration)
+    }
+
+    /// Builds a source that plays `digits` with the given duration of tone and of silence for
+    /// each digit.
+    ///
+    /// The valid digits are `0` to `9`, `*`, `#` and `A` to `D`.
+    ///
+    /// # Panic
+    ///
+    /// Panics if `sample_rate` is zero.
+    pub fn with_timing(
+        sample_rate: u32, digits: &str, tone: Duration, gap: Duration,
+    ) -> Result<Dtmf, InvalidDtmfDigit> {
+        assert!(sample_rate != 0);
+
+        let digits = digits
+            .chars()
+            .map(|digit| {
+                let upper = digit.to_ascii_uppercase();
+                for (row, keys) in KEYPAD.iter().enumerate() {
+                    if let Some(column) = keys.iter().position(|&key| key == upper) {
+                        return Ok((ROW_FREQS[row], COLUMN_FREQS[column]));
+                    }
+                }
+                Err(InvalidDtmfDigit(digit))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let samples = |duration: Duration| duration_to_frames(duration, sample_rate);
+
+        Ok(Dtmf {
+            sample_rate,
+            digits,
+            tone_samples: samples(tone),
+            gap_samples: samples(gap),
+            current_digit: 0,
+            position: 0,
+        })
+    }
+
+    #[inline]
+    fn remaining_samples(&self) -> u64 {
+        let per_digit = self.tone_samples + self.gap_samples;
+        let remaining_digits = (self.digits.len() - self.current_digit) as u64;
+        (remaining_digits * per_digit).saturating_sub(self.position)
+    }
+}
+
+impl Iterator for Dtmf {
+    type Item = f32;
+
+    #[inline]
+    fn next(&mut self) -> Option<f32> {
+        // The digits have no samples at all if both the tone and the gap are empty.
+        if self.current_digit >= self.digits.len() || self.tone_samples + self.gap_samples == 0 {
+            return None;
+        }
+        if self.position >= self.tone_samples + self.gap_samples {
+            self.current_digit += 1;
+            self.position = 0;
+        }
+
+        let (low, high) = *self.digits.get(self.current_digit)?;
+
+        let value = if self.position < self.tone_samples {
+            let t = self.position as f32 / self.sample_rate as f32;
+            0.5 * (2.0 * PI * low * t).sin() + 0.5 * (2.0 * PI * high * t).sin()
+        } else {
+            0.0
+        };
+        self.position += 1;
+        Some(value)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.remaining_samples() as usize;
+        (remaining, Some(remaining))
+    }
+}
+
+impl ExactSizeIterator for Dtmf {}
+
+impl Source for Dtmf {
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        None
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        1
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        let per_digit = self.tone_samples + self.gap_samples;
+        let ns = frames_to_ns(self.digits.len() as u64 * per_digit, self.sample_rate);
+        Some(Duration::from_nanos(ns))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use source::{Dtmf, InvalidDtmfDigit, Source};
+
+    #[test]
+    fn tones_and_gaps() {
+        let source = Dtmf::new(8000, "1#").unwrap();
+        assert_eq!(source.len(), 3200);
+        assert_eq!(source.total_duration(), Some(Duration::from_millis(400)));
+
+        let samples = source.collect::<Vec<_>>();
+        assert!(samples[.. 800].iter().any(|s| s.abs() > 0.5));
+        assert!(samples[800 .. 1600].iter().all(|s| *s == 0.0));
+        assert!(samples[1600 .. 2400].iter().any(|s| s.abs() > 0.5));
+        assert!(samples[2400 ..].iter().all(|s| *s == 0.0));
+    }
+
+    #[test]
+    fn empty_tones_and_gaps() {
+        let zero = Duration::from_secs(0);
+        let mut source = Dtmf::with_timing(8000, "123", zero, zero).unwrap();
+        assert_eq!(source.len(), 0);
+        assert_eq!(source.next(), None);
+    }
+
+    #[test]
+    fn invalid_digit() {
+        assert_eq!(Dtmf::new(8000, "12x").unwrap_err(), InvalidDtmfDigit('x'));
+    }
+}
@@ -1,5 +1,6 @@
 use std::marker::PhantomData;
 use std::time::Duration;
+use source::SeekError;
 use Sample;
 use Source;
 
@@ -46,4 +47,10 @@ where
     fn total_duration(&self) -> Option<Duration> {
         Some(Duration::new(0, 0))
     }
+
+    #[inline]
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        let _ = pos;
+        Ok(())
+    }
 }
@@ -0,0 +1,207 @@
+use std::f32::consts::PI;
+use std::time::Duration;
+
+use source::SeekError;
+use Sample;
+use Source;
+
+/// How hard the high frequencies are driven into the saturation that creates the harmonics.
+const DRIVE: f32 = 4.0;
+
+/// Internal function that builds an `Exciter` object.
+pub fn exciter<I>(input: I, amount: f32) -> Exciter<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    Exciter {
+        input,
+        amount,
+        frequency: 3000.0,
+        sample_rate: 0,
+        coeff: 0.0,
+        states: Vec::new(),
+        current_channel: 0,
+    }
+}
+
+/// Filter that adds harmonics to the high frequencies of a source, which makes dull sounds
+/// brighter.
+///
+/// The frequencies above a cutoff, 3 kHz by default, are saturated, and the harmonics that the
+/// saturation creates above the cutoff are added to the sound.
+#[derive(Clone, Debug)]
+pub struct Exciter<I> {
+    input: I,
+    amount: f32,
+    frequency: f32,
+    // Sample rate that `coeff` was computed for.
+    sample_rate: u32,
+    // Coefficient of the high-pass filters.
+    coeff: f32,
+    // Last input and output of the two high-pass filters of each channel.
+    states: Vec<[f32; 4]>,
+    // Channel of the next sample returned by `next`.
+    current_channel: u16,
+}
+
+impl<I> Exciter<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    /// Modifies how much of the harmonics is added to the sound.
+    #[inline]
+    pub fn set_amount(&mut self, amount: f32) {
+        self.amount = amount;
+    }
+
+    /// Modifies the frequency in Hertz above which harmonics are created.
+    #[inline]
+    pub fn set_frequency(&mut self, frequency: f32) {
+        self.frequency = frequency;
+        self.sample_rate = 0;
+    }
+
+    /// Returns a reference to the inner source.
+    #[inline]
+    pub fn inner(&self) -> &I {
+        &self.input
+    }
+
+    /// Returns a mutable reference to the inner source.
+    #[inline]
+    pub fn inner_mut(&mut self) -> &mut I {
+        &mut self.input
+    }
+
+    /// Returns the inner source.
+    #[inline]
+    pub fn into_inner(self) -> I {
+        self.input
+    }
+
+    // Called at the start of each frame.
+    fn next_frame(&mut self) {
+        let sample_rate = self.input.sample_rate();
+        if sample_rate != self.sample_rate {
+            self.sample_rate = sample_rate;
+            let rc = 1.0 / (2.0 * PI * self.frequency);
+            let dt = 1.0 / sample_rate as f32;
+            self.coeff = rc / (rc + dt);
+        }
+
+        let channels = self.input.channels() as usize;
+        if self.states.len() != channels {
+            self.states.resize(channels, [0.0; 4]);
+        }
+    }
+}
+
+impl<I> Iterator for Exciter<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    type Item = I::Item;
+
+    #[inline]
+    fn next(&mut self) -> Option<I::Item> {
+        if self.current_channel >= self.input.channels() {
+            self.current_channel = 0;
+        }
+        if self.current_channel == 0 {
+            self.next_frame();
+        }
+        let channel = self.current_channel as usize;
+        self.current_channel += 1;
+
+        let value = self.input.next()?;
+        if self.amount == 0.0 {
+            return Some(value);
+        }
+
+        let coeff = self.coeff;
+        let state = &mut self.states[channel];
+        let x = value.to_f32();
+
+        // Keeps the high frequencies, saturates them, and removes what the saturation created
+        // under the cutoff.
+        let high = coeff * (state[1] + x - state[0]);
+        let saturated = (high * DRIVE).tanh() / DRIVE;
+        let harmonics = coeff * (state[3] + saturated - state[2]);
+        *state = [x, high, saturated, harmonics];
+
+        Some(Sample::from_sample(&(x + harmonics * self.amount)))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.input.size_hint()
+    }
+}
+
+impl<I> ExactSizeIterator for Exciter<I>
+where
+    I: Source + ExactSizeIterator,
+    I::Item: Sample,
+{
+}
+
+impl<I> Source for Exciter<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        self.input.current_frame_len()
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        self.input.channels()
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        self.input.sample_rate()
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        self.input.total_duration()
+    }
+
+    #[inline]
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        self.input.try_seek(pos)?;
+        self.current_channel = 0;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use source::{SineWave, Source};
+
+    // Returns the highest difference between a sine wave and the same wave through an exciter.
+    fn added(freq: u32, amount: f32) -> f32 {
+        let source = SineWave::new(freq).excite(amount).take(44100);
+        source
+            .zip(SineWave::new(freq))
+            .skip(1000)
+            .fold(0.0, |max, (a, b)| max.max((a - b).abs()))
+    }
+
+    #[test]
+    fn brightens_high_frequencies() {
+        assert!(added(6000, 1.0) > 0.05);
+    }
+
+    #[test]
+    fn leaves_low_frequencies() {
+        assert!(added(100, 1.0) < 0.05);
+        assert_eq!(added(6000, 0.0), 0.0);
+    }
+}
@@ -0,0 +1,198 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use conversions::{duration_to_ns, frames_to_ns};
+use source::SeekError;
+use Sample;
+use Source;
+
+/// Internal function that builds a `FadeStop` object.
+pub fn fade_stop<I>(input: I, duration: Duration) -> FadeStop<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    FadeStop {
+        input,
+        stop: Arc::new(AtomicBool::new(false)),
+        fade_ns: duration_to_ns(duration),
+        elapsed_ns: None,
+        gain: 1.0,
+        current_channel: 0,
+        finished: false,
+    }
+}
+
+/// Filter that fades a source out and then ends it once it is told to stop.
+#[derive(Clone, Debug)]
+pub struct FadeStop<I> {
+    input: I,
+    stop: Arc<AtomicBool>,
+    fade_ns: u64,
+    // Time elapsed since the start of the fade, if it has started.
+    elapsed_ns: Option<u64>,
+    gain: f32,
+    // Channel of the next sample returned by `next`.
+    current_channel: u16,
+    // Set once the fade is over.
+    finished: bool,
+}
+
+/// Handle that stops a `FadeStop` from another thread.
+#[derive(Clone, Debug)]
+pub struct FadeStopHandle {
+    stop: Arc<AtomicBool>,
+}
+
+impl FadeStopHandle {
+    /// Starts fading the source out. It ends once the fade is over.
+    #[inline]
+    pub fn stop(&self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
+
+impl<I> FadeStop<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    /// Starts fading the source out. It ends once the fade is over.
+    #[inline]
+    pub fn stop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+
+    /// Returns a handle that can stop the source after it has been handed to a sink.
+    #[inline]
+    pub fn handle(&self) -> FadeStopHandle {
+        FadeStopHandle {
+            stop: self.stop.clone(),
+        }
+    }
+
+    /// Returns a reference to the inner source.
+    #[inline]
+    pub fn inner(&self) -> &I {
+        &self.input
+    }
+
+    /// Returns a mutable reference to the inner source.
+    #[inline]
+    pub fn inner_mut(&mut self) -> &mut I {
+        &mut self.input
+    }
+
+    /// Returns the inner source.
+    #[inline]
+    pub fn into_inner(self) -> I {
+        self.input
+    }
+
+    // Called at the start of each frame. Returns `false` once the fade is over.
+    fn next_frame(&mut self) -> bool {
+        if self.elapsed_ns.is_none() && self.stop.load(Ordering::Relaxed) {
+            self.elapsed_ns = Some(0);
+        }
+
+        if let Some(elapsed_ns) = self.elapsed_ns {
+            if elapsed_ns >= self.fade_ns {
+                self.finished = true;
+                return false;
+            }
+            self.gain = 1.0 - elapsed_ns as f32 / self.fade_ns as f32;
+            self.elapsed_ns = Some(elapsed_ns + frames_to_ns(1, self.input.sample_rate()));
+        }
+        true
+    }
+}
+
+impl<I> Iterator for FadeStop<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    type Item = I::Item;
+
+    #[inline]
+    fn next(&mut self) -> Option<I::Item> {
+        if self.finished {
+            return None;
+        }
+        if self.current_channel >= self.input.channels() {
+            self.current_channel = 0;
+        }
+        if self.current_channel == 0 && !self.next_frame() {
+            return None;
+        }
+        self.current_channel += 1;
+
+        self.input.next().map(|value| value.amplify(self.gain))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, self.input.size_hint().1)
+    }
+}
+
+impl<I> Source for FadeStop<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        if self.finished {
+            return Some(0);
+        }
+        self.input.current_frame_len()
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        self.input.channels()
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        self.input.sample_rate()
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        self.input.total_duration()
+    }
+
+    #[inline]
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        self.input.try_seek(pos)?;
+        self.current_channel = 0;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use buffer::SamplesBuffer;
+    use source::Source;
+
+    #[test]
+    fn fades_then_ends() {
+        let source = SamplesBuffer::new(2, 4, vec![1.0f32; 20]);
+        let mut source = source.stoppable_with_fade(Duration::from_secs(1));
+        let handle = source.handle();
+
+        assert_eq!(source.next(), Some(1.0));
+        assert_eq!(source.next(), Some(1.0));
+        handle.stop();
+        assert_eq!(
+            source.by_ref().collect::<Vec<_>>(),
+            [1.0, 1.0, 0.75, 0.75, 0.5, 0.5, 0.25, 0.25]
+        );
+        assert_eq!(source.current_frame_len(), Some(0));
+    }
+}
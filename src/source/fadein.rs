@@ -1,5 +1,7 @@
 use std::time::Duration;
 
+use conversions::duration_to_ns;
+use source::SeekError;
 use Sample;
 use Source;
 
@@ -9,7 +11,7 @@ where
     I: Source,
     I::Item: Sample,
 {
-    let duration = duration.as_secs() * 1000000000 + duration.subsec_nanos() as u64;
+    let duration = duration_to_ns(duration);
 
     FadeIn {
         input: input,
@@ -106,4 +108,12 @@ where
     fn total_duration(&self) -> Option<Duration> {
         self.input.total_duration()
     }
+
+    #[inline]
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        self.input.try_seek(pos)?;
+        let pos_ns = duration_to_ns(pos);
+        self.remaining_ns = (self.total_ns - pos_ns as f32).max(0.0);
+        Ok(())
+    }
 }
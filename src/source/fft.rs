@@ -0,0 +1,55 @@
+use std::f32::consts::PI;
+
+/// In-place iterative radix-2 FFT. The length must be a power of two.
+pub(crate) fn fft(real: &mut [f32], imag: &mut [f32]) {
+    let len = real.len();
+
+    let mut j = 0;
+    for i in 1 .. len {
+        let mut bit = len >> 1;
+        while j & bit != 0 {
+            j ^= bit;
+            bit >>= 1;
+        }
+        j |= bit;
+        if i < j {
+            real.swap(i, j);
+            imag.swap(i, j);
+        }
+    }
+
+    let mut size = 2;
+    while size <= len {
+        let angle = -2.0 * PI / size as f32;
+        for start in (0 .. len).step_by(size) {
+            for k in 0 .. size / 2 {
+                let (w_im, w_re) = (angle * k as f32).sin_cos();
+                let a = start + k;
+                let b = a + size / 2;
+                let t_re = real[b] * w_re - imag[b] * w_im;
+                let t_im = real[b] * w_im + imag[b] * w_re;
+                real[b] = real[a] - t_re;
+                imag[b] = imag[a] - t_im;
+                real[a] += t_re;
+                imag[a] += t_im;
+            }
+        }
+        size *= 2;
+    }
+}
+
+/// In-place inverse of `fft`, including the division by the length.
+pub(crate) fn ifft(real: &mut [f32], imag: &mut [f32]) {
+    for value in imag.iter_mut() {
+        *value = -*value;
+    }
+    fft(real, imag);
+
+    let scale = 1.0 / real.len() as f32;
+    for value in real.iter_mut() {
+        *value *= scale;
+    }
+    for value in imag.iter_mut() {
+        *value *= -scale;
+    }
+}
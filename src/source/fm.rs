@@ -0,0 +1,105 @@
+use std::f32::consts::PI;
+use std::time::Duration;
+use Source;
+
+/// An infinite source that produces a two-operator FM (frequency modulation) tone.
+///
+/// A sine modulator whose frequency is `ratio` times the carrier frequency modulates the phase of
+/// a sine carrier. The modulation index controls how strong the modulation is, and therefore how
+/// bright the sound is: an index of `0.0` produces a pure sine.
+///
+/// Always has a rate of 48kHz and one channel.
+#[derive(Clone, Debug)]
+pub struct FmSynth {
+    carrier_freq: f32,
+    ratio: f32,
+    index: f32,
+    // Positions in the current periods, between 0 and 1.
+    carrier_phase: f32,
+    modulator_phase: f32,
+}
+
+impl FmSynth {
+    /// Builds a new FM source.
+    #[inline]
+    pub fn new(carrier_freq: f32, ratio: f32, index: f32) -> FmSynth {
+        FmSynth {
+            carrier_freq,
+            ratio,
+            index,
+            carrier_phase: 0.0,
+            modulator_phase: 0.0,
+        }
+    }
+
+    /// Modifies the frequency of the carrier. The modulator follows it.
+    #[inline]
+    pub fn set_carrier_frequency(&mut self, freq: f32) {
+        self.carrier_freq = freq;
+    }
+
+    /// Modifies the ratio between the frequency of the modulator and the one of the carrier.
+    #[inline]
+    pub fn set_ratio(&mut self, ratio: f32) {
+        self.ratio = ratio;
+    }
+
+    /// Modifies the modulation index.
+    #[inline]
+    pub fn set_index(&mut self, index: f32) {
+        self.index = index;
+    }
+}
+
+impl Iterator for FmSynth {
+    type Item = f32;
+
+    #[inline]
+    fn next(&mut self) -> Option<f32> {
+        let modulator = (2.0 * PI * self.modulator_phase).sin();
+        let value = (2.0 * PI * self.carrier_phase + self.index * modulator).sin();
+
+        let step = self.carrier_freq / 48000.0;
+        self.carrier_phase = (self.carrier_phase + step) % 1.0;
+        self.modulator_phase = (self.modulator_phase + step * self.ratio) % 1.0;
+        Some(value)
+    }
+}
+
+impl Source for FmSynth {
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        None
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        1
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        48000
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::PI;
+
+    use source::FmSynth;
+
+    #[test]
+    fn zero_index_is_sine() {
+        let samples = FmSynth::new(1000.0, 2.0, 0.0).take(48).collect::<Vec<_>>();
+        for (n, sample) in samples.into_iter().enumerate() {
+            let expected = (2.0 * PI * 1000.0 * n as f32 / 48000.0).sin();
+            assert!((sample - expected).abs() < 1e-3);
+        }
+    }
+}
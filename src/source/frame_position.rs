@@ -0,0 +1,86 @@
+use conversions::frames_to_ns;
+
+/// Position in a source, counted in frames, that follows the changes of its sample rate.
+///
+/// The adapters count each sample with `is_frame_start`, and each frame with `next_frame`.
+#[derive(Clone, Debug)]
+pub(crate) struct FramePosition {
+    // Channel of the next sample.
+    current_channel: u16,
+    // Position at the last change of sample rate, and number of frames counted since.
+    offset_ns: u64,
+    rate: u32,
+    frames: u64,
+}
+
+impl FramePosition {
+    #[inline]
+    pub(crate) fn new(sample_rate: u32) -> FramePosition {
+        FramePosition {
+            current_channel: 0,
+            offset_ns: 0,
+            rate: sample_rate,
+            frames: 0,
+        }
+    }
+
+    /// Counts a sample of a source with the given number of channels. Returns true if it is the
+    /// first sample of a frame.
+    #[inline]
+    pub(crate) fn is_frame_start(&mut self, channels: u16) -> bool {
+        if self.current_channel >= channels {
+            self.current_channel = 0;
+        }
+        self.current_channel += 1;
+        self.current_channel == 1
+    }
+
+    /// Counts a frame at the given sample rate, and returns the position of its start in
+    /// nanoseconds.
+    #[inline]
+    pub(crate) fn next_frame(&mut self, sample_rate: u32) -> u64 {
+        if sample_rate != self.rate {
+            self.offset_ns = self.position_ns();
+            self.rate = sample_rate;
+            self.frames = 0;
+        }
+        let position_ns = self.position_ns();
+        self.frames += 1;
+        position_ns
+    }
+
+    /// Returns the position of the end of the frames counted so far, in nanoseconds.
+    #[inline]
+    pub(crate) fn position_ns(&self) -> u64 {
+        self.offset_ns + frames_to_ns(self.frames, self.rate)
+    }
+
+    /// Moves to the start of a frame at the given position, in nanoseconds.
+    #[inline]
+    pub(crate) fn set_position_ns(&mut self, position_ns: u64, sample_rate: u32) {
+        self.current_channel = 0;
+        self.offset_ns = position_ns;
+        self.rate = sample_rate;
+        self.frames = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use source::frame_position::FramePosition;
+
+    #[test]
+    fn follows_rate_changes() {
+        let mut position = FramePosition::new(10);
+        assert!(position.is_frame_start(2));
+        assert!(!position.is_frame_start(2));
+        assert_eq!(position.next_frame(10), 0);
+        assert_eq!(position.next_frame(10), 100_000_000);
+        assert_eq!(position.next_frame(20), 200_000_000);
+        assert_eq!(position.position_ns(), 250_000_000);
+
+        position.set_position_ns(1_000_000_000, 10);
+        assert!(position.is_frame_start(2));
+        assert_eq!(position.next_frame(10), 1_000_000_000);
+    }
+}
@@ -36,3 +36,84 @@ where
         (0, None)
     }
 }
+
+/// Builds a source that chains sources built on demand by a list of factories.
+///
+/// Each element of `factories` is a closure that produces a source, for example by opening and
+/// decoding a file. The first closure is called immediately, and each of the next ones is only
+/// called when the previous source ends, so that a long playlist doesn't open all of its files up
+/// front.
+///
+/// If a closure returns `None`, for example because a file couldn't be opened, then the next
+/// closure is called instead. The sound ends after the last closure.
+pub fn from_factories<I, F, S>(factories: I) -> FromIter<FromFactoriesIter<I::IntoIter>>
+where
+    I: IntoIterator<Item = F>,
+    F: FnOnce() -> Option<S>,
+{
+    from_iter(FromFactoriesIter {
+        factories: factories.into_iter(),
+    })
+}
+
+/// Internal type used by `from_factories`.
+pub struct FromFactoriesIter<I> {
+    factories: I,
+}
+
+impl<I, F, S> Iterator for FromFactoriesIter<I>
+where
+    I: Iterator<Item = F>,
+    F: FnOnce() -> Option<S>,
+{
+    type Item = S;
+
+    #[inline]
+    fn next(&mut self) -> Option<S> {
+        loop {
+            let factory = self.factories.next()?;
+            if let Some(source) = factory() {
+                return Some(source);
+            }
+        }
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, self.factories.size_hint().1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+
+    use buffer::SamplesBuffer;
+    use source::from_factories;
+
+    #[test]
+    fn factories_are_lazy() {
+        let calls = Cell::new(0);
+        let factory = |value: i16| {
+            let calls = &calls;
+            move || {
+                calls.set(calls.get() + 1);
+                if value == 0 {
+                    None
+                } else {
+                    Some(SamplesBuffer::new(1, 44100, vec![value; 2]))
+                }
+            }
+        };
+
+        let mut source = from_factories(vec![factory(1), factory(0), factory(2)]);
+        assert_eq!(calls.get(), 1);
+        assert_eq!(source.next(), Some(1));
+        assert_eq!(source.next(), Some(1));
+        assert_eq!(calls.get(), 1);
+        assert_eq!(source.next(), Some(2));
+        assert_eq!(calls.get(), 3);
+        assert_eq!(source.next(), Some(2));
+        assert_eq!(source.next(), None);
+    }
+}
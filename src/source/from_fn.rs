@@ -0,0 +1,115 @@
+use std::time::Duration;
+
+use Source;
+
+/// Builds an infinite source whose samples are computed by a closure.
+///
+/// The closure is called once per frame with the time elapsed since the start of the source, in
+/// seconds, and the value that it returns is played on every channel. The time is an `f64`, which
+/// keeps it precise to the sample even after hours of playback.
+///
+/// # Example
+///
+/// ```
+/// use std::f64::consts::PI;
+/// use rodio::source::from_fn;
+///
+/// // A 440Hz sine.
+/// let source = from_fn(44100, 1, |t| (2.0 * PI * 440.0 * t).sin() as f32);
+/// ```
+///
+/// # Panic
+///
+/// Panics if `sample_rate` or `channels` is zero.
+pub fn from_fn<F>(sample_rate: u32, channels: u16, function: F) -> FromFn<F>
+where
+    F: FnMut(f64) -> f32,
+{
+    assert!(sample_rate != 0);
+    assert!(channels != 0);
+
+    FromFn {
+        function,
+        sample_rate,
+        channels,
+        frame: 0,
+        current_channel: 0,
+        value: 0.0,
+    }
+}
+
+/// A source whose samples are computed by a closure of time.
+#[derive(Clone, Debug)]
+pub struct FromFn<F> {
+    function: F,
+    sample_rate: u32,
+    channels: u16,
+    // Index of the next frame.
+    frame: u64,
+    // Channel of the next sample returned by `next`.
+    current_channel: u16,
+    // Value of the current frame.
+    value: f32,
+}
+
+impl<F> Iterator for FromFn<F>
+where
+    F: FnMut(f64) -> f32,
+{
+    type Item = f32;
+
+    #[inline]
+    fn next(&mut self) -> Option<f32> {
+        if self.current_channel == 0 {
+            let t = self.frame as f64 / self.sample_rate as f64;
+            self.value = (self.function)(t);
+            self.frame += 1;
+        }
+
+        self.current_channel += 1;
+        if self.current_channel == self.channels {
+            self.current_channel = 0;
+        }
+
+        Some(self.value)
+    }
+}
+
+impl<F> Source for FromFn<F>
+where
+    F: FnMut(f64) -> f32,
+{
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        None
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        self.channels
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use source::from_fn;
+
+    #[test]
+    fn time_per_frame() {
+        let source = from_fn(4, 2, |t| t as f32);
+        assert_eq!(
+            source.take(6).collect::<Vec<_>>(),
+            [0.0, 0.0, 0.25, 0.25, 0.5, 0.5]
+        );
+    }
+}
@@ -0,0 +1,283 @@
+use std::f32::consts::PI;
+use std::sync::Arc;
+use std::time::Duration;
+
+use buffer::SamplesBuffer;
+use conversions::{duration_to_frames, Random};
+use Sample;
+use Source;
+
+/// An infinite source that plays many short, overlapping extracts of a buffer, called grains.
+///
+/// Every grain starts at the current position in the buffer, optionally moved by a random amount,
+/// and is played with a Hann window at a pitch that can also be randomized. Grains that reach the
+/// end of the buffer wrap around to its start.
+///
+/// By default, 20 grains of 50ms are started per second, at the start of the buffer, and the
+/// position doesn't move. Moving the position slowly with `set_scan_speed` stretches the buffer
+/// in time without changing its pitch.
+#[derive(Clone, Debug)]
+pub struct Granular {
+    // Interleaved samples of the buffer.
+    data: Arc<Vec<f32>>,
+    channels: u16,
+    sample_rate: u32,
+    grain_frames: usize,
+    // Number of grains started per second.
+    density: f32,
+    // Position between `0.0` and `1.0`.
+    position: f64,
+    position_jitter: f32,
+    scan_speed: f32,
+    pitch: f32,
+    pitch_jitter: f32,
+    grains: Vec<Grain>,
+    // Number of frames until the next grain starts.
+    frames_until_grain: f32,
+    // Samples of the current output frame.
+    frame: Vec<f32>,
+    // Channel of the next sample returned by `next`.
+    current_channel: u16,
+    random: Random,
+}
+
+#[derive(Clone, Debug)]
+struct Grain {
+    // Position in the buffer of the start of the grain, in frames.
+    start: f64,
+    // Number of frames of the buffer read per frame of output.
+    step: f64,
+    // Number of frames of the grain played so far.
+    age: usize,
+    len: usize,
+}
+
+impl Granular {
+    /// Builds a new granular source from a buffer, with a random seed.
+    #[inline]
+    pub fn new<S>(buffer: SamplesBuffer<S>) -> Granular
+    where
+        S: Sample,
+    {
+        Granular::with_random(buffer, Random::new())
+    }
+
+    /// Builds a new granular source from a buffer, that always produces the same samples for a
+    /// given seed.
+    #[inline]
+    pub fn with_seed<S>(buffer: SamplesBuffer<S>, seed: u64) -> Granular
+    where
+        S: Sample,
+    {
+        Granular::with_random(buffer, Random::with_seed(seed))
+    }
+
+    fn with_random<S>(buffer: SamplesBuffer<S>, random: Random) -> Granular
+    where
+        S: Sample,
+    {
+        let channels = buffer.channels();
+        let sample_rate = buffer.sample_rate();
+        let data = buffer.map(|s| s.to_f32()).collect();
+
+        Granular {
+            data: Arc::new(data),
+            channels,
+            sample_rate,
+            grain_frames: sample_rate as usize / 20,
+            density: 20.0,
+            position: 0.0,
+            position_jitter: 0.0,
+            scan_speed: 0.0,
+            pitch: 1.0,
+            pitch_jitter: 0.0,
+            grains: Vec::new(),
+            frames_until_grain: 0.0,
+            frame: vec![0.0; channels as usize],
+            current_channel: channels,
+            random,
+        }
+    }
+
+    /// Modifies the duration of the grains that are started from now on.
+    #[inline]
+    pub fn set_grain_size(&mut self, size: Duration) {
+        self.grain_frames = duration_to_frames(size, self.sample_rate).max(1) as usize;
+    }
+
+    /// Modifies the number of grains started per second.
+    #[inline]
+    pub fn set_density(&mut self, grains_per_second: f32) {
+        self.density = grains_per_second.max(0.0);
+    }
+
+    /// Modifies the position in the buffer where grains start, between `0.0` and `1.0`.
+    #[inline]
+    pub fn set_position(&mut self, position: f32) {
+        self.position = position.clamp(0.0, 1.0) as f64;
+    }
+
+    /// Returns the position in the buffer where grains start, between `0.0` and `1.0`.
+    #[inline]
+    pub fn position(&self) -> f32 {
+        self.position as f32
+    }
+
+    /// Modifies the maximum random offset added to the start of each grain, as a fraction of the
+    /// length of the buffer.
+    #[inline]
+    pub fn set_position_jitter(&mut self, jitter: f32) {
+        self.position_jitter = jitter.max(0.0);
+    }
+
+    /// Modifies the speed at which the position moves through the buffer. `1.0` moves at the
+    /// speed of playback, and `0.1` stretches the buffer to ten times its duration.
+    #[inline]
+    pub fn set_scan_speed(&mut self, speed: f32) {
+        self.scan_speed = speed;
+    }
+
+    /// Modifies the playback speed of the grains, `1.0` keeping the original pitch.
+    #[inline]
+    pub fn set_pitch(&mut self, pitch: f32) {
+        self.pitch = pitch;
+    }
+
+    /// Modifies the maximum random change of pitch of each grain, in semitones.
+    #[inline]
+    pub fn set_pitch_jitter(&mut self, semitones: f32) {
+        self.pitch_jitter = semitones.max(0.0);
+    }
+
+    fn start_grain(&mut self) {
+        let frames = (self.data.len() / self.channels as usize) as f64;
+        let jitter = self.position_jitter * self.random.signed();
+        let start = (self.position + jitter as f64) * frames;
+        let semitones = self.pitch_jitter * self.random.signed();
+        let step = self.pitch * 2f32.powf(semitones / 12.0);
+
+        self.grains.push(Grain {
+            start: start.rem_euclid(frames),
+            step: step as f64,
+            age: 0,
+            len: self.grain_frames,
+        });
+    }
+
+    // Computes the next output frame.
+    fn next_frame(&mut self) {
+        let frames = self.data.len() / self.channels as usize;
+        if frames == 0 {
+            return;
+        }
+
+        if self.density > 0.0 {
+            while self.frames_until_grain <= 0.0 {
+                self.start_grain();
+                self.frames_until_grain += self.sample_rate as f32 / self.density;
+            }
+            self.frames_until_grain -= 1.0;
+        }
+
+        // The Hann window averages to `0.5`, so the output keeps the level of the buffer as long as
+        // two grains or more overlap.
+        let grain_secs = self.grain_frames as f32 / self.sample_rate as f32;
+        let gain = 1.0 / (self.density * grain_secs * 0.5).max(1.0);
+
+        for value in &mut self.frame {
+            *value = 0.0;
+        }
+
+        let channels = self.channels as usize;
+        let data = &self.data;
+        let frame = &mut self.frame;
+        self.grains.retain(|grain| grain.age < grain.len);
+        for grain in &mut self.grains {
+            let window = 0.5 - 0.5 * (2.0 * PI * grain.age as f32 / grain.len as f32).cos();
+            let pos = (grain.start + grain.age as f64 * grain.step).rem_euclid(frames as f64);
+            let index = pos as usize;
+            let next_index = (index + 1) % frames;
+            let frac = (pos - index as f64) as f32;
+
+            for (channel, value) in frame.iter_mut().enumerate() {
+                let a = data[index * channels + channel];
+                let b = data[next_index * channels + channel];
+                *value += (a + (b - a) * frac) * window * gain;
+            }
+            grain.age += 1;
+        }
+
+        let step = self.scan_speed as f64 / frames as f64;
+        self.position = (self.position + step).rem_euclid(1.0);
+    }
+}
+
+impl Iterator for Granular {
+    type Item = f32;
+
+    #[inline]
+    fn next(&mut self) -> Option<f32> {
+        if self.current_channel >= self.channels {
+            self.next_frame();
+            self.current_channel = 0;
+        }
+
+        let value = self.frame[self.current_channel as usize];
+        self.current_channel += 1;
+        Some(value)
+    }
+}
+
+impl Source for Granular {
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        None
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        self.channels
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use buffer::SamplesBuffer;
+    use source::{Granular, Source};
+
+    #[test]
+    fn back_to_back_grains() {
+        let buffer = SamplesBuffer::new(2, 100, vec![1.0f32; 200]);
+        let mut source = Granular::with_seed(buffer, 1);
+        source.set_grain_size(Duration::from_millis(100));
+        source.set_density(10.0);
+
+        assert_eq!(source.channels(), 2);
+        let samples = source.take(40).collect::<Vec<_>>();
+        assert_eq!(samples[0], 0.0);
+        assert_eq!(samples[0], samples[1]);
+        // Each grain is a Hann window over 10 frames.
+        let sum = samples.iter().sum::<f32>();
+        assert!((sum - 20.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn zero_density_is_silent() {
+        let buffer = SamplesBuffer::new(1, 100, vec![1.0f32; 100]);
+        let mut source = Granular::with_seed(buffer, 1);
+        source.set_density(0.0);
+        assert!(source.take(100).all(|s| s == 0.0));
+    }
+}
@@ -0,0 +1,456 @@
+use std::f32::consts::PI;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use source::uniform::UniformSourceIterator;
+use source::{SeekError, ToMono};
+use Sample;
+use Source;
+
+// Radius of the head and speed of sound used by `HrirSet::spherical_head`.
+const HEAD_RADIUS: f32 = 0.0875;
+const SPEED_OF_SOUND: f32 = 343.0;
+
+// Reflections on the pinna used by `HrirSet::spherical_head`, as gain, delay scale and delay
+// offset in samples at 44100 Hz.
+const PINNA_ECHOES: [(f32, f32, f32); 5] = [
+    (0.5, 1.0, 2.0),
+    (-1.0, 5.0, 4.0),
+    (0.5, 5.0, 7.0),
+    (-0.25, 5.0, 11.0),
+    (0.25, 5.0, 13.0),
+];
+
+// Number of samples over which the output moves from an impulse response to the next one.
+const CROSSFADE_LEN: u32 = 256;
+
+/// Internal function that builds a `Hrtf` object.
+pub fn hrtf<I>(input: I, hrirs: Arc<HrirSet>, azimuth: f32, elevation: f32) -> Hrtf<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    assert!(!hrirs.entries.is_empty(), "The set of impulse responses is empty");
+
+    let sample_rate = hrirs.sample_rate;
+    let len = hrirs.len;
+    let current = hrirs.nearest(azimuth, elevation);
+
+    Hrtf {
+        input: UniformSourceIterator::new(input.to_mono(), 1, sample_rate),
+        hrirs,
+        direction: Arc::new(Direction {
+            azimuth: AtomicU32::new(azimuth.to_bits()),
+            elevation: AtomicU32::new(elevation.to_bits()),
+        }),
+        last_direction: (azimuth.to_bits(), elevation.to_bits()),
+        current,
+        previous: current,
+        crossfade: CROSSFADE_LEN,
+        history: vec![0.0; 2 * len],
+        history_pos: 0,
+        remaining_tail: len,
+        right: None,
+    }
+}
+
+/// A set of head-related impulse responses, measured or computed for many directions around the
+/// head of a listener.
+///
+/// Directions are given in degrees. An azimuth of `0.0` is in front of the listener and an
+/// azimuth of `90.0` is on their left. An elevation of `0.0` is at the height of the ears and an
+/// elevation of `90.0` is above the head.
+#[derive(Clone, Debug)]
+pub struct HrirSet {
+    sample_rate: u32,
+    // Length of the longest impulse response. The others are padded with zeroes.
+    len: usize,
+    entries: Vec<Hrir>,
+}
+
+#[derive(Clone, Debug)]
+struct Hrir {
+    // Unit vector pointing to the direction of the response.
+    direction: [f32; 3],
+    left: Vec<f32>,
+    right: Vec<f32>,
+}
+
+impl HrirSet {
+    /// Builds an empty set of impulse responses recorded at the given sample rate.
+    #[inline]
+    pub fn new(sample_rate: u32) -> HrirSet {
+        HrirSet {
+            sample_rate,
+            len: 0,
+            entries: Vec::new(),
+        }
+    }
+
+    /// Builds a set of impulse responses computed from a model of a spherical head.
+    ///
+    /// The model combines the delay and the shadowing of the head with a few reflections on the
+    /// outer ear. It is less accurate than measured responses, but it gives elevation and
+    /// front/back cues.
+    pub fn spherical_head(sample_rate: u32) -> HrirSet {
+        let len = (128 * sample_rate as usize).div_ceil(44100);
+        let mut set = HrirSet::new(sample_rate);
+
+        for elevation in (-3 ..= 6).map(|e| e as f32 * 15.0) {
+            for azimuth in (0 .. 24).map(|a| a as f32 * 15.0) {
+                let left = spherical_head_response(sample_rate, len, azimuth, elevation, 1.0);
+                let right = spherical_head_response(sample_rate, len, azimuth, elevation, -1.0);
+                set.add(azimuth, elevation, left, right);
+            }
+        }
+
+        set
+    }
+
+    /// Adds the impulse responses of the left and right ears for a direction.
+    pub fn add(&mut self, azimuth: f32, elevation: f32, left: Vec<f32>, right: Vec<f32>) {
+        self.len = self.len.max(left.len()).max(right.len());
+        for entry in &mut self.entries {
+            entry.left.resize(self.len, 0.0);
+            entry.right.resize(self.len, 0.0);
+        }
+
+        let mut left = left;
+        let mut right = right;
+        left.resize(self.len, 0.0);
+        right.resize(self.len, 0.0);
+
+        self.entries.push(Hrir {
+            direction: direction_vector(azimuth, elevation),
+            left,
+            right,
+        });
+    }
+
+    /// Returns the sample rate of the impulse responses.
+    #[inline]
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    // Returns the length of the responses.
+    #[inline]
+    pub(crate) fn response_len(&self) -> usize {
+        self.len
+    }
+
+    // Returns the responses of the left and right ears at an index returned by `nearest`.
+    #[inline]
+    pub(crate) fn response(&self, index: usize) -> (&[f32], &[f32]) {
+        let entry = &self.entries[index];
+        (&entry.left, &entry.right)
+    }
+
+    // Returns the index of the response closest to a direction.
+    pub(crate) fn nearest(&self, azimuth: f32, elevation: f32) -> usize {
+        let direction = direction_vector(azimuth, elevation);
+        let mut best = 0;
+        let mut best_dot = f32::MIN;
+        for (index, entry) in self.entries.iter().enumerate() {
+            let dot = (0 .. 3).map(|i| entry.direction[i] * direction[i]).sum::<f32>();
+            if dot > best_dot {
+                best = index;
+                best_dot = dot;
+            }
+        }
+        best
+    }
+}
+
+// Returns the unit vector of a direction. X points to the front, Y to the left and Z up.
+fn direction_vector(azimuth: f32, elevation: f32) -> [f32; 3] {
+    let (azimuth, elevation) = (azimuth.to_radians(), elevation.to_radians());
+    [
+        elevation.cos() * azimuth.cos(),
+        elevation.cos() * azimuth.sin(),
+        elevation.sin(),
+    ]
+}
+
+// Computes the response of one ear of a spherical head. `side` is `1.0` for the left ear and
+// `-1.0` for the right ear.
+fn spherical_head_response(
+    sample_rate: u32, len: usize, azimuth: f32, elevation: f32, side: f32,
+) -> Vec<f32> {
+    let direction = direction_vector(azimuth, elevation);
+    // Angle between the axis of the ear and the direction of the sound.
+    let incidence = (direction[1] * side).clamp(-1.0, 1.0).acos();
+
+    // Delay around the head, shifted so that it is never negative.
+    let delay = if incidence < PI / 2.0 {
+        -incidence.cos()
+    } else {
+        incidence - PI / 2.0
+    };
+    let delay = (delay + 1.0) * HEAD_RADIUS / SPEED_OF_SOUND * sample_rate as f32;
+
+    let mut response = vec![0.0; len];
+    let mut add_tap = |position: f32, gain: f32| {
+        let index = position as usize;
+        let frac = position - index as f32;
+        if index + 1 < len {
+            response[index] += gain * (1.0 - frac);
+            response[index + 1] += gain * frac;
+        }
+    };
+
+    add_tap(delay, 1.0);
+    let scale = sample_rate as f32 / 44100.0;
+    let azimuth_cos = (azimuth.to_radians() / 2.0).cos();
+    let elevation_sin = (PI / 2.0 - elevation.to_radians()).sin();
+    for &(gain, amount, offset) in PINNA_ECHOES.iter() {
+        let echo = (amount * azimuth_cos * elevation_sin + offset) * scale;
+        add_tap(delay + echo, gain);
+    }
+
+    // Shadow of the head, as a one-pole one-zero filter.
+    let alpha = 1.05 + 0.95 * (incidence / (150f32.to_radians()) * PI).cos();
+    let w0 = 2.0 * SPEED_OF_SOUND / HEAD_RADIUS;
+    let k = 2.0 * sample_rate as f32;
+    let b0 = (w0 + alpha * k) / (w0 + k);
+    let b1 = (w0 - alpha * k) / (w0 + k);
+    let a1 = (w0 - k) / (w0 + k);
+    let (mut x1, mut y1) = (0.0, 0.0);
+    for value in &mut response {
+        let y = b0 * *value + b1 * x1 - a1 * y1;
+        x1 = *value;
+        y1 = y;
+        *value = y;
+    }
+
+    response
+}
+
+/// Renders a source for headphones, as if it was coming from a direction around the listener.
+///
+/// The source is converted to mono and to the sample rate of the impulse responses, and is then
+/// convolved with the response closest to the direction. The output has two channels.
+#[derive(Clone)]
+pub struct Hrtf<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    input: UniformSourceIterator<ToMono<I>, f32>,
+    hrirs: Arc<HrirSet>,
+    direction: Arc<Direction>,
+    // Direction when it was last read, as bits.
+    last_direction: (u32, u32),
+    // Index of the response in use, and of the one it replaced.
+    current: usize,
+    previous: usize,
+    // Number of samples since the start of the move from `previous` to `current`.
+    crossfade: u32,
+    // The last samples of the input, written twice so that they can always be read as one slice.
+    history: Vec<f32>,
+    history_pos: usize,
+    // Number of samples to play after the input ends, so that the responses aren't cut.
+    remaining_tail: usize,
+    // Sample of the right channel to return next.
+    right: Option<f32>,
+}
+
+#[derive(Debug)]
+struct Direction {
+    azimuth: AtomicU32,
+    elevation: AtomicU32,
+}
+
+/// Handle that changes the direction of a `Hrtf` from another thread.
+#[derive(Clone, Debug)]
+pub struct HrtfHandle {
+    direction: Arc<Direction>,
+}
+
+impl HrtfHandle {
+    /// Changes the direction of the sound, in degrees.
+    #[inline]
+    pub fn set_direction(&self, azimuth: f32, elevation: f32) {
+        self.direction.azimuth.store(azimuth.to_bits(), Ordering::Relaxed);
+        self.direction.elevation.store(elevation.to_bits(), Ordering::Relaxed);
+    }
+}
+
+impl<I> Hrtf<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    /// Changes the direction of the sound, in degrees.
+    #[inline]
+    pub fn set_direction(&mut self, azimuth: f32, elevation: f32) {
+        self.direction.azimuth.store(azimuth.to_bits(), Ordering::Relaxed);
+        self.direction.elevation.store(elevation.to_bits(), Ordering::Relaxed);
+    }
+
+    /// Returns a handle that can change the direction after the source has been handed to a sink.
+    #[inline]
+    pub fn handle(&self) -> HrtfHandle {
+        HrtfHandle {
+            direction: self.direction.clone(),
+        }
+    }
+
+    // Picks the response to use if the direction has changed.
+    fn update_direction(&mut self) {
+        let azimuth = self.direction.azimuth.load(Ordering::Relaxed);
+        let elevation = self.direction.elevation.load(Ordering::Relaxed);
+        if (azimuth, elevation) == self.last_direction {
+            return;
+        }
+        self.last_direction = (azimuth, elevation);
+
+        let nearest = self
+            .hrirs
+            .nearest(f32::from_bits(azimuth), f32::from_bits(elevation));
+        if nearest != self.current {
+            self.previous = self.current;
+            self.current = nearest;
+            self.crossfade = 0;
+        }
+    }
+
+    // Returns the left and right outputs of a response for the history.
+    fn convolve(&self, index: usize) -> (f32, f32) {
+        let len = self.hrirs.len;
+        let history = &self.history[self.history_pos + 1 .. self.history_pos + 1 + len];
+        let entry = &self.hrirs.entries[index];
+
+        let mut left = 0.0;
+        let mut right = 0.0;
+        for (k, &value) in history.iter().rev().enumerate() {
+            left += value * entry.left[k];
+            right += value * entry.right[k];
+        }
+        (left, right)
+    }
+}
+
+impl<I> Iterator for Hrtf<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    type Item = f32;
+
+    #[inline]
+    fn next(&mut self) -> Option<f32> {
+        if let Some(right) = self.right.take() {
+            return Some(right);
+        }
+
+        let value = match self.input.next() {
+            Some(value) => value,
+            None if self.remaining_tail > 1 => {
+                self.remaining_tail -= 1;
+                0.0
+            },
+            None => return None,
+        };
+
+        let len = self.hrirs.len;
+        self.history_pos = (self.history_pos + 1) % len;
+        self.history[self.history_pos] = value;
+        self.history[self.history_pos + len] = value;
+
+        self.update_direction();
+        let (mut left, mut right) = self.convolve(self.current);
+        if self.crossfade < CROSSFADE_LEN {
+            let (previous_left, previous_right) = self.convolve(self.previous);
+            let t = self.crossfade as f32 / CROSSFADE_LEN as f32;
+            left = previous_left + (left - previous_left) * t;
+            right = previous_right + (right - previous_right) * t;
+            self.crossfade += 1;
+        }
+
+        self.right = Some(right);
+        Some(left)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let (min, max) = self.input.size_hint();
+        let pending = self.right.is_some() as usize;
+        let tail = self.remaining_tail.saturating_sub(1);
+        (
+            min * 2 + pending,
+            max.map(|max| (max + tail) * 2 + pending),
+        )
+    }
+}
+
+impl<I> Source for Hrtf<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        None
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        2
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        self.hrirs.sample_rate
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        self.input.total_duration()
+    }
+
+    #[inline]
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        self.input.try_seek(pos)?;
+        for value in &mut self.history {
+            *value = 0.0;
+        }
+        self.remaining_tail = self.hrirs.len;
+        self.right = None;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use buffer::SamplesBuffer;
+    use source::{HrirSet, Source};
+
+    #[test]
+    fn convolves_with_nearest_response() {
+        let mut hrirs = HrirSet::new(44100);
+        hrirs.add(90.0, 0.0, vec![1.0], vec![0.0, 0.5]);
+        hrirs.add(-90.0, 0.0, vec![0.0, 0.5], vec![1.0]);
+        let hrirs = Arc::new(hrirs);
+
+        let source = SamplesBuffer::new(1, 44100, vec![1.0f32, 2.0]);
+        let source = source.binaural(hrirs, 80.0, 10.0);
+        assert_eq!(source.channels(), 2);
+        assert_eq!(source.collect::<Vec<_>>(), [1.0, 0.0, 2.0, 0.5, 0.0, 1.0]);
+    }
+
+    #[test]
+    fn spherical_head_is_louder_on_the_near_side() {
+        let hrirs = Arc::new(HrirSet::spherical_head(44100));
+        let data = (0 .. 1000).map(|n| if n % 3 == 0 { 1.0 } else { -0.5 });
+        let source = SamplesBuffer::new(1, 44100, data.collect::<Vec<f32>>());
+        let samples = source.binaural(hrirs, 90.0, 0.0).collect::<Vec<_>>();
+
+        let left = samples.iter().step_by(2).map(|v| v * v).sum::<f32>();
+        let right = samples.iter().skip(1).step_by(2).map(|v| v * v).sum::<f32>();
+        assert!(left > right * 2.0);
+    }
+}
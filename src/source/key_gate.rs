@@ -0,0 +1,212 @@
+use std::time::Duration;
+
+use conversions::db_to_amplitude;
+use source::uniform::UniformSourceIterator;
+use source::SeekError;
+use Sample;
+use Source;
+
+/// Time it takes for the envelope of the key to decay by a factor of `e`.
+const RELEASE_SECS: f32 = 0.1;
+/// Time constant of the gain of the gate when it opens or closes, to avoid clicks.
+const RAMP_SECS: f32 = 0.005;
+
+/// Internal function that builds a `KeyGate` object.
+pub fn key_gate<I, K>(input: I, key: K, threshold_db: f32) -> KeyGate<I, K>
+where
+    I: Source,
+    I::Item: Sample,
+    K: Source,
+    K::Item: Sample,
+{
+    let channels = input.channels();
+    let rate = input.sample_rate();
+
+    KeyGate {
+        input,
+        key: UniformSourceIterator::new(key, channels, rate),
+        threshold: db_to_amplitude(threshold_db),
+        envelope: 0.0,
+        gain: 0.0,
+        current_channel: 0,
+    }
+}
+
+/// Filter that only lets a source through when the level of another source, the key, is above a
+/// threshold.
+///
+/// The level of the key is measured with an envelope follower that reacts instantly to peaks and
+/// decays over 100ms. Once the key ends, the gate stays closed.
+#[derive(Clone)]
+pub struct KeyGate<I, K>
+where
+    I: Source,
+    I::Item: Sample,
+    K: Source,
+    K::Item: Sample,
+{
+    input: I,
+    key: UniformSourceIterator<K, I::Item>,
+    // Threshold as an amplitude between `0.0` and `1.0`.
+    threshold: f32,
+    envelope: f32,
+    // Current gain, between `0.0` when closed and `1.0` when open.
+    gain: f32,
+    // Channel of the next sample returned by `next`.
+    current_channel: u16,
+}
+
+impl<I, K> KeyGate<I, K>
+where
+    I: Source,
+    I::Item: Sample,
+    K: Source,
+    K::Item: Sample,
+{
+    /// Modifies the threshold above which the gate opens.
+    #[inline]
+    pub fn set_threshold_db(&mut self, threshold_db: f32) {
+        self.threshold = db_to_amplitude(threshold_db);
+    }
+
+    /// Returns `true` if the level of the key is above the threshold.
+    #[inline]
+    pub fn is_open(&self) -> bool {
+        self.envelope >= self.threshold
+    }
+
+    /// Returns a reference to the inner source.
+    #[inline]
+    pub fn inner(&self) -> &I {
+        &self.input
+    }
+
+    /// Returns a mutable reference to the inner source.
+    #[inline]
+    pub fn inner_mut(&mut self) -> &mut I {
+        &mut self.input
+    }
+
+    /// Returns the inner source.
+    #[inline]
+    pub fn into_inner(self) -> I {
+        self.input
+    }
+
+    // Called at the start of each frame.
+    fn next_frame(&mut self) {
+        let rate = self.input.sample_rate() as f32;
+
+        let mut peak = 0.0f32;
+        for _ in 0 .. self.key.channels() {
+            if let Some(value) = self.key.next() {
+                peak = peak.max(value.to_f32().abs());
+            }
+        }
+
+        let release = (-1.0 / (RELEASE_SECS * rate)).exp();
+        self.envelope = peak.max(self.envelope * release);
+
+        let target = if self.is_open() { 1.0 } else { 0.0 };
+        let ramp = 1.0 - (-1.0 / (RAMP_SECS * rate)).exp();
+        self.gain += (target - self.gain) * ramp;
+    }
+}
+
+impl<I, K> Iterator for KeyGate<I, K>
+where
+    I: Source,
+    I::Item: Sample,
+    K: Source,
+    K::Item: Sample,
+{
+    type Item = I::Item;
+
+    #[inline]
+    fn next(&mut self) -> Option<I::Item> {
+        let value = self.input.next()?;
+
+        if self.current_channel >= self.input.channels() {
+            self.current_channel = 0;
+        }
+        if self.current_channel == 0 {
+            self.next_frame();
+        }
+        self.current_channel += 1;
+
+        Some(value.amplify(self.gain))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.input.size_hint()
+    }
+}
+
+impl<I, K> ExactSizeIterator for KeyGate<I, K>
+where
+    I: Source + ExactSizeIterator,
+    I::Item: Sample,
+    K: Source,
+    K::Item: Sample,
+{
+}
+
+impl<I, K> Source for KeyGate<I, K>
+where
+    I: Source,
+    I::Item: Sample,
+    K: Source,
+    K::Item: Sample,
+{
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        self.input.current_frame_len()
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        self.input.channels()
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        self.input.sample_rate()
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        self.input.total_duration()
+    }
+
+    #[inline]
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        self.input.try_seek(pos)?;
+        self.key.try_seek(pos)?;
+        self.current_channel = 0;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use buffer::SamplesBuffer;
+    use source::Source;
+
+    #[test]
+    fn follows_key() {
+        let mut key = vec![0.5f32; 1000];
+        key.extend(vec![0.0; 1000]);
+        let key = SamplesBuffer::new(1, 1000, key);
+
+        let output = SamplesBuffer::new(1, 1000, vec![1.0f32; 2000])
+            .gated_by(key, -20.0)
+            .collect::<Vec<_>>();
+
+        // The gate opens within a few milliseconds, and closes once the envelope of the key has
+        // decayed under the threshold, about 160ms after the key stops.
+        assert!(output[50] > 0.99);
+        assert!(output[1100] > 0.99);
+        assert!(output[1400] < 0.01);
+    }
+}
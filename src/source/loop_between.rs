@@ -0,0 +1,220 @@
+use std::cmp;
+use std::time::Duration;
+
+use conversions::duration_to_frames;
+use source::buffered::Buffered;
+use source::SeekError;
+
+use Sample;
+use Source;
+
+/// Internal function that builds a `LoopBetween` object.
+pub fn loop_between<I>(input: I, start: Duration, end: Duration) -> LoopBetween<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    assert!(start < end);
+
+    // The positions are rounded to a frame so that the channels don't get swapped when looping.
+    let sample_rate = input.sample_rate();
+    let channels = input.channels() as u64;
+    let to_sample = |duration: Duration| duration_to_frames(duration, sample_rate) * channels;
+
+    let input = input.buffered();
+
+    LoopBetween {
+        inner: input.clone(),
+        source_start: input,
+        loop_start: None,
+        position: 0,
+        start_sample: to_sample(start),
+        end_sample: to_sample(end),
+    }
+}
+
+/// A source that plays the given source until an end position, and then repeatedly goes back to
+/// a start position.
+pub struct LoopBetween<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    inner: Buffered<I>,
+    // Copy of the source at its start, used when seeking.
+    source_start: Buffered<I>,
+    // Copy of the source at the start of the loop, once it has been reached.
+    loop_start: Option<Buffered<I>>,
+    // Number of samples between the start of the source and the next sample of `inner`.
+    position: u64,
+    start_sample: u64,
+    end_sample: u64,
+}
+
+impl<I> LoopBetween<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    // Goes back to the start of the loop. Returns `false` if it hasn't been reached yet.
+    #[inline]
+    fn jump_to_start(&mut self) -> bool {
+        match self.loop_start {
+            Some(ref loop_start) => {
+                self.inner = loop_start.clone();
+                self.position = self.start_sample;
+                true
+            },
+            None => false,
+        }
+    }
+}
+
+impl<I> Iterator for LoopBetween<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    type Item = <I as Iterator>::Item;
+
+    #[inline]
+    fn next(&mut self) -> Option<<I as Iterator>::Item> {
+        if self.position == self.start_sample && self.loop_start.is_none() {
+            self.loop_start = Some(self.inner.clone());
+        }
+        if self.position >= self.end_sample {
+            self.jump_to_start();
+        }
+
+        let value = match self.inner.next() {
+            Some(value) => value,
+            // The source ends before the end of the loop, in which case the loop ends there.
+            None if self.position > self.start_sample && self.jump_to_start() => self.inner.next()?,
+            None => return None,
+        };
+
+        self.position += 1;
+        Some(value)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, None)
+    }
+}
+
+impl<I> Source for LoopBetween<I>
+where
+    I: Iterator + Source,
+    I::Item: Sample,
+{
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        if self.position >= self.end_sample {
+            let loop_len = (self.end_sample - self.start_sample) as usize;
+            let frame_len = self.loop_start.as_ref().and_then(|s| s.current_frame_len());
+            return Some(frame_len.map_or(loop_len, |len| cmp::min(len, loop_len)));
+        }
+
+        // Frames have to end at the start of the loop so that it can be captured in time, and at
+        // the end of the loop so that the jump can happen.
+        let boundary = if self.position < self.start_sample {
+            self.start_sample
+        } else {
+            self.end_sample
+        };
+        let remaining = (boundary - self.position) as usize;
+        Some(self.inner.current_frame_len().map_or(remaining, |len| cmp::min(len, remaining)))
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        match self.loop_start {
+            Some(ref loop_start) if self.position >= self.end_sample => loop_start.channels(),
+            _ => self.inner.channels(),
+        }
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        match self.loop_start {
+            Some(ref loop_start) if self.position >= self.end_sample => loop_start.sample_rate(),
+            _ => self.inner.sample_rate(),
+        }
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        None
+    }
+
+    #[inline]
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        let frame = duration_to_frames(pos, self.source_start.sample_rate());
+        let mut samples = frame * self.source_start.channels() as u64;
+        if samples >= self.end_sample {
+            let loop_len = self.end_sample - self.start_sample;
+            samples = self.start_sample + (samples - self.start_sample) % loop_len;
+        }
+
+        self.inner = self.source_start.clone();
+        self.position = 0;
+        for _ in 0 .. samples {
+            if self.next().is_none() {
+                break;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<I> Clone for LoopBetween<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    #[inline]
+    fn clone(&self) -> LoopBetween<I> {
+        LoopBetween {
+            inner: self.inner.clone(),
+            source_start: self.source_start.clone(),
+            loop_start: self.loop_start.clone(),
+            position: self.position,
+            start_sample: self.start_sample,
+            end_sample: self.end_sample,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use buffer::SamplesBuffer;
+    use source::Source;
+
+    #[test]
+    fn intro_then_loop() {
+        let source = SamplesBuffer::new(2, 2, vec![1i16, 1, 2, 2, 3, 3, 4, 4, 5, 5]);
+        let source = source.loop_between(Duration::from_millis(500), Duration::from_millis(1500));
+        assert_eq!(
+            source.take(12).collect::<Vec<_>>(),
+            [1, 1, 2, 2, 3, 3, 2, 2, 3, 3, 2, 2]
+        );
+    }
+
+    #[test]
+    fn seek() {
+        let source = SamplesBuffer::new(1, 1, vec![1i16, 2, 3, 4]);
+        let mut source = source.loop_between(Duration::from_secs(1), Duration::from_secs(3));
+        source.try_seek(Duration::from_secs(4)).unwrap();
+        assert_eq!(source.take(3).collect::<Vec<_>>(), [3, 2, 3]);
+    }
+
+    #[test]
+    fn end_after_source() {
+        let source = SamplesBuffer::new(1, 1, vec![1i16, 2, 3]);
+        let source = source.loop_between(Duration::from_secs(1), Duration::from_secs(10));
+        assert_eq!(source.take(7).collect::<Vec<_>>(), [1, 2, 3, 2, 3, 2, 3]);
+    }
+}
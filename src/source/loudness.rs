@@ -0,0 +1,380 @@
+use std::f32::consts::PI;
+use std::time::Duration;
+
+use conversions::db_to_amplitude;
+use source::biquad::Biquad;
+use source::SeekError;
+use Sample;
+use Source;
+
+// Blocks below this loudness are ignored, as per EBU R128.
+const ABSOLUTE_GATE_LUFS: f32 = -70.0;
+// Blocks quieter than the average loudness by more than this are ignored, as per EBU R128.
+const RELATIVE_GATE_LU: f32 = -10.0;
+// Resolution and upper bound of the histogram of block loudnesses.
+const HISTOGRAM_STEP_LU: f32 = 0.1;
+const HISTOGRAM_MAX_LUFS: f32 = 5.0;
+// Number of 100ms sub-blocks in a 400ms measurement block.
+const SUB_BLOCKS_PER_BLOCK: usize = 4;
+// The gain is never raised above this, so that silence isn't boosted.
+const MAX_GAIN_DB: f32 = 20.0;
+// Time constant with which the applied gain follows the measurement, in seconds.
+const GAIN_SMOOTHING_SECS: f32 = 0.4;
+
+/// Internal function that builds a `LoudnessNormalization` object.
+pub fn normalize_loudness<I>(input: I, target_lufs: f32) -> LoudnessNormalization<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    let histogram_len = ((HISTOGRAM_MAX_LUFS - ABSOLUTE_GATE_LUFS) / HISTOGRAM_STEP_LU) as usize;
+
+    LoudnessNormalization {
+        input,
+        target_lufs,
+        filters: Vec::new(),
+        filters_rate: 0,
+        current_channel: 0,
+        sub_block_sum: 0.0,
+        sub_block_frames: 0,
+        sub_blocks: [0.0; SUB_BLOCKS_PER_BLOCK],
+        sub_blocks_count: 0,
+        histogram_counts: vec![0; histogram_len],
+        histogram_powers: vec![0.0; histogram_len],
+        integrated_lufs: None,
+        target_gain: 1.0,
+        gain: 1.0,
+    }
+}
+
+/// Filter that adjusts the volume of a source so that its loudness, as defined by EBU R128,
+/// reaches a target.
+///
+/// The integrated loudness is measured while the source plays: the signal is K-weighted, cut into
+/// 400ms blocks overlapping by 75%, and the blocks are gated. The volume smoothly follows the
+/// measurement, which means that it is left untouched during the first 400ms and then becomes more
+/// stable as more of the source is heard. The amplification is capped at 20dB.
+///
+/// For 6 channels sources the channels are assumed to be in the usual 5.1 order, and are weighted
+/// accordingly. Otherwise all channels have the same weight.
+#[derive(Clone, Debug)]
+pub struct LoudnessNormalization<I> {
+    input: I,
+    target_lufs: f32,
+    // K-weighting filter of each channel.
+    filters: Vec<KWeighting>,
+    // Sample rate that the filters have been computed for.
+    filters_rate: u32,
+    // Channel of the next sample returned by `next`.
+    current_channel: u16,
+    // Weighted sum of the squares of the filtered samples of the current 100ms sub-block.
+    sub_block_sum: f32,
+    sub_block_frames: u32,
+    // Mean squares of the last sub-blocks, used as a ring buffer.
+    sub_blocks: [f32; SUB_BLOCKS_PER_BLOCK],
+    sub_blocks_count: usize,
+    // Number and total power of the blocks whose loudness falls in each bin.
+    histogram_counts: Vec<u32>,
+    histogram_powers: Vec<f64>,
+    integrated_lufs: Option<f32>,
+    // Gain that the measurement calls for, and gain actually applied.
+    target_gain: f32,
+    gain: f32,
+}
+
+impl<I> LoudnessNormalization<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    /// Modifies the loudness that the filter is aiming for, in LUFS.
+    #[inline]
+    pub fn set_target_lufs(&mut self, target_lufs: f32) {
+        self.target_lufs = target_lufs;
+        self.update_target_gain();
+    }
+
+    /// Returns the integrated loudness of what has been played so far, in LUFS, or `None` if
+    /// nothing loud enough has been played yet.
+    #[inline]
+    pub fn integrated_loudness(&self) -> Option<f32> {
+        self.integrated_lufs
+    }
+
+    /// Returns a reference to the inner source.
+    #[inline]
+    pub fn inner(&self) -> &I {
+        &self.input
+    }
+
+    /// Returns a mutable reference to the inner source.
+    #[inline]
+    pub fn inner_mut(&mut self) -> &mut I {
+        &mut self.input
+    }
+
+    /// Returns the inner source.
+    #[inline]
+    pub fn into_inner(self) -> I {
+        self.input
+    }
+
+    // Called at the start of each frame.
+    fn next_frame(&mut self) {
+        let channels = self.input.channels() as usize;
+        let rate = self.input.sample_rate();
+
+        if self.sub_block_frames >= rate / 10 {
+            self.push_sub_block();
+        }
+
+        if self.filters.len() != channels || self.filters_rate != rate {
+            self.filters = vec![KWeighting::new(rate); channels];
+            self.filters_rate = rate;
+        }
+
+        self.sub_block_frames += 1;
+    }
+
+    fn push_sub_block(&mut self) {
+        let mean_square = self.sub_block_sum / self.sub_block_frames as f32;
+        self.sub_blocks[self.sub_blocks_count % SUB_BLOCKS_PER_BLOCK] = mean_square;
+        self.sub_blocks_count += 1;
+        self.sub_block_sum = 0.0;
+        self.sub_block_frames = 0;
+
+        if self.sub_blocks_count < SUB_BLOCKS_PER_BLOCK {
+            return;
+        }
+
+        let power = self.sub_blocks.iter().sum::<f32>() / SUB_BLOCKS_PER_BLOCK as f32;
+        let loudness = power_to_lufs(power as f64);
+        if loudness < ABSOLUTE_GATE_LUFS {
+            return;
+        }
+
+        let bin = histogram_bin(loudness).min(self.histogram_counts.len() - 1);
+        self.histogram_counts[bin] += 1;
+        self.histogram_powers[bin] += power as f64;
+
+        self.update_integrated_loudness();
+        self.update_target_gain();
+    }
+
+    fn update_integrated_loudness(&mut self) {
+        let (count, power) = self.sum_bins_from(0);
+        if count == 0 {
+            return;
+        }
+
+        let relative_gate = power_to_lufs(power / count as f64) + RELATIVE_GATE_LU;
+        let (count, power) = self.sum_bins_from(histogram_bin(relative_gate));
+        if count != 0 {
+            self.integrated_lufs = Some(power_to_lufs(power / count as f64));
+        }
+    }
+
+    fn sum_bins_from(&self, first_bin: usize) -> (u64, f64) {
+        let counts = self.histogram_counts.iter().skip(first_bin);
+        let powers = self.histogram_powers.iter().skip(first_bin);
+        counts
+            .zip(powers)
+            .fold((0, 0.0), |(count, power), (c, p)| (count + *c as u64, power + p))
+    }
+
+    fn update_target_gain(&mut self) {
+        if let Some(integrated) = self.integrated_lufs {
+            let gain_db = (self.target_lufs - integrated).min(MAX_GAIN_DB);
+            self.target_gain = db_to_amplitude(gain_db);
+        }
+    }
+}
+
+#[inline]
+fn power_to_lufs(power: f64) -> f32 {
+    (-0.691 + 10.0 * power.log10()) as f32
+}
+
+#[inline]
+fn histogram_bin(loudness: f32) -> usize {
+    ((loudness - ABSOLUTE_GATE_LUFS).max(0.0) / HISTOGRAM_STEP_LU) as usize
+}
+
+#[inline]
+fn channel_weight(channel: usize, channels: usize) -> f32 {
+    match (channels, channel) {
+        // Low frequency effects.
+        (6, 3) => 0.0,
+        // Surround channels.
+        (6, 4) | (6, 5) => 1.41,
+        _ => 1.0,
+    }
+}
+
+impl<I> Iterator for LoudnessNormalization<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    type Item = I::Item;
+
+    #[inline]
+    fn next(&mut self) -> Option<I::Item> {
+        if self.current_channel >= self.input.channels() {
+            self.current_channel = 0;
+        }
+        if self.current_channel == 0 {
+            self.next_frame();
+        }
+        let channel = self.current_channel as usize;
+        self.current_channel += 1;
+
+        let value = self.input.next()?;
+
+        let filtered = self.filters[channel].apply(value.to_f32());
+        self.sub_block_sum +=
+            channel_weight(channel, self.filters.len()) * filtered * filtered;
+
+        let samples_per_sec = self.filters_rate as f32 * self.filters.len() as f32;
+        let coefficient = 1.0 - (-1.0 / (GAIN_SMOOTHING_SECS * samples_per_sec)).exp();
+        self.gain += (self.target_gain - self.gain) * coefficient;
+
+        Some(value.amplify(self.gain))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.input.size_hint()
+    }
+}
+
+impl<I> ExactSizeIterator for LoudnessNormalization<I>
+where
+    I: Source + ExactSizeIterator,
+    I::Item: Sample,
+{
+}
+
+impl<I> Source for LoudnessNormalization<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        self.input.current_frame_len()
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        self.input.channels()
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        self.input.sample_rate()
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        self.input.total_duration()
+    }
+
+    #[inline]
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        self.input.try_seek(pos)?;
+        self.current_channel = 0;
+        Ok(())
+    }
+}
+
+/// The K-weighting filter of ITU-R BS.1770: a high shelf followed by a high-pass filter.
+#[derive(Clone, Debug)]
+struct KWeighting {
+    shelf: Biquad,
+    high_pass: Biquad,
+    shelf_state: [f32; 4],
+    high_pass_state: [f32; 4],
+}
+
+impl KWeighting {
+    // The formulas allow computing the coefficients for any sample rate. They are the ones of
+    // libebur128.
+    fn new(sample_rate: u32) -> KWeighting {
+        let rate = sample_rate as f32;
+
+        let f0 = 1_681.974_5;
+        let gain_db = 3.999_843_9;
+        let q = 0.707_175_25;
+        let k = (PI * f0 / rate).tan();
+        let vh = db_to_amplitude(gain_db);
+        let vb = vh.powf(0.499_666_78);
+        let a0 = 1.0 + k / q + k * k;
+        let shelf = Biquad::new(
+            [(vh + vb * k / q + k * k) / a0, 2.0 * (k * k - vh) / a0, (vh - vb * k / q + k * k) / a0],
+            [2.0 * (k * k - 1.0) / a0, (1.0 - k / q + k * k) / a0],
+        );
+
+        let f0 = 38.135_47;
+        let q = 0.500_327;
+        let k = (PI * f0 / rate).tan();
+        let a0 = 1.0 + k / q + k * k;
+        let high_pass = Biquad::new(
+            [1.0, -2.0, 1.0],
+            [2.0 * (k * k - 1.0) / a0, (1.0 - k / q + k * k) / a0],
+        );
+
+        KWeighting {
+            shelf,
+            high_pass,
+            shelf_state: [0.0; 4],
+            high_pass_state: [0.0; 4],
+        }
+    }
+
+    #[inline]
+    fn apply(&mut self, x: f32) -> f32 {
+        let x = self.shelf.apply(&mut self.shelf_state, x);
+        self.high_pass.apply(&mut self.high_pass_state, x)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::PI;
+
+    use buffer::SamplesBuffer;
+    use source::Source;
+
+    fn sine(amplitude: f32, secs: usize) -> SamplesBuffer<f32> {
+        let data = (0 .. 48000 * secs)
+            .map(|n| amplitude * (2.0 * PI * 997.0 * n as f32 / 48000.0).sin())
+            .collect::<Vec<_>>();
+        SamplesBuffer::new(1, 48000, data)
+    }
+
+    #[test]
+    fn measures_reference_tone() {
+        // A full scale 997Hz sine is defined to be -3.01 LUFS.
+        let mut source = sine(1.0, 2).normalize_loudness(-23.0);
+        source.by_ref().count();
+        let loudness = source.integrated_loudness().unwrap();
+        assert!((loudness + 3.01).abs() < 0.1, "{}", loudness);
+    }
+
+    #[test]
+    fn reaches_target() {
+        // -9.03 LUFS normalized to -23 LUFS means a gain of -13.97dB.
+        let samples = sine(0.5, 6).normalize_loudness(-23.0).collect::<Vec<_>>();
+        let peak = samples[samples.len() - 4800 ..]
+            .iter()
+            .fold(0.0f32, |peak, s| peak.max(s.abs()));
+        assert!((peak - 0.1).abs() < 0.005, "{}", peak);
+    }
+
+    #[test]
+    fn silence_is_untouched() {
+        let source = SamplesBuffer::new(2, 48000, vec![0.0f32; 96000]).normalize_loudness(-23.0);
+        assert!(source.collect::<Vec<_>>().iter().all(|s| *s == 0.0));
+    }
+}
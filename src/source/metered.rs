@@ -0,0 +1,242 @@
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use source::SeekError;
+use Sample;
+use Source;
+
+/// Duration over which the levels are measured before being published.
+const WINDOW_MS: u64 = 50;
+
+/// Internal function that builds a `Metered` object.
+pub fn metered<I>(input: I) -> Metered<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    let channels = input.channels() as usize;
+
+    Metered {
+        input,
+        levels: Arc::new(Mutex::new(vec![ChannelLevel::default(); channels])),
+        current_channel: 0,
+        window_frames: 0,
+        peaks: vec![0.0; channels],
+        sum_squares: vec![0.0; channels],
+    }
+}
+
+/// Tap that measures the peak and RMS level of each channel of a source.
+///
+/// The samples are passed through untouched. The levels are measured over windows of 50ms and
+/// published to a `MeterHandle` at the end of each window.
+#[derive(Debug)]
+pub struct Metered<I> {
+    input: I,
+    levels: Arc<Mutex<Vec<ChannelLevel>>>,
+    // Channel of the next sample returned by `next`.
+    current_channel: u16,
+    // Number of frames of the current window read so far.
+    window_frames: u64,
+    peaks: Vec<f32>,
+    sum_squares: Vec<f32>,
+}
+
+/// Level of one channel, as amplitudes between `0.0` and `1.0`.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct ChannelLevel {
+    /// Highest absolute value of a sample.
+    pub peak: f32,
+    /// Root mean square of the samples.
+    pub rms: f32,
+}
+
+/// Handle that reads the levels measured by a `Metered` from another thread.
+#[derive(Clone, Debug)]
+pub struct MeterHandle {
+    levels: Arc<Mutex<Vec<ChannelLevel>>>,
+}
+
+impl MeterHandle {
+    /// Returns the levels of each channel measured over the last complete window.
+    #[inline]
+    pub fn levels(&self) -> Vec<ChannelLevel> {
+        self.levels.lock().unwrap().clone()
+    }
+}
+
+impl<I> Metered<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    /// Returns a handle that can read the levels after the source has been handed to a sink.
+    #[inline]
+    pub fn handle(&self) -> MeterHandle {
+        MeterHandle {
+            levels: self.levels.clone(),
+        }
+    }
+
+    /// Returns a reference to the inner source.
+    #[inline]
+    pub fn inner(&self) -> &I {
+        &self.input
+    }
+
+    /// Returns a mutable reference to the inner source.
+    #[inline]
+    pub fn inner_mut(&mut self) -> &mut I {
+        &mut self.input
+    }
+
+    /// Returns the inner source.
+    #[inline]
+    pub fn into_inner(self) -> I {
+        self.input
+    }
+
+    // Called at the start of each frame.
+    fn next_frame(&mut self) {
+        let window_len = WINDOW_MS * self.input.sample_rate() as u64 / 1000;
+        let channels = self.input.channels() as usize;
+
+        if self.window_frames >= window_len.max(1) || channels != self.peaks.len() {
+            self.publish();
+            self.peaks.resize(channels, 0.0);
+            self.sum_squares.resize(channels, 0.0);
+        }
+
+        self.window_frames += 1;
+    }
+
+    // Publishes the levels of the current window and starts a new one.
+    fn publish(&mut self) {
+        if self.window_frames == 0 {
+            return;
+        }
+
+        // The audio thread must not block, so the levels of this window are dropped if the
+        // handle is being read.
+        if let Ok(mut levels) = self.levels.try_lock() {
+            let window_frames = self.window_frames as f32;
+            levels.clear();
+            levels.extend(self.peaks.iter().zip(&self.sum_squares).map(|(&peak, &sum)| {
+                ChannelLevel {
+                    peak,
+                    rms: (sum / window_frames).sqrt(),
+                }
+            }));
+        }
+
+        self.window_frames = 0;
+        for peak in &mut self.peaks {
+            *peak = 0.0;
+        }
+        for sum in &mut self.sum_squares {
+            *sum = 0.0;
+        }
+    }
+}
+
+impl<I> Iterator for Metered<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    type Item = I::Item;
+
+    #[inline]
+    fn next(&mut self) -> Option<I::Item> {
+        let value = match self.input.next() {
+            Some(value) => value,
+            None => {
+                self.publish();
+                return None;
+            },
+        };
+
+        if self.current_channel >= self.input.channels() {
+            self.current_channel = 0;
+        }
+        if self.current_channel == 0 {
+            self.next_frame();
+        }
+
+        let channel = self.current_channel as usize;
+        if channel < self.peaks.len() {
+            let amplitude = value.to_f32();
+            self.peaks[channel] = self.peaks[channel].max(amplitude.abs());
+            self.sum_squares[channel] += amplitude * amplitude;
+        }
+        self.current_channel += 1;
+
+        Some(value)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.input.size_hint()
+    }
+}
+
+impl<I> ExactSizeIterator for Metered<I>
+where
+    I: Source + ExactSizeIterator,
+    I::Item: Sample,
+{
+}
+
+impl<I> Source for Metered<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        self.input.current_frame_len()
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        self.input.channels()
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        self.input.sample_rate()
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        self.input.total_duration()
+    }
+
+    #[inline]
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        self.input.try_seek(pos)?;
+        self.current_channel = 0;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use buffer::SamplesBuffer;
+    use source::{ChannelLevel, Source};
+
+    #[test]
+    fn measures_each_channel() {
+        let data = vec![0.5f32, 0.0, -0.5, 0.25, 0.5, 0.0, -0.5, 0.25];
+        let source = SamplesBuffer::new(2, 80, data.clone()).metered();
+        let handle = source.handle();
+
+        assert_eq!(handle.levels(), [ChannelLevel::default(); 2]);
+        assert_eq!(source.collect::<Vec<_>>(), data);
+
+        let levels = handle.levels();
+        assert_eq!(levels[0], ChannelLevel { peak: 0.5, rms: 0.5 });
+        assert_eq!(levels[1].peak, 0.25);
+        assert!((levels[1].rms - 0.176_776_7).abs() < 1e-6);
+    }
+}
@@ -0,0 +1,302 @@
+use std::time::Duration;
+
+use source::SeekError;
+use Sample;
+use Source;
+
+/// Internal function that builds a `MidSideEncoder` object.
+pub fn encode<I>(input: I) -> MidSideEncoder<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    MidSideEncoder {
+        input,
+        pending: None,
+    }
+}
+
+/// Internal function that builds a `MidSideDecoder` object.
+pub fn decode<I>(input: I) -> MidSideDecoder<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    MidSideDecoder {
+        input,
+        pending: None,
+    }
+}
+
+// Reads a stereo frame and returns its two samples, or `None` if the source has ended. A missing
+// second sample is silent.
+#[inline]
+pub(crate) fn read_stereo<I>(input: &mut I) -> Option<(f32, f32)>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    let first = input.next()?.to_f32();
+    let second = input.next().map_or(0.0, |value| value.to_f32());
+    Some((first, second))
+}
+
+/// Converts the left and right channels of a stereo source into mid and side channels.
+///
+/// The first channel of the output is the mid channel, `(left + right) / 2`, and the second one
+/// is the side channel, `(left - right) / 2`. Frames that don't have two channels are left
+/// untouched.
+#[derive(Clone, Debug)]
+pub struct MidSideEncoder<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    input: I,
+    // Second sample of the current frame.
+    pending: Option<I::Item>,
+}
+
+impl<I> MidSideEncoder<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    /// Returns a reference to the inner source.
+    #[inline]
+    pub fn inner(&self) -> &I {
+        &self.input
+    }
+
+    /// Returns a mutable reference to the inner source.
+    #[inline]
+    pub fn inner_mut(&mut self) -> &mut I {
+        &mut self.input
+    }
+
+    /// Returns the inner source.
+    #[inline]
+    pub fn into_inner(self) -> I {
+        self.input
+    }
+}
+
+impl<I> Iterator for MidSideEncoder<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    type Item = I::Item;
+
+    #[inline]
+    fn next(&mut self) -> Option<I::Item> {
+        if let Some(value) = self.pending.take() {
+            return Some(value);
+        }
+        if self.input.channels() != 2 {
+            return self.input.next();
+        }
+
+        let (left, right) = read_stereo(&mut self.input)?;
+        self.pending = Some(Sample::from_sample(&((left - right) / 2.0)));
+        Some(Sample::from_sample(&((left + right) / 2.0)))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let pending = self.pending.is_some() as usize;
+        let (min, max) = self.input.size_hint();
+        (min + pending, max.map(|max| max + pending))
+    }
+}
+
+impl<I> ExactSizeIterator for MidSideEncoder<I>
+where
+    I: Source + ExactSizeIterator,
+    I::Item: Sample,
+{
+}
+
+impl<I> Source for MidSideEncoder<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        if self.pending.is_some() {
+            return Some(1);
+        }
+        self.input.current_frame_len()
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        if self.pending.is_some() {
+            2
+        } else {
+            self.input.channels()
+        }
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        self.input.sample_rate()
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        self.input.total_duration()
+    }
+
+    #[inline]
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        self.input.try_seek(pos)?;
+        self.pending = None;
+        Ok(())
+    }
+}
+
+/// Converts the mid and side channels of a source back into left and right channels.
+///
+/// This is the inverse of `MidSideEncoder`: the left channel is `mid + side` and the right
+/// channel is `mid - side`. Frames that don't have two channels are left untouched.
+#[derive(Clone, Debug)]
+pub struct MidSideDecoder<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    input: I,
+    // Second sample of the current frame.
+    pending: Option<I::Item>,
+}
+
+impl<I> MidSideDecoder<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    /// Returns a reference to the inner source.
+    #[inline]
+    pub fn inner(&self) -> &I {
+        &self.input
+    }
+
+    /// Returns a mutable reference to the inner source.
+    #[inline]
+    pub fn inner_mut(&mut self) -> &mut I {
+        &mut self.input
+    }
+
+    /// Returns the inner source.
+    #[inline]
+    pub fn into_inner(self) -> I {
+        self.input
+    }
+}
+
+impl<I> Iterator for MidSideDecoder<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    type Item = I::Item;
+
+    #[inline]
+    fn next(&mut self) -> Option<I::Item> {
+        if let Some(value) = self.pending.take() {
+            return Some(value);
+        }
+        if self.input.channels() != 2 {
+            return self.input.next();
+        }
+
+        let (mid, side) = read_stereo(&mut self.input)?;
+        self.pending = Some(Sample::from_sample(&(mid - side)));
+        Some(Sample::from_sample(&(mid + side)))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let pending = self.pending.is_some() as usize;
+        let (min, max) = self.input.size_hint();
+        (min + pending, max.map(|max| max + pending))
+    }
+}
+
+impl<I> ExactSizeIterator for MidSideDecoder<I>
+where
+    I: Source + ExactSizeIterator,
+    I::Item: Sample,
+{
+}
+
+impl<I> Source for MidSideDecoder<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        if self.pending.is_some() {
+            return Some(1);
+        }
+        self.input.current_frame_len()
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        if self.pending.is_some() {
+            2
+        } else {
+            self.input.channels()
+        }
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        self.input.sample_rate()
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        self.input.total_duration()
+    }
+
+    #[inline]
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        self.input.try_seek(pos)?;
+        self.pending = None;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use buffer::SamplesBuffer;
+    use source::Source;
+
+    #[test]
+    fn encode() {
+        let source = SamplesBuffer::new(2, 44100, vec![0.5f32, 0.25, -0.5, 0.5]);
+        assert_eq!(
+            source.encode_mid_side().collect::<Vec<_>>(),
+            [0.375, 0.125, 0.0, -0.5]
+        );
+    }
+
+    #[test]
+    fn round_trip() {
+        let data = vec![0.5f32, 0.25, -0.5, 0.5, 1.0, -1.0];
+        let source = SamplesBuffer::new(2, 44100, data.clone());
+        let source = source.encode_mid_side().decode_mid_side();
+        assert_eq!(source.collect::<Vec<_>>(), data);
+    }
+
+    #[test]
+    fn mono_is_untouched() {
+        let source = SamplesBuffer::new(1, 44100, vec![0.5f32, 0.25]);
+        assert_eq!(source.encode_mid_side().collect::<Vec<_>>(), [0.5, 0.25]);
+    }
+}
@@ -3,6 +3,7 @@ use std::time::Duration;
 
 use source::uniform::UniformSourceIterator;
 
+use source::SeekError;
 use Sample;
 use Source;
 
@@ -120,4 +121,10 @@ where
             _ => None,
         }
     }
+
+    #[inline]
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        self.input1.try_seek(pos)?;
+        self.input2.try_seek(pos)
+    }
 }
@@ -0,0 +1,172 @@
+use std::cmp;
+use std::time::Duration;
+
+use source::uniform::UniformSourceIterator;
+use source::SeekError;
+use Sample;
+use Source;
+
+/// Builds a source that mixes many sources together, each one with its own gain.
+///
+/// The sources are converted to the highest number of channels and the highest sample rate among
+/// them. The result ends when all the sources have ended. The mix is clamped between `-1.0` and
+/// `1.0`.
+///
+/// This is faster than nesting calls to `Source::mix`, as all the sources are mixed in one pass.
+pub fn mix_all<I>(sources: Vec<(I, f32)>) -> MixAll<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    let channels = sources.iter().map(|s| s.0.channels()).max().unwrap_or(1);
+    let sample_rate = sources.iter().map(|s| s.0.sample_rate()).max().unwrap_or(44100);
+
+    let inputs = sources
+        .into_iter()
+        .map(|(source, gain)| (UniformSourceIterator::new(source, channels, sample_rate), gain))
+        .collect();
+
+    MixAll {
+        inputs,
+        channels,
+        sample_rate,
+    }
+}
+
+/// A source that mixes many sources with individual gains.
+#[derive(Clone)]
+pub struct MixAll<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    inputs: Vec<(UniformSourceIterator<I, I::Item>, f32)>,
+    channels: u16,
+    sample_rate: u32,
+}
+
+impl<I> MixAll<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    /// Changes the gain of the source at `index` in the list given to `mix_all`.
+    #[inline]
+    pub fn set_gain(&mut self, index: usize, gain: f32) {
+        self.inputs[index].1 = gain;
+    }
+}
+
+impl<I> Iterator for MixAll<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    type Item = I::Item;
+
+    #[inline]
+    fn next(&mut self) -> Option<I::Item> {
+        let mut sum = 0.0;
+        let mut any = false;
+
+        for &mut (ref mut input, gain) in &mut self.inputs {
+            if let Some(value) = input.next() {
+                sum += value.to_f32() * gain;
+                any = true;
+            }
+        }
+
+        if any {
+            Some(Sample::from_sample(&sum.clamp(-1.0, 1.0)))
+        } else {
+            None
+        }
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let mut min = 0;
+        let mut max = Some(0);
+        for input in &self.inputs {
+            let hint = input.0.size_hint();
+            min = cmp::max(min, hint.0);
+            max = match (max, hint.1) {
+                (Some(max), Some(hint)) => Some(cmp::max(max, hint)),
+                _ => None,
+            };
+        }
+        (min, max)
+    }
+}
+
+impl<I> Source for MixAll<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        let mut len = None;
+        for input in &self.inputs {
+            match input.0.current_frame_len() {
+                // Sources that have ended don't constrain the frame.
+                Some(0) => (),
+                Some(frame) => len = Some(len.map_or(frame, |len| cmp::min(len, frame))),
+                None => return None,
+            }
+        }
+        Some(len.unwrap_or(0))
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        self.channels
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        let mut total = Duration::new(0, 0);
+        for input in &self.inputs {
+            total = cmp::max(total, input.0.total_duration()?);
+        }
+        Some(total)
+    }
+
+    #[inline]
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        for input in &mut self.inputs {
+            input.0.try_seek(pos)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use buffer::SamplesBuffer;
+    use source::{mix_all, Source};
+
+    #[test]
+    fn mixes_with_gains() {
+        let source = mix_all(vec![
+            (SamplesBuffer::new(1, 10, vec![0.5f32, 0.5, 0.5]), 0.5),
+            (SamplesBuffer::new(1, 10, vec![0.25f32]), 2.0),
+        ]);
+        assert_eq!(source.collect::<Vec<_>>(), [0.75, 0.25, 0.25]);
+    }
+
+    #[test]
+    fn converts_formats() {
+        let source = mix_all(vec![
+            (SamplesBuffer::new(1, 10, vec![0.5f32, 0.25]), 1.0),
+            (SamplesBuffer::new(2, 10, vec![0.25f32, -0.25]), 1.0),
+        ]);
+        assert_eq!(source.channels(), 2);
+        assert_eq!(source.collect::<Vec<_>>(), [0.75, 0.25, 0.25, 0.25]);
+    }
+}
@@ -1,56 +1,188 @@
 //! Sources of sound and various filters.
 
+use std::error::Error;
+use std::fmt;
+use std::io;
+use std::path::Path;
+use std::sync::Arc;
 use std::time::Duration;
 
 use Sample;
 
+pub use self::adsr::{Adsr, AdsrHandle};
+pub use self::agc::AutomaticGainControl;
+pub use self::align_frames::AlignFrames;
+pub use self::ambisonic::{
+    AmbisonicBinaural, AmbisonicDecoder, AmbisonicEncoder, AmbisonicEncoderHandle, AmbisonicRotate,
+    AmbisonicRotateHandle,
+};
 pub use self::amplify::Amplify;
-pub use self::blt::BltFilter;
+pub use self::automate::{Automate, Interpolation};
+pub use self::balance::Balance;
+pub use self::blend::{Blend, BlendHandle};
+pub use self::blt::{BltFilter, FilterHandle};
+pub use self::brown_noise::BrownNoise;
 pub use self::buffered::Buffered;
+pub use self::channel_layout::{ChannelLayout, ConvertLayout, Speaker};
 pub use self::channel_volume::ChannelVolume;
+pub use self::chirp::Chirp;
 pub use self::crossfade::Crossfade;
+pub use self::crossfeed::Crossfeed;
+pub use self::de_esser::DeEsser;
+pub use self::denoise::{Denoise, NoiseProfile};
 pub use self::delay::Delay;
+pub use self::detect_clipping::DetectClipping;
+pub use self::detect_silence::DetectSilence;
+pub use self::disk_cache::DiskCache;
 pub use self::done::Done;
+pub use self::doppler::{Doppler, DopplerHandle};
+pub use self::dtmf::{Dtmf, InvalidDtmfDigit};
 pub use self::empty::Empty;
+pub use self::exciter::Exciter;
+pub use self::fade_stop::{FadeStop, FadeStopHandle};
 pub use self::fadein::FadeIn;
-pub use self::from_factory::{from_factory, FromFactoryIter};
+pub use self::fm::FmSynth;
+pub use self::from_factory::{from_factories, from_factory, FromFactoriesIter, FromFactoryIter};
+pub use self::from_fn::{from_fn, FromFn};
 pub use self::from_iter::{from_iter, FromIter};
+pub use self::granular::Granular;
+pub use self::hrtf::{HrirSet, Hrtf, HrtfHandle};
+pub use self::key_gate::KeyGate;
+pub use self::loop_between::LoopBetween;
+pub use self::loudness::LoudnessNormalization;
+pub use self::metered::{ChannelLevel, MeterHandle, Metered};
+pub use self::mid_side::{MidSideDecoder, MidSideEncoder};
 pub use self::mix::Mix;
-pub use self::pausable::Pausable;
-pub use self::periodic::PeriodicAccess;
-pub use self::repeat::Repeat;
+pub use self::mix_all::{mix_all, MixAll};
+pub use self::modulate::Modulate;
+pub use self::multiband::{CompressorBand, MultibandCompressor};
+pub use self::music_loop::{music_loop, LoopPoints, MusicLoop};
+pub use self::on_progress::OnProgress;
+pub use self::pad::PadTo;
+pub use self::pausable::{PauseMode, Pausable};
+pub use self::periodic::{FramePeriodicAccess, PeriodicAccess};
+pub use self::pink_noise::PinkNoise;
+pub use self::remap_channels::{ChannelMap, RemapChannels};
+pub use self::remove_center::RemoveCenter;
+pub use self::repeat::{Repeat, RepeatN};
+pub use self::reverse::Reverse;
 pub use self::samples_converter::SamplesConverter;
+pub use self::sawtooth::SawtoothWave;
+pub use self::shared_buffered::SharedBuffered;
 pub use self::sine::SineWave;
-pub use self::spatial::Spatial;
+#[cfg(feature = "dasp")]
+pub use self::signal::{from_signal, FromSignal, IntoSignal};
+pub use self::skip_silence::SkipSilence;
+pub use self::skippable::{SkipHandle, Skippable};
+pub use self::soft_clip::SoftClip;
+pub use self::spatial::{DistanceAttenuation, DistanceModel, SoundCone, Spatial};
+pub use self::spectrum::{Spectrum, SpectrumHandle};
 pub use self::speed::Speed;
+pub use self::speed_hq::SpeedHq;
+pub use self::square::SquareWave;
 pub use self::stoppable::stoppable;
 pub use self::stoppable::Stoppable;
 pub use self::take::TakeDuration;
+pub use self::take_frames::TakeFrames;
+pub use self::tee::Tee;
+pub use self::to_mono::ToMono;
+pub use self::track_position::{PositionHandle, TrackPosition};
+pub use self::triangle::TriangleWave;
+pub use self::trim_silence::TrimSilence;
 pub use self::uniform::UniformSourceIterator;
+pub use self::wavetable::WavetableOscillator;
+pub use self::white_noise::WhiteNoise;
+pub use self::widen_mono::WidenMono;
 pub use self::zero::Zero;
 
+pub(crate) use self::channel_layout::convert_to_layout;
+pub(crate) use self::frame_position::FramePosition;
+pub(crate) use self::normalize::peak_factor;
+
+mod adsr;
+mod agc;
+mod align_frames;
+mod ambisonic;
 mod amplify;
+mod automate;
+mod balance;
+mod biquad;
+mod blend;
 mod blt;
+mod brown_noise;
 mod buffered;
+mod channel_layout;
 mod channel_volume;
+mod chirp;
 mod crossfade;
+mod crossfeed;
+mod de_esser;
+mod denoise;
 mod delay;
+mod detect_clipping;
+mod detect_silence;
+mod disk_cache;
 mod done;
+mod doppler;
+mod dtmf;
 mod empty;
+mod exciter;
+mod fft;
+mod fade_stop;
 mod fadein;
+mod fm;
+mod frame_position;
 mod from_factory;
+mod from_fn;
 mod from_iter;
+mod granular;
+mod hrtf;
+mod key_gate;
+mod loop_between;
+mod loudness;
+mod metered;
+mod mid_side;
 mod mix;
+mod mix_all;
+mod modulate;
+mod multiband;
+mod music_loop;
+mod normalize;
+mod on_progress;
+mod pad;
 mod pausable;
 mod periodic;
+mod pink_noise;
+mod remap_channels;
+mod remove_center;
 mod repeat;
+mod reverse;
 mod samples_converter;
+mod sawtooth;
+mod shared_buffered;
+#[cfg(feature = "dasp")]
+mod signal;
 mod sine;
+mod skip_silence;
+mod skippable;
+mod soft_clip;
 mod spatial;
+mod spectrum;
 mod speed;
+mod speed_hq;
+mod square;
 mod stoppable;
 mod take;
+mod take_frames;
+mod tee;
+mod to_mono;
+mod track_position;
+mod triangle;
+mod trim_silence;
 mod uniform;
+mod wavetable;
+mod white_noise;
+mod widen_mono;
 mod zero;
 
 /// A source of samples.
@@ -143,6 +275,22 @@ where
     /// `None` indicates at the same time "infinite" or "unknown".
     fn total_duration(&self) -> Option<Duration>;
 
+    /// Attempts to move the playback to `pos`, measured from the start of the source.
+    ///
+    /// Filters forward the call to the source that they wrap, which means that a decoder can be
+    /// seeked after having been wrapped in filters. Seeking past the end of a finite source
+    /// makes it end.
+    ///
+    /// The WAV, Vorbis and FLAC decoders support seeking, but the MP3 decoder doesn't.
+    ///
+    /// The default implementation returns `SeekError::NotSupported`.
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        let _ = pos;
+        Err(SeekError::NotSupported {
+            underlying_source: ::std::any::type_name::<Self>(),
+        })
+    }
+
     /// Stores the source in a buffer in addition to returning it. This iterator can be cloned.
     #[inline]
     fn buffered(self) -> Buffered<Self>
@@ -163,6 +311,21 @@ where
         mix::mix(self, other)
     }
 
+    /// Mixes this source with another one, with a ratio that can be changed while playing.
+    ///
+    /// A ratio of `0.0` only plays this source, and a ratio of `1.0` only plays `other`. The ratio
+    /// can be changed with `Blend::handle`, for example to crossfade between layers of music
+    /// depending on the state of a game.
+    #[inline]
+    fn blend<S>(self, other: S, ratio: f32) -> Blend<Self, S>
+    where
+        Self: Sized,
+        S: Source,
+        S::Item: Sample,
+    {
+        blend::blend(self, other, ratio)
+    }
+
     /// Repeats this source forever.
     ///
     /// Note that this works by storing the data in a buffer, so the amount of memory used is
@@ -175,6 +338,18 @@ where
         repeat::repeat(self)
     }
 
+    /// Plays this source `times` times in a row, without any gap between the repetitions.
+    ///
+    /// Note that this works by storing the data in a buffer, so the amount of memory used is
+    /// proportional to the size of the sound.
+    #[inline]
+    fn repeat(self, times: u32) -> RepeatN<Self>
+    where
+        Self: Sized,
+    {
+        repeat::repeat_n(self, times)
+    }
+
     /// Takes a certain duration of this source and then stops.
     #[inline]
     fn take_duration(self, duration: Duration) -> TakeDuration<Self>
@@ -184,6 +359,28 @@ where
         take::take_duration(self, duration)
     }
 
+    /// Takes a certain number of frames of this source, and then ends.
+    ///
+    /// Unlike `take_duration`, the length doesn't depend on rounding the duration to a number of
+    /// samples.
+    #[inline]
+    fn take_frames(self, frames: u64) -> TakeFrames<Self>
+    where
+        Self: Sized,
+    {
+        take_frames::take_frames(self, frames)
+    }
+
+    /// Gives the source an exact duration, by appending silence to it if it is shorter or by
+    /// truncating it if it is longer.
+    #[inline]
+    fn pad_to(self, duration: Duration) -> PadTo<Self>
+    where
+        Self: Sized,
+    {
+        pad::pad_to(self, duration)
+    }
+
     /// Delays the sound by a certain duration.
     ///
     /// The rate and channels of the silence will use the same format as the first frame of the
@@ -205,6 +402,17 @@ where
         amplify::amplify(self, value)
     }
 
+    /// Amplifies the sound by the given number of decibels.
+    ///
+    /// `0.0` leaves the sound untouched, and every `-6.0` approximately halves the amplitude.
+    #[inline]
+    fn amplify_db(self, decibels: f32) -> Amplify<Self>
+    where
+        Self: Sized,
+    {
+        amplify::amplify_db(self, decibels)
+    }
+
     /// Mixes this sound fading out with another sound fading in for the given duration.
     ///
     /// Only the crossfaded portion (beginning of self, beginning of other) is returned.
@@ -254,6 +462,136 @@ where
         speed::speed(self, ratio)
     }
 
+    /// Changes the play speed of the sound, and resamples it back to its sample rate so that the
+    /// pitch and tempo change without another conversion.
+    #[inline]
+    fn speed_hq(self, factor: f32) -> SpeedHq<Self>
+    where
+        Self: Sized,
+        Self::Item: Sample,
+    {
+        speed_hq::speed_hq(self, factor)
+    }
+
+    /// Shifts the pitch of the sound according to the velocity of its emitter relative to the
+    /// listener, as the Doppler effect does.
+    ///
+    /// `velocity` is positive when the emitter moves away from the listener and negative when it
+    /// comes closer. It is expressed in the same unit as `speed_of_sound`, which is about `343.0`
+    /// meters per second in the air. The velocity can be changed with `Doppler::handle`, for
+    /// example alongside the positions of a `SpatialSink`.
+    #[inline]
+    fn doppler(self, velocity: f32, speed_of_sound: f32) -> Doppler<Self>
+    where
+        Self: Sized,
+        Self::Item: Sample,
+    {
+        doppler::doppler(self, velocity, speed_of_sound)
+    }
+
+    /// Renders the sound for headphones, as if it was coming from a direction around the
+    /// listener.
+    ///
+    /// The sound is downmixed to mono and convolved with the head-related impulse responses of
+    /// `hrirs` closest to the direction, which gives elevation and front/back cues that panning
+    /// can't give. The direction is in degrees, and can be changed with `Hrtf::handle`. See
+    /// `HrirSet` for the coordinates.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::sync::Arc;
+    /// use rodio::Source;
+    /// use rodio::source::{HrirSet, SineWave};
+    ///
+    /// let hrirs = Arc::new(HrirSet::spherical_head(44100));
+    /// // A sine wave coming from the front left, slightly above the listener.
+    /// let source = SineWave::new(440).binaural(hrirs, 45.0, 20.0);
+    /// ```
+    ///
+    /// # Panic
+    ///
+    /// Panics if `hrirs` is empty.
+    #[inline]
+    fn binaural(self, hrirs: Arc<HrirSet>, azimuth: f32, elevation: f32) -> Hrtf<Self>
+    where
+        Self: Sized,
+    {
+        hrtf::hrtf(self, hrirs, azimuth, elevation)
+    }
+
+    /// Encodes the sound into first-order ambisonics, as if it was coming from a direction.
+    ///
+    /// The sound is downmixed to mono, and the output has the four channels of the AmbiX format.
+    /// The direction is in degrees, with the same coordinates as `HrirSet`, and can be changed
+    /// with `AmbisonicEncoder::handle`. Encoded sources can be mixed together, rotated with
+    /// `ambisonic_rotate`, and then decoded with `ambisonic_decode` or `ambisonic_binaural`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rodio::Source;
+    /// use rodio::source::SineWave;
+    ///
+    /// let field = SineWave::new(440)
+    ///     .ambisonic_encode(30.0, 0.0)
+    ///     .mix(SineWave::new(660).ambisonic_encode(-90.0, 45.0));
+    /// // The listener looks slightly to the left.
+    /// let stereo = field
+    ///     .ambisonic_rotate(20.0, 0.0, 0.0)
+    ///     .ambisonic_decode(&[(90.0, 0.0), (-90.0, 0.0)]);
+    /// ```
+    #[inline]
+    fn ambisonic_encode(self, azimuth: f32, elevation: f32) -> AmbisonicEncoder<Self>
+    where
+        Self: Sized,
+    {
+        ambisonic::encode(self, azimuth, elevation)
+    }
+
+    /// Rotates a first-order ambisonic sound field to follow the orientation of the head of the
+    /// listener.
+    ///
+    /// The angles are in degrees, and can be changed with `AmbisonicRotate::handle`. Frames that
+    /// don't have four channels are left untouched.
+    #[inline]
+    fn ambisonic_rotate(self, yaw: f32, pitch: f32, roll: f32) -> AmbisonicRotate<Self>
+    where
+        Self: Sized,
+    {
+        ambisonic::rotate(self, yaw, pitch, roll)
+    }
+
+    /// Decodes a first-order ambisonic sound field to a layout of speakers.
+    ///
+    /// Each speaker is given as an azimuth and an elevation in degrees, and gets one channel of
+    /// the output.
+    ///
+    /// # Panic
+    ///
+    /// Panics if `speakers` is empty.
+    #[inline]
+    fn ambisonic_decode(self, speakers: &[(f32, f32)]) -> AmbisonicDecoder<Self>
+    where
+        Self: Sized,
+    {
+        ambisonic::decode(self, speakers)
+    }
+
+    /// Decodes a first-order ambisonic sound field for headphones, with head-related impulse
+    /// responses.
+    ///
+    /// # Panic
+    ///
+    /// Panics if `hrirs` is empty.
+    #[inline]
+    fn ambisonic_binaural(self, hrirs: &HrirSet) -> AmbisonicBinaural<Self>
+    where
+        Self: Sized,
+    {
+        ambisonic::binaural(self, hrirs)
+    }
+
     /// Adds a basic reverb effect.
     ///
     /// This function requires the source to implement `Clone`. This can be done by using
@@ -276,6 +614,9 @@ where
     }
 
     /// Converts the samples of this source to another type.
+    ///
+    /// When converting to an integer type, `SamplesConverter::set_dither` can be used to avoid
+    /// the distortion of quiet sounds.
     #[inline]
     fn convert_samples<D>(self) -> SamplesConverter<Self, D>
     where
@@ -295,6 +636,18 @@ where
         pausable::pausable(self, initially_paused)
     }
 
+    /// Makes the sound pausable, choosing what happens to the sound while it is paused.
+    ///
+    /// With `PauseMode::KeepRunning`, the sound keeps advancing silently while paused, which is
+    /// useful to keep it in sync with something else.
+    #[inline]
+    fn pausable_with_mode(self, initially_paused: bool, mode: PauseMode) -> Pausable<Self>
+    where
+        Self: Sized,
+    {
+        pausable::pausable_with_mode(self, initially_paused, mode)
+    }
+
     /// Makes the sound stoppable.
     // TODO: add example
     #[inline]
@@ -305,8 +658,24 @@ where
         stoppable::stoppable(self)
     }
 
+    /// Makes the sound stoppable with a fade out of the given duration, so that it doesn't click
+    /// when it is stopped.
+    ///
+    /// The sound can be stopped after being handed to a sink through the handle returned by
+    /// `FadeStop::handle`.
+    #[inline]
+    fn stoppable_with_fade(self, duration: Duration) -> FadeStop<Self>
+    where
+        Self: Sized,
+    {
+        fade_stop::fade_stop(self, duration)
+    }
+
     /// Applies a low-pass filter to the source.
     /// **Warning**: Probably buggy.
+    ///
+    /// The cutoff frequency can be changed while the source is playing through the handle
+    /// returned by `BltFilter::handle`.
     #[inline]
     fn low_pass(self, freq: u32) -> BltFilter<Self>
     where
@@ -315,6 +684,620 @@ where
     {
         blt::low_pass(self, freq)
     }
+
+    /// Downmixes the sound to a single channel.
+    ///
+    /// The channels of each frame are averaged rather than summed, so that the result doesn't
+    /// clip when the same signal is present on every channel.
+    #[inline]
+    fn to_mono(self) -> ToMono<Self>
+    where
+        Self: Sized,
+    {
+        to_mono::to_mono(self)
+    }
+
+    /// Reorders, duplicates or drops the channels of the sound.
+    ///
+    /// The output has one channel per entry of `map`, and each entry describes where the samples
+    /// of that channel come from. For example `&[ChannelMap::Input(1), ChannelMap::Input(0)]`
+    /// swaps the left and right channels of a stereo sound.
+    ///
+    /// # Panic
+    ///
+    /// Panics if `map` is empty.
+    #[inline]
+    fn remap_channels(self, map: &[ChannelMap]) -> RemapChannels<Self>
+    where
+        Self: Sized,
+    {
+        remap_channels::remap_channels(self, map)
+    }
+
+    /// Converts the sound from a channel layout to another, playing each channel on the matching
+    /// speaker.
+    ///
+    /// Speakers missing from `to` are folded into the nearest ones at -3dB, and the subwoofer is
+    /// dropped if `to` has none. An output opened with a layout by `open_output` converts the
+    /// mono, stereo, 5.1 and 7.1 sounds played on it this way, so that their channels aren't
+    /// simply truncated or repeated.
+    ///
+    /// # Panic
+    ///
+    /// Panics if the sound doesn't have the number of channels of `from`.
+    #[inline]
+    fn convert_layout(self, from: ChannelLayout, to: ChannelLayout) -> ConvertLayout<Self>
+    where
+        Self: Sized,
+    {
+        channel_layout::convert_layout(self, from, to)
+    }
+
+    /// Plays the sound on a single speaker of a channel layout, and silence on the others.
+    ///
+    /// The channels of the sound are averaged.
+    ///
+    /// # Panic
+    ///
+    /// Panics if `layout` doesn't have `speaker`.
+    #[inline]
+    fn to_speaker(self, speaker: Speaker, layout: ChannelLayout) -> ConvertLayout<Self>
+    where
+        Self: Sized,
+    {
+        channel_layout::to_speaker(self, speaker, layout)
+    }
+
+    /// Attenuates the left or the right channel relative to the other.
+    ///
+    /// `-1.0` only keeps the left channel, `0.0` leaves the sound untouched and `1.0` only keeps
+    /// the right channel. Unlike panning the channels are never mixed together, and only the
+    /// first two channels are affected.
+    #[inline]
+    fn balance(self, value: f32) -> Balance<Self>
+    where
+        Self: Sized,
+    {
+        balance::balance(self, value)
+    }
+
+    /// Converts the left and right channels of the sound into mid and side channels, so that
+    /// they can be processed separately.
+    ///
+    /// The first channel becomes the mid channel, `(left + right) / 2`, and the second one
+    /// becomes the side channel, `(left - right) / 2`. Use `decode_mid_side` to convert them
+    /// back. Only frames with two channels are converted.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rodio::Source;
+    /// use rodio::buffer::SamplesBuffer;
+    ///
+    /// let stereo = SamplesBuffer::new(2, 44100, vec![0.5f32, -0.5, 0.25, 0.75]);
+    /// // Halves the side channel, which narrows the stereo image.
+    /// let narrower = stereo.encode_mid_side().balance(-0.5).decode_mid_side();
+    /// ```
+    #[inline]
+    fn encode_mid_side(self) -> MidSideEncoder<Self>
+    where
+        Self: Sized,
+    {
+        mid_side::encode(self)
+    }
+
+    /// Converts mid and side channels, as produced by `encode_mid_side`, back into left and
+    /// right channels.
+    #[inline]
+    fn decode_mid_side(self) -> MidSideDecoder<Self>
+    where
+        Self: Sized,
+    {
+        mid_side::decode(self)
+    }
+
+    /// Removes what is panned to the center of a stereo sound, usually the lead vocals, for
+    /// karaoke-style playback.
+    ///
+    /// This subtracts the mid channel from both channels, so everything that is identical in
+    /// the left and right channels cancels out. Frames that don't have two channels are left
+    /// untouched.
+    #[inline]
+    fn remove_center(self) -> RemoveCenter<Self>
+    where
+        Self: Sized,
+    {
+        remove_center::remove_center(self)
+    }
+
+    /// Turns a mono sound into a stereo one, by adding a delayed copy of it to the left channel
+    /// and subtracting it from the right one.
+    ///
+    /// The two channels still add up to the original sound, so the result can be mixed back to
+    /// mono. Frames that don't have one channel are left untouched.
+    #[inline]
+    fn widen_mono(self) -> WidenMono<Self>
+    where
+        Self: Sized,
+    {
+        widen_mono::widen_mono(self)
+    }
+
+    /// Bleeds a low-passed copy of each channel of a stereo sound into the other one, which
+    /// makes hard-panned sounds less tiring to listen to with headphones.
+    ///
+    /// `cutoff` is the frequency in Hertz under which the channels are crossed, and `level_db`
+    /// is how much, in decibels, the crossed part is quieter than the direct one at low
+    /// frequencies. Common settings are `(700, 4.5)`, `(700, 6.0)` for a subtler effect and
+    /// `(650, 9.5)`. Only frames with two channels are affected.
+    #[inline]
+    fn crossfeed(self, cutoff: u32, level_db: f32) -> Crossfeed<Self>
+    where
+        Self: Sized,
+    {
+        crossfeed::crossfeed(self, cutoff, level_db)
+    }
+
+    /// Compresses the sibilance of a voice, the harsh "s" and "sh" sounds.
+    ///
+    /// When the level of the frequencies between 5 kHz and 10 kHz goes above `threshold_db`, the
+    /// high frequencies are compressed with a ratio of 4. The band and the ratio can be changed
+    /// with `DeEsser::set_band` and `DeEsser::set_ratio`.
+    #[inline]
+    fn de_ess(self, threshold_db: f32) -> DeEsser<Self>
+    where
+        Self: Sized,
+    {
+        de_esser::de_esser(self, threshold_db)
+    }
+
+    /// Adds harmonics to the high frequencies of the sound, which makes dull sounds brighter.
+    ///
+    /// `amount` is how much of the harmonics is added: `0.0` leaves the sound untouched, and
+    /// values around `0.5` give a clear effect. Harmonics are created from the frequencies above
+    /// 3 kHz, which can be changed with `Exciter::set_frequency`.
+    #[inline]
+    fn excite(self, amount: f32) -> Exciter<Self>
+    where
+        Self: Sized,
+    {
+        exciter::exciter(self, amount)
+    }
+
+    /// Splits the sound into frequency bands and compresses each band on its own.
+    ///
+    /// `crossovers` are the frequencies in Hertz between the bands, from the lowest to the
+    /// highest, and `bands` the settings of each band, starting with the one under the first
+    /// crossover. Three or four bands are usual for broadcast-style loudness.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there isn't exactly one more band than crossover frequencies.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// let mut bands = [CompressorBand::default(); 3];
+    /// bands[0].threshold_db = -18.0;
+    /// bands[0].ratio = 3.0;
+    /// let source = source.compress_multiband(&[200.0, 3000.0], &bands);
+    /// ```
+    #[inline]
+    fn compress_multiband(
+        self,
+        crossovers: &[f32],
+        bands: &[CompressorBand],
+    ) -> MultibandCompressor<Self>
+    where
+        Self: Sized,
+    {
+        multiband::multiband(self, crossovers, bands)
+    }
+
+    /// Smoothly limits the samples to full scale, which tames the peaks of many sounds played
+    /// at once without the harshness of hard clipping.
+    ///
+    /// Samples whose absolute value is under `threshold`, between `0.0` and `1.0`, are left
+    /// untouched. Louder samples are bent towards `1.0` without ever reaching it.
+    #[inline]
+    fn soft_clip(self, threshold: f32) -> SoftClip<Self>
+    where
+        Self: Sized,
+    {
+        soft_clip::soft_clip(self, threshold)
+    }
+
+    /// Reduces steady background noise, such as the hiss of a microphone or of an old
+    /// recording, by up to `reduction_db` decibels.
+    ///
+    /// The noise is either learned from the start of the source or estimated as it plays,
+    /// depending on `profile`. The output is delayed by 512 frames, and the source must keep the
+    /// same number of channels.
+    #[inline]
+    fn denoise(self, profile: NoiseProfile, reduction_db: f32) -> Denoise<Self>
+    where
+        Self: Sized,
+        Self::Item: Sample,
+    {
+        denoise::denoise(self, profile, reduction_db)
+    }
+
+    /// Shapes the volume of the sound with an attack-decay-sustain-release envelope.
+    ///
+    /// The volume rises from silence to its maximum over `attack`, falls to `sustain_level` over
+    /// `decay`, and stays there until `note_off` is called on the returned source or on one of its
+    /// handles. It then fades back to silence over `release`, after which the sound ends.
+    #[inline]
+    fn adsr(
+        self, attack: Duration, decay: Duration, sustain_level: f32, release: Duration,
+    ) -> Adsr<Self>
+    where
+        Self: Sized,
+    {
+        adsr::adsr(self, attack, decay, sustain_level, release)
+    }
+
+    /// Drives a parameter of the sound with another source, such as a low-frequency oscillator.
+    ///
+    /// At the start of every frame, `param` is called with the source and with the current value
+    /// of `modulator` multiplied by `depth`. The modulator is read at its own sample rate, and only
+    /// its first channel is used.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rodio::Source;
+    /// use rodio::source::SineWave;
+    ///
+    /// // Tremolo: the volume oscillates between 0.5 and 1.5 five times per second.
+    /// let source = SineWave::new(440)
+    ///     .amplify(1.0)
+    ///     .modulate(SineWave::new(5), 0.5, |src, value| src.set_factor(1.0 + value));
+    /// ```
+    #[inline]
+    fn modulate<M, F>(self, modulator: M, depth: f32, param: F) -> Modulate<Self, M, F>
+    where
+        Self: Sized,
+        M: Source<Item = f32>,
+        F: FnMut(&mut Self, f32),
+    {
+        modulate::modulate(self, modulator, depth, param)
+    }
+
+    /// Continuously adjusts the volume so that the RMS level of the sound stays close to
+    /// `target_level`, between `0.0` and `1.0`.
+    ///
+    /// `attack` is the time constant with which the volume goes down when the sound gets louder,
+    /// and `release` the one with which it goes back up when the sound gets quieter.
+    #[inline]
+    fn agc(
+        self, target_level: f32, attack: Duration, release: Duration,
+    ) -> AutomaticGainControl<Self>
+    where
+        Self: Sized,
+    {
+        agc::agc(self, target_level, attack, release)
+    }
+
+    /// Adjusts the volume of the source so that its loudness, as defined by EBU R128, reaches
+    /// `target_lufs`.
+    ///
+    /// The loudness is measured while the source plays, which makes it possible for playlists of
+    /// differently mastered tracks to play at a consistent loudness. EBU R128 recommends
+    /// -23 LUFS, and streaming services usually aim for around -14 LUFS.
+    #[inline]
+    fn normalize_loudness(self, target_lufs: f32) -> LoudnessNormalization<Self>
+    where
+        Self: Sized,
+    {
+        loudness::normalize_loudness(self, target_lufs)
+    }
+
+    /// Amplifies the source so that its loudest sample reaches `peak`, between `0.0` and `1.0`.
+    ///
+    /// The whole source is first played through a clone in order to find its loudest sample,
+    /// which means that the source must be finite. This is typically used on sources that have
+    /// been `buffered`, for example when loading sound effects.
+    #[inline]
+    fn normalize(self, peak: f32) -> Amplify<Self>
+    where
+        Self: Sized + Clone,
+    {
+        normalize::normalize(self, peak)
+    }
+
+    /// Fast-forwards through the silent stretches of the source.
+    ///
+    /// Frames whose samples are all quieter than `threshold_db` (in decibels, for example
+    /// `-50.0`) are considered silent. Silences are shortened to `min_duration`, which keeps the
+    /// speech natural while skipping long pauses, for example in podcasts.
+    #[inline]
+    fn skip_silence(self, threshold_db: f32, min_duration: Duration) -> SkipSilence<Self>
+    where
+        Self: Sized,
+    {
+        skip_silence::skip_silence(self, threshold_db, min_duration)
+    }
+
+    /// Calls `callback` with `true` when the source becomes silent and with `false` when it stops
+    /// being silent, without modifying the samples.
+    ///
+    /// The RMS level of the source is measured over consecutive windows of duration `window`,
+    /// and compared to `threshold_db` (in decibels, for example `-50.0`). The source is assumed
+    /// not to be silent when it starts. This can be used for auto-pause or voice activity
+    /// detection.
+    #[inline]
+    fn detect_silence<F>(
+        self, threshold_db: f32, window: Duration, callback: F,
+    ) -> DetectSilence<Self, F>
+    where
+        Self: Sized,
+        F: FnMut(bool),
+    {
+        detect_silence::detect_silence(self, threshold_db, window, callback)
+    }
+
+    /// Calls `callback` for each sample whose absolute value is greater than `1.0`, without
+    /// modifying the samples.
+    ///
+    /// The callback receives the channel of the sample and its value. Such samples are clipped
+    /// when they are played, so this can be used to find which sounds of a mix are too loud.
+    #[inline]
+    fn detect_clipping<F>(self, callback: F) -> DetectClipping<Self, F>
+    where
+        Self: Sized,
+        F: FnMut(u16, f32),
+    {
+        detect_clipping::detect_clipping(self, callback)
+    }
+
+    /// Removes the silence at the start and at the end of the source.
+    ///
+    /// Frames whose samples are all quieter than `threshold_db` (in decibels, for example
+    /// `-50.0`) are considered silent. The silence at the start is skipped immediately, and the
+    /// source ends as soon as only silence remains. In order to know that, silences in the middle
+    /// of the source are read ahead and kept in memory until the sound resumes, up to the
+    /// duration given to `TrimSilence::set_max_silence`.
+    #[inline]
+    fn trim_silence(self, threshold_db: f32) -> TrimSilence<Self>
+    where
+        Self: Sized,
+    {
+        trim_silence::trim_silence(self, threshold_db)
+    }
+
+    /// Plays the source backwards.
+    ///
+    /// The whole source is read and kept in memory when this function is called, which means that
+    /// the source must be finite. A `buffered` source can be cloned before being reversed in order
+    /// to play it in both directions.
+    #[inline]
+    fn reverse(self) -> Reverse<Self>
+    where
+        Self: Sized,
+    {
+        reverse::reverse(self)
+    }
+
+    /// Plays this source until `end`, and then repeatedly goes back to `start`.
+    ///
+    /// The part before `start` is only played once, which is typically used for music that has
+    /// an intro. The positions are rounded down to a frame of the source, and the loop is
+    /// seamless. If the source ends before `end`, the loop ends at the end of the source.
+    ///
+    /// Note that this works by storing the data in a buffer, so the amount of memory used is
+    /// proportional to the size of the sound.
+    ///
+    /// # Panic
+    ///
+    /// Panics if `start` isn't before `end`.
+    #[inline]
+    fn loop_between(self, start: Duration, end: Duration) -> LoopBetween<Self>
+    where
+        Self: Sized,
+    {
+        loop_between::loop_between(self, start, end)
+    }
+
+    /// Keeps track of the playback position of the source.
+    ///
+    /// The position can be read from the returned source or, once the source has been handed to
+    /// a sink, from the `PositionHandle` returned by `TrackPosition::handle`.
+    #[inline]
+    fn track_position(self) -> TrackPosition<Self>
+    where
+        Self: Sized,
+    {
+        track_position::track_position(self)
+    }
+
+    /// Measures the peak and RMS level of each channel, for example to draw a VU meter.
+    ///
+    /// The samples are left untouched. The levels can be read from the `MeterHandle` returned by
+    /// `Metered::handle`.
+    #[inline]
+    fn metered(self) -> Metered<Self>
+    where
+        Self: Sized,
+    {
+        metered::metered(self)
+    }
+
+    /// Computes the frequency spectrum of the source, for example to draw a visualizer.
+    ///
+    /// The samples are left untouched. Every `hop` frames, the magnitudes of the FFT of the last
+    /// `fft_size` frames are published to the `SpectrumHandle` returned by `Spectrum::handle`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `fft_size` is not a power of two or if `hop` is zero.
+    #[inline]
+    fn spectrum(self, fft_size: usize, hop: usize) -> Spectrum<Self>
+    where
+        Self: Sized,
+    {
+        spectrum::spectrum(self, fft_size, hop)
+    }
+
+    /// Calls `callback` with the elapsed duration every time `interval` of the source has been
+    /// played.
+    ///
+    /// Unlike `periodic_access`, the elapsed duration is computed from the frames that were
+    /// actually read, taking changes of sample rate into account.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `interval` is zero.
+    #[inline]
+    fn on_progress<F>(self, interval: Duration, callback: F) -> OnProgress<Self, F>
+    where
+        Self: Sized,
+        F: FnMut(Duration),
+    {
+        on_progress::on_progress(self, interval, callback)
+    }
+
+    /// Calls the `access` closure on `Self` the first time the source is iterated and every
+    /// time `period` frames have been read.
+    ///
+    /// Unlike `periodic_access`, frames are counted exactly, and changes of `channels()` are taken
+    /// into account.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `period` is zero.
+    #[inline]
+    fn periodic_access_frames<F>(self, period: u32, access: F) -> FramePeriodicAccess<Self, F>
+    where
+        Self: Sized,
+        F: FnMut(&mut Self),
+    {
+        periodic::periodic_frames(self, period, access)
+    }
+
+    /// Splits the source into two copies that can be played independently, for example to play
+    /// one while recording or analyzing the other.
+    ///
+    /// Samples read by one copy are stored until the other copy reads them, up to one second of
+    /// audio.
+    #[inline]
+    fn tee(self) -> (Tee<Self>, Tee<Self>)
+    where
+        Self: Sized,
+    {
+        tee::tee(self)
+    }
+
+    /// Writes the samples to the file at `path` while they are played, so that seeking backwards
+    /// or replaying the source reads them from the file instead of decoding them again.
+    ///
+    /// The file is created immediately, and is deleted when the returned source is dropped.
+    #[inline]
+    fn cache_to_disk<P>(self, path: P) -> io::Result<DiskCache<Self>>
+    where
+        Self: Sized,
+        Self::Item: Sample,
+        P: AsRef<Path>,
+    {
+        disk_cache::disk_cache(self, path)
+    }
+
+    /// Only lets the source through while the level of `key` is above `threshold_db`, for
+    /// example to play a sound only while someone is speaking.
+    ///
+    /// `key` is converted to the format of this source and is not played.
+    #[inline]
+    fn gated_by<K>(self, key: K, threshold_db: f32) -> KeyGate<Self, K>
+    where
+        Self: Sized,
+        K: Source,
+        K::Item: Sample,
+    {
+        key_gate::key_gate(self, key, threshold_db)
+    }
+
+    /// Drives a parameter of the source along a timeline of keyframes, each one being a position
+    /// in the source and a value.
+    ///
+    /// At the start of every frame, `param` is called with the source and with the value of the
+    /// timeline at that position, interpolated between the surrounding keyframes.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use rodio::Source;
+    /// use rodio::source::{Interpolation, SineWave};
+    ///
+    /// // Fades in over one second, then fades out between the fourth and the fifth second.
+    /// let keyframes = vec![
+    ///     (Duration::from_secs(0), 0.0),
+    ///     (Duration::from_secs(1), 1.0),
+    ///     (Duration::from_secs(4), 1.0),
+    ///     (Duration::from_secs(5), 0.0),
+    /// ];
+    /// let source = SineWave::new(440)
+    ///     .amplify(0.0)
+    ///     .automate(|src, value| src.set_factor(value), keyframes, Interpolation::Linear);
+    /// ```
+    #[inline]
+    fn automate<F>(
+        self, param: F, keyframes: Vec<(Duration, f32)>, interpolation: Interpolation,
+    ) -> Automate<Self, F>
+    where
+        Self: Sized,
+        F: FnMut(&mut Self, f32),
+    {
+        automate::automate(self, param, keyframes, interpolation)
+    }
+
+    /// Makes it possible to jump forward in the source by `amount`, for example for a "skip 15
+    /// seconds" button.
+    ///
+    /// The jump is done by seeking if the source supports it, and by reading and dropping samples
+    /// otherwise. It can be requested after the source has been handed to a sink through the
+    /// handle returned by `Skippable::handle`.
+    #[inline]
+    fn skippable_by(self, amount: Duration) -> Skippable<Self>
+    where
+        Self: Sized,
+    {
+        skippable::skippable(self, amount)
+    }
+
+    /// Makes sure that the source only contains whole frames, by completing with silence the
+    /// frames that are cut short.
+    ///
+    /// This prevents the channels from being swapped when a source or one of its frames ends in
+    /// the middle of a frame.
+    #[inline]
+    fn align_frames(self) -> AlignFrames<Self>
+    where
+        Self: Sized,
+    {
+        align_frames::align_frames(self)
+    }
+
+    /// Converts the source to a `dasp` signal whose frames are `F`, so that it can be processed by
+    /// the DSP nodes of the `dasp` ecosystem.
+    ///
+    /// The channels of the source are converted to the number of channels of `F`. Use
+    /// `source::from_signal` to play the result.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// let signal = source.into_signal::<[f32; 2]>();
+    /// ```
+    #[cfg(feature = "dasp")]
+    #[inline]
+    fn into_signal<F>(self) -> IntoSignal<Self, F>
+    where
+        Self: Sized,
+        F: ::dasp::Frame<Sample = f32>,
+    {
+        signal::into_signal(self)
+    }
 }
 
 impl<S> Source for Box<dyn Source<Item = S>>
@@ -340,6 +1323,11 @@ where
     fn total_duration(&self) -> Option<Duration> {
         (**self).total_duration()
     }
+
+    #[inline]
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        (**self).try_seek(pos)
+    }
 }
 
 impl<S> Source for Box<dyn Source<Item = S> + Send>
@@ -365,6 +1353,11 @@ where
     fn total_duration(&self) -> Option<Duration> {
         (**self).total_duration()
     }
+
+    #[inline]
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        (**self).try_seek(pos)
+    }
 }
 
 impl<S> Source for Box<dyn Source<Item = S> + Send + Sync>
@@ -390,4 +1383,51 @@ where
     fn total_duration(&self) -> Option<Duration> {
         (**self).total_duration()
     }
+
+    #[inline]
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        (**self).try_seek(pos)
+    }
+}
+
+/// Error that can happen when seeking in a source.
+#[derive(Debug)]
+pub enum SeekError {
+    /// The source, or one of the sources that it wraps, doesn't support seeking.
+    NotSupported {
+        /// Name of the type of the source that doesn't support seeking.
+        underlying_source: &'static str,
+    },
+    /// Seeking in the data of a decoder failed.
+    Io(io::Error),
+    /// The seek was cancelled before being done, for example because the sound ended first.
+    NotDone,
+    /// Seeking in Vorbis data failed.
+    #[cfg(feature = "vorbis")]
+    Vorbis(::lewton::VorbisError),
+}
+
+impl fmt::Display for SeekError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            SeekError::NotSupported { underlying_source } => {
+                write!(f, "Seeking is not supported by {}", underlying_source)
+            },
+            SeekError::Io(ref err) => write!(f, "Error while seeking: {}", err),
+            SeekError::NotDone => write!(f, "The seek was cancelled before being done"),
+            #[cfg(feature = "vorbis")]
+            SeekError::Vorbis(ref err) => write!(f, "Error while seeking: {}", err),
+        }
+    }
+}
+
+impl Error for SeekError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match *self {
+            SeekError::NotSupported { .. } | SeekError::NotDone => None,
+            SeekError::Io(ref err) => Some(err),
+            #[cfg(feature = "vorbis")]
+            SeekError::Vorbis(ref err) => Some(err),
+        }
+    }
 }
@@ -0,0 +1,205 @@
+use std::time::Duration;
+
+use source::SeekError;
+use Sample;
+use Source;
+
+/// Internal function that builds a `Modulate` object.
+pub fn modulate<I, M, F>(input: I, modulator: M, depth: f32, param: F) -> Modulate<I, M, F>
+where
+    I: Source,
+    I::Item: Sample,
+    M: Source<Item = f32>,
+    F: FnMut(&mut I, f32),
+{
+    Modulate {
+        input,
+        modulator,
+        depth,
+        param,
+        // Makes sure that a value is read from the modulator before the first frame.
+        pending_modulator_frames: 1.0,
+        value: 0.0,
+        current_channel: 0,
+    }
+}
+
+/// Drives a parameter of a source with the samples of another source, such as a low-frequency
+/// oscillator.
+///
+/// At the start of every frame of the inner source, the closure is called with the inner source
+/// and with the current value of the modulator multiplied by the depth. The modulator is read at
+/// its own sample rate, and only its first channel is used. Once the modulator ends, its last
+/// value keeps being used.
+#[derive(Clone, Debug)]
+pub struct Modulate<I, M, F> {
+    input: I,
+    modulator: M,
+    depth: f32,
+    param: F,
+    // Number of modulator frames to read before the next frame of `input`.
+    pending_modulator_frames: f32,
+    // Last value read from the modulator.
+    value: f32,
+    // Channel of the next sample returned by `next`.
+    current_channel: u16,
+}
+
+impl<I, M, F> Modulate<I, M, F>
+where
+    I: Source,
+    I::Item: Sample,
+    M: Source<Item = f32>,
+    F: FnMut(&mut I, f32),
+{
+    /// Modifies the depth of the modulation.
+    #[inline]
+    pub fn set_depth(&mut self, depth: f32) {
+        self.depth = depth;
+    }
+
+    /// Returns a reference to the modulator.
+    #[inline]
+    pub fn modulator(&self) -> &M {
+        &self.modulator
+    }
+
+    /// Returns a mutable reference to the modulator.
+    #[inline]
+    pub fn modulator_mut(&mut self) -> &mut M {
+        &mut self.modulator
+    }
+
+    /// Returns a reference to the inner source.
+    #[inline]
+    pub fn inner(&self) -> &I {
+        &self.input
+    }
+
+    /// Returns a mutable reference to the inner source.
+    #[inline]
+    pub fn inner_mut(&mut self) -> &mut I {
+        &mut self.input
+    }
+
+    /// Returns the inner source.
+    #[inline]
+    pub fn into_inner(self) -> I {
+        self.input
+    }
+
+    // Reads the modulator up to the time of the next frame and applies its value.
+    fn next_frame(&mut self) {
+        while self.pending_modulator_frames >= 1.0 {
+            self.pending_modulator_frames -= 1.0;
+
+            let channels = self.modulator.channels();
+            if let Some(value) = self.modulator.next() {
+                self.value = value;
+                for _ in 1 .. channels {
+                    self.modulator.next();
+                }
+            }
+        }
+        self.pending_modulator_frames +=
+            self.modulator.sample_rate() as f32 / self.input.sample_rate() as f32;
+
+        (self.param)(&mut self.input, self.value * self.depth);
+    }
+}
+
+impl<I, M, F> Iterator for Modulate<I, M, F>
+where
+    I: Source,
+    I::Item: Sample,
+    M: Source<Item = f32>,
+    F: FnMut(&mut I, f32),
+{
+    type Item = I::Item;
+
+    #[inline]
+    fn next(&mut self) -> Option<I::Item> {
+        if self.current_channel >= self.input.channels() {
+            self.current_channel = 0;
+        }
+        if self.current_channel == 0 {
+            self.next_frame();
+        }
+        self.current_channel += 1;
+
+        self.input.next()
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.input.size_hint()
+    }
+}
+
+impl<I, M, F> Source for Modulate<I, M, F>
+where
+    I: Source,
+    I::Item: Sample,
+    M: Source<Item = f32>,
+    F: FnMut(&mut I, f32),
+{
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        self.input.current_frame_len()
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        self.input.channels()
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        self.input.sample_rate()
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        self.input.total_duration()
+    }
+
+    #[inline]
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        self.input.try_seek(pos)?;
+        self.current_channel = 0;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use buffer::SamplesBuffer;
+    use source::Source;
+
+    #[test]
+    fn drives_parameter() {
+        let input = SamplesBuffer::new(2, 4, vec![1.0f32; 8]);
+        let lfo = SamplesBuffer::new(1, 4, vec![0.0f32, 1.0, -1.0, 0.5]);
+        let source = input
+            .amplify(1.0)
+            .modulate(lfo, 0.5, |src, value| src.set_factor(1.0 + value));
+
+        assert_eq!(
+            source.collect::<Vec<_>>(),
+            [1.0, 1.0, 1.5, 1.5, 0.5, 0.5, 1.25, 1.25]
+        );
+    }
+
+    #[test]
+    fn slower_modulator() {
+        // The modulator runs at half the rate of the input, so each value is held two frames.
+        // Once it ends its last value is kept.
+        let input = SamplesBuffer::new(1, 4, vec![1.0f32; 6]);
+        let lfo = SamplesBuffer::new(1, 2, vec![0.0f32, 1.0]);
+        let source = input
+            .amplify(1.0)
+            .modulate(lfo, 1.0, |src, value| src.set_factor(value));
+
+        assert_eq!(source.collect::<Vec<_>>(), [0.0, 0.0, 1.0, 1.0, 1.0, 1.0]);
+    }
+}
@@ -0,0 +1,342 @@
+use std::time::Duration;
+
+use conversions::db_to_amplitude;
+use source::biquad::Biquad;
+use source::SeekError;
+use Sample;
+use Source;
+
+/// Time it takes for the envelope of a band to rise by a factor of `e`.
+const ATTACK_SECS: f32 = 0.005;
+/// Time it takes for the envelope of a band to decay by a factor of `e`.
+const RELEASE_SECS: f32 = 0.1;
+
+/// Internal function that builds a `MultibandCompressor` object.
+///
+/// # Panics
+///
+/// Panics if there isn't exactly one more band than crossover frequencies.
+pub fn multiband<I>(
+    input: I,
+    crossovers: &[f32],
+    bands: &[CompressorBand],
+) -> MultibandCompressor<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    assert_eq!(bands.len(), crossovers.len() + 1);
+    MultibandCompressor {
+        input,
+        crossovers: crossovers.to_vec(),
+        bands: bands.to_vec(),
+        sample_rate: 0,
+        low_pass: Vec::new(),
+        high_pass: Vec::new(),
+        all_pass: Vec::new(),
+        states: Vec::new(),
+        envelopes: vec![0.0; bands.len()],
+        values: Vec::new(),
+        frame: Vec::new(),
+        current_channel: 0,
+    }
+}
+
+/// Settings of one band of a `MultibandCompressor`.
+///
+/// The default leaves the band untouched.
+#[derive(Clone, Copy, Debug)]
+pub struct CompressorBand {
+    /// Level of the band, in decibels, above which it is compressed.
+    pub threshold_db: f32,
+    /// How much the level above the threshold is compressed. `4.0` means that the level above
+    /// the threshold is divided by 4.
+    pub ratio: f32,
+    /// Gain in decibels applied to the band after the compression.
+    pub makeup_db: f32,
+}
+
+impl Default for CompressorBand {
+    fn default() -> CompressorBand {
+        CompressorBand {
+            threshold_db: 0.0,
+            ratio: 1.0,
+            makeup_db: 0.0,
+        }
+    }
+}
+
+/// Filter that splits a sound into frequency bands and compresses each band on its own.
+///
+/// The bands are split with Linkwitz-Riley crossovers, so that they add up to the original sound
+/// when nothing is compressed. The level of each band is followed on all the channels at once.
+#[derive(Clone, Debug)]
+pub struct MultibandCompressor<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    input: I,
+    crossovers: Vec<f32>,
+    bands: Vec<CompressorBand>,
+    // Sample rate that the filters were computed for.
+    sample_rate: u32,
+    // Filters of each crossover. The low-pass and high-pass filters are applied twice.
+    low_pass: Vec<Biquad>,
+    high_pass: Vec<Biquad>,
+    // Filters that give the bands under a crossover the same phase as the bands above it.
+    all_pass: Vec<Biquad>,
+    // State of the filters of each channel, in the order they are applied.
+    states: Vec<Vec<[f32; 4]>>,
+    envelopes: Vec<f32>,
+    // Value of each band of each channel of the current frame.
+    values: Vec<f32>,
+    // Output samples of the current frame.
+    frame: Vec<I::Item>,
+    // Channel of the next sample returned by `next`.
+    current_channel: u16,
+}
+
+impl<I> MultibandCompressor<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    /// Modifies the settings of a band. The bands are numbered from the lowest frequencies.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is not the index of a band.
+    #[inline]
+    pub fn set_band(&mut self, index: usize, band: CompressorBand) {
+        self.bands[index] = band;
+    }
+
+    /// Modifies the frequency in Hertz of a crossover between two bands. The crossover `index`
+    /// is between the bands `index` and `index + 1`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is not the index of a crossover.
+    #[inline]
+    pub fn set_crossover(&mut self, index: usize, frequency: f32) {
+        self.crossovers[index] = frequency;
+        self.sample_rate = 0;
+    }
+
+    /// Returns a reference to the inner source.
+    #[inline]
+    pub fn inner(&self) -> &I {
+        &self.input
+    }
+
+    /// Returns a mutable reference to the inner source.
+    #[inline]
+    pub fn inner_mut(&mut self) -> &mut I {
+        &mut self.input
+    }
+
+    /// Returns the inner source.
+    #[inline]
+    pub fn into_inner(self) -> I {
+        self.input
+    }
+
+    // Reads and processes a frame of the input. Returns `false` if the input has ended.
+    fn next_frame(&mut self) -> bool {
+        let sample_rate = self.input.sample_rate();
+        if sample_rate != self.sample_rate {
+            self.sample_rate = sample_rate;
+            // The filters must stay under the Nyquist frequency.
+            let max_freq = sample_rate as f32 * 0.45;
+            let freqs = self.crossovers.iter().map(|&freq| freq.min(max_freq));
+            self.low_pass = freqs.clone().map(|f| Biquad::low_pass(f, sample_rate)).collect();
+            self.high_pass = freqs.clone().map(|f| Biquad::high_pass(f, sample_rate)).collect();
+            self.all_pass = freqs.map(|f| Biquad::all_pass(f, sample_rate)).collect();
+        }
+
+        let channels = self.input.channels() as usize;
+        self.frame.clear();
+        self.frame.extend(self.input.by_ref().take(channels));
+        if self.frame.is_empty() {
+            return false;
+        }
+
+        // Each crossover has four filters, plus one all-pass filter per band under it.
+        let crossovers = self.crossovers.len();
+        let filters = 4 * crossovers + crossovers * crossovers.saturating_sub(1) / 2;
+        self.states.resize(self.frame.len(), Vec::new());
+        for state in &mut self.states {
+            state.resize(filters, [0.0; 4]);
+        }
+
+        // Splits each channel into bands.
+        let bands = self.bands.len();
+        self.values.clear();
+        self.values.resize(self.frame.len() * bands, 0.0);
+        for (channel, value) in self.frame.iter().enumerate() {
+            let state = &mut self.states[channel];
+            let values = &mut self.values[channel * bands .. (channel + 1) * bands];
+            let mut rest = value.to_f32();
+            let mut filter = 0;
+            for crossover in 0 .. crossovers {
+                let low_pass = &self.low_pass[crossover];
+                let high_pass = &self.high_pass[crossover];
+                let low = low_pass.apply(&mut state[filter], rest);
+                let low = low_pass.apply(&mut state[filter + 1], low);
+                let high = high_pass.apply(&mut state[filter + 2], rest);
+                let high = high_pass.apply(&mut state[filter + 3], high);
+                filter += 4;
+                for value in &mut values[.. crossover] {
+                    *value = self.all_pass[crossover].apply(&mut state[filter], *value);
+                    filter += 1;
+                }
+                values[crossover] = low;
+                rest = high;
+            }
+            values[crossovers] = rest;
+        }
+
+        // Compresses each band.
+        let mut gains = Vec::with_capacity(bands);
+        for (band, settings) in self.bands.iter().enumerate() {
+            let level = (0 .. self.frame.len())
+                .map(|channel| self.values[channel * bands + band].abs())
+                .fold(0.0f32, f32::max);
+
+            let envelope = &mut self.envelopes[band];
+            let time = if level > *envelope { ATTACK_SECS } else { RELEASE_SECS };
+            let coeff = (-1.0 / (time * sample_rate as f32)).exp();
+            *envelope = level + coeff * (*envelope - level);
+
+            let threshold = db_to_amplitude(settings.threshold_db);
+            let makeup = db_to_amplitude(settings.makeup_db);
+            let gain = if *envelope > threshold {
+                (threshold / *envelope).powf(1.0 - 1.0 / settings.ratio.max(1.0))
+            } else {
+                1.0
+            };
+            gains.push(gain * makeup);
+        }
+
+        for (channel, value) in self.frame.iter_mut().enumerate() {
+            let values = &self.values[channel * bands .. (channel + 1) * bands];
+            let sum: f32 = values.iter().zip(&gains).map(|(value, gain)| value * gain).sum();
+            *value = Sample::from_sample(&sum);
+        }
+        true
+    }
+}
+
+impl<I> Iterator for MultibandCompressor<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    type Item = I::Item;
+
+    #[inline]
+    fn next(&mut self) -> Option<I::Item> {
+        if self.current_channel as usize >= self.frame.len() {
+            if !self.next_frame() {
+                return None;
+            }
+            self.current_channel = 0;
+        }
+
+        let value = self.frame[self.current_channel as usize];
+        self.current_channel += 1;
+        Some(value)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let in_frame = self.frame.len().saturating_sub(self.current_channel as usize);
+        let (min, max) = self.input.size_hint();
+        (min + in_frame, max.map(|max| max + in_frame))
+    }
+}
+
+impl<I> ExactSizeIterator for MultibandCompressor<I>
+where
+    I: Source + ExactSizeIterator,
+    I::Item: Sample,
+{
+}
+
+impl<I> Source for MultibandCompressor<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        let in_frame = self.frame.len().saturating_sub(self.current_channel as usize);
+        if in_frame != 0 {
+            return Some(in_frame);
+        }
+        self.input.current_frame_len()
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        if (self.current_channel as usize) < self.frame.len() {
+            self.frame.len() as u16
+        } else {
+            self.input.channels()
+        }
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        self.input.sample_rate()
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        self.input.total_duration()
+    }
+
+    #[inline]
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        self.input.try_seek(pos)?;
+        self.frame.clear();
+        self.current_channel = 0;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use buffer::SamplesBuffer;
+    use source::{CompressorBand, SineWave, Source};
+
+    const CROSSOVERS: [f32; 2] = [200.0, 2000.0];
+
+    // Returns the highest absolute value among the last samples of a sine wave that went through
+    // a compressor.
+    fn tail_peak(freq: u32, bands: &[CompressorBand]) -> f32 {
+        let wave = SineWave::new(freq).take(22050).collect::<Vec<_>>();
+        let source = SamplesBuffer::new(1, 44100, wave).compress_multiband(&CROSSOVERS, bands);
+        source.skip(20000).fold(0.0, |peak, s| peak.max(s.abs()))
+    }
+
+    #[test]
+    fn untouched_bands_add_up() {
+        let bands = [CompressorBand::default(); 3];
+        for &freq in &[100, 200, 700, 2000, 5000] {
+            let peak = tail_peak(freq, &bands);
+            assert!((peak - 1.0).abs() < 0.02, "{} {}", freq, peak);
+        }
+    }
+
+    #[test]
+    fn compresses_one_band() {
+        let mut bands = [CompressorBand::default(); 3];
+        bands[1].threshold_db = -20.0;
+        bands[1].ratio = 10.0;
+        assert!(tail_peak(700, &bands) < 0.3);
+        assert!(tail_peak(50, &bands) > 0.95);
+        assert!(tail_peak(8000, &bands) > 0.95);
+    }
+}
@@ -0,0 +1,301 @@
+use std::f32::consts::PI;
+use std::sync::Arc;
+use std::time::Duration;
+
+use source::SeekError;
+use Sample;
+use Source;
+
+/// Distance around a loop point within which a zero crossing is looked for.
+const ZERO_CROSSING_SEARCH_SECS: f32 = 0.01;
+
+/// Builds a source that plays the intro of a piece of music once, and then loops its body
+/// forever.
+///
+/// The whole input is decoded in memory first, so this is typically given a `Decoder`. The
+/// input must keep the same channels and sample rate until its end.
+///
+/// The end of the body is spliced to its start with an equal-power crossfade of
+/// `points.splice`, which hides small discontinuities of the loop points. The first time, the
+/// body is played from its start; afterwards, the loop goes from the end of the crossfade to the
+/// end of the body.
+///
+/// # Panic
+///
+/// Panics if `points.start` isn't before `points.end`.
+pub fn music_loop<I>(input: I, points: LoopPoints) -> MusicLoop<I::Item>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    if let Some(end) = points.end {
+        assert!(points.start < end);
+    }
+
+    let channels = input.channels();
+    let sample_rate = input.sample_rate();
+    let samples = input.collect::<Vec<_>>();
+
+    let channels_usize = channels as usize;
+    let len = samples.len() / channels_usize;
+    let to_frame = |duration: Duration| {
+        let frame = duration.as_secs_f64() * sample_rate as f64;
+        (frame as usize).min(len)
+    };
+    let mut start = to_frame(points.start);
+    let mut end = points.end.map_or(len, to_frame);
+    if points.snap_to_zero_crossings {
+        let search = (ZERO_CROSSING_SEARCH_SECS * sample_rate as f32) as usize;
+        start = nearest_zero_crossing(&samples, channels_usize, start, search);
+        end = nearest_zero_crossing(&samples, channels_usize, end, search);
+    }
+    let end = end.max(start);
+
+    let splice = to_frame(points.splice).min((end - start) / 2);
+    let frame = |index: usize| &samples[index * channels_usize .. (index + 1) * channels_usize];
+
+    // The intro and the first pass of the body, up to the crossfade.
+    let once = samples[.. (end - splice) * channels_usize].to_vec();
+
+    // The crossfade from the end of the body to its start, and the rest of the body.
+    let mut body = Vec::with_capacity((end - start - splice) * channels_usize);
+    for i in 0 .. splice {
+        let t = (i as f32 + 0.5) / splice as f32 * PI / 2.0;
+        let (fade_in, fade_out) = t.sin_cos();
+        let tail = frame(end - splice + i);
+        let head = frame(start + i);
+        for (tail, head) in tail.iter().zip(head) {
+            let value = tail.to_f32() * fade_out + head.to_f32() * fade_in;
+            body.push(Sample::from_sample(&value));
+        }
+    }
+    body.extend_from_slice(&samples[(start + splice) * channels_usize .. once.len()]);
+
+    MusicLoop {
+        once: Arc::new(once),
+        body: Arc::new(body),
+        in_body: false,
+        position: 0,
+        channels,
+        sample_rate,
+    }
+}
+
+// Returns the frame closest to `frame`, at most `search` frames away, where the sum of the
+// channels changes sign. Returns `frame` if there is none.
+fn nearest_zero_crossing<S>(samples: &[S], channels: usize, frame: usize, search: usize) -> usize
+where
+    S: Sample,
+{
+    let len = samples.len() / channels;
+    let level = |index: usize| {
+        samples[index * channels .. (index + 1) * channels]
+            .iter()
+            .map(|s| s.to_f32())
+            .sum::<f32>()
+    };
+    let crosses = |index: usize| {
+        index > 0 && index < len && (level(index - 1) < 0.0) != (level(index) < 0.0)
+    };
+
+    for distance in 0 ..= search {
+        if frame >= distance && crosses(frame - distance) {
+            return frame - distance;
+        }
+        if crosses(frame + distance) {
+            return frame + distance;
+        }
+    }
+    frame
+}
+
+/// Loop points of a piece of music, given to `music_loop`.
+///
+/// The default loops the whole sound with no crossfade.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LoopPoints {
+    /// Start of the body of the music. What comes before is the intro, played only once.
+    pub start: Duration,
+    /// End of the body of the music, or `None` to loop until the end of the sound. What comes
+    /// after is never played.
+    pub end: Option<Duration>,
+    /// Duration of the crossfade from the end of the body to its start.
+    pub splice: Duration,
+    /// If true, `start` and `end` are moved to the nearest zero crossing within 10
+    /// milliseconds, for loop points that aren't sample-accurate.
+    pub snap_to_zero_crossings: bool,
+}
+
+impl Default for LoopPoints {
+    fn default() -> LoopPoints {
+        LoopPoints {
+            start: Duration::from_secs(0),
+            end: None,
+            splice: Duration::from_secs(0),
+            snap_to_zero_crossings: false,
+        }
+    }
+}
+
+/// A source that plays the intro of a piece of music once, and then loops its body forever.
+#[derive(Clone, Debug)]
+pub struct MusicLoop<S> {
+    // The intro and the first pass of the body.
+    once: Arc<Vec<S>>,
+    // The part of the body that is looped, starting with the crossfade.
+    body: Arc<Vec<S>>,
+    // Whether `position` is in `body` rather than in `once`.
+    in_body: bool,
+    position: usize,
+    channels: u16,
+    sample_rate: u32,
+}
+
+impl<S> MusicLoop<S> {
+    // Returns the samples currently being played.
+    #[inline]
+    fn current(&self) -> &[S] {
+        if self.in_body {
+            &self.body
+        } else {
+            &self.once
+        }
+    }
+}
+
+impl<S> Iterator for MusicLoop<S>
+where
+    S: Sample,
+{
+    type Item = S;
+
+    #[inline]
+    fn next(&mut self) -> Option<S> {
+        if self.position >= self.current().len() {
+            if self.body.is_empty() {
+                return None;
+            }
+            self.in_body = true;
+            self.position = 0;
+        }
+
+        let value = self.current()[self.position];
+        self.position += 1;
+        Some(value)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        if self.body.is_empty() {
+            let remaining = self.once.len().saturating_sub(self.position);
+            (remaining, Some(remaining))
+        } else {
+            (usize::MAX, None)
+        }
+    }
+}
+
+impl<S> Source for MusicLoop<S>
+where
+    S: Sample,
+{
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        let remaining = self.current().len() - self.position;
+        if remaining != 0 {
+            Some(remaining)
+        } else if self.body.is_empty() {
+            Some(0)
+        } else {
+            Some(self.body.len())
+        }
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        self.channels
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        if self.body.is_empty() {
+            let frames = self.once.len() / self.channels as usize;
+            Some(Duration::from_secs_f64(frames as f64 / self.sample_rate as f64))
+        } else {
+            None
+        }
+    }
+
+    #[inline]
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        let frame = (pos.as_secs_f64() * self.sample_rate as f64) as usize;
+        let sample = frame * self.channels as usize;
+        if sample < self.once.len() || self.body.is_empty() {
+            self.in_body = false;
+            self.position = sample.min(self.once.len());
+        } else {
+            self.in_body = true;
+            self.position = (sample - self.once.len()) % self.body.len();
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::PI;
+    use std::time::Duration;
+
+    use buffer::SamplesBuffer;
+    use source::{music_loop, LoopPoints};
+
+    #[test]
+    fn intro_then_body() {
+        let source = SamplesBuffer::new(1, 1, vec![1.0f32, 2.0, 3.0, 4.0, 5.0]);
+        let points = LoopPoints {
+            start: Duration::from_secs(1),
+            end: Some(Duration::from_secs(4)),
+            ..LoopPoints::default()
+        };
+        let output = music_loop(source, points).take(10).collect::<Vec<_>>();
+        assert_eq!(output, [1.0, 2.0, 3.0, 4.0, 2.0, 3.0, 4.0, 2.0, 3.0, 4.0]);
+    }
+
+    #[test]
+    fn equal_power_splice() {
+        let source = SamplesBuffer::new(1, 1, vec![0.0f32, 1.0, 1.0, 1.0, 1.0, -1.0, -1.0]);
+        let points = LoopPoints {
+            start: Duration::from_secs(1),
+            end: Some(Duration::from_secs(7)),
+            splice: Duration::from_secs(2),
+            ..LoopPoints::default()
+        };
+        let output = music_loop(source, points).take(11).collect::<Vec<_>>();
+        assert_eq!(&output[.. 5], [0.0, 1.0, 1.0, 1.0, 1.0]);
+
+        // The crossfade goes from -1 to 1, with gains whose squares add up to 1.
+        let (sin, cos) = (PI / 8.0).sin_cos();
+        assert!((output[5] - (sin - cos)).abs() < 1e-6);
+        assert!((output[6] - (cos - sin)).abs() < 1e-6);
+        assert_eq!(&output[7 .. 9], [1.0, 1.0]);
+        assert!((output[9] - (sin - cos)).abs() < 1e-6);
+    }
+
+    #[test]
+    fn snaps_to_zero_crossings() {
+        let source = SamplesBuffer::new(1, 100, vec![-1.0f32, -1.0, 1.0, 1.0, 1.0, 1.0, -1.0, -1.0]);
+        let points = LoopPoints {
+            start: Duration::from_millis(10),
+            end: Some(Duration::from_millis(70)),
+            snap_to_zero_crossings: true,
+            ..LoopPoints::default()
+        };
+        let output = music_loop(source, points).take(10).collect::<Vec<_>>();
+        assert_eq!(output, [-1.0, -1.0, 1.0, 1.0, 1.0, 1.0, 1.0, 1.0, 1.0, 1.0]);
+    }
+}
@@ -0,0 +1,50 @@
+use source::amplify::{self, Amplify};
+use Sample;
+use Source;
+
+/// Internal function that builds an `Amplify` object whose loudest sample reaches `peak`.
+///
+/// A clone of `input` is played until its end in order to find its loudest sample.
+pub fn normalize<I>(input: I, peak: f32) -> Amplify<I>
+where
+    I: Source + Clone,
+    I::Item: Sample,
+{
+    let factor = peak_factor(input.clone(), peak);
+    amplify::amplify(input, factor)
+}
+
+/// Returns the factor by which `samples` must be amplified for their loudest one to reach `peak`.
+///
+/// Returns `1.0` if all the samples are silent.
+pub(crate) fn peak_factor<I>(samples: I, peak: f32) -> f32
+where
+    I: Iterator,
+    I::Item: Sample,
+{
+    let max = samples.fold(0.0f32, |max, sample| max.max(sample.to_f32().abs()));
+    if max > 0.0 {
+        peak / max
+    } else {
+        1.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use buffer::SamplesBuffer;
+    use source::Source;
+
+    #[test]
+    fn scales_to_peak() {
+        let source = SamplesBuffer::new(1, 44100, vec![0.1f32, -0.4, 0.2]).buffered();
+        let samples = source.normalize(0.8).collect::<Vec<_>>();
+        assert_eq!(samples, [0.2, -0.8, 0.4]);
+    }
+
+    #[test]
+    fn silence_is_untouched() {
+        let source = SamplesBuffer::new(1, 44100, vec![0i16; 4]).buffered();
+        assert_eq!(source.normalize(1.0).collect::<Vec<_>>(), [0; 4]);
+    }
+}
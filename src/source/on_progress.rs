@@ -0,0 +1,186 @@
+use std::time::Duration;
+
+use conversions::duration_to_ns;
+use source::{FramePosition, SeekError};
+use Sample;
+use Source;
+
+/// Internal function that builds a `OnProgress` object.
+pub fn on_progress<I, F>(input: I, interval: Duration, callback: F) -> OnProgress<I, F>
+where
+    I: Source,
+    I::Item: Sample,
+    F: FnMut(Duration),
+{
+    let interval_ns = duration_to_ns(interval);
+    assert!(interval_ns != 0);
+    let position = FramePosition::new(input.sample_rate());
+
+    OnProgress {
+        input,
+        callback,
+        interval_ns,
+        next_report_ns: interval_ns,
+        position,
+    }
+}
+
+/// Calls a function with the elapsed duration every time an interval of the source has been
+/// played.
+///
+/// The elapsed duration is measured in frames read from the source, and not in wall time.
+#[derive(Clone, Debug)]
+pub struct OnProgress<I, F> {
+    input: I,
+    callback: F,
+    interval_ns: u64,
+    // Elapsed duration at which the callback will be called next.
+    next_report_ns: u64,
+    position: FramePosition,
+}
+
+impl<I, F> OnProgress<I, F>
+where
+    I: Source,
+    I::Item: Sample,
+    F: FnMut(Duration),
+{
+    /// Returns a reference to the inner source.
+    #[inline]
+    pub fn inner(&self) -> &I {
+        &self.input
+    }
+
+    /// Returns a mutable reference to the inner source.
+    #[inline]
+    pub fn inner_mut(&mut self) -> &mut I {
+        &mut self.input
+    }
+
+    /// Returns the inner source.
+    #[inline]
+    pub fn into_inner(self) -> I {
+        self.input
+    }
+
+    // Called when the first sample of each frame is read.
+    fn next_frame(&mut self) {
+        self.position.next_frame(self.input.sample_rate());
+
+        let elapsed_ns = self.position.position_ns();
+        if elapsed_ns >= self.next_report_ns {
+            (self.callback)(Duration::from_nanos(elapsed_ns));
+            self.next_report_ns += self.interval_ns;
+        }
+    }
+}
+
+impl<I, F> Iterator for OnProgress<I, F>
+where
+    I: Source,
+    I::Item: Sample,
+    F: FnMut(Duration),
+{
+    type Item = I::Item;
+
+    #[inline]
+    fn next(&mut self) -> Option<I::Item> {
+        let value = self.input.next()?;
+
+        if self.position.is_frame_start(self.input.channels()) {
+            self.next_frame();
+        }
+
+        Some(value)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.input.size_hint()
+    }
+}
+
+impl<I, F> ExactSizeIterator for OnProgress<I, F>
+where
+    I: Source + ExactSizeIterator,
+    I::Item: Sample,
+    F: FnMut(Duration),
+{
+}
+
+impl<I, F> Source for OnProgress<I, F>
+where
+    I: Source,
+    I::Item: Sample,
+    F: FnMut(Duration),
+{
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        self.input.current_frame_len()
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        self.input.channels()
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        self.input.sample_rate()
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        self.input.total_duration()
+    }
+
+    #[inline]
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        self.input.try_seek(pos)?;
+
+        let pos_ns = duration_to_ns(pos);
+        self.position.set_position_ns(pos_ns, self.input.sample_rate());
+        self.next_report_ns = (pos_ns / self.interval_ns + 1) * self.interval_ns;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use buffer::SamplesBuffer;
+    use source::Source;
+
+    #[test]
+    fn reports_elapsed_audio() {
+        let source = SamplesBuffer::new(2, 4, vec![0i16; 24]);
+
+        let mut reports = Vec::new();
+        source
+            .on_progress(Duration::from_millis(1000), |elapsed| reports.push(elapsed))
+            .for_each(drop);
+
+        assert_eq!(
+            reports,
+            [Duration::from_secs(1), Duration::from_secs(2), Duration::from_secs(3)]
+        );
+    }
+
+    #[test]
+    fn seek() {
+        let source = SamplesBuffer::new(1, 10, vec![0i16; 40]);
+
+        let mut reports = Vec::new();
+        {
+            let mut source = source.on_progress(Duration::from_secs(1), |e| reports.push(e));
+            source.try_seek(Duration::from_millis(1500)).unwrap();
+            source.for_each(drop);
+        }
+
+        assert_eq!(
+            reports,
+            [Duration::from_secs(2), Duration::from_secs(3), Duration::from_secs(4)]
+        );
+    }
+}
@@ -0,0 +1,194 @@
+use std::cmp;
+use std::time::Duration;
+
+use conversions::duration_to_frames;
+use source::SeekError;
+use Sample;
+use Source;
+
+/// Internal function that builds a `PadTo` object.
+pub fn pad_to<I>(input: I, duration: Duration) -> PadTo<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    let frames = duration_to_frames(duration, input.sample_rate());
+    let channels = input.channels();
+    let sample_rate = input.sample_rate();
+
+    PadTo {
+        input,
+        input_ended: false,
+        frames,
+        remaining_frames: frames,
+        current_channel: channels,
+        channels,
+        sample_rate,
+        duration,
+    }
+}
+
+/// Filter that gives a source an exact duration, by appending silence to it or by truncating it.
+///
+/// The number of frames is computed from the sample rate of the first frame of the source, and the
+/// silence has the format of the last frame.
+#[derive(Clone, Debug)]
+pub struct PadTo<I> {
+    input: I,
+    input_ended: bool,
+    frames: u64,
+    // Number of frames that haven't been started yet.
+    remaining_frames: u64,
+    // Channel of the next sample returned by `next`.
+    current_channel: u16,
+    // Format of the current frame.
+    channels: u16,
+    sample_rate: u32,
+    duration: Duration,
+}
+
+impl<I> PadTo<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    /// Returns a reference to the inner source.
+    #[inline]
+    pub fn inner(&self) -> &I {
+        &self.input
+    }
+
+    /// Returns a mutable reference to the inner source.
+    #[inline]
+    pub fn inner_mut(&mut self) -> &mut I {
+        &mut self.input
+    }
+
+    /// Returns the inner source.
+    #[inline]
+    pub fn into_inner(self) -> I {
+        self.input
+    }
+
+    // Returns the number of samples left before the end.
+    #[inline]
+    fn remaining_samples(&self) -> usize {
+        let in_frame = self.channels.saturating_sub(self.current_channel) as usize;
+        in_frame + self.remaining_frames as usize * self.channels() as usize
+    }
+}
+
+impl<I> Iterator for PadTo<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    type Item = I::Item;
+
+    #[inline]
+    fn next(&mut self) -> Option<I::Item> {
+        if self.current_channel >= self.channels {
+            if self.remaining_frames == 0 {
+                return None;
+            }
+            self.remaining_frames -= 1;
+            self.current_channel = 0;
+            if !self.input_ended {
+                self.channels = self.input.channels();
+                self.sample_rate = self.input.sample_rate();
+            }
+        }
+        self.current_channel += 1;
+
+        if !self.input_ended {
+            if let Some(value) = self.input.next() {
+                return Some(value);
+            }
+            self.input_ended = true;
+        }
+        Some(I::Item::zero_value())
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.remaining_samples();
+        (remaining, Some(remaining))
+    }
+}
+
+impl<I> Source for PadTo<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        let remaining = self.remaining_samples();
+        if self.input_ended {
+            return Some(remaining);
+        }
+        match self.input.current_frame_len() {
+            // The input is followed by silence, which uses the same format.
+            Some(0) => Some(remaining),
+            Some(len) => Some(cmp::min(len, remaining)),
+            None => Some(remaining),
+        }
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        if self.input_ended {
+            self.channels
+        } else {
+            self.input.channels()
+        }
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        if self.input_ended {
+            self.sample_rate
+        } else {
+            self.input.sample_rate()
+        }
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        Some(self.duration)
+    }
+
+    #[inline]
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        self.input.try_seek(pos)?;
+
+        let frame = duration_to_frames(pos, self.sample_rate);
+        self.remaining_frames = self.frames.saturating_sub(frame);
+        self.input_ended = false;
+        self.current_channel = self.channels;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use buffer::SamplesBuffer;
+    use source::Source;
+
+    #[test]
+    fn pads_with_silence() {
+        let source = SamplesBuffer::new(2, 2, vec![1i16, 2, 3]);
+        let source = source.pad_to(Duration::from_secs(2));
+        assert_eq!(source.total_duration(), Some(Duration::from_secs(2)));
+        assert_eq!(source.collect::<Vec<_>>(), [1, 2, 3, 0, 0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn truncates() {
+        let source = SamplesBuffer::new(1, 2, vec![1i16, 2, 3, 4, 5]);
+        let source = source.pad_to(Duration::from_secs(1));
+        assert_eq!(source.collect::<Vec<_>>(), [1, 2]);
+    }
+}
@@ -1,10 +1,20 @@
 use std::time::Duration;
 
+use source::SeekError;
 use Sample;
 use Source;
 
 /// Internal function that builds a `Pausable` object.
 pub fn pausable<I>(source: I, paused: bool) -> Pausable<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    pausable_with_mode(source, paused, PauseMode::Freeze)
+}
+
+/// Internal function that builds a `Pausable` object with a given `PauseMode`.
+pub fn pausable_with_mode<I>(source: I, paused: bool, mode: PauseMode) -> Pausable<I>
 where
     I: Source,
     I::Item: Sample,
@@ -18,14 +28,27 @@ where
         input: source,
         paused_channels,
         remaining_paused_samples: 0,
+        mode,
     }
 }
 
+/// What happens to the inner source of a `Pausable` while it is paused.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum PauseMode {
+    /// The inner source isn't read, and playback resumes where it was paused.
+    Freeze,
+    /// The inner source keeps being read and its samples are replaced with silence, so that
+    /// playback resumes where it would have been without the pause. This keeps the source in sync
+    /// with a clock.
+    KeepRunning,
+}
+
 #[derive(Clone, Debug)]
 pub struct Pausable<I> {
     input: I,
     paused_channels: Option<u16>,
     remaining_paused_samples: u16,
+    mode: PauseMode,
 }
 
 impl<I> Pausable<I>
@@ -45,6 +68,18 @@ where
         }
     }
 
+    /// Modifies what happens to the inner source while paused.
+    #[inline]
+    pub fn set_pause_mode(&mut self, mode: PauseMode) {
+        self.mode = mode;
+    }
+
+    /// Returns what happens to the inner source while paused.
+    #[inline]
+    pub fn pause_mode(&self) -> PauseMode {
+        self.mode
+    }
+
     /// Returns a reference to the inner source.
     #[inline]
     pub fn inner(&self) -> &I {
@@ -73,6 +108,11 @@ where
 
     #[inline]
     fn next(&mut self) -> Option<I::Item> {
+        if self.paused_channels.is_some() && self.mode == PauseMode::KeepRunning {
+            self.input.next()?;
+            return Some(I::Item::zero_value());
+        }
+
         if self.remaining_paused_samples > 0 {
             self.remaining_paused_samples -= 1;
             return Some(I::Item::zero_value());
@@ -116,4 +156,37 @@ where
     fn total_duration(&self) -> Option<Duration> {
         self.input.total_duration()
     }
+
+    #[inline]
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        self.input.try_seek(pos)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use buffer::SamplesBuffer;
+    use source::{PauseMode, Source};
+
+    #[test]
+    fn freeze() {
+        let mut source = SamplesBuffer::new(1, 1, vec![1i16, 2, 3]).pausable(false);
+        assert_eq!(source.next(), Some(1));
+        source.set_paused(true);
+        assert_eq!(source.next(), Some(0));
+        source.set_paused(false);
+        assert_eq!(source.collect::<Vec<_>>(), [2, 3]);
+    }
+
+    #[test]
+    fn keep_running() {
+        let source = SamplesBuffer::new(1, 1, vec![1i16, 2, 3, 4]);
+        let mut source = source.pausable_with_mode(false, PauseMode::KeepRunning);
+        assert_eq!(source.next(), Some(1));
+        source.set_paused(true);
+        assert_eq!(source.next(), Some(0));
+        assert_eq!(source.next(), Some(0));
+        source.set_paused(false);
+        assert_eq!(source.collect::<Vec<_>>(), [4]);
+    }
 }
@@ -1,5 +1,6 @@
 use std::time::Duration;
 
+use source::SeekError;
 use Sample;
 use Source;
 
@@ -23,6 +24,23 @@ where
     }
 }
 
+/// Internal function that builds a `FramePeriodicAccess` object.
+pub fn periodic_frames<I, F>(source: I, period: u32, modifier: F) -> FramePeriodicAccess<I, F>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    assert!(period != 0);
+
+    FramePeriodicAccess {
+        input: source,
+        modifier,
+        period,
+        frames_until_update: 1,
+        current_channel: 0,
+    }
+}
+
 /// Calls a function on a source every time a period elapsed.
 #[derive(Clone, Debug)]
 pub struct PeriodicAccess<I, F> {
@@ -115,6 +133,116 @@ where
     fn total_duration(&self) -> Option<Duration> {
         self.input.total_duration()
     }
+
+    #[inline]
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        self.input.try_seek(pos)
+    }
+}
+
+/// Calls a function on a source every time a number of frames have been read.
+#[derive(Clone, Debug)]
+pub struct FramePeriodicAccess<I, F> {
+    input: I,
+    modifier: F,
+    // Number of frames between two calls.
+    period: u32,
+    // Number of frames to start before the next call, including the next one.
+    frames_until_update: u32,
+    // Channel of the next sample returned by `next`.
+    current_channel: u16,
+}
+
+impl<I, F> FramePeriodicAccess<I, F>
+where
+    I: Source,
+    I::Item: Sample,
+    F: FnMut(&mut I),
+{
+    /// Returns a reference to the inner source.
+    #[inline]
+    pub fn inner(&self) -> &I {
+        &self.input
+    }
+
+    /// Returns a mutable reference to the inner source.
+    #[inline]
+    pub fn inner_mut(&mut self) -> &mut I {
+        &mut self.input
+    }
+
+    /// Returns the inner source.
+    #[inline]
+    pub fn into_inner(self) -> I {
+        self.input
+    }
+}
+
+impl<I, F> Iterator for FramePeriodicAccess<I, F>
+where
+    I: Source,
+    I::Item: Sample,
+    F: FnMut(&mut I),
+{
+    type Item = I::Item;
+
+    #[inline]
+    fn next(&mut self) -> Option<I::Item> {
+        // The number of channels is read again at each frame, so that changes are taken into
+        // account.
+        if self.current_channel >= self.input.channels() {
+            self.current_channel = 0;
+        }
+        if self.current_channel == 0 {
+            self.frames_until_update -= 1;
+            if self.frames_until_update == 0 {
+                (self.modifier)(&mut self.input);
+                self.frames_until_update = self.period;
+            }
+        }
+        self.current_channel += 1;
+
+        self.input.next()
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.input.size_hint()
+    }
+}
+
+impl<I, F> Source for FramePeriodicAccess<I, F>
+where
+    I: Source,
+    I::Item: Sample,
+    F: FnMut(&mut I),
+{
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        self.input.current_frame_len()
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        self.input.channels()
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        self.input.sample_rate()
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        self.input.total_duration()
+    }
+
+    #[inline]
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        self.input.try_seek(pos)?;
+        self.current_channel = 0;
+        Ok(())
+    }
 }
 
 #[cfg(test)]
@@ -155,5 +283,23 @@ mod tests {
         source.next();
         source.next(); // Would overflow here.
     }
-}
 
+    #[test]
+    fn frame_access_follows_channels() {
+        use queue;
+
+        let (input, output) = queue::queue(false);
+        input.append(SamplesBuffer::new(1, 1, vec![1i16, 2, 3]));
+        input.append(SamplesBuffer::new(2, 1, vec![4i16, 4, 5, 5, 6, 6]));
+
+        let calls = RefCell::new(Vec::new());
+        let mut source = output.periodic_access_frames(2, |src| {
+            calls.borrow_mut().push(src.channels());
+        });
+
+        let samples = source.by_ref().take(9).collect::<Vec<_>>();
+        assert_eq!(samples, [1, 2, 3, 4, 4, 5, 5, 6, 6]);
+        // Called on the first, third and fifth frame.
+        assert_eq!(*calls.borrow(), [1, 1, 2]);
+    }
+}
@@ -0,0 +1,95 @@
+use std::time::Duration;
+
+use source::WhiteNoise;
+use Source;
+
+/// An infinite source that produces pink noise, whose power decreases by 3dB per octave.
+///
+/// This is white noise passed through Paul Kellet's refined pinking filter. The filter is tuned
+/// for 44.1kHz but remains accurate enough at other common rates. The output stays roughly
+/// between `-1.0` and `1.0`.
+///
+/// Always has one channel.
+#[derive(Clone, Debug)]
+pub struct PinkNoise {
+    white: WhiteNoise,
+    // State of the filter.
+    b: [f32; 7],
+}
+
+impl PinkNoise {
+    /// Builds a new pink noise generator with a random seed.
+    #[inline]
+    pub fn new(sample_rate: u32) -> PinkNoise {
+        PinkNoise::from_white_noise(WhiteNoise::new(sample_rate))
+    }
+
+    /// Builds a new pink noise generator that always produces the same samples for a given seed.
+    #[inline]
+    pub fn with_seed(sample_rate: u32, seed: u64) -> PinkNoise {
+        PinkNoise::from_white_noise(WhiteNoise::with_seed(sample_rate, seed))
+    }
+
+    #[inline]
+    fn from_white_noise(white: WhiteNoise) -> PinkNoise {
+        PinkNoise { white, b: [0.0; 7] }
+    }
+}
+
+impl Iterator for PinkNoise {
+    type Item = f32;
+
+    #[inline]
+    fn next(&mut self) -> Option<f32> {
+        let white = self.white.next()?;
+        let b = &mut self.b;
+
+        b[0] = 0.99886 * b[0] + white * 0.0555179;
+        b[1] = 0.99332 * b[1] + white * 0.0750759;
+        b[2] = 0.96900 * b[2] + white * 0.153852;
+        b[3] = 0.86650 * b[3] + white * 0.3104856;
+        b[4] = 0.55000 * b[4] + white * 0.5329522;
+        b[5] = -0.7616 * b[5] - white * 0.0168980;
+        let pink = b[0] + b[1] + b[2] + b[3] + b[4] + b[5] + b[6] + white * 0.5362;
+        b[6] = white * 0.115926;
+
+        Some(pink * 0.11)
+    }
+}
+
+impl Source for PinkNoise {
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        None
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        1
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        self.white.sample_rate()
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use source::PinkNoise;
+
+    #[test]
+    fn bounded_and_not_constant() {
+        let samples = PinkNoise::with_seed(44100, 1).take(44100).collect::<Vec<_>>();
+
+        assert!(samples.iter().all(|s| s.abs() <= 1.0));
+        let min = samples.iter().cloned().fold(f32::INFINITY, f32::min);
+        let max = samples.iter().cloned().fold(f32::NEG_INFINITY, f32::max);
+        assert!(max - min > 0.5);
+    }
+}
@@ -0,0 +1,214 @@
+use std::time::Duration;
+
+use source::SeekError;
+use Sample;
+use Source;
+
+/// Describes where the samples of an output channel of `RemapChannels` come from.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ChannelMap {
+    /// Copies the given channel of the input. Channels are numbered from 0.
+    ///
+    /// If the input doesn't have this channel, silence is produced instead.
+    Input(u16),
+
+    /// Produces silence.
+    Silence,
+}
+
+/// Internal function that builds a `RemapChannels` object.
+///
+/// # Panic
+///
+/// Panics if `map` is empty.
+pub fn remap_channels<I>(input: I, map: &[ChannelMap]) -> RemapChannels<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    assert!(!map.is_empty());
+
+    RemapChannels {
+        input,
+        map: map.to_vec(),
+        frame: Vec::with_capacity(map.len()),
+        input_frame: Vec::new(),
+        position_in_frame: 0,
+    }
+}
+
+/// Reorders, duplicates or drops the channels of a source.
+#[derive(Clone, Debug)]
+pub struct RemapChannels<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    input: I,
+    // One entry per output channel.
+    map: Vec<ChannelMap>,
+    // The output frame currently being returned.
+    frame: Vec<I::Item>,
+    // The last input frame, kept to reuse its allocation.
+    input_frame: Vec<I::Item>,
+    position_in_frame: usize,
+}
+
+impl<I> RemapChannels<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    /// Returns a reference to the inner source.
+    #[inline]
+    pub fn inner(&self) -> &I {
+        &self.input
+    }
+
+    /// Returns a mutable reference to the inner source.
+    #[inline]
+    pub fn inner_mut(&mut self) -> &mut I {
+        &mut self.input
+    }
+
+    /// Returns the inner source.
+    #[inline]
+    pub fn into_inner(self) -> I {
+        self.input
+    }
+
+    /// Reads the next input frame and builds the corresponding output frame.
+    fn next_frame(&mut self) -> bool {
+        let input_channels = self.input.channels() as usize;
+        self.input_frame.clear();
+        self.input_frame.extend(self.input.by_ref().take(input_channels));
+
+        if self.input_frame.is_empty() {
+            return false;
+        }
+
+        self.frame.clear();
+        for map in &self.map {
+            let sample = match *map {
+                ChannelMap::Input(channel) => self
+                    .input_frame
+                    .get(channel as usize)
+                    .cloned()
+                    .unwrap_or_else(I::Item::zero_value),
+                ChannelMap::Silence => I::Item::zero_value(),
+            };
+            self.frame.push(sample);
+        }
+        self.position_in_frame = 0;
+        true
+    }
+
+    #[inline]
+    fn remaining_in_frame(&self) -> usize {
+        self.frame.len() - self.position_in_frame
+    }
+
+    #[inline]
+    fn input_to_output_len(&self, len: usize) -> usize {
+        len / self.input.channels() as usize * self.map.len()
+    }
+}
+
+impl<I> Iterator for RemapChannels<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    type Item = I::Item;
+
+    #[inline]
+    fn next(&mut self) -> Option<I::Item> {
+        if self.position_in_frame >= self.frame.len() && !self.next_frame() {
+            return None;
+        }
+
+        let sample = self.frame[self.position_in_frame];
+        self.position_in_frame += 1;
+        Some(sample)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let (min, max) = self.input.size_hint();
+        let remaining = self.remaining_in_frame();
+        (
+            self.input_to_output_len(min) + remaining,
+            max.map(|max| self.input_to_output_len(max) + remaining),
+        )
+    }
+}
+
+impl<I> Source for RemapChannels<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        self.input
+            .current_frame_len()
+            .map(|len| self.input_to_output_len(len) + self.remaining_in_frame())
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        self.map.len() as u16
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        self.input.sample_rate()
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        self.input.total_duration()
+    }
+
+    #[inline]
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        self.input.try_seek(pos)?;
+        self.frame.clear();
+        self.position_in_frame = 0;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use buffer::SamplesBuffer;
+    use source::{ChannelMap, Source};
+
+    #[test]
+    fn swap_left_right() {
+        let input = SamplesBuffer::new(2, 44100, vec![1i16, 2, 3, 4]);
+        let source = input.remap_channels(&[ChannelMap::Input(1), ChannelMap::Input(0)]);
+        assert_eq!(source.collect::<Vec<_>>(), [2, 1, 4, 3]);
+    }
+
+    #[test]
+    fn mono_to_center() {
+        let input = SamplesBuffer::new(1, 44100, vec![7i16, 8]);
+        let mut map = [ChannelMap::Silence; 6];
+        map[2] = ChannelMap::Input(0);
+        let source = input.remap_channels(&map);
+
+        assert_eq!(source.channels(), 6);
+        assert_eq!(
+            source.collect::<Vec<_>>(),
+            [0, 0, 7, 0, 0, 0, 0, 0, 8, 0, 0, 0]
+        );
+    }
+
+    #[test]
+    fn drop_and_duplicate() {
+        let input = SamplesBuffer::new(3, 44100, vec![1i16, 2, 3, 4, 5, 6]);
+        let source = input.remap_channels(&[ChannelMap::Input(2), ChannelMap::Input(2)]);
+        assert_eq!(source.collect::<Vec<_>>(), [3, 3, 6, 6]);
+    }
+}
@@ -0,0 +1,152 @@
+use std::time::Duration;
+
+use source::mid_side::read_stereo;
+use source::SeekError;
+use Sample;
+use Source;
+
+/// Internal function that builds a `RemoveCenter` object.
+pub fn remove_center<I>(input: I) -> RemoveCenter<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    RemoveCenter {
+        input,
+        pending: None,
+    }
+}
+
+/// Filter that removes what is panned to the center of a stereo source, which is usually the
+/// lead vocals.
+///
+/// The mid channel, `(left + right) / 2`, is subtracted from both channels, so the left channel
+/// becomes `(left - right) / 2` and the right one `(right - left) / 2`. Everything that is
+/// identical in both channels cancels out, including centered instruments such as the bass.
+/// Frames that don't have two channels are left untouched.
+#[derive(Clone, Debug)]
+pub struct RemoveCenter<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    input: I,
+    // Second sample of the current frame.
+    pending: Option<I::Item>,
+}
+
+impl<I> RemoveCenter<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    /// Returns a reference to the inner source.
+    #[inline]
+    pub fn inner(&self) -> &I {
+        &self.input
+    }
+
+    /// Returns a mutable reference to the inner source.
+    #[inline]
+    pub fn inner_mut(&mut self) -> &mut I {
+        &mut self.input
+    }
+
+    /// Returns the inner source.
+    #[inline]
+    pub fn into_inner(self) -> I {
+        self.input
+    }
+}
+
+impl<I> Iterator for RemoveCenter<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    type Item = I::Item;
+
+    #[inline]
+    fn next(&mut self) -> Option<I::Item> {
+        if let Some(value) = self.pending.take() {
+            return Some(value);
+        }
+        if self.input.channels() != 2 {
+            return self.input.next();
+        }
+
+        let (left, right) = read_stereo(&mut self.input)?;
+        let side = (left - right) / 2.0;
+        self.pending = Some(Sample::from_sample(&-side));
+        Some(Sample::from_sample(&side))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let pending = self.pending.is_some() as usize;
+        let (min, max) = self.input.size_hint();
+        (min + pending, max.map(|max| max + pending))
+    }
+}
+
+impl<I> ExactSizeIterator for RemoveCenter<I>
+where
+    I: Source + ExactSizeIterator,
+    I::Item: Sample,
+{
+}
+
+impl<I> Source for RemoveCenter<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        if self.pending.is_some() {
+            return Some(1);
+        }
+        self.input.current_frame_len()
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        if self.pending.is_some() {
+            2
+        } else {
+            self.input.channels()
+        }
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        self.input.sample_rate()
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        self.input.total_duration()
+    }
+
+    #[inline]
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        self.input.try_seek(pos)?;
+        self.pending = None;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use buffer::SamplesBuffer;
+    use source::Source;
+
+    #[test]
+    fn cancels_the_center() {
+        let source = SamplesBuffer::new(2, 44100, vec![0.5f32, 0.5, 0.75, 0.25, -0.5, 0.5]);
+        assert_eq!(
+            source.remove_center().collect::<Vec<_>>(),
+            [0.0, 0.0, 0.25, -0.25, -0.5, 0.5]
+        );
+    }
+}
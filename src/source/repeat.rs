@@ -1,6 +1,8 @@
 use std::time::Duration;
 
+use conversions::duration_to_frames;
 use source::buffered::Buffered;
+use source::SeekError;
 
 use Sample;
 use Source;
@@ -18,6 +20,31 @@ where
     }
 }
 
+/// Internal function that builds a `RepeatN` object.
+pub fn repeat_n<I>(input: I, times: u32) -> RepeatN<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    let input = input.buffered();
+    RepeatN {
+        inner: input.clone(),
+        next: input,
+        times,
+        remaining: times,
+    }
+}
+
+// Returns the number of samples of `source` that are played during `duration`.
+#[inline]
+fn duration_to_samples<I>(source: &I, duration: Duration) -> u64
+where
+    I: Source,
+    I::Item: Sample,
+{
+    duration_to_frames(duration, source.sample_rate()) * source.channels() as u64
+}
+
 /// A source that repeats the given source.
 pub struct Repeat<I>
 where
@@ -85,6 +112,26 @@ where
     fn total_duration(&self) -> Option<Duration> {
         None
     }
+
+    #[inline]
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        // Only the position inside of one repetition matters.
+        let mut samples = duration_to_samples(&self.next, pos);
+        if let Some(duration) = self.next.total_duration() {
+            let len = duration_to_samples(&self.next, duration);
+            if len != 0 {
+                samples %= len;
+            }
+        }
+
+        self.inner = self.next.clone();
+        for _ in 0 .. samples {
+            if self.next().is_none() {
+                break;
+            }
+        }
+        Ok(())
+    }
 }
 
 impl<I> Clone for Repeat<I>
@@ -100,3 +147,160 @@ where
         }
     }
 }
+
+/// A source that plays the given source a fixed number of times.
+pub struct RepeatN<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    inner: Buffered<I>,
+    next: Buffered<I>,
+    times: u32,
+    // Number of times that the source will be played, including the current one.
+    remaining: u32,
+}
+
+impl<I> RepeatN<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    // Returns the source that will provide the next sample.
+    #[inline]
+    fn current(&self) -> &Buffered<I> {
+        match self.inner.current_frame_len() {
+            Some(0) if self.remaining > 1 => &self.next,
+            _ => &self.inner,
+        }
+    }
+}
+
+impl<I> Iterator for RepeatN<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    type Item = <I as Iterator>::Item;
+
+    #[inline]
+    fn next(&mut self) -> Option<<I as Iterator>::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+
+        if let Some(value) = self.inner.next() {
+            return Some(value);
+        }
+
+        self.remaining -= 1;
+        if self.remaining == 0 {
+            return None;
+        }
+
+        self.inner = self.next.clone();
+        self.inner.next()
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        if self.remaining == 0 {
+            (0, Some(0))
+        } else {
+            (0, None)
+        }
+    }
+}
+
+impl<I> Source for RepeatN<I>
+where
+    I: Iterator + Source,
+    I::Item: Sample,
+{
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        if self.remaining == 0 {
+            return Some(0);
+        }
+        self.current().current_frame_len()
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        self.current().channels()
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        self.current().sample_rate()
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        self.next.total_duration().map(|duration| duration * self.times)
+    }
+
+    #[inline]
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        self.inner = self.next.clone();
+        self.remaining = self.times;
+        for _ in 0 .. duration_to_samples(&self.next, pos) {
+            if self.next().is_none() {
+                break;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<I> Clone for RepeatN<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    #[inline]
+    fn clone(&self) -> RepeatN<I> {
+        RepeatN {
+            inner: self.inner.clone(),
+            next: self.next.clone(),
+            times: self.times,
+            remaining: self.remaining,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use buffer::SamplesBuffer;
+    use source::Source;
+
+    #[test]
+    fn repeats_n_times() {
+        let source = SamplesBuffer::new(1, 2, vec![1i16, 2, 3]).repeat(3);
+        assert_eq!(source.total_duration(), Some(Duration::from_millis(4500)));
+        assert_eq!(source.collect::<Vec<_>>(), [1, 2, 3, 1, 2, 3, 1, 2, 3]);
+    }
+
+    #[test]
+    fn total_duration_does_not_change_while_playing() {
+        let mut source = SamplesBuffer::new(1, 2, vec![1i16, 2, 3]).repeat(3);
+        source.by_ref().take(5).for_each(drop);
+        assert_eq!(source.total_duration(), Some(Duration::from_millis(4500)));
+    }
+
+    #[test]
+    fn seek() {
+        let mut source = SamplesBuffer::new(1, 2, vec![1i16, 2, 3]).repeat(2);
+        source.try_seek(Duration::from_secs(2)).unwrap();
+        assert_eq!(source.collect::<Vec<_>>(), [2, 3]);
+    }
+
+    #[test]
+    fn zero_times() {
+        let source = SamplesBuffer::new(1, 44100, vec![1i16, 2, 3]).repeat(0);
+        assert_eq!(source.current_frame_len(), Some(0));
+        assert_eq!(source.count(), 0);
+    }
+}
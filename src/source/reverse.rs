@@ -0,0 +1,159 @@
+use std::cmp;
+use std::collections::VecDeque;
+use std::time::Duration;
+use std::vec::IntoIter as VecIntoIter;
+
+use Sample;
+use Source;
+
+/// Internal function that builds a `Reverse` object.
+///
+/// The whole input is read immediately.
+pub fn reverse<I>(mut input: I) -> Reverse<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    let total_duration = input.total_duration();
+
+    // Parts of the input that have the same channels and sample rate, in the input order.
+    let mut segments: Vec<(u16, u32, Vec<I::Item>)> = Vec::new();
+    loop {
+        let frame_len = input.current_frame_len();
+        if frame_len == Some(0) {
+            break;
+        }
+
+        let channels = input.channels();
+        let rate = input.sample_rate();
+        let data = input
+            .by_ref()
+            .take(cmp::min(frame_len.unwrap_or(32768), 32768))
+            .collect::<Vec<_>>();
+        if data.is_empty() {
+            break;
+        }
+
+        match segments.last_mut() {
+            Some(&mut (c, r, ref mut last)) if c == channels && r == rate => last.extend(data),
+            _ => segments.push((channels, rate, data)),
+        }
+    }
+
+    let mut data = Vec::new();
+    let mut frames = VecDeque::new();
+    for (channels, rate, samples) in segments.into_iter().rev() {
+        // Reversing the order of the frames but not of the channels inside each frame.
+        data.extend(samples.chunks(channels as usize).rev().flatten().cloned());
+        frames.push_back((samples.len(), channels, rate));
+    }
+
+    Reverse {
+        data: data.into_iter(),
+        frames,
+        total_duration,
+    }
+}
+
+/// A source that plays another source backwards.
+///
+/// The channels stay in the same order, only the frames are reversed.
+#[derive(Clone, Debug)]
+pub struct Reverse<I>
+where
+    I: Iterator,
+{
+    data: VecIntoIter<I::Item>,
+    // Remaining length, channels and sample rate of the parts of `data` that have the same format.
+    frames: VecDeque<(usize, u16, u32)>,
+    total_duration: Option<Duration>,
+}
+
+impl<I> Iterator for Reverse<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    type Item = I::Item;
+
+    #[inline]
+    fn next(&mut self) -> Option<I::Item> {
+        let sample = self.data.next()?;
+
+        let frame_ended = match self.frames.front_mut() {
+            Some(&mut (ref mut len, _, _)) => {
+                *len -= 1;
+                *len == 0
+            },
+            None => false,
+        };
+        if frame_ended {
+            self.frames.pop_front();
+        }
+
+        Some(sample)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.data.size_hint()
+    }
+}
+
+impl<I> ExactSizeIterator for Reverse<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+}
+
+impl<I> Source for Reverse<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        Some(self.frames.front().map_or(0, |&(len, _, _)| len))
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        self.frames.front().map_or(1, |&(_, channels, _)| channels)
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        self.frames.front().map_or(44100, |&(_, _, rate)| rate)
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        self.total_duration
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use buffer::SamplesBuffer;
+    use source::Source;
+
+    #[test]
+    fn reverses_frames() {
+        let source = SamplesBuffer::new(2, 44100, vec![1i16, 2, 3, 4, 5, 6]).buffered();
+        let reversed = source.reverse();
+        assert_eq!(reversed.channels(), 2);
+        assert_eq!(reversed.current_frame_len(), Some(6));
+        assert_eq!(reversed.collect::<Vec<_>>(), [5, 6, 3, 4, 1, 2]);
+    }
+
+    #[test]
+    fn keeps_duration() {
+        let source = SamplesBuffer::new(1, 4, vec![0.1f32, 0.2, 0.3, 0.4, 0.5, 0.6]);
+        let reversed = source.buffered().reverse();
+        assert_eq!(reversed.total_duration(), Some(Duration::from_millis(1500)));
+        assert_eq!(reversed.len(), 6);
+    }
+}
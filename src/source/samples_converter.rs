@@ -1,7 +1,9 @@
 use std::marker::PhantomData;
 use std::time::Duration;
 
-use cpal::Sample as CpalSample;
+use conversions::sample::Ditherer;
+use conversions::Dither;
+use source::SeekError;
 use Sample;
 use Source;
 
@@ -13,6 +15,9 @@ use Source;
 #[derive(Clone)]
 pub struct SamplesConverter<I, D> {
     inner: I,
+    ditherer: Ditherer,
+    // Channel of the next sample returned by `next`.
+    current_channel: u16,
     dest: PhantomData<D>,
 }
 
@@ -21,10 +26,19 @@ impl<I, D> SamplesConverter<I, D> {
     pub fn new(input: I) -> SamplesConverter<I, D> {
         SamplesConverter {
             inner: input,
+            ditherer: Ditherer::new(Dither::None),
+            current_channel: 0,
             dest: PhantomData,
         }
     }
 
+    /// Modifies the noise added to the samples when `D` is an integer format. The default is
+    /// `Dither::None`.
+    #[inline]
+    pub fn set_dither(&mut self, dither: Dither) {
+        self.ditherer.set_dither(dither);
+    }
+
     /// Returns a reference to the inner source.
     #[inline]
     pub fn inner(&self) -> &I {
@@ -54,7 +68,15 @@ where
 
     #[inline]
     fn next(&mut self) -> Option<D> {
-        self.inner.next().map(|s| CpalSample::from(&s))
+        let sample = self.inner.next()?;
+
+        if self.current_channel >= self.inner.channels() {
+            self.current_channel = 0;
+        }
+        let channel = self.current_channel;
+        self.current_channel += 1;
+
+        Some(self.ditherer.convert(sample, channel))
     }
 
     #[inline]
@@ -96,4 +118,11 @@ where
     fn total_duration(&self) -> Option<Duration> {
         self.inner.total_duration()
     }
+
+    #[inline]
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        self.inner.try_seek(pos)?;
+        self.current_channel = 0;
+        Ok(())
+    }
 }
@@ -0,0 +1,97 @@
+use std::time::Duration;
+
+use source::square::poly_blep;
+use Source;
+
+/// An infinite source that produces a band-limited sawtooth wave, rising from `-amplitude` to
+/// `amplitude` over each period.
+///
+/// The discontinuity at the end of each period is smoothed with the polyBLEP method, which
+/// removes most of the aliasing that a naive sawtooth would produce.
+///
+/// Always has a rate of 48kHz and one channel.
+#[derive(Clone, Debug)]
+pub struct SawtoothWave {
+    // Phase increment per sample.
+    phase_step: f32,
+    // Position in the current period, between 0 and 1.
+    phase: f32,
+    amplitude: f32,
+}
+
+impl SawtoothWave {
+    /// The frequency of the sawtooth. The amplitude is `1.0`.
+    #[inline]
+    pub fn new(freq: u32) -> SawtoothWave {
+        SawtoothWave {
+            phase_step: freq as f32 / 48000.0,
+            phase: 0.0,
+            amplitude: 1.0,
+        }
+    }
+
+    /// Modifies the frequency of the sawtooth. The phase is preserved.
+    #[inline]
+    pub fn set_frequency(&mut self, freq: u32) {
+        self.phase_step = freq as f32 / 48000.0;
+    }
+
+    /// Modifies the peak amplitude of the sawtooth.
+    #[inline]
+    pub fn set_amplitude(&mut self, amplitude: f32) {
+        self.amplitude = amplitude;
+    }
+}
+
+impl Iterator for SawtoothWave {
+    type Item = f32;
+
+    #[inline]
+    fn next(&mut self) -> Option<f32> {
+        let dt = self.phase_step;
+        let value = 2.0 * self.phase - 1.0 - poly_blep(self.phase, dt);
+
+        self.phase = (self.phase + dt) % 1.0;
+        Some(value * self.amplitude)
+    }
+}
+
+impl Source for SawtoothWave {
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        None
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        1
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        48000
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use source::SawtoothWave;
+
+    #[test]
+    fn ramp() {
+        // 480 samples per period.
+        let mut source = SawtoothWave::new(100);
+        source.set_amplitude(0.5);
+        let samples = source.take(480).collect::<Vec<_>>();
+
+        assert!(samples[240].abs() < 1e-3);
+        assert!((samples[120] + 0.25).abs() < 1e-3);
+        assert!((samples[360] - 0.25).abs() < 1e-3);
+        assert!(samples.iter().all(|s| s.abs() <= 0.5));
+    }
+}
@@ -0,0 +1,196 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use conversions::{duration_to_frames, duration_to_ns, frames_to_ns};
+use source::SeekError;
+use Sample;
+use Source;
+
+/// Internal function that builds a `SharedBuffered` object.
+pub fn shared_buffered<I>(mut input: I) -> SharedBuffered<I::Item>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    let total_duration = input.total_duration();
+    let mut samples = Vec::new();
+    let mut frames = Vec::new();
+
+    loop {
+        let channels = input.channels();
+        let rate = input.sample_rate();
+        let start = samples.len();
+        match input.current_frame_len() {
+            Some(0) => break,
+            Some(len) => samples.extend(input.by_ref().take(len)),
+            None => samples.extend(input.by_ref()),
+        }
+        if samples.len() == start {
+            break;
+        }
+
+        frames.push(FrameInfo {
+            end: samples.len(),
+            channels,
+            rate,
+        });
+    }
+
+    SharedBuffered {
+        data: Arc::new(SharedData {
+            samples,
+            frames,
+            total_duration,
+        }),
+        frame: 0,
+        position: 0,
+    }
+}
+
+/// Source that plays samples shared between all its clones.
+///
+/// Cloning a `SharedBuffered` only increments a reference count, and playing it doesn't allocate
+/// or lock anything.
+#[derive(Debug)]
+pub struct SharedBuffered<S> {
+    data: Arc<SharedData<S>>,
+    // Index of the current frame in `frames`.
+    frame: usize,
+    // Index of the next sample in `samples`.
+    position: usize,
+}
+
+#[derive(Debug)]
+struct SharedData<S> {
+    samples: Vec<S>,
+    frames: Vec<FrameInfo>,
+    total_duration: Option<Duration>,
+}
+
+#[derive(Debug)]
+struct FrameInfo {
+    // Index in `samples` of the end of the frame.
+    end: usize,
+    channels: u16,
+    rate: u32,
+}
+
+impl<S> Clone for SharedBuffered<S> {
+    #[inline]
+    fn clone(&self) -> SharedBuffered<S> {
+        SharedBuffered {
+            data: self.data.clone(),
+            frame: self.frame,
+            position: self.position,
+        }
+    }
+}
+
+impl<S> Iterator for SharedBuffered<S>
+where
+    S: Sample,
+{
+    type Item = S;
+
+    #[inline]
+    fn next(&mut self) -> Option<S> {
+        let value = *self.data.samples.get(self.position)?;
+        self.position += 1;
+        if self.position >= self.data.frames[self.frame].end {
+            self.frame += 1;
+        }
+        Some(value)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.data.samples.len() - self.position;
+        (len, Some(len))
+    }
+}
+
+impl<S> ExactSizeIterator for SharedBuffered<S> where S: Sample {}
+
+impl<S> Source for SharedBuffered<S>
+where
+    S: Sample,
+{
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        match self.data.frames.get(self.frame) {
+            Some(frame) => Some(frame.end - self.position),
+            None => Some(0),
+        }
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        match self.data.frames.get(self.frame) {
+            Some(frame) => frame.channels,
+            None => 1,
+        }
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        match self.data.frames.get(self.frame) {
+            Some(frame) => frame.rate,
+            None => 44100,
+        }
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        self.data.total_duration
+    }
+
+    #[inline]
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        let mut remaining_ns = duration_to_ns(pos);
+        let mut start = 0;
+
+        for (index, frame) in self.data.frames.iter().enumerate() {
+            let channels = frame.channels as u64;
+            let frame_count = (frame.end - start) as u64 / channels;
+            let frame_ns = frames_to_ns(frame_count, frame.rate);
+            if remaining_ns < frame_ns {
+                let skipped = duration_to_frames(Duration::from_nanos(remaining_ns), frame.rate);
+                self.frame = index;
+                self.position = start + (skipped * channels) as usize;
+                return Ok(());
+            }
+            remaining_ns -= frame_ns;
+            start = frame.end;
+        }
+
+        self.frame = self.data.frames.len();
+        self.position = self.data.samples.len();
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use buffer::SamplesBuffer;
+    use source::Source;
+
+    #[test]
+    fn clones_share_samples() {
+        let source = SamplesBuffer::new(2, 10, vec![1i16, 2, 3, 4]).buffered().into_shared();
+        let clone = source.clone();
+        assert_eq!(source.channels(), 2);
+        assert_eq!(source.collect::<Vec<_>>(), [1, 2, 3, 4]);
+        assert_eq!(clone.collect::<Vec<_>>(), [1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn seek() {
+        let data = (0 .. 20).collect::<Vec<i16>>();
+        let mut source = SamplesBuffer::new(2, 10, data).buffered().into_shared();
+        source.try_seek(Duration::from_millis(500)).unwrap();
+        assert_eq!(source.next(), Some(10));
+        assert_eq!(source.current_frame_len(), Some(9));
+    }
+}
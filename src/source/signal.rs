@@ -0,0 +1,231 @@
+use std::time::Duration;
+
+use dasp::sample::ToSample;
+use dasp::{Frame, Signal};
+
+use source::UniformSourceIterator;
+use Sample;
+use Source;
+
+/// Builds a source that plays a `dasp` signal, so that its DSP nodes can be played by rodio.
+///
+/// The source ends when the signal is exhausted. Infinite signals, such as oscillators, never
+/// end.
+///
+/// # Panic
+///
+/// Panics if `sample_rate` is zero, or if `channels` isn't the number of channels of the frames
+/// of the signal.
+pub fn from_signal<S>(signal: S, sample_rate: u32, channels: u16) -> FromSignal<S>
+where
+    S: Signal,
+    <S::Frame as Frame>::Sample: ToSample<f32>,
+{
+    assert!(sample_rate != 0);
+    assert_eq!(channels as usize, <S::Frame as Frame>::CHANNELS);
+
+    FromSignal {
+        signal,
+        sample_rate,
+        channels,
+        frame: <S::Frame as Frame>::EQUILIBRIUM,
+        current_channel: 0,
+    }
+}
+
+/// Internal function that builds an `IntoSignal` object.
+pub fn into_signal<I, F>(input: I) -> IntoSignal<I, F>
+where
+    I: Source,
+    I::Item: Sample,
+    F: Frame<Sample = f32>,
+{
+    // The format of the signal is fixed, so the format of the source is converted to the one it
+    // starts with.
+    let sample_rate = input.sample_rate();
+    let mut input = UniformSourceIterator::new(input, F::CHANNELS as u16, sample_rate);
+    let next_frame = F::from_samples(&mut input);
+
+    IntoSignal {
+        input,
+        sample_rate,
+        next_frame,
+    }
+}
+
+/// A source that plays a `dasp` signal.
+#[derive(Clone, Debug)]
+pub struct FromSignal<S>
+where
+    S: Signal,
+{
+    signal: S,
+    sample_rate: u32,
+    channels: u16,
+    // Frame whose samples are being returned.
+    frame: S::Frame,
+    // Channel of the next sample returned by `next`.
+    current_channel: u16,
+}
+
+impl<S> FromSignal<S>
+where
+    S: Signal,
+{
+    /// Returns a reference to the inner signal.
+    #[inline]
+    pub fn inner(&self) -> &S {
+        &self.signal
+    }
+
+    /// Returns a mutable reference to the inner signal.
+    #[inline]
+    pub fn inner_mut(&mut self) -> &mut S {
+        &mut self.signal
+    }
+
+    /// Returns the inner signal.
+    #[inline]
+    pub fn into_inner(self) -> S {
+        self.signal
+    }
+}
+
+impl<S> Iterator for FromSignal<S>
+where
+    S: Signal,
+    <S::Frame as Frame>::Sample: ToSample<f32>,
+{
+    type Item = f32;
+
+    #[inline]
+    fn next(&mut self) -> Option<f32> {
+        if self.current_channel == 0 {
+            if self.signal.is_exhausted() {
+                return None;
+            }
+            self.frame = self.signal.next();
+        }
+
+        let value = *self.frame.channel(self.current_channel as usize)?;
+        self.current_channel = (self.current_channel + 1) % self.channels;
+        Some(value.to_sample_())
+    }
+}
+
+impl<S> Source for FromSignal<S>
+where
+    S: Signal,
+    <S::Frame as Frame>::Sample: ToSample<f32>,
+{
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        None
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        self.channels
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        None
+    }
+}
+
+/// A `dasp` signal that plays a source, returned by `Source::into_signal`.
+///
+/// The signal has the sample rate that the source has when it is converted, and the source is
+/// converted to it if its format changes later. The signal is exhausted when the source ends.
+pub struct IntoSignal<I, F>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    input: UniformSourceIterator<I, f32>,
+    sample_rate: u32,
+    // The frame returned by the next call to `next`, or `None` if the source has ended. It is read
+    // in advance so that `is_exhausted` is accurate.
+    next_frame: Option<F>,
+}
+
+impl<I, F> IntoSignal<I, F>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    /// Returns the sample rate of the signal.
+    #[inline]
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+}
+
+impl<I, F> Signal for IntoSignal<I, F>
+where
+    I: Source,
+    I::Item: Sample,
+    F: Frame<Sample = f32>,
+{
+    type Frame = F;
+
+    #[inline]
+    fn next(&mut self) -> F {
+        match self.next_frame {
+            Some(frame) => {
+                self.next_frame = F::from_samples(&mut self.input);
+                frame
+            },
+            None => F::EQUILIBRIUM,
+        }
+    }
+
+    #[inline]
+    fn is_exhausted(&self) -> bool {
+        self.next_frame.is_none()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use dasp::{signal, Signal};
+
+    use buffer::SamplesBuffer;
+    use source::{from_signal, Source};
+
+    #[test]
+    fn from_signal_interleaves_frames() {
+        let frames = signal::from_iter(vec![[1i16 << 14, -1 << 14], [0, 1 << 14]]);
+        let source = from_signal(frames, 44100, 2);
+        assert_eq!(source.channels(), 2);
+        assert_eq!(source.collect::<Vec<_>>(), [0.5, -0.5, 0.0, 0.5]);
+    }
+
+    #[test]
+    fn into_signal_groups_frames() {
+        let source = SamplesBuffer::new(2, 44100, vec![0.5f32, -0.5, 0.25, -0.25]);
+        let mut signal = source.into_signal::<[f32; 2]>();
+        assert_eq!(signal.sample_rate(), 44100);
+        assert!(!signal.is_exhausted());
+        assert_eq!(signal.next(), [0.5, -0.5]);
+        assert_eq!(signal.next(), [0.25, -0.25]);
+        assert!(signal.is_exhausted());
+        assert_eq!(signal.next(), [0.0, 0.0]);
+    }
+
+    #[test]
+    fn round_trip() {
+        let source = SamplesBuffer::new(1, 8000, vec![0.1f32, 0.2, 0.3]);
+        let signal = source.into_signal::<f32>().scale_amp(2.0);
+        let source = from_signal(signal, 8000, 1);
+        let samples = source.collect::<Vec<_>>();
+        assert_eq!(samples.len(), 3);
+        assert!((samples[2] - 0.6).abs() < 1e-6);
+    }
+}
@@ -0,0 +1,221 @@
+use std::time::Duration;
+
+use conversions::{db_to_amplitude, duration_to_frames};
+use source::SeekError;
+use Sample;
+use Source;
+
+/// Most silent frames dropped in a single call to `next`. A silent frame is played after that, so
+/// that an endless silence doesn't block the caller.
+const MAX_SKIPPED_FRAMES: u32 = 4096;
+
+/// Internal function that builds a `SkipSilence` object.
+pub fn skip_silence<I>(input: I, threshold_db: f32, min_duration: Duration) -> SkipSilence<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    let channels = input.channels();
+    let sample_rate = input.sample_rate();
+
+    SkipSilence {
+        input,
+        threshold: db_to_amplitude(threshold_db),
+        min_duration,
+        frame: Vec::with_capacity(channels as usize),
+        position: 0,
+        channels,
+        sample_rate,
+        silent_frames: 0,
+    }
+}
+
+/// Filter that fast-forwards through the silent stretches of a source.
+///
+/// A frame is silent if all its samples are quieter than the threshold. Once a silence has lasted
+/// for the minimum duration, the rest of it is dropped, so that pauses are shortened to the
+/// minimum duration rather than removed altogether. A silent frame is still played for every few
+/// thousand frames dropped, so that an endless silence doesn't block the caller.
+#[derive(Clone, Debug)]
+pub struct SkipSilence<I>
+where
+    I: Iterator,
+{
+    input: I,
+    // Threshold as an amplitude between `0.0` and `1.0`.
+    threshold: f32,
+    min_duration: Duration,
+    // Frame read from the input and being returned.
+    frame: Vec<I::Item>,
+    position: usize,
+    channels: u16,
+    sample_rate: u32,
+    // Number of consecutive silent frames read so far.
+    silent_frames: u64,
+}
+
+impl<I> SkipSilence<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    /// Modifies the level under which a frame is considered silent, in decibels.
+    #[inline]
+    pub fn set_threshold_db(&mut self, threshold_db: f32) {
+        self.threshold = db_to_amplitude(threshold_db);
+    }
+
+    /// Returns a reference to the inner source.
+    #[inline]
+    pub fn inner(&self) -> &I {
+        &self.input
+    }
+
+    /// Returns a mutable reference to the inner source.
+    #[inline]
+    pub fn inner_mut(&mut self) -> &mut I {
+        &mut self.input
+    }
+
+    /// Returns the inner source.
+    #[inline]
+    pub fn into_inner(self) -> I {
+        self.input
+    }
+
+    // Reads the next frame that isn't skipped. Returns `false` if the input has ended.
+    fn read_frame(&mut self) -> bool {
+        let mut skipped_frames = 0;
+        loop {
+            self.channels = self.input.channels();
+            self.sample_rate = self.input.sample_rate();
+            self.frame.clear();
+            self.position = 0;
+            self.frame.extend(self.input.by_ref().take(self.channels as usize));
+            if self.frame.is_empty() {
+                return false;
+            }
+
+            let threshold = self.threshold;
+            if self.frame.iter().any(|s| s.to_f32().abs() >= threshold) {
+                self.silent_frames = 0;
+                return true;
+            }
+
+            self.silent_frames += 1;
+            let min_frames = duration_to_frames(self.min_duration, self.sample_rate);
+            if self.silent_frames <= min_frames || skipped_frames >= MAX_SKIPPED_FRAMES {
+                return true;
+            }
+            skipped_frames += 1;
+        }
+    }
+}
+
+impl<I> Iterator for SkipSilence<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    type Item = I::Item;
+
+    #[inline]
+    fn next(&mut self) -> Option<I::Item> {
+        if self.position >= self.frame.len() && !self.read_frame() {
+            return None;
+        }
+
+        let sample = self.frame[self.position];
+        self.position += 1;
+        Some(sample)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let buffered = self.frame.len() - self.position;
+        let max = self.input.size_hint().1.map(|max| max + buffered);
+        (buffered, max)
+    }
+}
+
+impl<I> Source for SkipSilence<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        let buffered = self.frame.len() - self.position;
+        self.input.current_frame_len().map(|len| len + buffered)
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        if self.position < self.frame.len() {
+            self.channels
+        } else {
+            self.input.channels()
+        }
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        if self.position < self.frame.len() {
+            self.sample_rate
+        } else {
+            self.input.sample_rate()
+        }
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        None
+    }
+
+    #[inline]
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        self.input.try_seek(pos)?;
+        self.frame.clear();
+        self.position = 0;
+        self.silent_frames = 0;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use buffer::SamplesBuffer;
+    use source::{Source, Zero};
+
+    #[test]
+    fn shortens_silence() {
+        let mut data = vec![0.5f32; 4];
+        data.extend(vec![0.0; 20]);
+        data.extend(vec![0.5; 4]);
+        let source = SamplesBuffer::new(2, 10, data);
+
+        // 0.3 seconds is 3 frames, or 6 samples.
+        let output = source
+            .skip_silence(-40.0, Duration::from_millis(300))
+            .collect::<Vec<_>>();
+        assert_eq!(output.len(), 4 + 6 + 4);
+        assert!(output[4 .. 10].iter().all(|s| *s == 0.0));
+        assert!(output[10 ..].iter().all(|s| *s == 0.5));
+    }
+
+    #[test]
+    fn endless_silence_returns() {
+        let mut source = Zero::<f32>::new(2, 44100).skip_silence(-40.0, Duration::new(0, 0));
+        assert_eq!(source.by_ref().take(4).collect::<Vec<_>>(), [0.0; 4]);
+    }
+
+    #[test]
+    fn quiet_frames_are_silent() {
+        // -40dB is an amplitude of 0.01, so only the frames that have a louder sample are kept.
+        let source = SamplesBuffer::new(2, 10, vec![0.005f32, 0.0, 0.005, 0.02, 0.001, 0.0]);
+        let output = source.skip_silence(-40.0, Duration::new(0, 0)).collect::<Vec<_>>();
+        assert_eq!(output, [0.005, 0.02]);
+    }
+}
@@ -0,0 +1,207 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use conversions::duration_to_ns;
+use source::{FramePosition, SeekError};
+use Sample;
+use Source;
+
+/// Internal function that builds a `Skippable` object.
+pub fn skippable<I>(input: I, amount: Duration) -> Skippable<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    let position = FramePosition::new(input.sample_rate());
+
+    Skippable {
+        input,
+        amount_ns: duration_to_ns(amount),
+        pending: Arc::new(AtomicUsize::new(0)),
+        position,
+    }
+}
+
+/// Filter that jumps forward in a source by a fixed amount every time it is asked to.
+///
+/// The inner source is seeked if it supports it. Otherwise, the samples are read and dropped.
+#[derive(Clone, Debug)]
+pub struct Skippable<I> {
+    input: I,
+    amount_ns: u64,
+    // Number of skips requested and not done yet.
+    pending: Arc<AtomicUsize>,
+    position: FramePosition,
+}
+
+/// Handle that skips forward in a `Skippable` from another thread.
+#[derive(Clone, Debug)]
+pub struct SkipHandle {
+    pending: Arc<AtomicUsize>,
+}
+
+impl SkipHandle {
+    /// Jumps forward in the source. The jump happens at the start of the next frame.
+    #[inline]
+    pub fn skip_forward(&self) {
+        self.pending.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+impl<I> Skippable<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    /// Jumps forward in the source. The jump happens at the start of the next frame.
+    #[inline]
+    pub fn skip_forward(&mut self) {
+        self.pending.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Returns a handle that can skip forward after the source has been handed to a sink.
+    #[inline]
+    pub fn handle(&self) -> SkipHandle {
+        SkipHandle {
+            pending: self.pending.clone(),
+        }
+    }
+
+    /// Returns a reference to the inner source.
+    #[inline]
+    pub fn inner(&self) -> &I {
+        &self.input
+    }
+
+    /// Returns a mutable reference to the inner source.
+    #[inline]
+    pub fn inner_mut(&mut self) -> &mut I {
+        &mut self.input
+    }
+
+    /// Returns the inner source.
+    #[inline]
+    pub fn into_inner(self) -> I {
+        self.input
+    }
+
+    // Called at the start of each frame.
+    fn next_frame(&mut self) {
+        let skips = self.pending.swap(0, Ordering::Relaxed) as u64;
+        if skips != 0 {
+            let target_ns = self.position.position_ns() + skips * self.amount_ns;
+            let target = Duration::from_nanos(target_ns);
+            if self.input.try_seek(target).is_ok() {
+                self.position.set_position_ns(target_ns, self.input.sample_rate());
+                // The sample being read is the first of the frame.
+                self.position.is_frame_start(self.input.channels());
+            } else {
+                self.drop_until(target_ns);
+            }
+        }
+
+        self.position.next_frame(self.input.sample_rate());
+    }
+
+    // Reads and drops frames until the position reaches `target_ns` or the source ends.
+    fn drop_until(&mut self, target_ns: u64) {
+        while self.position.position_ns() < target_ns {
+            let rate = self.input.sample_rate();
+            let channels = self.input.channels() as usize;
+            if self.input.by_ref().take(channels).count() < channels {
+                return;
+            }
+            self.position.next_frame(rate);
+        }
+    }
+}
+
+impl<I> Iterator for Skippable<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    type Item = I::Item;
+
+    #[inline]
+    fn next(&mut self) -> Option<I::Item> {
+        if self.position.is_frame_start(self.input.channels()) {
+            self.next_frame();
+        }
+
+        self.input.next()
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, self.input.size_hint().1)
+    }
+}
+
+impl<I> Source for Skippable<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        self.input.current_frame_len()
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        self.input.channels()
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        self.input.sample_rate()
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        self.input.total_duration()
+    }
+
+    #[inline]
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        self.input.try_seek(pos)?;
+        self.position.set_position_ns(duration_to_ns(pos), self.input.sample_rate());
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use buffer::SamplesBuffer;
+    use source::Source;
+
+    #[test]
+    fn skip_with_seek() {
+        let data = (0 .. 20).collect::<Vec<i16>>();
+        let source = SamplesBuffer::new(2, 2, data);
+        let mut source = source.skippable_by(Duration::from_secs(2));
+
+        assert_eq!(source.next(), Some(0));
+        source.skip_forward();
+        // The current frame is finished first.
+        assert_eq!(source.by_ref().take(3).collect::<Vec<_>>(), [1, 10, 11]);
+    }
+
+    #[test]
+    fn skip_without_seek() {
+        let data = (0 .. 20).collect::<Vec<i16>>();
+        let source = SamplesBuffer::new(2, 2, data).amplify(1.0).buffered();
+        let mut source = source.skippable_by(Duration::from_secs(2));
+        let handle = source.handle();
+
+        assert_eq!(source.next(), Some(0));
+        handle.skip_forward();
+        handle.skip_forward();
+        assert_eq!(source.by_ref().take(3).collect::<Vec<_>>(), [1, 18, 19]);
+        assert_eq!(source.next(), None);
+    }
+}
@@ -0,0 +1,139 @@
+use std::time::Duration;
+
+use source::SeekError;
+use Sample;
+use Source;
+
+/// Internal function that builds a `SoftClip` object.
+pub fn soft_clip<I>(input: I, threshold: f32) -> SoftClip<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    SoftClip {
+        input,
+        threshold: threshold.clamp(0.0, 1.0),
+    }
+}
+
+/// Filter that smoothly limits the samples of a source to full scale.
+///
+/// Samples whose absolute value is under the threshold are left untouched. Above it, they are
+/// bent by a `tanh` curve that reaches `1.0` only for infinitely loud samples, so the output never
+/// clips.
+#[derive(Clone, Debug)]
+pub struct SoftClip<I> {
+    input: I,
+    threshold: f32,
+}
+
+impl<I> SoftClip<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    /// Modifies the amplitude, between `0.0` and `1.0`, above which the samples are bent.
+    #[inline]
+    pub fn set_threshold(&mut self, threshold: f32) {
+        self.threshold = threshold.clamp(0.0, 1.0);
+    }
+
+    /// Returns a reference to the inner source.
+    #[inline]
+    pub fn inner(&self) -> &I {
+        &self.input
+    }
+
+    /// Returns a mutable reference to the inner source.
+    #[inline]
+    pub fn inner_mut(&mut self) -> &mut I {
+        &mut self.input
+    }
+
+    /// Returns the inner source.
+    #[inline]
+    pub fn into_inner(self) -> I {
+        self.input
+    }
+}
+
+impl<I> Iterator for SoftClip<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    type Item = I::Item;
+
+    #[inline]
+    fn next(&mut self) -> Option<I::Item> {
+        let value = self.input.next()?;
+        let x = value.to_f32();
+        if x.abs() <= self.threshold {
+            return Some(value);
+        }
+
+        let knee = 1.0 - self.threshold;
+        let bent = self.threshold + knee * ((x.abs() - self.threshold) / knee).tanh();
+        Some(Sample::from_sample(&(bent.min(1.0) * x.signum())))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.input.size_hint()
+    }
+}
+
+impl<I> ExactSizeIterator for SoftClip<I>
+where
+    I: Source + ExactSizeIterator,
+    I::Item: Sample,
+{
+}
+
+impl<I> Source for SoftClip<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        self.input.current_frame_len()
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        self.input.channels()
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        self.input.sample_rate()
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        self.input.total_duration()
+    }
+
+    #[inline]
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        self.input.try_seek(pos)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use buffer::SamplesBuffer;
+    use source::Source;
+
+    #[test]
+    fn bends_loud_samples() {
+        let source = SamplesBuffer::new(1, 44100, vec![0.25f32, -0.5, 0.9, -3.0, 100.0]);
+        let output = source.soft_clip(0.5).collect::<Vec<_>>();
+
+        assert_eq!(&output[.. 2], [0.25, -0.5]);
+        assert!(output[2] > 0.8 && output[2] < 0.9);
+        assert!(output[3] < -0.99 && output[3] >= -1.0);
+        assert!(output[4] <= 1.0);
+    }
+}
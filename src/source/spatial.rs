@@ -1,9 +1,17 @@
 use source::ChannelVolume;
+use std::f32;
 use std::fmt::Debug;
 use std::time::Duration;
+use conversions::db_to_amplitude;
+use source::SeekError;
 use Sample;
 use Source;
 
+// Cutoff frequency of the low-pass filter of a fully occluded source, and its reduction of the
+// volume in decibels.
+const OCCLUSION_CUTOFF: f32 = 800.0;
+const OCCLUSION_DB: f32 = -12.0;
+
 /// Combines channels in input into a single mono source, then plays that mono sound
 /// to each channel at the volume given for that channel.
 #[derive(Clone, Debug)]
@@ -13,6 +21,122 @@ where
     I::Item: Sample + Debug,
 {
     input: ChannelVolume<I>,
+    attenuation: DistanceAttenuation,
+    emitter_position: [f32; 3],
+    left_ear: [f32; 3],
+    right_ear: [f32; 3],
+    emitter_orientation: [f32; 3],
+    cone: SoundCone,
+    occlusion: f32,
+    // Coefficient of the low-pass filter, and the sample rate it was computed for.
+    occlusion_coeff: f32,
+    occlusion_rate: u32,
+    // Last output of the low-pass filter for each channel.
+    occlusion_state: [f32; 2],
+    // Channel of the next sample returned by `next`.
+    current_channel: u16,
+}
+
+/// Curve used to compute the volume of a sound from the distance between its emitter and an ear.
+#[derive(Clone, Copy, Debug)]
+pub enum DistanceModel {
+    /// The volume is `min_distance / (min_distance + rolloff * (distance - min_distance))`.
+    Inverse,
+    /// The volume decreases linearly from `1.0` at `min_distance` to `1.0 - rolloff` at
+    /// `max_distance`. It never goes below `0.0`. `max_distance` must be finite.
+    Linear,
+    /// The volume is `(distance / min_distance) ^ -rolloff`.
+    Exponential,
+    /// The volume is computed by the given function from the distance.
+    Custom(fn(f32) -> f32),
+}
+
+/// How the volume of a `Spatial` source decreases with the distance.
+///
+/// Before the model is applied, the distance is clamped between `min_distance` and
+/// `max_distance`. The default is an exponential model with a rolloff of `2.0` and a minimal
+/// distance of `1.0`, in other words the inverse square law.
+#[derive(Clone, Copy, Debug)]
+pub struct DistanceAttenuation {
+    /// Curve of the attenuation.
+    pub model: DistanceModel,
+    /// How fast the volume decreases. Unused by custom models.
+    pub rolloff: f32,
+    /// Distance under which the volume doesn't increase anymore. Must be greater than `0.0`.
+    pub min_distance: f32,
+    /// Distance above which the volume doesn't decrease anymore.
+    pub max_distance: f32,
+}
+
+impl DistanceAttenuation {
+    /// Returns the volume of a sound heard from the given distance.
+    pub fn gain(&self, distance: f32) -> f32 {
+        let distance = distance.max(self.min_distance).min(self.max_distance);
+        match self.model {
+            DistanceModel::Inverse => {
+                self.min_distance
+                    / (self.min_distance + self.rolloff * (distance - self.min_distance))
+            },
+            DistanceModel::Linear => {
+                let range = self.max_distance - self.min_distance;
+                (1.0 - self.rolloff * (distance - self.min_distance) / range).max(0.0)
+            },
+            DistanceModel::Exponential => (distance / self.min_distance).powf(-self.rolloff),
+            DistanceModel::Custom(curve) => curve(distance),
+        }
+    }
+}
+
+/// Directivity of an emitter, which is louder in the direction it is facing.
+///
+/// Listeners inside the inner cone hear the full volume, and listeners outside of the outer cone
+/// hear the volume multiplied by `outer_gain`. The volume changes linearly between the two. The
+/// default is an emitter that is as loud in every direction.
+#[derive(Clone, Copy, Debug)]
+pub struct SoundCone {
+    /// Angle of the inner cone, in degrees.
+    pub inner_angle: f32,
+    /// Angle of the outer cone, in degrees.
+    pub outer_angle: f32,
+    /// Volume outside of the outer cone.
+    pub outer_gain: f32,
+}
+
+impl SoundCone {
+    /// Returns the volume heard by a listener, from the angle in degrees between the direction
+    /// the emitter is facing and the direction from the emitter to the listener.
+    pub fn gain(&self, angle: f32) -> f32 {
+        let inner = self.inner_angle / 2.0;
+        let outer = self.outer_angle / 2.0;
+        if angle <= inner {
+            1.0
+        } else if angle >= outer {
+            self.outer_gain
+        } else {
+            1.0 + (self.outer_gain - 1.0) * (angle - inner) / (outer - inner)
+        }
+    }
+}
+
+impl Default for SoundCone {
+    fn default() -> SoundCone {
+        SoundCone {
+            inner_angle: 360.0,
+            outer_angle: 360.0,
+            outer_gain: 1.0,
+        }
+    }
+}
+
+impl Default for DistanceAttenuation {
+    fn default() -> DistanceAttenuation {
+        DistanceAttenuation {
+            model: DistanceModel::Exponential,
+            rolloff: 2.0,
+            min_distance: 1.0,
+            max_distance: f32::INFINITY,
+        }
+    }
 }
 
 fn dist_sq(a: [f32; 3], b: [f32; 3]) -> f32 {
@@ -35,6 +159,17 @@ where
     {
         let mut ret = Spatial {
             input: ChannelVolume::new(input, vec![0.0, 0.0]),
+            attenuation: Default::default(),
+            emitter_position,
+            left_ear,
+            right_ear,
+            emitter_orientation: [0.0; 3],
+            cone: Default::default(),
+            occlusion: 0.0,
+            occlusion_coeff: 1.0,
+            occlusion_rate: 0,
+            occlusion_state: [0.0; 2],
+            current_channel: 0,
         };
         ret.set_positions(emitter_position, left_ear, right_ear);
         ret
@@ -44,6 +179,10 @@ where
     pub fn set_positions(
         &mut self, emitter_pos: [f32; 3], left_ear: [f32; 3], right_ear: [f32; 3],
     ) {
+        self.emitter_position = emitter_pos;
+        self.left_ear = left_ear;
+        self.right_ear = right_ear;
+
         let left_dist_sq = dist_sq(left_ear, emitter_pos);
         let right_dist_sq = dist_sq(right_ear, emitter_pos);
         let max_diff = dist_sq(left_ear, right_ear).sqrt();
@@ -51,12 +190,91 @@ where
         let right_dist = right_dist_sq.sqrt();
         let left_diff_modifier = ((left_dist - right_dist) / max_diff + 1.0) / 4.0 + 0.5;
         let right_diff_modifier = ((right_dist - left_dist) / max_diff + 1.0) / 4.0 + 0.5;
-        let left_dist_modifier = (1.0 / left_dist_sq).min(1.0);
-        let right_dist_modifier = (1.0 / right_dist_sq).min(1.0);
+        let left_dist_modifier = self.attenuation.gain(left_dist);
+        let right_dist_modifier = self.attenuation.gain(right_dist);
+        let cone_modifier = self.cone_gain();
         self.input
-            .set_volume(0, left_diff_modifier * left_dist_modifier);
+            .set_volume(0, left_diff_modifier * left_dist_modifier * cone_modifier);
         self.input
-            .set_volume(1, right_diff_modifier * right_dist_modifier);
+            .set_volume(1, right_diff_modifier * right_dist_modifier * cone_modifier);
+    }
+
+    /// Sets the direction the emitter is facing, which is used with its cone.
+    ///
+    /// A null vector means that the emitter isn't facing any direction, and is as loud in every
+    /// direction.
+    pub fn set_emitter_orientation(&mut self, orientation: [f32; 3]) {
+        self.emitter_orientation = orientation;
+        let (emitter_pos, left_ear, right_ear) =
+            (self.emitter_position, self.left_ear, self.right_ear);
+        self.set_positions(emitter_pos, left_ear, right_ear);
+    }
+
+    /// Changes how the volume depends on the direction the emitter is facing.
+    pub fn set_cone(&mut self, cone: SoundCone) {
+        self.cone = cone;
+        let (emitter_pos, left_ear, right_ear) =
+            (self.emitter_position, self.left_ear, self.right_ear);
+        self.set_positions(emitter_pos, left_ear, right_ear);
+    }
+
+    // Returns the volume given by the cone for the current positions.
+    fn cone_gain(&self) -> f32 {
+        let orientation = self.emitter_orientation;
+        let orientation_len = dist_sq(orientation, [0.0; 3]).sqrt();
+
+        let mut to_listener = [0.0; 3];
+        for (i, value) in to_listener.iter_mut().enumerate() {
+            *value = (self.left_ear[i] + self.right_ear[i]) / 2.0 - self.emitter_position[i];
+        }
+        let to_listener_len = dist_sq(to_listener, [0.0; 3]).sqrt();
+
+        if orientation_len == 0.0 || to_listener_len == 0.0 {
+            return 1.0;
+        }
+        let dot = (0 .. 3).map(|i| orientation[i] * to_listener[i]).sum::<f32>();
+        let cos = (dot / (orientation_len * to_listener_len)).clamp(-1.0, 1.0);
+        self.cone.gain(cos.acos().to_degrees())
+    }
+
+    /// Changes how the volume decreases with the distance between the emitter and the ears.
+    pub fn set_attenuation(&mut self, attenuation: DistanceAttenuation) {
+        self.attenuation = attenuation;
+        let (emitter_pos, left_ear, right_ear) =
+            (self.emitter_position, self.left_ear, self.right_ear);
+        self.set_positions(emitter_pos, left_ear, right_ear);
+    }
+
+    /// Sets how much the sound is occluded, for example by a wall between the emitter and the
+    /// listener.
+    ///
+    /// The value is clamped between `0.0`, which leaves the sound untouched, and `1.0`, which
+    /// muffles it with a low-pass filter and lowers its volume.
+    pub fn set_occlusion(&mut self, occlusion: f32) {
+        self.occlusion = occlusion.clamp(0.0, 1.0);
+    }
+
+    // Applies the occlusion to a sample of a channel.
+    fn occlude(&mut self, value: I::Item, channel: usize) -> I::Item {
+        let rate = self.input.sample_rate();
+        if rate != self.occlusion_rate {
+            self.occlusion_rate = rate;
+            self.occlusion_coeff =
+                1.0 - (-2.0 * f32::consts::PI * OCCLUSION_CUTOFF / rate as f32).exp();
+        }
+
+        // The filter always runs, so that the occlusion can change without clicks.
+        let sample = value.to_f32();
+        let state = &mut self.occlusion_state[channel];
+        *state += self.occlusion_coeff * (sample - *state);
+        if self.occlusion == 0.0 {
+            return value;
+        }
+
+        let value = sample;
+        let gain = db_to_amplitude(OCCLUSION_DB * self.occlusion);
+        let value = (value + (*state - value) * self.occlusion) * gain;
+        Sample::from_sample(&value)
     }
 }
 
@@ -69,7 +287,14 @@ where
 
     #[inline]
     fn next(&mut self) -> Option<I::Item> {
-        self.input.next()
+        if self.current_channel >= self.input.channels() {
+            self.current_channel = 0;
+        }
+        let channel = self.current_channel as usize;
+        self.current_channel += 1;
+
+        let value = self.input.next()?;
+        Some(self.occlude(value, channel))
     }
 
     #[inline]
@@ -109,4 +334,72 @@ where
     fn total_duration(&self) -> Option<Duration> {
         self.input.total_duration()
     }
+
+    #[inline]
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        self.input.try_seek(pos)?;
+        self.current_channel = 0;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{DistanceAttenuation, DistanceModel, SoundCone, Spatial};
+    use buffer::SamplesBuffer;
+
+    #[test]
+    fn default_is_inverse_square() {
+        let attenuation = DistanceAttenuation::default();
+        assert_eq!(attenuation.gain(0.5), 1.0);
+        assert_eq!(attenuation.gain(2.0), 0.25);
+    }
+
+    #[test]
+    fn linear_clamps_distance() {
+        let attenuation = DistanceAttenuation {
+            model: DistanceModel::Linear,
+            rolloff: 1.0,
+            min_distance: 2.0,
+            max_distance: 6.0,
+        };
+        assert_eq!(attenuation.gain(1.0), 1.0);
+        assert_eq!(attenuation.gain(4.0), 0.5);
+        assert_eq!(attenuation.gain(10.0), 0.0);
+    }
+
+    #[test]
+    fn inverse() {
+        let attenuation = DistanceAttenuation {
+            model: DistanceModel::Inverse,
+            rolloff: 1.0,
+            min_distance: 1.0,
+            max_distance: 3.0,
+        };
+        assert_eq!(attenuation.gain(2.0), 0.5);
+        assert_eq!(attenuation.gain(5.0), 1.0 / 3.0);
+    }
+
+    #[test]
+    fn occlusion_muffles_and_lowers_volume() {
+        let data = (0 .. 2000).map(|n| if n % 4 < 2 { 0.5 } else { -0.5 }).collect::<Vec<f32>>();
+        let source = SamplesBuffer::new(1, 44100, data);
+        let mut spatial = Spatial::new(source, [0.0, 1.0, 0.0], [-1.0, 0.0, 0.0], [1.0, 0.0, 0.0]);
+        let clear = spatial.by_ref().take(400).map(|v| v.abs()).sum::<f32>();
+        spatial.set_occlusion(1.0);
+        let occluded = spatial.by_ref().take(400).map(|v| v.abs()).sum::<f32>();
+        assert!(occluded < clear * 0.25);
+    }
+
+    #[test]
+    fn cone() {
+        let cone = SoundCone {
+            inner_angle: 90.0,
+            outer_angle: 270.0,
+            outer_gain: 0.5,
+        };
+        assert_eq!(cone.gain(30.0), 1.0);
+        assert_eq!(cone.gain(90.0), 0.75);
+        assert_eq!(cone.gain(180.0), 0.5);
+    }
 }
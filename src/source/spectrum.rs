@@ -0,0 +1,263 @@
+use std::collections::VecDeque;
+use std::f32::consts::PI;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use source::fft::fft;
+use source::SeekError;
+use Sample;
+use Source;
+
+/// Internal function that builds a `Spectrum` object.
+pub fn spectrum<I>(input: I, fft_size: usize, hop: usize) -> Spectrum<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    assert!(fft_size >= 2 && fft_size.is_power_of_two());
+    assert!(hop >= 1);
+
+    // Hann window, scaled so that a full scale sine wave has a magnitude of `1.0`.
+    let window = (0 .. fft_size)
+        .map(|n| 0.5 - 0.5 * (2.0 * PI * n as f32 / fft_size as f32).cos())
+        .collect::<Vec<_>>();
+    let gain = 2.0 / window.iter().sum::<f32>();
+    let window = window.into_iter().map(|w| w * gain).collect();
+
+    let shared = Arc::new(SharedSpectrum {
+        bins: (0 .. fft_size / 2 + 1).map(|_| AtomicU32::new(0)).collect(),
+        sample_rate: AtomicU32::new(input.sample_rate()),
+    });
+
+    Spectrum {
+        input,
+        shared,
+        fft_size,
+        hop,
+        window,
+        current_channel: 0,
+        frame_sum: 0.0,
+        history: VecDeque::with_capacity(fft_size),
+        frames_since_fft: 0,
+        real: vec![0.0; fft_size],
+        imag: vec![0.0; fft_size],
+    }
+}
+
+/// Tap that computes the frequency spectrum of a source.
+///
+/// The samples are passed through untouched. The channels are mixed down, and every `hop` frames
+/// the magnitudes of the FFT of the last `fft_size` frames are published to a `SpectrumHandle`.
+#[derive(Debug)]
+pub struct Spectrum<I> {
+    input: I,
+    shared: Arc<SharedSpectrum>,
+    fft_size: usize,
+    hop: usize,
+    window: Vec<f32>,
+    // Channel of the next sample returned by `next`.
+    current_channel: u16,
+    // Sum of the samples of the current frame.
+    frame_sum: f32,
+    // Last `fft_size` frames, mixed down to mono.
+    history: VecDeque<f32>,
+    frames_since_fft: usize,
+    // Buffers for the FFT, kept around to avoid allocating on the audio thread.
+    real: Vec<f32>,
+    imag: Vec<f32>,
+}
+
+#[derive(Debug)]
+struct SharedSpectrum {
+    // Magnitudes stored as the bits of `f32`s, so that they can be read without locking.
+    bins: Vec<AtomicU32>,
+    sample_rate: AtomicU32,
+}
+
+/// Handle that reads the spectrum computed by a `Spectrum` from another thread.
+#[derive(Clone, Debug)]
+pub struct SpectrumHandle {
+    shared: Arc<SharedSpectrum>,
+}
+
+impl SpectrumHandle {
+    /// Returns the magnitudes of the last FFT, from 0Hz to the Nyquist frequency.
+    ///
+    /// There are `fft_size / 2 + 1` bins. A bin may belong to the FFT that follows the others if
+    /// it is read while being updated.
+    #[inline]
+    pub fn bins(&self) -> Vec<f32> {
+        self.shared.bins.iter().map(|bin| f32::from_bits(bin.load(Ordering::Relaxed))).collect()
+    }
+
+    /// Returns the frequency in Hz at the center of a bin.
+    #[inline]
+    pub fn frequency(&self, bin: usize) -> f32 {
+        let fft_size = (self.shared.bins.len() - 1) * 2;
+        let sample_rate = self.shared.sample_rate.load(Ordering::Relaxed);
+        bin as f32 * sample_rate as f32 / fft_size as f32
+    }
+}
+
+impl<I> Spectrum<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    /// Returns a handle that can read the spectrum after the source has been handed to a sink.
+    #[inline]
+    pub fn handle(&self) -> SpectrumHandle {
+        SpectrumHandle {
+            shared: self.shared.clone(),
+        }
+    }
+
+    /// Returns a reference to the inner source.
+    #[inline]
+    pub fn inner(&self) -> &I {
+        &self.input
+    }
+
+    /// Returns a mutable reference to the inner source.
+    #[inline]
+    pub fn inner_mut(&mut self) -> &mut I {
+        &mut self.input
+    }
+
+    /// Returns the inner source.
+    #[inline]
+    pub fn into_inner(self) -> I {
+        self.input
+    }
+
+    // Called at the end of each frame.
+    fn end_frame(&mut self, channels: u16) {
+        if self.history.len() == self.fft_size {
+            self.history.pop_front();
+        }
+        self.history.push_back(self.frame_sum / channels as f32);
+        self.frame_sum = 0.0;
+
+        self.frames_since_fft += 1;
+        if self.frames_since_fft >= self.hop && self.history.len() == self.fft_size {
+            self.frames_since_fft = 0;
+            self.compute();
+        }
+    }
+
+    fn compute(&mut self) {
+        for (n, (&sample, &w)) in self.history.iter().zip(&self.window).enumerate() {
+            self.real[n] = sample * w;
+            self.imag[n] = 0.0;
+        }
+        fft(&mut self.real, &mut self.imag);
+
+        for (k, bin) in self.shared.bins.iter().enumerate() {
+            let magnitude = (self.real[k] * self.real[k] + self.imag[k] * self.imag[k]).sqrt();
+            bin.store(magnitude.to_bits(), Ordering::Relaxed);
+        }
+        self.shared.sample_rate.store(self.input.sample_rate(), Ordering::Relaxed);
+    }
+}
+
+impl<I> Iterator for Spectrum<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    type Item = I::Item;
+
+    #[inline]
+    fn next(&mut self) -> Option<I::Item> {
+        let value = self.input.next()?;
+        let channels = self.input.channels();
+
+        if self.current_channel >= channels {
+            self.current_channel = 0;
+        }
+        self.frame_sum += value.to_f32();
+        self.current_channel += 1;
+        if self.current_channel >= channels {
+            self.end_frame(channels);
+        }
+
+        Some(value)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.input.size_hint()
+    }
+}
+
+impl<I> ExactSizeIterator for Spectrum<I>
+where
+    I: Source + ExactSizeIterator,
+    I::Item: Sample,
+{
+}
+
+impl<I> Source for Spectrum<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        self.input.current_frame_len()
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        self.input.channels()
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        self.input.sample_rate()
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        self.input.total_duration()
+    }
+
+    #[inline]
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        self.input.try_seek(pos)?;
+        self.current_channel = 0;
+        self.frame_sum = 0.0;
+        self.history.clear();
+        self.frames_since_fft = 0;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::PI;
+
+    use buffer::SamplesBuffer;
+    use source::Source;
+
+    #[test]
+    fn finds_sine_frequency() {
+        let data = (0 .. 128)
+            .flat_map(|n| {
+                let value = (2.0 * PI * 8.0 * n as f32 / 64.0).sin();
+                vec![value, value]
+            })
+            .collect::<Vec<_>>();
+        let source = SamplesBuffer::new(2, 64, data).spectrum(64, 16);
+        let handle = source.handle();
+        source.for_each(drop);
+
+        let bins = handle.bins();
+        assert_eq!(bins.len(), 33);
+        assert!((bins[8] - 1.0).abs() < 1e-3);
+        assert!((bins[7] - 0.5).abs() < 1e-3);
+        assert!(bins[20].abs() < 1e-3);
+        assert_eq!(handle.frequency(8), 8.0);
+    }
+}
@@ -1,5 +1,7 @@
 use std::time::Duration;
 
+use conversions::duration_to_ns;
+use source::SeekError;
 use Sample;
 use Source;
 
@@ -23,6 +25,20 @@ where
     I: Source,
     I::Item: Sample,
 {
+    /// Modifies the speed factor.
+    ///
+    /// The new sample rate is usually taken into account at the start of the next frame.
+    #[inline]
+    pub fn set_factor(&mut self, factor: f32) {
+        self.factor = factor;
+    }
+
+    /// Returns the speed factor.
+    #[inline]
+    pub fn factor(&self) -> f32 {
+        self.factor
+    }
+
     /// Returns a reference to the inner source.
     #[inline]
     pub fn inner(&self) -> &I {
@@ -92,7 +108,7 @@ where
     fn total_duration(&self) -> Option<Duration> {
         // TODO: the crappy API of duration makes this code difficult to write
         if let Some(duration) = self.input.total_duration() {
-            let as_ns = duration.as_secs() * 1000000000 + duration.subsec_nanos() as u64;
+            let as_ns = duration_to_ns(duration);
             let new_val = (as_ns as f32 / self.factor) as u64;
             Some(Duration::new(
                 new_val / 1000000000,
@@ -102,4 +118,10 @@ where
             None
         }
     }
+
+    #[inline]
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        // The position is measured in the time of the output.
+        self.input.try_seek(pos.mul_f32(self.factor))
+    }
 }
@@ -0,0 +1,138 @@
+use std::time::Duration;
+
+use conversions::ResampleQuality;
+use source::speed::{self, Speed};
+use source::SeekError;
+use source::UniformSourceIterator;
+use Sample;
+use Source;
+
+/// Internal function that builds a `SpeedHq` object.
+pub fn speed_hq<I>(input: I, factor: f32) -> SpeedHq<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    let channels = input.channels();
+    let sample_rate = input.sample_rate();
+    let input = speed::speed(input, factor);
+    SpeedHq {
+        input: UniformSourceIterator::with_quality(
+            input,
+            channels,
+            sample_rate,
+            ResampleQuality::High,
+        ),
+    }
+}
+
+/// Filter that changes the play speed of a sound and converts it back to its sample rate.
+///
+/// Unlike `Speed`, the samples are resampled, so a mixer running at a fixed rate doesn't need
+/// to convert them again. The output keeps the channels and sample rate that the sound has when
+/// the filter is built.
+#[derive(Clone)]
+pub struct SpeedHq<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    input: UniformSourceIterator<Speed<I>, I::Item>,
+}
+
+impl<I> SpeedHq<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    /// Returns a reference to the inner source.
+    #[inline]
+    pub fn inner(&self) -> &I {
+        self.input.inner().inner()
+    }
+
+    /// Returns a mutable reference to the inner source.
+    #[inline]
+    pub fn inner_mut(&mut self) -> &mut I {
+        self.input.inner_mut().inner_mut()
+    }
+
+    /// Returns the inner source.
+    #[inline]
+    pub fn into_inner(self) -> I {
+        self.input.into_inner().into_inner()
+    }
+}
+
+impl<I> Iterator for SpeedHq<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    type Item = I::Item;
+
+    #[inline]
+    fn next(&mut self) -> Option<I::Item> {
+        self.input.next()
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.input.size_hint()
+    }
+}
+
+impl<I> Source for SpeedHq<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        self.input.current_frame_len()
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        self.input.channels()
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        self.input.sample_rate()
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        self.input.total_duration()
+    }
+
+    #[inline]
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        self.input.try_seek(pos)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use buffer::SamplesBuffer;
+    use source::{SineWave, Source};
+
+    // Returns the number of times a signal changes sign.
+    fn zero_crossings(samples: &[f32]) -> usize {
+        samples.windows(2).filter(|w| (w[0] < 0.0) != (w[1] < 0.0)).count()
+    }
+
+    #[test]
+    fn doubles_the_pitch() {
+        let wave = SineWave::new(440).take(48000).collect::<Vec<_>>();
+        let source = SamplesBuffer::new(1, 48000, wave).speed_hq(2.0);
+        assert_eq!(source.sample_rate(), 48000);
+        let output = source.collect::<Vec<f32>>();
+        assert!((output.len() as i32 - 24000).abs() <= 1, "{}", output.len());
+
+        // 880 Hz changes sign 176 times in 0.1 second.
+        let crossings = zero_crossings(&output[4800 .. 9600]);
+        assert!((crossings as i32 - 176).abs() <= 1, "{}", crossings);
+    }
+}
@@ -0,0 +1,107 @@
+use std::time::Duration;
+use Source;
+
+/// An infinite source that produces a band-limited square wave.
+///
+/// The discontinuities are smoothed with the polyBLEP method, which removes most of the aliasing
+/// that a naive square wave would produce.
+///
+/// Always has a rate of 48kHz and one channel.
+#[derive(Clone, Debug)]
+pub struct SquareWave {
+    // Phase increment per sample.
+    phase_step: f32,
+    // Position in the current period, between 0 and 1.
+    phase: f32,
+}
+
+impl SquareWave {
+    /// The frequency of the square wave.
+    #[inline]
+    pub fn new(freq: u32) -> SquareWave {
+        SquareWave {
+            phase_step: freq as f32 / 48000.0,
+            phase: 0.0,
+        }
+    }
+}
+
+/// Polynomial approximation of a band-limited step, to be subtracted from a naive waveform at
+/// each of its discontinuities.
+///
+/// `t` is the position in the period, between 0 and 1, relative to the discontinuity, and `dt`
+/// is the phase increment per sample.
+#[inline]
+pub(crate) fn poly_blep(t: f32, dt: f32) -> f32 {
+    if t < dt {
+        let t = t / dt;
+        t + t - t * t - 1.0
+    } else if t > 1.0 - dt {
+        let t = (t - 1.0) / dt;
+        t * t + t + t + 1.0
+    } else {
+        0.0
+    }
+}
+
+impl Iterator for SquareWave {
+    type Item = f32;
+
+    #[inline]
+    fn next(&mut self) -> Option<f32> {
+        let dt = self.phase_step;
+        let naive = if self.phase < 0.5 { 1.0 } else { -1.0 };
+        let value = naive + poly_blep(self.phase, dt) - poly_blep((self.phase + 0.5) % 1.0, dt);
+
+        self.phase = (self.phase + dt) % 1.0;
+        Some(value)
+    }
+}
+
+impl Source for SquareWave {
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        None
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        1
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        48000
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use source::SquareWave;
+
+    #[test]
+    fn alternates_around_zero() {
+        // 480 samples per period.
+        let samples = SquareWave::new(100).take(480).collect::<Vec<_>>();
+
+        assert_eq!(samples[100], 1.0);
+        assert_eq!(samples[340], -1.0);
+        let mean = samples.iter().sum::<f32>() / samples.len() as f32;
+        assert!(mean.abs() < 1e-3);
+    }
+
+    #[test]
+    fn edges_are_smoothed() {
+        let samples = SquareWave::new(100).skip(10).take(480).collect::<Vec<_>>();
+
+        // Each of the two edges of a period is spread over a couple of samples.
+        let smoothed = samples.iter().filter(|s| s.abs() < 1.0).count();
+        assert!(smoothed >= 2 && smoothed <= 4);
+        assert!(samples.iter().all(|s| s.abs() <= 1.0));
+    }
+}
@@ -1,5 +1,6 @@
 use std::time::Duration;
 
+use source::SeekError;
 use Sample;
 use Source;
 
@@ -89,4 +90,9 @@ where
     fn total_duration(&self) -> Option<Duration> {
         self.input.total_duration()
     }
+
+    #[inline]
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        self.input.try_seek(pos)
+    }
 }
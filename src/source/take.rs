@@ -1,5 +1,6 @@
 use std::time::Duration;
 
+use source::SeekError;
 use Sample;
 use Source;
 
@@ -175,4 +176,17 @@ where
             None
         }
     }
+
+    #[inline]
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        self.input.try_seek(pos)?;
+        self.remaining_duration = if pos < self.requested_duration {
+            self.requested_duration - pos
+        } else {
+            Duration::new(0, 0)
+        };
+        self.current_frame_len = self.input.current_frame_len();
+        self.duration_per_sample = Self::get_duration_per_sample(&self.input);
+        Ok(())
+    }
 }
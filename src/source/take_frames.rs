@@ -0,0 +1,170 @@
+use std::cmp;
+use std::time::Duration;
+
+use conversions::{duration_to_frames, frames_to_ns};
+use source::SeekError;
+use Sample;
+use Source;
+
+/// Internal function that builds a `TakeFrames` object.
+pub fn take_frames<I>(input: I, frames: u64) -> TakeFrames<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    TakeFrames {
+        input,
+        frames,
+        remaining_frames: frames,
+        current_channel: 0,
+        channels: 0,
+    }
+}
+
+/// Filter that only keeps a given number of frames of a source.
+#[derive(Clone, Debug)]
+pub struct TakeFrames<I> {
+    input: I,
+    frames: u64,
+    // Number of frames that haven't been started yet.
+    remaining_frames: u64,
+    // Channel of the next sample returned by `next`.
+    current_channel: u16,
+    // Number of channels of the current frame.
+    channels: u16,
+}
+
+impl<I> TakeFrames<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    /// Returns a reference to the inner source.
+    #[inline]
+    pub fn inner(&self) -> &I {
+        &self.input
+    }
+
+    /// Returns a mutable reference to the inner source.
+    #[inline]
+    pub fn inner_mut(&mut self) -> &mut I {
+        &mut self.input
+    }
+
+    /// Returns the inner source.
+    #[inline]
+    pub fn into_inner(self) -> I {
+        self.input
+    }
+
+    // Returns the number of samples left before the end.
+    #[inline]
+    fn remaining_samples(&self) -> u64 {
+        let in_frame = self.channels.saturating_sub(self.current_channel) as u64;
+        in_frame + self.remaining_frames * self.input.channels() as u64
+    }
+}
+
+impl<I> Iterator for TakeFrames<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    type Item = I::Item;
+
+    #[inline]
+    fn next(&mut self) -> Option<I::Item> {
+        if self.current_channel >= self.channels {
+            if self.remaining_frames == 0 {
+                return None;
+            }
+            self.remaining_frames -= 1;
+            self.current_channel = 0;
+            self.channels = self.input.channels();
+        }
+        self.current_channel += 1;
+
+        self.input.next()
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.remaining_samples() as usize;
+        let (min, max) = self.input.size_hint();
+        let max = max.map_or(remaining, |max| cmp::min(max, remaining));
+        (cmp::min(min, remaining), Some(max))
+    }
+}
+
+impl<I> ExactSizeIterator for TakeFrames<I>
+where
+    I: Source + ExactSizeIterator,
+    I::Item: Sample,
+{
+}
+
+impl<I> Source for TakeFrames<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        let remaining = self.remaining_samples() as usize;
+        match self.input.current_frame_len() {
+            Some(len) => Some(cmp::min(len, remaining)),
+            None => Some(remaining),
+        }
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        self.input.channels()
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        self.input.sample_rate()
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        let ns = frames_to_ns(self.frames, self.input.sample_rate());
+        let duration = Duration::from_nanos(ns);
+        match self.input.total_duration() {
+            Some(total) => Some(cmp::min(total, duration)),
+            None => Some(duration),
+        }
+    }
+
+    #[inline]
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        self.input.try_seek(pos)?;
+
+        let frame = duration_to_frames(pos, self.input.sample_rate());
+        self.remaining_frames = self.frames.saturating_sub(frame);
+        self.current_channel = 0;
+        self.channels = 0;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use buffer::SamplesBuffer;
+    use source::Source;
+
+    #[test]
+    fn exact_frames() {
+        let data = (0 .. 30).collect::<Vec<i16>>();
+        let source = SamplesBuffer::new(3, 44100, data).take_frames(4);
+        assert_eq!(source.current_frame_len(), Some(12));
+        assert_eq!(source.collect::<Vec<_>>(), (0 .. 12).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn shorter_source() {
+        let source = SamplesBuffer::new(2, 44100, vec![1i16, 2]).take_frames(10);
+        assert_eq!(source.collect::<Vec<_>>(), [1, 2]);
+    }
+}
@@ -0,0 +1,238 @@
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use Sample;
+use Source;
+
+/// Internal function that builds the two halves of a `Tee`.
+pub fn tee<I>(input: I) -> (Tee<I>, Tee<I>)
+where
+    I: Source,
+    I::Item: Sample,
+{
+    // One second of audio.
+    let capacity = input.sample_rate() as usize * input.channels() as usize;
+
+    let shared = Arc::new(Mutex::new(TeeShared {
+        input,
+        buffers: [TeeBuffer::new(), TeeBuffer::new()],
+        capacity,
+    }));
+
+    let first = Tee {
+        shared: shared.clone(),
+        side: 0,
+    };
+    let second = Tee { shared, side: 1 };
+    (first, second)
+}
+
+/// One of the two copies of a source returned by `Source::tee`.
+///
+/// The samples read from the inner source by one copy are stored until the other copy reads them.
+/// At most one second of audio is stored: if one copy gets further ahead, the oldest samples are
+/// dropped from the other one.
+pub struct Tee<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    shared: Arc<Mutex<TeeShared<I>>>,
+    // Index of the buffer of this copy in `buffers`.
+    side: usize,
+}
+
+struct TeeShared<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    input: I,
+    // Samples read by one copy and not yet by the other.
+    buffers: [TeeBuffer<I::Item>; 2],
+    // Maximum number of samples in a buffer.
+    capacity: usize,
+}
+
+// Buffered samples, split in spans that have the same format.
+struct TeeBuffer<S> {
+    spans: VecDeque<Span<S>>,
+    // Total number of samples in `spans`.
+    len: usize,
+}
+
+struct Span<S> {
+    channels: u16,
+    sample_rate: u32,
+    samples: VecDeque<S>,
+}
+
+impl<S> TeeBuffer<S> {
+    #[inline]
+    fn new() -> TeeBuffer<S> {
+        TeeBuffer {
+            spans: VecDeque::new(),
+            len: 0,
+        }
+    }
+
+    fn push(&mut self, value: S, channels: u16, sample_rate: u32) {
+        let same_format = match self.spans.back() {
+            Some(span) => span.channels == channels && span.sample_rate == sample_rate,
+            None => false,
+        };
+        if !same_format {
+            self.spans.push_back(Span {
+                channels,
+                sample_rate,
+                samples: VecDeque::new(),
+            });
+        }
+        self.spans.back_mut().unwrap().samples.push_back(value);
+        self.len += 1;
+    }
+
+    fn pop(&mut self) -> Option<S> {
+        let value = self.spans.front_mut()?.samples.pop_front();
+        if self.spans[0].samples.is_empty() {
+            self.spans.pop_front();
+        }
+        self.len -= 1;
+        value
+    }
+
+    // Drops the oldest frame. A whole frame is dropped so that the channels don't get swapped.
+    fn drop_frame(&mut self) {
+        let emptied = match self.spans.front_mut() {
+            Some(span) => {
+                let count = (span.channels as usize).min(span.samples.len());
+                span.samples.drain(.. count);
+                self.len -= count;
+                span.samples.is_empty()
+            },
+            None => false,
+        };
+        if emptied {
+            self.spans.pop_front();
+        }
+    }
+}
+
+impl<I> Iterator for Tee<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    type Item = I::Item;
+
+    #[inline]
+    fn next(&mut self) -> Option<I::Item> {
+        // Nothing needs to be stored for the other copy if it has been dropped.
+        let other_alive = Arc::strong_count(&self.shared) > 1;
+
+        let mut shared = self.shared.lock().unwrap();
+        if let Some(value) = shared.buffers[self.side].pop() {
+            return Some(value);
+        }
+
+        let value = shared.input.next()?;
+        if other_alive {
+            // Sources move to the format of their next frame when they return its first sample,
+            // so the format read now is the one of `value`.
+            let channels = shared.input.channels();
+            let sample_rate = shared.input.sample_rate();
+            let capacity = shared.capacity;
+            let other = &mut shared.buffers[1 - self.side];
+            if other.len >= capacity {
+                other.drop_frame();
+            }
+            other.push(value, channels, sample_rate);
+        }
+        Some(value)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let shared = self.shared.lock().unwrap();
+        let buffered = shared.buffers[self.side].len;
+        let (min, max) = shared.input.size_hint();
+        (min + buffered, max.map(|max| max + buffered))
+    }
+}
+
+impl<I> Source for Tee<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        let shared = self.shared.lock().unwrap();
+        match shared.buffers[self.side].spans.front() {
+            Some(span) => Some(span.samples.len()),
+            None => shared.input.current_frame_len(),
+        }
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        let shared = self.shared.lock().unwrap();
+        match shared.buffers[self.side].spans.front() {
+            Some(span) => span.channels,
+            None => shared.input.channels(),
+        }
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        let shared = self.shared.lock().unwrap();
+        match shared.buffers[self.side].spans.front() {
+            Some(span) => span.sample_rate,
+            None => shared.input.sample_rate(),
+        }
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        self.shared.lock().unwrap().input.total_duration()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use buffer::SamplesBuffer;
+    use source::{from_iter, Source};
+
+    #[test]
+    fn both_copies_get_all_samples() {
+        let (mut first, second) = SamplesBuffer::new(1, 10, vec![1i16, 2, 3, 4]).tee();
+        assert_eq!(first.next(), Some(1));
+        assert_eq!(second.take(2).collect::<Vec<_>>(), [1, 2]);
+        assert_eq!(first.collect::<Vec<_>>(), [2, 3, 4]);
+    }
+
+    #[test]
+    fn buffering_is_bounded() {
+        let data = (0 .. 20).collect::<Vec<i16>>();
+        let (first, second) = SamplesBuffer::new(2, 2, data).tee();
+        assert_eq!(first.count(), 20);
+        assert_eq!(second.collect::<Vec<_>>(), [16, 17, 18, 19]);
+    }
+
+    #[test]
+    fn lagging_copy_keeps_the_format() {
+        let source = from_iter(vec![
+            SamplesBuffer::new(1, 10, vec![1i16, 2]),
+            SamplesBuffer::new(2, 20, vec![3i16, 4]),
+        ]);
+        let (first, mut second) = source.tee();
+        assert_eq!(first.count(), 4);
+
+        assert_eq!((second.channels(), second.sample_rate()), (1, 10));
+        assert_eq!(second.current_frame_len(), Some(2));
+        assert_eq!(second.by_ref().take(2).collect::<Vec<_>>(), [1, 2]);
+        assert_eq!((second.channels(), second.sample_rate()), (2, 20));
+        assert_eq!(second.collect::<Vec<_>>(), [3, 4]);
+    }
+}
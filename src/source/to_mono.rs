@@ -0,0 +1,136 @@
+use std::time::Duration;
+
+use source::SeekError;
+use Sample;
+use Source;
+
+/// Internal function that builds a `ToMono` object.
+pub fn to_mono<I>(input: I) -> ToMono<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    ToMono { input }
+}
+
+/// Downmixes all the channels of a source into a single channel.
+///
+/// Each output sample is the average of the samples of one input frame, so that a signal present
+/// on every channel keeps the same level once downmixed.
+#[derive(Clone, Debug)]
+pub struct ToMono<I> {
+    input: I,
+}
+
+impl<I> ToMono<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    /// Returns a reference to the inner source.
+    #[inline]
+    pub fn inner(&self) -> &I {
+        &self.input
+    }
+
+    /// Returns a mutable reference to the inner source.
+    #[inline]
+    pub fn inner_mut(&mut self) -> &mut I {
+        &mut self.input
+    }
+
+    /// Returns the inner source.
+    #[inline]
+    pub fn into_inner(self) -> I {
+        self.input
+    }
+}
+
+impl<I> Iterator for ToMono<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    type Item = I::Item;
+
+    #[inline]
+    fn next(&mut self) -> Option<I::Item> {
+        let channels = self.input.channels();
+        if channels == 1 {
+            return self.input.next();
+        }
+
+        let gain = 1.0 / channels as f32;
+        let mut sum = self.input.next()?.amplify(gain);
+        for _ in 1 .. channels {
+            match self.input.next() {
+                Some(sample) => sum = sum.saturating_add(sample.amplify(gain)),
+                None => break,
+            }
+        }
+        Some(sum)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let channels = self.input.channels() as usize;
+        let (min, max) = self.input.size_hint();
+        (min / channels, max.map(|max| max / channels))
+    }
+}
+
+impl<I> Source for ToMono<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        let channels = self.input.channels() as usize;
+        self.input.current_frame_len().map(|len| len / channels)
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        1
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        self.input.sample_rate()
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        self.input.total_duration()
+    }
+
+    #[inline]
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        self.input.try_seek(pos)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use buffer::SamplesBuffer;
+    use source::Source;
+
+    #[test]
+    fn averages_channels() {
+        let input = SamplesBuffer::new(2, 44100, vec![10i16, 20, 30, 50]);
+        let mut source = input.to_mono();
+
+        assert_eq!(source.channels(), 1);
+        assert_eq!(source.next(), Some(15));
+        assert_eq!(source.next(), Some(40));
+        assert_eq!(source.next(), None);
+    }
+
+    #[test]
+    fn mono_is_untouched() {
+        let input = SamplesBuffer::new(1, 44100, vec![1.0f32, -0.5]);
+        let output = input.to_mono().collect::<Vec<_>>();
+        assert_eq!(output, [1.0, -0.5]);
+    }
+}
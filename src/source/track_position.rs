@@ -0,0 +1,213 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use conversions::{duration_to_frames, duration_to_ns};
+use source::{FramePosition, SeekError};
+use Sample;
+use Source;
+
+/// Internal function that builds a `TrackPosition` object.
+pub fn track_position<I>(input: I) -> TrackPosition<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    let position = FramePosition::new(input.sample_rate());
+
+    TrackPosition {
+        input,
+        shared: Arc::new(SharedPosition {
+            samples: AtomicU64::new(0),
+            nanos: AtomicU64::new(0),
+        }),
+        position,
+    }
+}
+
+/// Filter that keeps track of the playback position of a source.
+///
+/// The position is measured in the time of the inner source: each frame lasts one period of its
+/// sample rate. It is therefore not affected by filters such as `speed` that are applied on top of
+/// this one.
+#[derive(Debug)]
+pub struct TrackPosition<I> {
+    input: I,
+    shared: Arc<SharedPosition>,
+    position: FramePosition,
+}
+
+#[derive(Debug)]
+struct SharedPosition {
+    samples: AtomicU64,
+    nanos: AtomicU64,
+}
+
+/// Handle that reads the playback position of a `TrackPosition` from another thread.
+#[derive(Clone, Debug)]
+pub struct PositionHandle {
+    shared: Arc<SharedPosition>,
+}
+
+impl PositionHandle {
+    /// Returns the playback position. See `TrackPosition::position`.
+    #[inline]
+    pub fn position(&self) -> Duration {
+        Duration::from_nanos(self.shared.nanos.load(Ordering::Relaxed))
+    }
+
+    /// Returns the playback position in samples. See `TrackPosition::samples`.
+    #[inline]
+    pub fn samples(&self) -> u64 {
+        self.shared.samples.load(Ordering::Relaxed)
+    }
+}
+
+impl<I> TrackPosition<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    /// Returns the time between the start of the source and the frame that was last returned.
+    #[inline]
+    pub fn position(&self) -> Duration {
+        Duration::from_nanos(self.shared.nanos.load(Ordering::Relaxed))
+    }
+
+    /// Returns the number of samples between the start of the source and the last returned one.
+    #[inline]
+    pub fn samples(&self) -> u64 {
+        self.shared.samples.load(Ordering::Relaxed)
+    }
+
+    /// Returns a handle that can read the position after the source has been handed to a sink.
+    #[inline]
+    pub fn handle(&self) -> PositionHandle {
+        PositionHandle {
+            shared: self.shared.clone(),
+        }
+    }
+
+    /// Returns a reference to the inner source.
+    #[inline]
+    pub fn inner(&self) -> &I {
+        &self.input
+    }
+
+    /// Returns a mutable reference to the inner source.
+    #[inline]
+    pub fn inner_mut(&mut self) -> &mut I {
+        &mut self.input
+    }
+
+    /// Returns the inner source.
+    #[inline]
+    pub fn into_inner(self) -> I {
+        self.input
+    }
+}
+
+impl<I> Iterator for TrackPosition<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    type Item = I::Item;
+
+    #[inline]
+    fn next(&mut self) -> Option<I::Item> {
+        let value = self.input.next()?;
+
+        if self.position.is_frame_start(self.input.channels()) {
+            let nanos = self.position.next_frame(self.input.sample_rate());
+            self.shared.nanos.store(nanos, Ordering::Relaxed);
+        }
+        self.shared.samples.fetch_add(1, Ordering::Relaxed);
+
+        Some(value)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.input.size_hint()
+    }
+}
+
+impl<I> ExactSizeIterator for TrackPosition<I>
+where
+    I: Source + ExactSizeIterator,
+    I::Item: Sample,
+{
+}
+
+impl<I> Source for TrackPosition<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        self.input.current_frame_len()
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        self.input.channels()
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        self.input.sample_rate()
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        self.input.total_duration()
+    }
+
+    #[inline]
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        self.input.try_seek(pos)?;
+
+        let pos_ns = duration_to_ns(pos);
+        let frame = duration_to_frames(pos, self.input.sample_rate());
+        self.shared.nanos.store(pos_ns, Ordering::Relaxed);
+        self.shared
+            .samples
+            .store(frame * self.input.channels() as u64, Ordering::Relaxed);
+
+        self.position.set_position_ns(pos_ns, self.input.sample_rate());
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use buffer::SamplesBuffer;
+    use source::Source;
+
+    #[test]
+    fn counts_frames() {
+        let source = SamplesBuffer::new(2, 4, vec![0i16; 16]).track_position();
+        let handle = source.handle();
+        let mut source = source.speed(2.0);
+
+        assert_eq!(handle.position(), Duration::new(0, 0));
+        source.by_ref().take(6).count();
+        assert_eq!(handle.samples(), 6);
+        // The third frame is being played, and each frame lasts a quarter of a second.
+        assert_eq!(handle.position(), Duration::from_millis(500));
+    }
+
+    #[test]
+    fn seek() {
+        let mut source = SamplesBuffer::new(1, 10, vec![0i16; 20]).track_position();
+        source.try_seek(Duration::from_millis(1500)).unwrap();
+        assert_eq!(source.samples(), 15);
+        source.next();
+        assert_eq!(source.position(), Duration::from_millis(1500));
+        assert_eq!(source.samples(), 16);
+    }
+}
@@ -0,0 +1,79 @@
+use std::time::Duration;
+use Source;
+
+/// An infinite source that produces a triangle wave.
+///
+/// Like `SineWave`, it starts at zero and rises first.
+///
+/// Always has a rate of 48kHz and one channel.
+#[derive(Clone, Debug)]
+pub struct TriangleWave {
+    // Phase increment per sample.
+    phase_step: f32,
+    // Position in the current period, between 0 and 1.
+    phase: f32,
+}
+
+impl TriangleWave {
+    /// The frequency of the triangle wave.
+    #[inline]
+    pub fn new(freq: u32) -> TriangleWave {
+        TriangleWave {
+            phase_step: freq as f32 / 48000.0,
+            phase: 0.0,
+        }
+    }
+}
+
+impl Iterator for TriangleWave {
+    type Item = f32;
+
+    #[inline]
+    fn next(&mut self) -> Option<f32> {
+        // Shifting by a quarter of period makes the wave start at zero.
+        let value = 1.0 - 4.0 * ((self.phase + 0.25) % 1.0 - 0.5).abs();
+
+        self.phase = (self.phase + self.phase_step) % 1.0;
+        Some(value)
+    }
+}
+
+impl Source for TriangleWave {
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        None
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        1
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        48000
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use source::TriangleWave;
+
+    #[test]
+    fn rises_then_falls() {
+        // 480 samples per period.
+        let samples = TriangleWave::new(100).take(480).collect::<Vec<_>>();
+
+        assert!(samples[0].abs() < 1e-3);
+        assert!((samples[60] - 0.5).abs() < 1e-3);
+        assert!((samples[120] - 1.0).abs() < 1e-3);
+        assert!(samples[240].abs() < 1e-3);
+        assert!((samples[360] + 1.0).abs() < 1e-3);
+        assert!(samples.iter().all(|s| s.abs() <= 1.0));
+    }
+}
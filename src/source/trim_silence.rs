@@ -0,0 +1,250 @@
+use std::collections::VecDeque;
+use std::time::Duration;
+
+use conversions::{db_to_amplitude, duration_to_frames};
+use source::SeekError;
+use Sample;
+use Source;
+
+/// Default longest silence that is held back, or dropped in a single call to `next`.
+const DEFAULT_MAX_SILENCE: Duration = Duration::from_secs(2);
+
+/// Internal function that builds a `TrimSilence` object.
+pub fn trim_silence<I>(input: I, threshold_db: f32) -> TrimSilence<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    let channels = input.channels();
+    let sample_rate = input.sample_rate();
+
+    TrimSilence {
+        input,
+        threshold: db_to_amplitude(threshold_db),
+        started: false,
+        max_silence: DEFAULT_MAX_SILENCE,
+        silence: Vec::new(),
+        skipped_frames: 0,
+        pending: VecDeque::new(),
+        channels,
+        sample_rate,
+    }
+}
+
+/// Filter that removes the silence at the start and at the end of a source.
+///
+/// A frame is silent if all its samples are quieter than the threshold. The silent frames at the
+/// start are dropped. Silent frames after that are held back until a frame that isn't silent is
+/// read, and are dropped if the source ends first.
+///
+/// In order to bound the memory and the time spent in a single call to `next`, at most
+/// `max_silence` of silence is held back: a longer silence is played, and only its last part is
+/// dropped at the end of the source. At the start, a single silent frame is played for each
+/// `max_silence` of silence dropped, so that an endless silence doesn't block the caller.
+#[derive(Clone, Debug)]
+pub struct TrimSilence<I>
+where
+    I: Iterator,
+{
+    input: I,
+    // Threshold as an amplitude between `0.0` and `1.0`.
+    threshold: f32,
+    // True once a frame that isn't silent has been read.
+    started: bool,
+    // Longest silence held back.
+    max_silence: Duration,
+    // Silent samples read since the last frame that isn't silent.
+    silence: Vec<I::Item>,
+    // Number of silent frames dropped at the start in the current call to `next`.
+    skipped_frames: u64,
+    // Samples read from the input and being returned.
+    pending: VecDeque<I::Item>,
+    channels: u16,
+    sample_rate: u32,
+}
+
+impl<I> TrimSilence<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    /// Changes the longest silence that is held back, `2` seconds by default.
+    #[inline]
+    pub fn set_max_silence(&mut self, max_silence: Duration) {
+        self.max_silence = max_silence;
+    }
+
+    /// Returns a reference to the inner source.
+    #[inline]
+    pub fn inner(&self) -> &I {
+        &self.input
+    }
+
+    /// Returns a mutable reference to the inner source.
+    #[inline]
+    pub fn inner_mut(&mut self) -> &mut I {
+        &mut self.input
+    }
+
+    /// Returns the inner source.
+    #[inline]
+    pub fn into_inner(self) -> I {
+        self.input
+    }
+
+    // Reads frames until one isn't silent, and moves it to `pending` along with the silence that
+    // precedes it. Also moves the silence to `pending` once it reaches `max_silence`. Returns
+    // `false` if the input has ended.
+    fn read_until_sound(&mut self) -> bool {
+        self.skipped_frames = 0;
+        loop {
+            if self.silence.is_empty() {
+                self.channels = self.input.channels();
+                self.sample_rate = self.input.sample_rate();
+            }
+            let max_frames = duration_to_frames(self.max_silence, self.sample_rate).max(1);
+
+            let start = self.silence.len();
+            let channels = self.input.channels() as usize;
+            self.silence.extend(self.input.by_ref().take(channels));
+            if self.silence.len() == start {
+                self.silence.clear();
+                return false;
+            }
+
+            let threshold = self.threshold;
+            if self.silence[start ..].iter().any(|s| s.to_f32().abs() >= threshold) {
+                self.started = true;
+                self.pending.extend(self.silence.drain(..));
+                return true;
+            }
+
+            if self.started {
+                if (self.silence.len() / channels) as u64 >= max_frames {
+                    self.pending.extend(self.silence.drain(..));
+                    return true;
+                }
+            } else {
+                self.skipped_frames += 1;
+                if self.skipped_frames >= max_frames {
+                    self.pending.extend(self.silence.drain(..));
+                    return true;
+                }
+                self.silence.clear();
+            }
+        }
+    }
+}
+impl<I> Iterator for TrimSilence<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    type Item = I::Item;
+
+    #[inline]
+    fn next(&mut self) -> Option<I::Item> {
+        if self.pending.is_empty() && !self.read_until_sound() {
+            return None;
+        }
+        self.pending.pop_front()
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let buffered = self.pending.len() + self.silence.len();
+        let max = self.input.size_hint().1.map(|max| max + buffered);
+        (self.pending.len(), max)
+    }
+}
+
+impl<I> Source for TrimSilence<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        if self.pending.is_empty() {
+            self.input.current_frame_len()
+        } else {
+            Some(self.pending.len())
+        }
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        if self.pending.is_empty() {
+            self.input.channels()
+        } else {
+            self.channels
+        }
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        if self.pending.is_empty() {
+            self.input.sample_rate()
+        } else {
+            self.sample_rate
+        }
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        None
+    }
+
+    #[inline]
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        self.input.try_seek(pos)?;
+        self.pending.clear();
+        self.silence.clear();
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use buffer::SamplesBuffer;
+    use source::{Source, Zero};
+
+    #[test]
+    fn trims_both_ends() {
+        let data = vec![0.0f32, 0.0, 0.001, 0.5, 0.0, 0.0, 0.5, 0.0, 0.001, 0.0];
+        let source = SamplesBuffer::new(1, 44100, data);
+        let output = source.trim_silence(-40.0).collect::<Vec<_>>();
+        assert_eq!(output, [0.5, 0.0, 0.0, 0.5]);
+    }
+
+    #[test]
+    fn long_gaps_are_played() {
+        let mut data = vec![0.5f32];
+        data.extend(vec![0.0; 25]);
+        data.push(0.5);
+        data.extend(vec![0.0; 25]);
+        let mut source = SamplesBuffer::new(1, 1000, data).trim_silence(-40.0);
+        source.set_max_silence(Duration::from_millis(10));
+
+        // The gap is played as it is held back, and only the end of the last silence is
+        // dropped.
+        let output = source.collect::<Vec<_>>();
+        assert_eq!(output.len(), 47);
+        assert_eq!(output[26], 0.5);
+    }
+
+    #[test]
+    fn endless_silence_returns() {
+        let mut source = Zero::<f32>::new(2, 1000).trim_silence(-40.0);
+        source.set_max_silence(Duration::from_millis(100));
+        assert_eq!(source.by_ref().take(4).collect::<Vec<_>>(), [0.0; 4]);
+    }
+
+    #[test]
+    fn silent_source_is_empty() {
+        let source = SamplesBuffer::new(2, 44100, vec![0i16; 10]);
+        assert_eq!(source.trim_silence(-40.0).count(), 0);
+    }
+}
@@ -5,8 +5,12 @@ use cpal;
 
 use conversions::ChannelCountConverter;
 use conversions::DataConverter;
+use conversions::ResampleQuality;
+use conversions::Resampler;
+use conversions::Resampling;
 use conversions::SampleRateConverter;
 
+use source::SeekError;
 use Sample;
 use Source;
 
@@ -25,6 +29,8 @@ where
     inner: Option<DataConverter<ChannelCountConverter<SampleRateConverter<Take<I>>>, D>>,
     target_channels: u16,
     target_sample_rate: u32,
+    quality: ResampleQuality,
+    resampler: Option<Box<dyn Resampler>>,
     total_duration: Option<Duration>,
 }
 
@@ -37,21 +43,84 @@ where
     #[inline]
     pub fn new(
         input: I, target_channels: u16, target_sample_rate: u32,
+    ) -> UniformSourceIterator<I, D> {
+        UniformSourceIterator::with_quality(
+            input,
+            target_channels,
+            target_sample_rate,
+            ResampleQuality::Linear,
+        )
+    }
+
+    /// Builds an iterator that converts the sample rate with the given algorithm.
+    #[inline]
+    pub fn with_quality(
+        input: I, target_channels: u16, target_sample_rate: u32, quality: ResampleQuality,
+    ) -> UniformSourceIterator<I, D> {
+        UniformSourceIterator::build(input, target_channels, target_sample_rate, quality, None)
+    }
+
+    /// Builds an iterator that converts the sample rate with the given `Resampler`.
+    #[inline]
+    pub fn with_resampler(
+        input: I, target_channels: u16, target_sample_rate: u32, resampler: Box<dyn Resampler>,
+    ) -> UniformSourceIterator<I, D> {
+        UniformSourceIterator::build(
+            input,
+            target_channels,
+            target_sample_rate,
+            ResampleQuality::Linear,
+            Some(resampler),
+        )
+    }
+
+    #[inline]
+    fn build(
+        input: I, target_channels: u16, target_sample_rate: u32, quality: ResampleQuality,
+        resampler: Option<Box<dyn Resampler>>,
     ) -> UniformSourceIterator<I, D> {
         let total_duration = input.total_duration();
-        let input = UniformSourceIterator::bootstrap(input, target_channels, target_sample_rate);
+        let input = UniformSourceIterator::bootstrap(
+            input,
+            target_channels,
+            target_sample_rate,
+            quality,
+            resampler.as_deref(),
+            None,
+        );
 
         UniformSourceIterator {
             inner: Some(input),
             target_channels: target_channels,
             target_sample_rate: target_sample_rate,
+            quality,
+            resampler,
             total_duration: total_duration,
         }
     }
 
+    /// Returns a reference to the inner source.
+    #[inline]
+    pub fn inner(&self) -> &I {
+        &self.inner.as_ref().unwrap().inner().inner().inner().iter
+    }
+
+    /// Returns a mutable reference to the inner source.
+    #[inline]
+    pub fn inner_mut(&mut self) -> &mut I {
+        &mut self.inner.as_mut().unwrap().inner_mut().inner_mut().inner_mut().iter
+    }
+
+    /// Returns the inner source.
+    #[inline]
+    pub fn into_inner(self) -> I {
+        self.inner.unwrap().into_inner().into_inner().into_inner().iter
+    }
+
     #[inline]
     fn bootstrap(
-        input: I, target_channels: u16, target_sample_rate: u32,
+        input: I, target_channels: u16, target_sample_rate: u32, quality: ResampleQuality,
+        resampler: Option<&dyn Resampler>, previous: Option<Resampling<I::Item>>,
     ) -> DataConverter<ChannelCountConverter<SampleRateConverter<Take<I>>>, D> {
         let frame_len = input.current_frame_len();
 
@@ -62,12 +131,33 @@ where
             iter: input,
             n: frame_len,
         };
-        let input = SampleRateConverter::new(
-            input,
-            cpal::SampleRate(from_sample_rate),
-            cpal::SampleRate(target_sample_rate),
-            from_channels,
-        );
+        let from_sample_rate = cpal::SampleRate(from_sample_rate);
+        let target_sample_rate = cpal::SampleRate(target_sample_rate);
+        let mut input = match (previous, resampler) {
+            (Some(previous), _) => SampleRateConverter::with_resampling(
+                input,
+                from_sample_rate,
+                target_sample_rate,
+                from_channels,
+                previous,
+            ),
+            (None, Some(resampler)) => SampleRateConverter::with_resampler(
+                input,
+                from_sample_rate,
+                target_sample_rate,
+                from_channels,
+                resampler.box_clone(),
+            ),
+            (None, None) => SampleRateConverter::with_quality(
+                input,
+                from_sample_rate,
+                target_sample_rate,
+                from_channels,
+                quality,
+            ),
+        };
+        // The interpolation of the end of a frame needs the start of the next one.
+        input.set_hold(frame_len.is_some());
         let input = ChannelCountConverter::new(input, from_channels, target_channels);
         let input = DataConverter::new(input);
 
@@ -89,16 +179,21 @@ where
             return Some(value);
         }
 
-        let input = self.inner
+        let (input, resampling) = self.inner
             .take()
             .unwrap()
             .into_inner()
             .into_inner()
-            .into_inner()
-            .iter;
+            .into_parts();
 
-        let mut input =
-            UniformSourceIterator::bootstrap(input, self.target_channels, self.target_sample_rate);
+        let mut input = UniformSourceIterator::bootstrap(
+            input.iter,
+            self.target_channels,
+            self.target_sample_rate,
+            self.quality,
+            self.resampler.as_deref(),
+            resampling,
+        );
 
         let value = input.next();
         self.inner = Some(input);
@@ -136,6 +231,28 @@ where
     fn total_duration(&self) -> Option<Duration> {
         self.total_duration
     }
+
+    #[inline]
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        let mut input = self.inner
+            .take()
+            .unwrap()
+            .into_inner()
+            .into_inner()
+            .into_inner()
+            .iter;
+
+        let result = input.try_seek(pos);
+        self.inner = Some(UniformSourceIterator::bootstrap(
+            input,
+            self.target_channels,
+            self.target_sample_rate,
+            self.quality,
+            self.resampler.as_deref(),
+            None,
+        ));
+        result
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -188,3 +305,83 @@ where
     I: ExactSizeIterator,
 {
 }
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::PI;
+
+    use buffer::SamplesBuffer;
+    use conversions::{ResampleQuality, Resampler};
+    use source::{from_iter, UniformSourceIterator};
+
+    // Repeats each frame when the sample rate is multiplied by an integer.
+    #[derive(Clone)]
+    struct Repeat {
+        times: usize,
+        channels: usize,
+    }
+
+    impl Resampler for Repeat {
+        fn reset(&mut self, from: u32, to: u32, channels: u16) {
+            self.times = (to / from) as usize;
+            self.channels = channels as usize;
+        }
+
+        fn process(&mut self, input: &[f32], output: &mut Vec<f32>) {
+            for frame in input.chunks(self.channels) {
+                for _ in 0 .. self.times {
+                    output.extend_from_slice(frame);
+                }
+            }
+        }
+
+        fn flush(&mut self, _: &mut Vec<f32>) {}
+
+        fn box_clone(&self) -> Box<dyn Resampler> {
+            Box::new(self.clone())
+        }
+    }
+
+    #[test]
+    fn custom_resampler() {
+        let frames = vec![
+            SamplesBuffer::new(2, 1000, vec![0.1f32, 0.2]),
+            SamplesBuffer::new(2, 1000, vec![0.3, 0.4, 0.5, 0.6]),
+        ];
+        let resampler = Box::new(Repeat { times: 0, channels: 0 });
+        let source = UniformSourceIterator::<_, f32>::with_resampler(
+            from_iter(frames),
+            2,
+            2000,
+            resampler,
+        );
+        assert_eq!(
+            source.collect::<Vec<_>>(),
+            [0.1, 0.2, 0.1, 0.2, 0.3, 0.4, 0.3, 0.4, 0.5, 0.6, 0.5, 0.6]
+        );
+    }
+
+    #[test]
+    fn sinc_is_continuous_across_frames() {
+        let wave = (0 .. 4410)
+            .map(|i| (2.0 * PI * 1000.0 * i as f32 / 44100.0).sin())
+            .collect::<Vec<_>>();
+        let whole = SamplesBuffer::new(1, 44100, wave.clone());
+        let frames = wave
+            .chunks(100)
+            .map(|chunk| SamplesBuffer::new(1, 44100, chunk.to_vec()))
+            .collect::<Vec<_>>();
+
+        let quality = ResampleQuality::Medium;
+        let whole = UniformSourceIterator::<_, f32>::with_quality(whole, 1, 48000, quality);
+        let frames = from_iter(frames);
+        let frames = UniformSourceIterator::<_, f32>::with_quality(frames, 1, 48000, quality);
+        let whole = whole.collect::<Vec<_>>();
+        let frames = frames.collect::<Vec<_>>();
+
+        assert_eq!(whole.len(), frames.len());
+        for (a, b) in whole.iter().zip(&frames) {
+            assert!((a - b).abs() < 1e-4, "{} {}", a, b);
+        }
+    }
+}
@@ -0,0 +1,106 @@
+use std::time::Duration;
+use Source;
+
+/// An infinite source that loops over a single cycle of a user-supplied waveform.
+///
+/// The table is played at the requested frequency, and the samples that fall between two entries
+/// of the table are linearly interpolated.
+///
+/// Always has a rate of 48kHz and one channel.
+#[derive(Clone, Debug)]
+pub struct WavetableOscillator {
+    table: Vec<f32>,
+    // Increment of `position` per sample.
+    step: f32,
+    // Position in the table, between 0 and its length.
+    position: f32,
+}
+
+impl WavetableOscillator {
+    /// Builds an oscillator that plays one cycle of `table` per period of `freq`.
+    ///
+    /// # Panic
+    ///
+    /// Panics if the table is empty.
+    #[inline]
+    pub fn new<T>(table: T, freq: f32) -> WavetableOscillator
+    where
+        T: Into<Vec<f32>>,
+    {
+        let table = table.into();
+        assert!(!table.is_empty());
+
+        let mut oscillator = WavetableOscillator {
+            table,
+            step: 0.0,
+            position: 0.0,
+        };
+        oscillator.set_frequency(freq);
+        oscillator
+    }
+
+    /// Modifies the frequency of the oscillator. The phase is preserved.
+    #[inline]
+    pub fn set_frequency(&mut self, freq: f32) {
+        self.step = freq * self.table.len() as f32 / 48000.0;
+    }
+}
+
+impl Iterator for WavetableOscillator {
+    type Item = f32;
+
+    #[inline]
+    fn next(&mut self) -> Option<f32> {
+        let len = self.table.len();
+        let index = self.position as usize;
+        let fract = self.position - index as f32;
+        let current = self.table[index % len];
+        let next = self.table[(index + 1) % len];
+
+        self.position = (self.position + self.step) % len as f32;
+        Some(current + (next - current) * fract)
+    }
+}
+
+impl Source for WavetableOscillator {
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        None
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        1
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        48000
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use source::WavetableOscillator;
+
+    #[test]
+    fn interpolates() {
+        // 4 entries at 6kHz means half an entry per sample.
+        let oscillator = WavetableOscillator::new(vec![0.0, 1.0, 0.0, -1.0], 6000.0);
+        assert_eq!(
+            oscillator.take(10).collect::<Vec<_>>(),
+            [0.0, 0.5, 1.0, 0.5, 0.0, -0.5, -1.0, -0.5, 0.0, 0.5]
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn panic_if_empty() {
+        WavetableOscillator::new(Vec::new(), 440.0);
+    }
+}
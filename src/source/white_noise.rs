@@ -0,0 +1,86 @@
+use std::time::Duration;
+
+use conversions::Random;
+use Source;
+
+/// An infinite source that produces white noise, uniformly distributed between `-1.0` and `1.0`.
+///
+/// Always has one channel.
+#[derive(Clone, Debug)]
+pub struct WhiteNoise {
+    sample_rate: u32,
+    random: Random,
+}
+
+impl WhiteNoise {
+    /// Builds a new white noise generator with a random seed.
+    #[inline]
+    pub fn new(sample_rate: u32) -> WhiteNoise {
+        WhiteNoise {
+            sample_rate,
+            random: Random::new(),
+        }
+    }
+
+    /// Builds a new white noise generator that always produces the same samples for a given
+    /// seed.
+    #[inline]
+    pub fn with_seed(sample_rate: u32, seed: u64) -> WhiteNoise {
+        WhiteNoise {
+            sample_rate,
+            random: Random::with_seed(seed),
+        }
+    }
+}
+
+impl Iterator for WhiteNoise {
+    type Item = f32;
+
+    #[inline]
+    fn next(&mut self) -> Option<f32> {
+        Some(self.random.signed())
+    }
+}
+
+impl Source for WhiteNoise {
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        None
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        1
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use source::WhiteNoise;
+
+    #[test]
+    fn range_and_mean() {
+        let samples = WhiteNoise::with_seed(48000, 1).take(48000).collect::<Vec<_>>();
+
+        assert!(samples.iter().all(|s| *s >= -1.0 && *s < 1.0));
+        let mean = samples.iter().sum::<f32>() / samples.len() as f32;
+        assert!(mean.abs() < 0.02);
+    }
+
+    #[test]
+    fn seeded() {
+        let a = WhiteNoise::with_seed(48000, 42).take(16).collect::<Vec<_>>();
+        let b = WhiteNoise::with_seed(48000, 42).take(16).collect::<Vec<_>>();
+        assert_eq!(a, b);
+    }
+}
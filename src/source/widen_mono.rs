@@ -0,0 +1,201 @@
+use std::time::Duration;
+
+use source::SeekError;
+use Sample;
+use Source;
+
+/// Delay of the comb filters, in seconds.
+const DELAY_SECS: f32 = 0.012;
+
+/// Internal function that builds a `WidenMono` object.
+pub fn widen_mono<I>(input: I) -> WidenMono<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    WidenMono {
+        input,
+        width: 0.5,
+        sample_rate: 0,
+        delay: Vec::new(),
+        delay_pos: 0,
+        pending: None,
+    }
+}
+
+/// Filter that turns a mono source into a stereo one.
+///
+/// A delayed copy of the sound is added to the left channel and subtracted from the right one.
+/// These complementary comb filters give each frequency a slightly different position, and the
+/// delayed copies cancel out when the two channels are mixed back to mono. Frames that don't
+/// have one channel are left untouched.
+#[derive(Clone, Debug)]
+pub struct WidenMono<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    input: I,
+    width: f32,
+    // Sample rate that the delay line was built for.
+    sample_rate: u32,
+    delay: Vec<f32>,
+    delay_pos: usize,
+    // Right sample of the current frame.
+    pending: Option<I::Item>,
+}
+
+impl<I> WidenMono<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    /// Modifies the gain of the delayed copy, between `0.0` for a mono image and `1.0` for the
+    /// widest one. The default is `0.5`.
+    #[inline]
+    pub fn set_width(&mut self, width: f32) {
+        self.width = width.clamp(0.0, 1.0);
+    }
+
+    /// Returns a reference to the inner source.
+    #[inline]
+    pub fn inner(&self) -> &I {
+        &self.input
+    }
+
+    /// Returns a mutable reference to the inner source.
+    #[inline]
+    pub fn inner_mut(&mut self) -> &mut I {
+        &mut self.input
+    }
+
+    /// Returns the inner source.
+    #[inline]
+    pub fn into_inner(self) -> I {
+        self.input
+    }
+}
+
+impl<I> Iterator for WidenMono<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    type Item = I::Item;
+
+    #[inline]
+    fn next(&mut self) -> Option<I::Item> {
+        if let Some(value) = self.pending.take() {
+            return Some(value);
+        }
+        if self.input.channels() != 1 {
+            return self.input.next();
+        }
+
+        let sample_rate = self.input.sample_rate();
+        if sample_rate != self.sample_rate {
+            self.sample_rate = sample_rate;
+            let len = (DELAY_SECS * sample_rate as f32) as usize;
+            self.delay = vec![0.0; len.max(1)];
+            self.delay_pos = 0;
+        }
+
+        let value = self.input.next()?.to_f32();
+        let delayed = self.delay[self.delay_pos] * self.width;
+        self.delay[self.delay_pos] = value;
+        self.delay_pos = (self.delay_pos + 1) % self.delay.len();
+
+        // Scaled so that the channels don't get louder than the input.
+        let scale = 1.0 / (1.0 + self.width);
+        self.pending = Some(Sample::from_sample(&((value - delayed) * scale)));
+        Some(Sample::from_sample(&((value + delayed) * scale)))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let pending = self.pending.is_some() as usize;
+        let factor = if self.input.channels() == 1 { 2 } else { 1 };
+        let (min, max) = self.input.size_hint();
+        (min * factor + pending, max.map(|max| max * factor + pending))
+    }
+}
+
+impl<I> ExactSizeIterator for WidenMono<I>
+where
+    I: Source + ExactSizeIterator,
+    I::Item: Sample,
+{
+}
+
+impl<I> Source for WidenMono<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        if self.pending.is_some() {
+            return Some(1);
+        }
+        let factor = if self.input.channels() == 1 { 2 } else { 1 };
+        self.input.current_frame_len().map(|len| len * factor)
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        if self.pending.is_some() || self.input.channels() == 1 {
+            2
+        } else {
+            self.input.channels()
+        }
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        self.input.sample_rate()
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        self.input.total_duration()
+    }
+
+    #[inline]
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        self.input.try_seek(pos)?;
+        self.pending = None;
+        for value in &mut self.delay {
+            *value = 0.0;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use buffer::SamplesBuffer;
+    use source::Source;
+
+    #[test]
+    fn mono_compatible() {
+        let data = (0 .. 2000).map(|i| (i as f32 * 0.1).sin()).collect::<Vec<_>>();
+        let source = SamplesBuffer::new(1, 44100, data.clone()).widen_mono();
+        assert_eq!(source.channels(), 2);
+        let output = source.collect::<Vec<_>>();
+        assert_eq!(output.len(), 4000);
+
+        let mut differs = false;
+        for (frame, &value) in output.chunks(2).zip(&data) {
+            // The two channels add up to the input, scaled by `1 / (1 + width)`.
+            assert!((frame[0] + frame[1] - value * 2.0 / 1.5).abs() < 1e-5);
+            differs |= (frame[0] - frame[1]).abs() > 0.1;
+        }
+        assert!(differs);
+    }
+
+    #[test]
+    fn stereo_is_untouched() {
+        let source = SamplesBuffer::new(2, 44100, vec![0.5f32, 0.25]);
+        assert_eq!(source.widen_mono().collect::<Vec<_>>(), [0.5, 0.25]);
+    }
+}
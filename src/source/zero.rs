@@ -1,5 +1,6 @@
 use std::marker::PhantomData;
 use std::time::Duration;
+use source::SeekError;
 use Sample;
 use Source;
 
@@ -57,4 +58,11 @@ where
     fn total_duration(&self) -> Option<Duration> {
         None
     }
+
+    #[inline]
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        // Silence sounds the same everywhere.
+        let _ = pos;
+        Ok(())
+    }
 }
@@ -1,8 +1,9 @@
-use source::Spatial;
+use source::{DistanceAttenuation, SoundCone, Spatial};
 use std::f32;
 use std::fmt::Debug;
 use std::sync::{Arc, Mutex};
 use std::time::Duration;
+use Completion;
 use Device;
 use Sample;
 use Sink;
@@ -17,8 +18,84 @@ struct SoundPositions {
     emitter_position: [f32; 3],
     left_ear: [f32; 3],
     right_ear: [f32; 3],
+    attenuation: DistanceAttenuation,
+    occlusion: f32,
+    emitter_velocity: [f32; 3],
+    listener_velocity: [f32; 3],
+    emitter_orientation: [f32; 3],
+    cone: SoundCone,
+    speed_of_sound: f32,
 }
 
+impl SoundPositions {
+    // Returns the position of the listener, between the ears.
+    fn listener_position(&self) -> [f32; 3] {
+        let mut position = [0.0; 3];
+        for (i, value) in position.iter_mut().enumerate() {
+            *value = (self.left_ear[i] + self.right_ear[i]) / 2.0;
+        }
+        position
+    }
+
+    // Moves the ears so that the listener is at `position`, without turning.
+    fn move_listener(&mut self, position: [f32; 3]) {
+        let listener = self.listener_position();
+        for i in 0 .. 3 {
+            self.left_ear[i] += position[i] - listener[i];
+            self.right_ear[i] += position[i] - listener[i];
+        }
+    }
+
+    // Turns the ears around the listener, so that it faces `forward` with `up` above its head.
+    fn orient_listener(&mut self, forward: [f32; 3], up: [f32; 3]) {
+        // The cross product of `forward` and `up` points to the right ear.
+        let right = [
+            forward[1] * up[2] - forward[2] * up[1],
+            forward[2] * up[0] - forward[0] * up[2],
+            forward[0] * up[1] - forward[1] * up[0],
+        ];
+        let length = right.iter().map(|v| v * v).sum::<f32>().sqrt();
+        if length == 0.0 {
+            return;
+        }
+
+        let listener = self.listener_position();
+        let half_width = (0 .. 3)
+            .map(|i| (self.right_ear[i] - self.left_ear[i]).powi(2))
+            .sum::<f32>()
+            .sqrt()
+            / 2.0;
+        for i in 0 .. 3 {
+            let offset = right[i] / length * half_width;
+            self.left_ear[i] = listener[i] - offset;
+            self.right_ear[i] = listener[i] + offset;
+        }
+    }
+
+    // Returns the velocity of the emitter away from the listener, as if the speed of sound was
+    // `SPEED_OF_SOUND`.
+    fn doppler_velocity(&self) -> f32 {
+        let mut direction = [0.0; 3];
+        let listener = self.listener_position();
+        for (i, value) in direction.iter_mut().enumerate() {
+            *value = self.emitter_position[i] - listener[i];
+        }
+        let distance = direction.iter().map(|v| v * v).sum::<f32>().sqrt();
+        if distance == 0.0 {
+            return 0.0;
+        }
+
+        let velocity = (0 .. 3)
+            .map(|i| (self.emitter_velocity[i] - self.listener_velocity[i]) * direction[i])
+            .sum::<f32>()
+            / distance;
+        velocity * SPEED_OF_SOUND / self.speed_of_sound
+    }
+}
+
+// Speed of sound in the air, in meters per second.
+const SPEED_OF_SOUND: f32 = 343.0;
+
 impl SpatialSink {
     /// Builds a new `SpatialSink`.
     #[inline]
@@ -31,6 +108,13 @@ impl SpatialSink {
                 emitter_position,
                 left_ear,
                 right_ear,
+                attenuation: Default::default(),
+                occlusion: 0.0,
+                emitter_velocity: [0.0; 3],
+                listener_velocity: [0.0; 3],
+                emitter_orientation: [0.0; 3],
+                cone: Default::default(),
+                speed_of_sound: SPEED_OF_SOUND,
             })),
         }
     }
@@ -50,25 +134,100 @@ impl SpatialSink {
         self.positions.lock().unwrap().right_ear = pos;
     }
 
+    /// Moves both ears so that the listener, between them, is at the given position. The
+    /// direction the listener faces doesn't change.
+    pub fn set_listener_position(&self, pos: [f32; 3]) {
+        self.positions.lock().unwrap().move_listener(pos);
+    }
+
+    /// Turns the listener so that it faces `forward`, with `up` pointing above its head. The
+    /// ears turn around the middle between them and keep their distance.
+    ///
+    /// Nothing changes if `forward` and `up` are parallel.
+    pub fn set_listener_orientation(&self, forward: [f32; 3], up: [f32; 3]) {
+        self.positions.lock().unwrap().orient_listener(forward, up);
+    }
+
+    /// Changes how the volume decreases with the distance between the emitter and the ears.
+    pub fn set_attenuation(&self, attenuation: DistanceAttenuation) {
+        self.positions.lock().unwrap().attenuation = attenuation;
+    }
+
+    /// Sets the velocity of the sound emitter, in units per second.
+    ///
+    /// The velocities of the emitter and of the listener shift the pitch of the sound, as the
+    /// Doppler effect does.
+    pub fn set_emitter_velocity(&self, velocity: [f32; 3]) {
+        self.positions.lock().unwrap().emitter_velocity = velocity;
+    }
+
+    /// Sets the velocity of the listener, in units per second.
+    pub fn set_listener_velocity(&self, velocity: [f32; 3]) {
+        self.positions.lock().unwrap().listener_velocity = velocity;
+    }
+
+    /// Sets the speed of sound, in units per second. The default is `343.0`, which is the speed
+    /// of sound in the air when a unit is a meter.
+    pub fn set_speed_of_sound(&self, speed_of_sound: f32) {
+        self.positions.lock().unwrap().speed_of_sound = speed_of_sound;
+    }
+
+    /// Sets the direction the sound emitter is facing, which is used with its cone.
+    ///
+    /// A null vector, the default, means that the emitter is as loud in every direction. The
+    /// orientation of the listener is given by the positions of the ears, which can be turned
+    /// with `set_listener_orientation`.
+    pub fn set_emitter_orientation(&self, orientation: [f32; 3]) {
+        self.positions.lock().unwrap().emitter_orientation = orientation;
+    }
+
+    /// Changes how the volume depends on the direction the sound emitter is facing.
+    pub fn set_cone(&self, cone: SoundCone) {
+        self.positions.lock().unwrap().cone = cone;
+    }
+
+    /// Sets how much the sound is occluded, for example by a wall between the emitter and the
+    /// listener.
+    ///
+    /// The value is clamped between `0.0`, which leaves the sound untouched, and `1.0`, which
+    /// muffles it with a low-pass filter and lowers its volume.
+    pub fn set_occlusion(&self, occlusion: f32) {
+        self.positions.lock().unwrap().occlusion = occlusion;
+    }
+
     /// Appends a sound to the queue of sounds to play.
+    ///
+    /// The returned handle completes when this sound ends. See `Sink::append`.
     #[inline]
-    pub fn append<S>(&self, source: S)
+    pub fn append<S>(&self, source: S) -> Completion
     where
         S: Source + Send + 'static,
         S::Item: Sample + Send + Debug,
     {
         let positions = self.positions.clone();
         let pos_lock = self.positions.lock().unwrap();
-        let source = Spatial::new(
-            source,
+        let doppler = source.doppler(pos_lock.doppler_velocity(), SPEED_OF_SOUND);
+        let doppler_handle = doppler.handle();
+        let mut spatial = Spatial::new(
+            doppler,
             pos_lock.emitter_position,
             pos_lock.left_ear,
             pos_lock.right_ear,
-        ).periodic_access(Duration::from_millis(10), move |i| {
+        );
+        spatial.set_attenuation(pos_lock.attenuation);
+        spatial.set_occlusion(pos_lock.occlusion);
+        spatial.set_emitter_orientation(pos_lock.emitter_orientation);
+        spatial.set_cone(pos_lock.cone);
+        let source = spatial.periodic_access(Duration::from_millis(10), move |i| {
             let pos = positions.lock().unwrap();
+            i.set_attenuation(pos.attenuation);
+            i.set_occlusion(pos.occlusion);
+            i.set_emitter_orientation(pos.emitter_orientation);
+            i.set_cone(pos.cone);
+            doppler_handle.set_velocity(pos.doppler_velocity());
             i.set_positions(pos.emitter_position, pos.left_ear, pos.right_ear);
         });
-        self.sink.append(source);
+        self.sink.append(source)
     }
 
     // Gets the volume of the sound.
@@ -89,6 +248,23 @@ impl SpatialSink {
         self.sink.set_volume(value);
     }
 
+    /// Gets the volume of the sound, in decibels.
+    ///
+    /// The value `0.0` is the "normal" volume (unfiltered input).
+    #[inline]
+    pub fn volume_db(&self) -> f32 {
+        self.sink.volume_db()
+    }
+
+    /// Changes the volume of the sound, in decibels.
+    ///
+    /// The value `0.0` is the "normal" volume (unfiltered input), and every `-6.0` approximately
+    /// halves the amplitude.
+    #[inline]
+    pub fn set_volume_db(&self, decibels: f32) {
+        self.sink.set_volume_db(decibels);
+    }
+
     /// Resumes playback of a paused sound.
     ///
     /// No effect if not paused.
@@ -137,3 +313,36 @@ impl SpatialSink {
         self.sink.empty()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use source::SoundCone;
+    use spatial_sink::SoundPositions;
+
+    #[test]
+    fn move_and_orient_listener() {
+        let mut positions = SoundPositions {
+            emitter_position: [0.0; 3],
+            left_ear: [-1.0, 0.0, 0.0],
+            right_ear: [1.0, 0.0, 0.0],
+            attenuation: Default::default(),
+            occlusion: 0.0,
+            emitter_velocity: [0.0; 3],
+            listener_velocity: [0.0; 3],
+            emitter_orientation: [0.0; 3],
+            cone: SoundCone::default(),
+            speed_of_sound: 343.0,
+        };
+        positions.move_listener([0.0, 0.0, 5.0]);
+        assert_eq!(positions.left_ear, [-1.0, 0.0, 5.0]);
+        assert_eq!(positions.right_ear, [1.0, 0.0, 5.0]);
+
+        // Facing right, so the right ear is in front.
+        positions.orient_listener([1.0, 0.0, 0.0], [0.0, 1.0, 0.0]);
+        assert_eq!(positions.left_ear, [0.0, 0.0, 4.0]);
+        assert_eq!(positions.right_ear, [0.0, 0.0, 6.0]);
+
+        positions.orient_listener([0.0, 2.0, 0.0], [0.0, 1.0, 0.0]);
+        assert_eq!(positions.right_ear, [0.0, 0.0, 6.0]);
+    }
+}
@@ -10,9 +10,12 @@
 //! ```
 //!
 
+use std::cmp;
 use std::time::Duration;
 use std::slice::Iter as SliceIter;
 
+use conversions::duration_to_frames;
+use source::SeekError;
 use source::Source;
 
 use Sample;
@@ -24,6 +27,8 @@ where
     S: 'static,
 {
     data: SliceIter<'static, S>,
+    // All the samples of the buffer, used when seeking.
+    samples: &'static [S],
     channels: u16,
     sample_rate: u32,
     duration: Duration,
@@ -56,6 +61,7 @@ where
 
         StaticSamplesBuffer {
             data: data.iter(),
+            samples: data,
             channels: channels,
             sample_rate: sample_rate,
             duration: duration,
@@ -86,6 +92,14 @@ where
     fn total_duration(&self) -> Option<Duration> {
         Some(self.duration)
     }
+
+    #[inline]
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        let frame = duration_to_frames(pos, self.sample_rate);
+        let index = cmp::min(frame * self.channels as u64, self.samples.len() as u64);
+        self.data = self.samples[index as usize ..].iter();
+        Ok(())
+    }
 }
 
 impl<S> Iterator for StaticSamplesBuffer<S>
@@ -107,6 +121,8 @@ where
 
 #[cfg(test)]
 mod tests {
+    use std::time::Duration;
+
     use static_buffer::StaticSamplesBuffer;
     use source::Source;
 
@@ -135,6 +151,18 @@ mod tests {
         assert_eq!(dur.subsec_nanos(), 500_000_000);
     }
 
+    #[test]
+    fn seek() {
+        let mut buf = StaticSamplesBuffer::new(2, 2, &[1i16, 2, 3, 4, 5, 6]);
+        buf.next();
+        buf.try_seek(Duration::from_millis(500)).unwrap();
+        assert_eq!(buf.next(), Some(3));
+        buf.try_seek(Duration::from_secs(0)).unwrap();
+        assert_eq!(buf.next(), Some(1));
+        buf.try_seek(Duration::from_secs(10)).unwrap();
+        assert_eq!(buf.next(), None);
+    }
+
     #[test]
     fn iteration() {
         let mut buf = StaticSamplesBuffer::new(1, 44100, &[1i16, 2, 3, 4, 5, 6]);
@@ -0,0 +1,212 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use conversions::frames_to_ns;
+use dynamic_mixer::DynamicMixerController;
+use keep_alive::{default_format, keep_alive_mixer, KeepAlive, KeepAliveMixer};
+use play_raw;
+use Device;
+use NullOutput;
+use Sink;
+use Source;
+
+/// Value of `SyncShared::start_frame` when the start isn't scheduled.
+const NOT_SCHEDULED: u64 = u64::MAX;
+
+/// Sinks that start playing on exactly the same sample, for example the stems of a piece of
+/// music.
+///
+/// The sinks are built with `SyncGroup::sink`, and nothing is read from them until the group
+/// starts. The group has its own clock, which counts the time played since the group was built,
+/// silence included.
+pub struct SyncGroup {
+    mixer: Arc<DynamicMixerController<f32>>,
+    shared: Arc<SyncShared>,
+    // Ends the output when the group is dropped.
+    _keep_alive: KeepAlive,
+}
+
+struct SyncShared {
+    sample_rate: u32,
+    // Number of frames played since the group was built.
+    frames_played: AtomicU64,
+    // Frame of the clock of the group on which the sinks start.
+    start_frame: AtomicU64,
+}
+
+impl SyncGroup {
+    /// Builds a new `SyncGroup` that plays on a device.
+    pub fn new(device: &Device) -> SyncGroup {
+        let (channels, sample_rate) = default_format(device);
+        let (group, output) = SyncGroup::new_idle(channels, sample_rate);
+        play_raw(device, output);
+        group
+    }
+
+    /// Builds a new `SyncGroup` that plays on an output without audio hardware.
+    pub fn new_null(output: &NullOutput) -> SyncGroup {
+        let (group, group_output) = SyncGroup::new_idle(output.channels(), output.sample_rate());
+        output.play_raw(group_output);
+        group
+    }
+
+    /// Builds a new `SyncGroup` whose sinks are mixed into the returned source, with the given
+    /// channels and sample rate.
+    ///
+    /// The source plays silence until the group starts, and ends when the group is dropped.
+    pub fn new_idle(channels: u16, sample_rate: u32) -> (SyncGroup, SyncGroupOutput) {
+        let (mixer_tx, keep_alive, mixer_rx) = keep_alive_mixer(channels, sample_rate);
+        let shared = Arc::new(SyncShared {
+            sample_rate,
+            frames_played: AtomicU64::new(0),
+            start_frame: AtomicU64::new(NOT_SCHEDULED),
+        });
+        let group = SyncGroup {
+            mixer: mixer_tx,
+            shared: shared.clone(),
+            _keep_alive: keep_alive,
+        };
+        let output = SyncGroupOutput {
+            mixer: mixer_rx,
+            shared,
+            current_channel: 0,
+            started: false,
+        };
+        (group, output)
+    }
+
+    /// Builds a new `Sink` that belongs to the group.
+    ///
+    /// Its sounds start with the group, or right away if the group has already started.
+    #[inline]
+    pub fn sink(&self) -> Sink {
+        let (sink, queue_rx) = Sink::new_idle();
+        self.mixer.add(queue_rx);
+        sink
+    }
+
+    /// Returns the time played since the group was built, silence included.
+    ///
+    /// The value is updated when the output reads the samples, so it is ahead of what is heard
+    /// by the latency of the output.
+    #[inline]
+    pub fn time(&self) -> Duration {
+        let frames = self.shared.frames_played.load(Ordering::SeqCst);
+        Duration::from_nanos(frames_to_ns(frames, self.shared.sample_rate))
+    }
+
+    /// Starts all the sinks at the given time of the clock of the group, or as soon as possible
+    /// if this time has passed.
+    #[inline]
+    pub fn start_at(&self, time: Duration) {
+        let frame = (time.as_secs_f64() * self.shared.sample_rate as f64).round() as u64;
+        self.shared.start_frame.store(frame, Ordering::SeqCst);
+    }
+
+    /// Starts all the sinks as soon as possible.
+    #[inline]
+    pub fn start(&self) {
+        self.shared.start_frame.store(0, Ordering::SeqCst);
+    }
+
+    /// Returns true if the sinks have started, or are scheduled to start.
+    #[inline]
+    pub fn is_started(&self) -> bool {
+        self.shared.start_frame.load(Ordering::SeqCst) != NOT_SCHEDULED
+    }
+}
+
+/// The mix of the sinks of a `SyncGroup`.
+pub struct SyncGroupOutput {
+    mixer: KeepAliveMixer,
+    shared: Arc<SyncShared>,
+    // Channel of the next sample returned by `next`.
+    current_channel: u16,
+    started: bool,
+}
+
+impl Iterator for SyncGroupOutput {
+    type Item = f32;
+
+    #[inline]
+    fn next(&mut self) -> Option<f32> {
+        if !self.mixer.is_alive() {
+            return None;
+        }
+
+        // The start is only checked at frame boundaries, so that all the channels of a frame
+        // start together.
+        if self.current_channel == 0 && !self.started {
+            let frame = self.shared.frames_played.load(Ordering::SeqCst);
+            self.started = frame >= self.shared.start_frame.load(Ordering::SeqCst);
+        }
+        let value = if self.started {
+            self.mixer.next()?
+        } else {
+            0.0
+        };
+
+        self.current_channel += 1;
+        if self.current_channel >= self.mixer.channels() {
+            self.current_channel = 0;
+            self.shared.frames_played.fetch_add(1, Ordering::SeqCst);
+        }
+        Some(value)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, None)
+    }
+}
+
+impl Source for SyncGroupOutput {
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        None
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        self.mixer.channels()
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        self.shared.sample_rate
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use buffer::SamplesBuffer;
+    use sync_group::SyncGroup;
+
+    #[test]
+    fn sinks_start_together() {
+        let (group, mut output) = SyncGroup::new_idle(1, 48000);
+        let first = group.sink();
+        let second = group.sink();
+        first.append(SamplesBuffer::new(1, 48000, vec![0.25f32, 0.25]));
+
+        assert_eq!(output.next(), Some(0.0));
+        second.append(SamplesBuffer::new(1, 48000, vec![0.5f32, 0.5]));
+        assert!(!group.is_started());
+        group.start_at(Duration::from_secs_f64(3.0 / 48000.0));
+        assert_eq!(output.next(), Some(0.0));
+        assert_eq!(output.next(), Some(0.0));
+        assert_eq!(group.time(), Duration::from_secs_f64(3.0 / 48000.0));
+
+        assert_eq!(output.next(), Some(0.75));
+        assert_eq!(output.next(), Some(0.75));
+        assert_eq!(output.next(), Some(0.0));
+    }
+}
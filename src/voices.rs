@@ -0,0 +1,390 @@
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use dynamic_mixer::DynamicMixerController;
+use keep_alive::{default_format, keep_alive_mixer, KeepAlive, KeepAliveMixer};
+use play_raw;
+use source::FadeStopHandle;
+use Device;
+use NullOutput;
+use Source;
+
+/// Duration of the fade out of a stolen voice, so that it doesn't click.
+const STEAL_FADE_MS: u64 = 5;
+
+/// Time constant of the decay of the level of a voice, used to find the quietest one.
+const LEVEL_DECAY_SECS: f32 = 0.1;
+
+/// Which voice is stopped when a sound is played while all the voices of `Voices` are busy.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum StealPolicy {
+    /// The new sound isn't played.
+    None,
+    /// The voice that started playing first is stopped.
+    Oldest,
+    /// The voice whose level has been the lowest recently is stopped.
+    Quietest,
+    /// The voice with the lowest priority is stopped, or the oldest of them if several have the
+    /// same priority. The new sound isn't played if all the voices have a higher priority.
+    LowestPriority,
+}
+
+/// Plays sounds with a maximum number of simultaneous voices, for example for a category of
+/// sound effects that can be triggered many times in a row.
+///
+/// When all the voices are busy, a voice is stolen according to the `StealPolicy`: it fades out
+/// over 5 milliseconds and the new sound takes its place.
+pub struct Voices {
+    mixer: Arc<DynamicMixerController<f32>>,
+    state: Mutex<VoicesState>,
+    // Ends the output when `Voices` is dropped.
+    _keep_alive: KeepAlive,
+}
+
+struct VoicesState {
+    max_voices: usize,
+    policy: StealPolicy,
+    voices: Vec<VoiceEntry>,
+    // Incremented for each voice, to know which one is the oldest.
+    next_order: u64,
+}
+
+// A sound played by `Voices`.
+struct VoiceEntry {
+    order: u64,
+    priority: i32,
+    stop: FadeStopHandle,
+    shared: Arc<VoiceShared>,
+}
+
+impl VoiceEntry {
+    #[inline]
+    fn level(&self) -> f32 {
+        f32::from_bits(self.shared.level.load(Ordering::Relaxed))
+    }
+}
+
+// What the voice shares with `Voices` while it plays.
+struct VoiceShared {
+    // Recent peak level, stored as the bits of an `f32`.
+    level: AtomicU32,
+    finished: AtomicBool,
+}
+
+impl Voices {
+    /// Builds a new `Voices` that plays on a device.
+    pub fn new(device: &Device, max_voices: usize, policy: StealPolicy) -> Voices {
+        let (channels, sample_rate) = default_format(device);
+        let (voices, output) = Voices::new_idle(channels, sample_rate, max_voices, policy);
+        play_raw(device, output);
+        voices
+    }
+
+    /// Builds a new `Voices` that plays on an output without audio hardware.
+    pub fn new_null(output: &NullOutput, max_voices: usize, policy: StealPolicy) -> Voices {
+        let (voices, voices_output) =
+            Voices::new_idle(output.channels(), output.sample_rate(), max_voices, policy);
+        output.play_raw(voices_output);
+        voices
+    }
+
+    /// Builds a new `Voices` whose sounds are mixed into the returned source, with the given
+    /// channels and sample rate.
+    ///
+    /// The source plays silence when no voice plays, and ends when `Voices` is dropped.
+    pub fn new_idle(
+        channels: u16, sample_rate: u32, max_voices: usize, policy: StealPolicy,
+    ) -> (Voices, VoicesOutput) {
+        let (mixer_tx, keep_alive, mixer_rx) = keep_alive_mixer(channels, sample_rate);
+        let voices = Voices {
+            mixer: mixer_tx,
+            state: Mutex::new(VoicesState {
+                max_voices,
+                policy,
+                voices: Vec::new(),
+                next_order: 0,
+            }),
+            _keep_alive: keep_alive,
+        };
+        let output = VoicesOutput { mixer: mixer_rx };
+        (voices, output)
+    }
+
+    /// Plays a sound with the default priority of `0`.
+    ///
+    /// Returns false if no voice was available and none could be stolen.
+    #[inline]
+    pub fn play<S>(&self, source: S) -> bool
+    where
+        S: Source<Item = f32> + Send + 'static,
+    {
+        self.play_with_priority(source, 0)
+    }
+
+    /// Plays a sound with a priority, used by `StealPolicy::LowestPriority`.
+    ///
+    /// Returns false if no voice was available and none could be stolen.
+    pub fn play_with_priority<S>(&self, source: S, priority: i32) -> bool
+    where
+        S: Source<Item = f32> + Send + 'static,
+    {
+        let mut state = self.state.lock().unwrap();
+        state
+            .voices
+            .retain(|voice| !voice.shared.finished.load(Ordering::Relaxed));
+
+        while state.voices.len() >= state.max_voices {
+            match state.victim(priority) {
+                Some(index) => {
+                    let victim = state.voices.remove(index);
+                    victim.stop.stop();
+                },
+                None => return false,
+            }
+        }
+
+        let samples_per_sec = source.sample_rate() as f32 * source.channels() as f32;
+        let shared = Arc::new(VoiceShared {
+            level: AtomicU32::new(0),
+            finished: AtomicBool::new(false),
+        });
+        let voice = Voice {
+            input: source,
+            shared: shared.clone(),
+            level: 0.0,
+            decay: (-1.0 / (LEVEL_DECAY_SECS * samples_per_sec)).exp(),
+        };
+        let voice = voice.stoppable_with_fade(Duration::from_millis(STEAL_FADE_MS));
+
+        let order = state.next_order;
+        state.next_order += 1;
+        state.voices.push(VoiceEntry {
+            order,
+            priority,
+            stop: voice.handle(),
+            shared,
+        });
+        self.mixer.add(voice);
+        true
+    }
+
+    /// Returns the number of voices currently playing.
+    #[inline]
+    pub fn active_voices(&self) -> usize {
+        let state = self.state.lock().unwrap();
+        state
+            .voices
+            .iter()
+            .filter(|voice| !voice.shared.finished.load(Ordering::Relaxed))
+            .count()
+    }
+
+    /// Changes the maximum number of simultaneous voices. The voices playing over the new
+    /// maximum keep playing until a sound is played, which steals enough of them.
+    #[inline]
+    pub fn set_max_voices(&self, max_voices: usize) {
+        self.state.lock().unwrap().max_voices = max_voices;
+    }
+
+    /// Changes the policy used when all the voices are busy.
+    #[inline]
+    pub fn set_policy(&self, policy: StealPolicy) {
+        self.state.lock().unwrap().policy = policy;
+    }
+
+    /// Stops all the voices, with a short fade out.
+    pub fn stop(&self) {
+        let mut state = self.state.lock().unwrap();
+        for voice in state.voices.drain(..) {
+            voice.stop.stop();
+        }
+    }
+}
+
+impl VoicesState {
+    // Returns the index of the voice to steal for a new sound with the given priority.
+    fn victim(&self, priority: i32) -> Option<usize> {
+        let voices = self.voices.iter().enumerate();
+        match self.policy {
+            StealPolicy::None => None,
+            StealPolicy::Oldest => voices.min_by_key(|&(_, voice)| voice.order),
+            StealPolicy::Quietest => voices.min_by(|&(_, a), &(_, b)| {
+                a.level()
+                    .partial_cmp(&b.level())
+                    .unwrap()
+                    .then(a.order.cmp(&b.order))
+            }),
+            StealPolicy::LowestPriority => voices
+                .filter(|&(_, voice)| voice.priority <= priority)
+                .min_by_key(|&(_, voice)| (voice.priority, voice.order)),
+        }
+        .map(|(index, _)| index)
+    }
+}
+
+/// The mix of the sounds played by `Voices`.
+pub struct VoicesOutput {
+    mixer: KeepAliveMixer,
+}
+
+impl Iterator for VoicesOutput {
+    type Item = f32;
+
+    #[inline]
+    fn next(&mut self) -> Option<f32> {
+        self.mixer.next()
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.mixer.size_hint()
+    }
+}
+
+impl Source for VoicesOutput {
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        self.mixer.current_frame_len()
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        self.mixer.channels()
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        self.mixer.sample_rate()
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        None
+    }
+}
+
+// A sound played by `Voices`, which publishes its level and when it ends.
+struct Voice<I> {
+    input: I,
+    shared: Arc<VoiceShared>,
+    level: f32,
+    // Factor applied to the level after each sample.
+    decay: f32,
+}
+
+impl<I> Iterator for Voice<I>
+where
+    I: Source<Item = f32>,
+{
+    type Item = f32;
+
+    #[inline]
+    fn next(&mut self) -> Option<f32> {
+        match self.input.next() {
+            Some(value) => {
+                self.level = (self.level * self.decay).max(value.abs());
+                self.shared
+                    .level
+                    .store(self.level.to_bits(), Ordering::Relaxed);
+                Some(value)
+            },
+            None => {
+                self.shared.finished.store(true, Ordering::Relaxed);
+                None
+            },
+        }
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.input.size_hint()
+    }
+}
+
+impl<I> Source for Voice<I>
+where
+    I: Source<Item = f32>,
+{
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        self.input.current_frame_len()
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        self.input.channels()
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        self.input.sample_rate()
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        self.input.total_duration()
+    }
+}
+
+impl<I> Drop for Voice<I> {
+    #[inline]
+    fn drop(&mut self) {
+        self.shared.finished.store(true, Ordering::Relaxed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use buffer::SamplesBuffer;
+    use voices::{StealPolicy, Voices};
+
+    fn sound(value: f32) -> SamplesBuffer<f32> {
+        SamplesBuffer::new(1, 1000, vec![value; 1000])
+    }
+
+    #[test]
+    fn rejects_over_the_limit() {
+        let (voices, _output) = Voices::new_idle(1, 1000, 2, StealPolicy::None);
+        assert!(voices.play(sound(0.1)));
+        assert!(voices.play(sound(0.1)));
+        assert!(!voices.play(sound(0.1)));
+        assert_eq!(voices.active_voices(), 2);
+    }
+
+    #[test]
+    fn steals_the_oldest() {
+        let (voices, mut output) = Voices::new_idle(1, 1000, 2, StealPolicy::Oldest);
+        voices.play(sound(0.1));
+        voices.play(sound(0.2));
+        output.next();
+        voices.play(sound(0.4));
+        assert_eq!(voices.active_voices(), 2);
+
+        // The first sound fades out in 5 milliseconds.
+        let mixed = output.by_ref().take(10).collect::<Vec<_>>();
+        assert!((mixed[9] - 0.6).abs() < 1e-4);
+    }
+
+    #[test]
+    fn steals_by_priority_and_level() {
+        let (voices, mut output) = Voices::new_idle(1, 1000, 2, StealPolicy::LowestPriority);
+        assert!(voices.play_with_priority(sound(0.1), 5));
+        assert!(voices.play_with_priority(sound(0.1), 1));
+        assert!(!voices.play_with_priority(sound(0.1), 0));
+        assert!(voices.play_with_priority(sound(0.1), 3));
+        output.next();
+
+        voices.stop();
+        output.by_ref().take(10).count();
+        assert_eq!(voices.active_voices(), 0);
+
+        voices.set_policy(StealPolicy::Quietest);
+        voices.play(sound(0.5));
+        voices.play(sound(0.1));
+        output.by_ref().take(10).count();
+        voices.play(sound(0.2));
+        let mixed = output.by_ref().take(20).collect::<Vec<_>>();
+        assert!((mixed[19] - 0.7).abs() < 1e-4);
+    }
+}
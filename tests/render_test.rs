@@ -0,0 +1,24 @@
+extern crate rodio;
+
+use std::time::Duration;
+
+use rodio::buffer::SamplesBuffer;
+use rodio::source::SineWave;
+use rodio::Source;
+
+#[test]
+fn test_render_until_end() {
+    let source = SamplesBuffer::new(2, 1000, vec![1i16, 2, 3, 4]).amplify(2.0);
+    let buffer = rodio::render(source, None);
+    assert_eq!(buffer.channels(), 2);
+    assert_eq!(buffer.sample_rate(), 1000);
+    assert_eq!(buffer.collect::<Vec<_>>(), [2, 4, 6, 8]);
+}
+
+#[test]
+fn test_render_duration() {
+    let buffer = rodio::render(SineWave::new(440), Some(Duration::from_millis(250)));
+    assert_eq!(buffer.sample_rate(), 48000);
+    assert_eq!(buffer.total_duration(), Some(Duration::from_millis(250)));
+    assert_eq!(buffer.count(), 12000);
+}
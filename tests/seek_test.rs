@@ -0,0 +1,52 @@
+extern crate rodio;
+
+use std::io::BufReader;
+use std::time::Duration;
+
+use rodio::source::{SeekError, SineWave};
+use rodio::Source;
+
+fn decoder() -> rodio::Decoder<BufReader<std::fs::File>> {
+    // 16 bit wav file exported from LMMS (2 channels)
+    let file = std::fs::File::open("tests/lmms16bit.wav").unwrap();
+    rodio::Decoder::new(BufReader::new(file)).unwrap()
+}
+
+#[test]
+fn test_seek_wav_through_filters() {
+    let frames = decoder().sample_rate() as usize / 2;
+    let expected = decoder().skip(frames * 2).take(100).collect::<Vec<_>>();
+
+    let mut source = decoder()
+        .amplify(1.0)
+        .take_duration(Duration::from_secs(60))
+        .speed(2.0);
+    // Half a second of the file is played in a quarter of a second.
+    source.try_seek(Duration::from_millis(250)).unwrap();
+    assert_eq!(source.take(100).collect::<Vec<_>>(), expected);
+}
+
+#[test]
+fn test_seek_flac() {
+    let decoder = || {
+        let file = std::fs::File::open("tests/audacity16bit_level5.flac").unwrap();
+        rodio::Decoder::new(BufReader::new(file)).unwrap()
+    };
+    let samples = (decoder().sample_rate() / 2) as usize * decoder().channels() as usize;
+    let expected = decoder().skip(samples).take(100).collect::<Vec<_>>();
+    assert_eq!(expected.len(), 100);
+
+    let mut source = decoder();
+    source.by_ref().take(1000).for_each(drop);
+    source.try_seek(Duration::from_millis(500)).unwrap();
+    assert_eq!(source.take(100).collect::<Vec<_>>(), expected);
+}
+
+#[test]
+fn test_seek_not_supported() {
+    let mut source = SineWave::new(440).amplify(0.5);
+    match source.try_seek(Duration::from_secs(1)) {
+        Err(SeekError::NotSupported { .. }) => (),
+        other => panic!("unexpected result: {:?}", other),
+    }
+}